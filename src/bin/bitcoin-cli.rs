@@ -0,0 +1,162 @@
+//! Command-line client for a running node (UI ONLY)
+//!
+//! Talks to the node's HTTP API, so wallet and chain commands
+//! run against the live node instead of loading a second copy
+//! of the chain.
+
+use clap::{Parser, Subcommand};
+use rpassword::prompt_password;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::{Context, Editor, Helper, Highlighter, Hinter, Validator};
+use serde_json::Value;
+
+use bitcoin_v0_2_revelation::interface::console::{self, find_method, METHODS};
+use bitcoin_v0_2_revelation::interface::rpc_client::{RpcClient, DEFAULT_RPC_URL};
+
+const HISTORY_FILE: &str = "data/console_history.txt";
+
+/// Talk to a running node over its HTTP API
+#[derive(Parser)]
+#[command(name = "bitcoin-cli", version)]
+struct Args {
+    /// Node API address
+    #[arg(long, default_value = DEFAULT_RPC_URL)]
+    url: String,
+    /// API token, if the node sets `api.auth_token`
+    #[arg(long, value_name = "TOKEN")]
+    auth_token: Option<String>,
+    #[command(subcommand)]
+    command: CliCommand,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Interactive prompt with history and tab completion
+    Console,
+    /// Run one method and exit, e.g. `bitcoin-cli getblock 10`
+    #[command(external_subcommand)]
+    Call(Vec<String>),
+}
+
+/// Completes method names (and `help <method>`)
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct MethodCompleter;
+
+impl Completer for MethodCompleter {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let head = &line[..pos];
+        let (start, word) = match head.strip_prefix("help ") {
+            Some(rest) => (pos - rest.len(), rest),
+            None => (0, head),
+        };
+
+        if word.contains(' ') {
+            return Ok((pos, Vec::new()));
+        }
+
+        let mut names: Vec<String> = console::complete(word).into_iter().map(String::from).collect();
+        if start == 0 {
+            names.extend(["help", "exit"].iter().filter(|n| n.starts_with(word)).map(|n| n.to_string()));
+        }
+        Ok((start, names))
+    }
+}
+
+fn pretty(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        json => serde_json::to_string_pretty(&json).unwrap(),
+    }
+}
+
+/// Run `words` (method and arguments); prompts for a wallet
+/// password rather than taking it on the command line
+fn run(client: &RpcClient, words: &[String]) -> Result<String, String> {
+    let (method, args) = words.split_first().ok_or("empty command")?;
+    let mut args = args.to_vec();
+
+    if method == "unlockwallet" && args.len() == 1 {
+        let password = prompt_password("🔑 Wallet password: ").map_err(|e| e.to_string())?;
+        args.push(password);
+    }
+
+    client.method(method, &args).map(pretty).map_err(|e| e.to_string())
+}
+
+fn help(method: Option<&str>) {
+    match method.map(|name| find_method(name).ok_or(name)) {
+        Some(Ok(m)) => println!("{}\n  {}", m.usage(), m.help),
+        Some(Err(name)) => println!("❌ Unknown method '{}'", name),
+        None => {
+            for m in METHODS {
+                println!("  {:<52} {}", m.usage(), m.help);
+            }
+            let (exit, text) = ("exit", "Leave the console");
+            println!("  {:<52} {}", exit, text);
+        }
+    }
+}
+
+fn console(client: &RpcClient) -> rustyline::Result<()> {
+    let mut editor: Editor<MethodCompleter, _> = Editor::new()?;
+    editor.set_helper(Some(MethodCompleter));
+    let _ = editor.load_history(HISTORY_FILE);
+
+    println!("⛓ Connected to {} — `help` lists methods, Tab completes", client.url());
+
+    loop {
+        let line = match editor.readline("revelation> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e),
+        };
+
+        let words: Vec<String> = line.split_whitespace().map(String::from).collect();
+        let Some(first) = words.first() else {
+            continue;
+        };
+
+        // Passwords typed inline stay out of the history file
+        if first != "unlockwallet" {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+
+        match first.as_str() {
+            "exit" | "quit" => break,
+            "help" => help(words.get(1).map(String::as_str)),
+            _ => match run(client, &words) {
+                Ok(out) => println!("{}", out),
+                Err(e) => println!("❌ {}", e),
+            },
+        }
+    }
+
+    let _ = std::fs::create_dir_all("data");
+    let _ = editor.save_history(HISTORY_FILE);
+    Ok(())
+}
+
+fn main() {
+    let args = Args::parse();
+    let client = RpcClient::new(&args.url).with_auth_token(args.auth_token);
+
+    match args.command {
+        CliCommand::Console => {
+            if let Err(e) = console(&client) {
+                eprintln!("❌ Console failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        CliCommand::Call(words) => match run(&client, &words) {
+            Ok(out) => println!("{}", out),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
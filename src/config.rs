@@ -1,20 +1,74 @@
 use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
 use serde::{Serialize, Deserialize};
 
-const CONFIG_FILE: &str = "data/miner_config.json";
+use crate::node::RuntimeMode;
+use crate::wallet::DEFAULT_GAP_LIMIT;
+
+/// Directory of the config files unless `--config` is given
+const DEFAULT_CONFIG_DIR: &str = "data";
+
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Read config files from `dir` instead of `data/`; only the
+/// first call counts, and it must come before any load
+pub fn set_config_dir(dir: PathBuf) {
+    let _ = CONFIG_DIR.set(dir);
+}
+
+/// Resolve the default config directory against the current
+/// directory now, so a later change of directory leaves it put
+pub fn pin_config_dir() {
+    let _ = CONFIG_DIR.set(std::env::current_dir().unwrap().join(DEFAULT_CONFIG_DIR));
+}
+
+fn config_dir() -> &'static PathBuf {
+    CONFIG_DIR.get_or_init(|| PathBuf::from(DEFAULT_CONFIG_DIR))
+}
+
+fn config_file(name: &str) -> PathBuf {
+    config_dir().join(name)
+}
+
+const CONFIG_FILE: &str = "miner_config.json";
+
+/// Share of the coinbase for one address
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PayoutSplit {
+    pub address: String,
+    pub percent: u8,
+}
 
 /// Miner configuration (POLICY ONLY)
 #[derive(Serialize, Deserialize)]
 pub struct MinerConfig {
     /// Wallet name used for coinbase rewards
     pub coinbase_wallet: String,
+    /// Addresses paid instead of the wallet, taking turns block
+    /// by block (e.g. a cold wallet the node never unlocks)
+    #[serde(default)]
+    pub payout_addresses: Vec<String>,
+    /// Split every coinbase by percentage instead (operator,
+    /// donation, cold storage, …); shares add up to 100
+    #[serde(default)]
+    pub payout_split: Vec<PayoutSplit>,
+    /// Mining worker threads (default: from the runtime CPU limit)
+    #[serde(default)]
+    pub threads: Option<usize>,
+    /// Run mining threads at the lowest OS priority
+    #[serde(default)]
+    pub low_priority: bool,
+    /// Cores to pin mining threads to (empty = any core)
+    #[serde(default)]
+    pub cpu_cores: Vec<usize>,
 }
 
 /// Load miner configuration from disk
 pub fn load_miner_config() -> MinerConfig {
-    fs::create_dir_all("data").unwrap();
+    fs::create_dir_all(config_dir()).unwrap();
 
-    if let Ok(data) = fs::read_to_string(CONFIG_FILE) {
+    if let Ok(data) = fs::read_to_string(config_file(CONFIG_FILE)) {
         if !data.trim().is_empty() {
             return serde_json::from_str(&data)
                 .expect("invalid miner_config.json");
@@ -23,12 +77,336 @@ pub fn load_miner_config() -> MinerConfig {
 
     let default = MinerConfig {
         coinbase_wallet: "default".to_string(),
+        payout_addresses: Vec::new(),
+        payout_split: Vec::new(),
+        threads: None,
+        low_priority: false,
+        cpu_cores: Vec::new(),
     };
 
     fs::write(
-        CONFIG_FILE,
+        config_file(CONFIG_FILE),
+        serde_json::to_string_pretty(&default).unwrap(),
+    ).unwrap();
+
+    default
+}
+
+const WALLET_CONFIG_FILE: &str = "wallet_config.json";
+
+/// Wallet configuration (POLICY ONLY)
+#[derive(Serialize, Deserialize)]
+pub struct WalletConfig {
+    /// Consecutive unused addresses that end address discovery
+    pub gap_limit: u32,
+    /// Helper program for a hardware signer, e.g.
+    /// "hw-bridge --device usb" (see `ExternalSigner`)
+    #[serde(default)]
+    pub external_signer: Option<String>,
+    /// Shared folder of an air-gapped signer (see `FileSigner`)
+    #[serde(default)]
+    pub signer_dir: Option<String>,
+}
+
+/// Load wallet configuration from disk
+pub fn load_wallet_config() -> WalletConfig {
+    fs::create_dir_all(config_dir()).unwrap();
+
+    if let Ok(data) = fs::read_to_string(config_file(WALLET_CONFIG_FILE)) {
+        if !data.trim().is_empty() {
+            return serde_json::from_str(&data)
+                .expect("invalid wallet_config.json");
+        }
+    }
+
+    let default = WalletConfig {
+        gap_limit: DEFAULT_GAP_LIMIT,
+        external_signer: None,
+        signer_dir: None,
+    };
+
+    fs::write(
+        config_file(WALLET_CONFIG_FILE),
+        serde_json::to_string_pretty(&default).unwrap(),
+    ).unwrap();
+
+    default
+}
+
+const NETWORK_CONFIG_FILE: &str = "network_config.json";
+
+/// Per-IP request limit of the API (see `RateLimiter`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApiRateLimit {
+    /// Sustained requests per second (0 = unlimited)
+    pub per_second: f64,
+    /// Requests allowed at once
+    pub burst: u32,
+}
+
+impl Default for ApiRateLimit {
+    fn default() -> Self {
+        Self { per_second: 10.0, burst: 40 }
+    }
+}
+
+/// PEM files for serving the API over HTTPS
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApiTls {
+    /// Certificate chain, leaf first
+    pub cert: String,
+    pub key: String,
+}
+
+/// Network configuration (POLICY ONLY)
+#[derive(Serialize, Deserialize, Default)]
+pub struct NetworkConfig {
+    /// Trusted peers: `ip` or `ip/prefix`
+    #[serde(default)]
+    pub whitelist: Vec<String>,
+    /// Listen address whose peers are all trusted
+    /// (local explorer, mining proxy), e.g. "127.0.0.1:8334"
+    #[serde(default)]
+    pub whitebind: Option<String>,
+    /// I2P SAM bridge, e.g. "127.0.0.1:7656"; enables I2P
+    #[serde(default)]
+    pub i2p_sam: Option<String>,
+    /// I2P peers to dial: base64 destinations or `.i2p` names
+    #[serde(default)]
+    pub i2p_peers: Vec<String>,
+    /// Local IPC endpoint for co-located processes: a socket
+    /// file on Unix ("data/node.sock"), a pipe name on Windows
+    #[serde(default)]
+    pub ipc: Option<String>,
+    #[serde(default)]
+    pub api_rate_limit: ApiRateLimit,
+    /// Browser origins allowed to call the API, e.g.
+    /// "https://explorer.example"; "*" for any
+    #[serde(default)]
+    pub api_cors_origins: Vec<String>,
+    /// Serve the API over TLS; plain HTTP if unset
+    #[serde(default)]
+    pub api_tls: Option<ApiTls>,
+}
+
+/// Load network configuration from disk
+pub fn load_network_config() -> NetworkConfig {
+    fs::create_dir_all(config_dir()).unwrap();
+
+    if let Ok(data) = fs::read_to_string(config_file(NETWORK_CONFIG_FILE)) {
+        if !data.trim().is_empty() {
+            return serde_json::from_str(&data)
+                .expect("invalid network_config.json");
+        }
+    }
+
+    let default = NetworkConfig::default();
+
+    fs::write(
+        config_file(NETWORK_CONFIG_FILE),
+        serde_json::to_string_pretty(&default).unwrap(),
+    ).unwrap();
+
+    default
+}
+
+const NODE_CONFIG_FILE: &str = "node_config.json";
+
+/// 🌱 Bootstrap seeds (non-consensus)
+pub const DEFAULT_SEEDS: &[&str] = &[
+    "bitcoin-revelation-node.fly.dev:8333",
+];
+
+/// Runtime profile: mobile limits or desktop ones
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeMode {
+    /// From the build target (Android/iOS = mobile)
+    Auto,
+    Desktop,
+    Mobile,
+}
+
+/// Listening ports
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PortConfig {
+    pub p2p: u16,
+    pub api: u16,
+}
+
+impl Default for PortConfig {
+    fn default() -> Self {
+        Self { p2p: 8333, api: 8080 }
+    }
+}
+
+/// Where the node finds peers
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PeerConfig {
+    /// `host:port` bootstrap seeds; hostnames are resolved via DNS
+    pub seeds: Vec<String>,
+    /// Never accept inbound peers, even on desktop
+    pub outbound_only: bool,
+}
+
+impl Default for PeerConfig {
+    fn default() -> Self {
+        Self {
+            seeds: DEFAULT_SEEDS.iter().map(|s| s.to_string()).collect(),
+            outbound_only: false,
+        }
+    }
+}
+
+/// Mining limits (payout and threads are in miner_config.json)
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MiningConfig {
+    /// Off = relay-only node
+    pub enabled: bool,
+    /// Share of the CPU mining may use (1-100)
+    pub max_cpu_percent: u8,
+}
+
+impl Default for MiningConfig {
+    fn default() -> Self {
+        Self { enabled: true, max_cpu_percent: 100 }
+    }
+}
+
+/// Limits applied in mobile mode
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MobileConfig {
+    pub battery_warning_percent: u8,
+    pub thermal_limit_celsius: f32,
+    /// Keep the UTXO cache small rather than the disk busy
+    pub ram_first: bool,
+}
+
+impl Default for MobileConfig {
+    fn default() -> Self {
+        Self {
+            battery_warning_percent: 20,
+            thermal_limit_celsius: 40.0,
+            ram_first: true,
+        }
+    }
+}
+
+/// API access
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Required as `Authorization: Bearer <token>` on every
+    /// request but /health; open API if unset
+    pub auth_token: Option<String>,
+}
+
+/// Node configuration (POLICY ONLY)
+///
+/// Loaded once at start from node_config.json, with command-line
+/// flags taking precedence, then handed to each subsystem.
+/// Missing fields take their defaults.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NodeConfig {
+    /// Only "main" exists
+    pub network: String,
+    /// Working directory of the node, as `--datadir`
+    pub datadir: Option<PathBuf>,
+    pub mode: NodeMode,
+    pub ports: PortConfig,
+    pub peers: PeerConfig,
+    pub mining: MiningConfig,
+    pub mobile: MobileConfig,
+    pub api: ApiConfig,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            network: "main".to_string(),
+            datadir: None,
+            mode: NodeMode::Auto,
+            ports: PortConfig::default(),
+            peers: PeerConfig::default(),
+            mining: MiningConfig::default(),
+            mobile: MobileConfig::default(),
+            api: ApiConfig::default(),
+        }
+    }
+}
+
+impl NodeConfig {
+    pub fn is_mobile(&self) -> bool {
+        match self.mode {
+            NodeMode::Auto => RuntimeMode::detect().is_mobile(),
+            NodeMode::Desktop => false,
+            NodeMode::Mobile => true,
+        }
+    }
+
+    /// Mobile nodes are always outbound-only
+    pub fn outbound_only(&self) -> bool {
+        self.peers.outbound_only || self.is_mobile()
+    }
+
+    /// URL of this node's own API
+    pub fn api_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.ports.api)
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.network != "main" {
+            return Err("unknown network (only \"main\" exists)");
+        }
+        if self.mining.max_cpu_percent == 0 || self.mining.max_cpu_percent > 100 {
+            return Err("mining.max_cpu_percent must be between 1 and 100");
+        }
+        if self.mobile.battery_warning_percent > 100 {
+            return Err("mobile.battery_warning_percent cannot exceed 100");
+        }
+        Ok(())
+    }
+}
+
+/// Load node configuration from disk
+pub fn load_node_config() -> NodeConfig {
+    fs::create_dir_all(config_dir()).unwrap();
+
+    if let Ok(data) = fs::read_to_string(config_file(NODE_CONFIG_FILE)) {
+        if !data.trim().is_empty() {
+            return serde_json::from_str(&data)
+                .expect("invalid node_config.json");
+        }
+    }
+
+    let default = NodeConfig::default();
+
+    fs::write(
+        config_file(NODE_CONFIG_FILE),
         serde_json::to_string_pretty(&default).unwrap(),
     ).unwrap();
 
     default
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_config_fills_missing_fields() {
+        let cfg: NodeConfig = serde_json::from_str(r#"{ "mode": "mobile", "ports": { "api": 9090 } }"#).unwrap();
+
+        assert_eq!(cfg.ports.api, 9090);
+        assert_eq!(cfg.ports.p2p, 8333);
+        assert!(cfg.is_mobile());
+        assert!(cfg.outbound_only());
+        assert!(cfg.mining.enabled);
+        assert!(cfg.validate().is_ok());
+    }
+}
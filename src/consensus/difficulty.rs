@@ -6,8 +6,9 @@
 // Any modification requires a version-gated fork.
 // ─────────────────────────────────────────────
 
-use crate::block::Block;
+use crate::block::{Block, BlockHeader};
 use crate::consensus::params::*;
+use crate::consensus::rules::rules_for_height;
 
 use num_bigint::BigUint;
 
@@ -24,14 +25,46 @@ fn clamp_target_big(target: BigUint) -> BigUint {
     }
 }
 
+fn target_to_bytes(target: BigUint) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let bytes = target.to_bytes_be();
+
+    // Right-align (big-endian)
+    let start = 32usize.saturating_sub(bytes.len());
+    out[start..].copy_from_slice(&bytes);
+
+    out
+}
+
 /// Calculate expected PoW target for NEXT block
+pub fn calculate_next_target(chain: &[Block]) -> [u8; 32] {
+    let headers: Vec<BlockHeader> =
+        chain.iter().map(|b| b.header.clone()).collect();
+
+    calculate_next_target_headers(&headers)
+}
+
+/// Expected PoW target for the header following `headers`
+///
+/// Dispatches to the retarget of the consensus version
+/// governing the next height.
+pub fn calculate_next_target_headers(headers: &[BlockHeader]) -> [u8; 32] {
+    // Genesis / empty chain
+    if headers.is_empty() {
+        return MAX_TARGET;
+    }
+
+    rules_for_height(headers.len() as u64).next_target(headers)
+}
+
+/// Interval retarget (CONSENSUS v3/v4)
 ///
 /// Formula:
 /// new_target = old_target * actual_time / expected_time
 ///
 /// ⚠️ CONSENSUS CRITICAL:
 /// actual_time is clamped to prevent time-warp attacks.
-pub fn calculate_next_target(chain: &[Block]) -> [u8; 32] {
+pub fn calculate_next_target_interval(chain: &[BlockHeader]) -> [u8; 32] {
     // Genesis / empty chain
     if chain.is_empty() {
         return MAX_TARGET;
@@ -42,26 +75,26 @@ pub fn calculate_next_target(chain: &[Block]) -> [u8; 32] {
 
     // Not enough blocks yet
     if height < DIFFICULTY_ADJUSTMENT_INTERVAL + 1 {
-        return last.header.target;
+        return last.target;
     }
 
     // Only adjust on interval
     if height % DIFFICULTY_ADJUSTMENT_INTERVAL != 0 {
-        return last.header.target;
+        return last.target;
     }
 
     let first =
         &chain[height - DIFFICULTY_ADJUSTMENT_INTERVAL - 1];
 
     let mut actual_time =
-        last.header.timestamp - first.header.timestamp;
+        last.timestamp - first.timestamp;
 
     let expected_time =
         TARGET_BLOCK_TIME * DIFFICULTY_ADJUSTMENT_INTERVAL as i64;
 
     // Prevent division by zero or negative time
     if actual_time <= 0 {
-        return last.header.target;
+        return last.target;
     }
 
     // ─────────────────────────────────────────
@@ -83,7 +116,7 @@ pub fn calculate_next_target(chain: &[Block]) -> [u8; 32] {
 
     // Convert target to BigUint
     let old_target =
-        BigUint::from_bytes_be(&last.header.target);
+        BigUint::from_bytes_be(&last.target);
 
     // Scale target
     let scaled =
@@ -92,13 +125,126 @@ pub fn calculate_next_target(chain: &[Block]) -> [u8; 32] {
 
     let new_target = clamp_target_big(scaled);
 
-    // Convert back to [u8; 32]
-    let mut out = [0u8; 32];
-    let bytes = new_target.to_bytes_be();
+    target_to_bytes(new_target)
+}
 
-    // Right-align (big-endian)
-    let start = 32usize.saturating_sub(bytes.len());
-    out[start..].copy_from_slice(&bytes);
+/// Linearly-weighted moving average retarget (CONSENSUS v5)
+///
+/// Formula (over the last LWMA_WINDOW blocks):
+/// new_target = avg_target * Σ(i * solvetime_i) / (T * N(N+1)/2)
+///
+/// ⚠️ CONSENSUS CRITICAL:
+/// each solvetime is clamped to [1, 6T] so a single
+/// bad timestamp cannot swing the target.
+pub fn calculate_next_target_lwma(chain: &[BlockHeader]) -> [u8; 32] {
+    let n = LWMA_WINDOW;
+    let height = chain.len();
+    let last = match chain.last() {
+        Some(b) => b,
+        None => return MAX_TARGET,
+    };
+
+    // Not enough history yet
+    if height < n + 1 {
+        return last.target;
+    }
 
-    out
+    let window = &chain[height - n - 1..];
+
+    let mut weighted_time = BigUint::from(0u64);
+    let mut target_sum = BigUint::from(0u64);
+
+    for i in 1..=n {
+        let solvetime = (window[i].timestamp
+            - window[i - 1].timestamp)
+            .clamp(1, 6 * TARGET_BLOCK_TIME);
+
+        weighted_time += BigUint::from(i as u64) * BigUint::from(solvetime as u64);
+        target_sum += BigUint::from_bytes_be(&window[i].target);
+    }
+
+    let k = BigUint::from((n * (n + 1) / 2) as u64)
+        * BigUint::from(TARGET_BLOCK_TIME as u64);
+
+    // avg_target * weighted_time / k, with the /n folded into k
+    let scaled = target_sum * weighted_time / (k * BigUint::from(n as u64));
+
+    target_to_bytes(clamp_target_big(scaled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2^248: room to scale either way within the bounds
+    const TARGET: [u8; 32] = {
+        let mut t = [0u8; 32];
+        t[0] = 1;
+        t
+    };
+
+    /// `count` headers at `target`, `spacing` seconds apart
+    fn headers(count: usize, spacing: i64) -> Vec<BlockHeader> {
+        (0..count)
+            .map(|i| BlockHeader {
+                height: i as u64,
+                timestamp: i as i64 * spacing,
+                prev_hash: vec![],
+                nonce: 0,
+                target: TARGET,
+                merkle_root: vec![],
+                utxo_commitment: vec![],
+            })
+            .collect()
+    }
+
+    fn scaled(target: [u8; 32], num: u64, den: u64) -> [u8; 32] {
+        target_to_bytes(BigUint::from_bytes_be(&target) * num / den)
+    }
+
+    #[test]
+    fn lwma_takes_over_at_v5_height() {
+        // Blocks twice as slow as targeted
+        let chain = headers(CONSENSUS_V5_HEIGHT as usize, 2 * TARGET_BLOCK_TIME);
+
+        // Last v4 block: not an interval boundary, target kept
+        let before = &chain[..CONSENSUS_V5_HEIGHT as usize - 1];
+        assert_eq!(calculate_next_target_headers(before), TARGET);
+
+        // First v5 block retargets straight away
+        assert_eq!(calculate_next_target_headers(&chain), scaled(TARGET, 2, 1));
+    }
+
+    #[test]
+    fn lwma_clamps_extreme_timestamps() {
+        let mut chain = headers(LWMA_WINDOW + 1, TARGET_BLOCK_TIME);
+        let last = chain.len() - 1;
+        let prev_time = chain[last - 1].timestamp;
+
+        // Far future counts as 6T, going backwards as 1 second
+        chain[last].timestamp = prev_time + 1_000_000_000;
+        let far_future = calculate_next_target_lwma(&chain);
+        chain[last].timestamp = prev_time + 6 * TARGET_BLOCK_TIME;
+        assert_eq!(far_future, calculate_next_target_lwma(&chain));
+
+        chain[last].timestamp = prev_time - 1_000_000_000;
+        let backwards = calculate_next_target_lwma(&chain);
+        chain[last].timestamp = prev_time + 1;
+        assert_eq!(backwards, calculate_next_target_lwma(&chain));
+
+        // Every solvetime zero: each counts as 1 second
+        let same_time = headers(LWMA_WINDOW + 1, 0);
+        assert_eq!(calculate_next_target_lwma(&same_time), scaled(TARGET, 1, TARGET_BLOCK_TIME as u64));
+    }
+
+    #[test]
+    fn lwma_keeps_target_until_window_is_full() {
+        assert_eq!(calculate_next_target_lwma(&[]), MAX_TARGET);
+
+        let short = headers(LWMA_WINDOW, 2 * TARGET_BLOCK_TIME);
+        assert_eq!(calculate_next_target_lwma(&short), TARGET);
+
+        let full = headers(LWMA_WINDOW + 1, 2 * TARGET_BLOCK_TIME);
+        assert_eq!(calculate_next_target_lwma(&full), scaled(TARGET, 2, 1));
+    }
 }
@@ -5,7 +5,7 @@
 // Any modification requires a new hard fork.
 // ─────────────────────────────────────────────
 
-use crate::core::block::Block;
+use crate::core::block::{Block, BlockHeader};
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 use std::collections::HashMap;
@@ -14,7 +14,12 @@ use std::collections::HashMap;
 ///
 /// work = 2^256 / (target + 1)
 pub fn block_work(block: &Block) -> BigUint {
-    let target = BigUint::from_bytes_be(&block.header.target);
+    header_work(&block.header)
+}
+
+/// Work committed to by a single header
+pub fn header_work(header: &BlockHeader) -> BigUint {
+    let target = BigUint::from_bytes_be(&header.target);
 
     if target.is_zero() {
         return BigUint::zero();
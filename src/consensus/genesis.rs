@@ -18,6 +18,7 @@ pub fn genesis_block() -> Block {
             merkle_root: hex::decode(
                 "REPLACE_WITH_GENESIS_MERKLE_ROOT"
             ).expect("genesis merkle"),
+            utxo_commitment: vec![],
         },
         transactions: vec![
             revelation_tx(),
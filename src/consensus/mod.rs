@@ -8,3 +8,4 @@ pub mod params;
 pub mod fork_choice;
 pub mod serialize;
 pub mod genesis;
+pub mod rules;
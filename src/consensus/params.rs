@@ -2,13 +2,57 @@
 pub const TARGET_BLOCK_TIME: i64 = 60;
 pub const DIFFICULTY_ADJUSTMENT_INTERVAL: usize = 10;
 
+/// LWMA per-block retarget (v5)
+pub const LWMA_WINDOW: usize = 45;
+
 /// Median Time Past
 pub const MTP_WINDOW: usize = 11;
 pub const MAX_FUTURE_DRIFT: i64 = 2 * 60 * 60; // 2 hours
 
+/// Monetary limits
+pub const COIN: u64 = 100_000_000;
+pub const MAX_MONEY: u64 = 21_000_000 * COIN;
+
+/// Blocks before a coinbase output may be spent
+pub const COINBASE_MATURITY: u64 = 100;
+
+/// Per-network consensus parameters
+#[derive(Debug)]
+pub struct NetworkParams {
+    pub name: &'static str,
+    /// Prefix of every P2P message on this network
+    pub magic: [u8; 4],
+    /// Human-readable part of bech32 addresses
+    pub bech32_hrp: &'static str,
+    /// Version byte of Base58Check addresses
+    pub base58_version: u8,
+    pub coinbase_maturity: u64,
+    pub max_future_drift: i64,
+}
+
+pub static MAINNET: NetworkParams = NetworkParams {
+    name: "main",
+    magic: [0xb7, 0x0c, 0x02, 0xd9],
+    bech32_hrp: "bc2",
+    base58_version: 0x00,
+    coinbase_maturity: COINBASE_MATURITY,
+    max_future_drift: MAX_FUTURE_DRIFT,
+};
+
+/// Fork activation heights
+pub const CONSENSUS_V5_HEIGHT: u64 = 20_000;
+pub const CONSENSUS_V6_HEIGHT: u64 = 40_000;
+pub const CONSENSUS_V7_HEIGHT: u64 = 60_000;
+
 /// Consensus block limits
 pub const MAX_BLOCK_SIZE: usize = 1_000_000;
 
+/// Validation cost limits (v5)
+pub const MAX_BLOCK_SIGOPS: usize = 20_000;
+pub const MAX_BLOCK_WEIGHT: usize = 4_000_000;
+pub const WEIGHT_PER_BYTE: usize = 4;
+pub const WEIGHT_PER_SIGOP: usize = 100;
+
 /// PoW target bounds
 pub const MAX_TARGET: [u8; 32] = [0xff; 32];
 pub const MIN_TARGET: [u8; 32] = [
@@ -0,0 +1,78 @@
+// ─────────────────────────────────────────────
+// CONSENSUS — VERSIONED RULE SETS
+//
+// Each consensus version lives in its own module and
+// is selected purely by block height. A new hard fork
+// adds a new module; existing versions never change.
+// ─────────────────────────────────────────────
+
+pub mod v4;
+pub mod v5;
+pub mod v6;
+pub mod v7;
+
+use crate::core::block::{Block, BlockHeader};
+use crate::core::error::{BlockError, ValidationError};
+use crate::core::transaction::{Transaction, TxInput};
+use crate::core::utxo::{UTXOSet, UTXO};
+use crate::consensus::params::{CONSENSUS_V5_HEIGHT, CONSENSUS_V6_HEIGHT, CONSENSUS_V7_HEIGHT};
+
+pub use v4::ConsensusV4;
+pub use v5::ConsensusV5;
+pub use v6::ConsensusV6;
+pub use v7::ConsensusV7;
+
+/// Rules that differ between consensus versions
+pub trait ConsensusRules: Send + Sync {
+    /// Consensus version number
+    fn version(&self) -> u32;
+
+    /// Expected PoW target for the header following `headers`
+    fn next_target(&self, headers: &[BlockHeader]) -> [u8; 32];
+
+    /// Transaction-wide checks, run before any input lookup
+    fn check_transaction(&self, tx: &Transaction) -> Result<(), ValidationError>;
+
+    /// Message signed by each input
+    fn sighash(&self, tx: &Transaction) -> Vec<u8>;
+
+    /// Authorise spending `utxo` with `input`
+    fn verify_input(&self, input: &TxInput, utxo: &UTXO, sighash: &[u8]) -> bool;
+
+    /// Upper bound on a transaction's summed input value
+    fn max_input_total(&self) -> Option<u64> {
+        None
+    }
+
+    /// Block rules beyond header, PoW and merkle checks
+    fn check_block(&self, block: &Block, utxos: &UTXOSet) -> Result<(), BlockError>;
+
+    /// Header commitment to the parent's UTXO set (empty = none)
+    fn utxo_commitment(&self, _utxos: &UTXOSet) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Limit on the coinbase value given the block's `fees`
+    /// (unchecked before v7)
+    fn check_coinbase_value(&self, _block: &Block, _fees: u64) -> Result<(), BlockError> {
+        Ok(())
+    }
+}
+
+static V4: ConsensusV4 = ConsensusV4;
+static V5: ConsensusV5 = ConsensusV5;
+static V6: ConsensusV6 = ConsensusV6;
+static V7: ConsensusV7 = ConsensusV7;
+
+/// Rule set governing a block at `height`
+pub fn rules_for_height(height: u64) -> &'static dyn ConsensusRules {
+    if height >= CONSENSUS_V7_HEIGHT {
+        &V7
+    } else if height >= CONSENSUS_V6_HEIGHT {
+        &V6
+    } else if height >= CONSENSUS_V5_HEIGHT {
+        &V5
+    } else {
+        &V4
+    }
+}
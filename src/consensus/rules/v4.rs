@@ -0,0 +1,65 @@
+// ─────────────────────────────────────────────
+// CONSENSUS v4 — FROZEN
+//
+// Implicit SHA256(pubkey) locks, interval retarget,
+// no additional block limits.
+// ─────────────────────────────────────────────
+
+use secp256k1::PublicKey;
+
+use crate::core::block::{Block, BlockHeader};
+use crate::core::error::{BlockError, ValidationError};
+use crate::core::transaction::{Transaction, TxInput};
+use crate::core::utxo::{UTXOSet, UTXO};
+use crate::consensus::difficulty::calculate_next_target_interval;
+use crate::crypto::{pubkey_hash, verify_signature};
+
+use super::ConsensusRules;
+
+pub struct ConsensusV4;
+
+impl ConsensusRules for ConsensusV4 {
+    fn version(&self) -> u32 {
+        4
+    }
+
+    fn next_target(&self, headers: &[BlockHeader]) -> [u8; 32] {
+        calculate_next_target_interval(headers)
+    }
+
+    /// Script fields are meaningless (and unhashed by old nodes) pre-v5
+    fn check_transaction(&self, tx: &Transaction) -> Result<(), ValidationError> {
+        if tx.inputs.iter().all(|i| i.script_sig.is_empty())
+            && tx.outputs.iter().all(|o| o.script_pubkey.is_empty())
+        {
+            Ok(())
+        } else {
+            Err(ValidationError::ScriptBeforeActivation)
+        }
+    }
+
+    /// Blocks below v5 never had their transactions validated,
+    /// so this only decides what the mempool admits; it matches
+    /// what wallets sign (a preimage holding the signatures
+    /// themselves could never be signed)
+    fn sighash(&self, tx: &Transaction) -> Vec<u8> {
+        tx.signature_hash()
+    }
+
+    fn verify_input(&self, input: &TxInput, utxo: &UTXO, sighash: &[u8]) -> bool {
+        let pubkey = match PublicKey::from_slice(&input.pubkey) {
+            Ok(pk) => pk,
+            Err(_) => return false,
+        };
+
+        if pubkey_hash(&pubkey) != utxo.pubkey_hash {
+            return false;
+        }
+
+        verify_signature(sighash, &input.signature, &pubkey.serialize())
+    }
+
+    fn check_block(&self, _block: &Block, _utxos: &UTXOSet) -> Result<(), BlockError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+// ─────────────────────────────────────────────
+// CONSENSUS v5 — HEIGHT-GATED (CONSENSUS_V5_HEIGHT)
+//
+// - output scripts (core::script)
+// - LWMA per-block retarget
+// - sigop / weight block limits
+// - duplicate txid protection
+// - coinbase height commitment (BIP34-style)
+// - MAX_MONEY range checks
+// ─────────────────────────────────────────────
+
+use std::collections::{HashMap, HashSet};
+
+use crate::core::block::{Block, BlockHeader};
+use crate::core::error::{BlockError, ValidationError};
+use crate::core::script::{count_sigops, verify_script};
+use crate::core::transaction::{Transaction, TxInput};
+use crate::core::utxo::{OutPoint, UTXOSet, UTXO};
+use crate::consensus::difficulty::calculate_next_target_lwma;
+use crate::consensus::params::{
+    MAX_MONEY,
+    MAX_BLOCK_SIZE,
+    MAX_BLOCK_SIGOPS,
+    MAX_BLOCK_WEIGHT,
+    WEIGHT_PER_BYTE,
+    WEIGHT_PER_SIGOP,
+};
+use crate::crypto::sha256;
+
+use super::ConsensusRules;
+
+pub struct ConsensusV5;
+
+impl ConsensusRules for ConsensusV5 {
+    fn version(&self) -> u32 {
+        5
+    }
+
+    fn next_target(&self, headers: &[BlockHeader]) -> [u8; 32] {
+        calculate_next_target_lwma(headers)
+    }
+
+    fn check_transaction(&self, tx: &Transaction) -> Result<(), ValidationError> {
+        if !check_money_range(tx) {
+            return Err(ValidationError::ValueOutOfRange);
+        }
+
+        // Script outputs are identified by SHA256(script_pubkey)
+        if tx.outputs.iter().any(|o| {
+            !o.script_pubkey.is_empty() && o.pubkey_hash != sha256(&o.script_pubkey)
+        }) {
+            return Err(ValidationError::ScriptHashMismatch);
+        }
+
+        Ok(())
+    }
+
+    fn sighash(&self, tx: &Transaction) -> Vec<u8> {
+        tx.signature_hash()
+    }
+
+    /// Every output is locked by a script
+    fn verify_input(&self, input: &TxInput, utxo: &UTXO, sighash: &[u8]) -> bool {
+        verify_script(&input.unlocking_script(), &utxo.locking_script(), sighash)
+    }
+
+    fn max_input_total(&self) -> Option<u64> {
+        Some(MAX_MONEY)
+    }
+
+    fn check_block(&self, block: &Block, utxos: &UTXOSet) -> Result<(), BlockError> {
+        check_block_limits(block, utxos)?;
+
+        if !check_block_money_range(block) {
+            return Err(BlockError::ValueOutOfRange);
+        }
+
+        if !check_coinbase_height(block) {
+            return Err(BlockError::BadCoinbaseHeight);
+        }
+
+        if !check_unique_txids(block, utxos) {
+            return Err(BlockError::DuplicateTxid);
+        }
+
+        Ok(())
+    }
+}
+
+/// Output value range
+pub fn check_money_range(tx: &Transaction) -> bool {
+    let mut total: u64 = 0;
+
+    for output in &tx.outputs {
+        if output.value > MAX_MONEY {
+            return false;
+        }
+
+        total = match total.checked_add(output.value) {
+            Some(t) if t <= MAX_MONEY => t,
+            _ => return false,
+        };
+    }
+
+    true
+}
+
+/// All outputs created by a block stay within MAX_MONEY
+pub fn check_block_money_range(block: &Block) -> bool {
+    let mut total: u64 = 0;
+
+    for tx in &block.transactions {
+        if !check_money_range(tx) {
+            return false;
+        }
+
+        for output in &tx.outputs {
+            total = match total.checked_add(output.value) {
+                Some(t) if t <= MAX_MONEY => t,
+                _ => return false,
+            };
+        }
+    }
+
+    true
+}
+
+/// Signature verifications required to connect a block
+///
+/// Each input is charged the sigops of the script it spends,
+/// including outputs created earlier in the same block.
+pub fn block_sigops(block: &Block, utxos: &UTXOSet) -> usize {
+    let mut created: HashMap<OutPoint, Vec<u8>> = HashMap::new();
+    let mut total = 0;
+
+    for tx in &block.transactions {
+        // The coinbase input spends nothing
+        let inputs = if tx.is_coinbase() { &[][..] } else { &tx.inputs[..] };
+
+        for input in inputs {
+            let outpoint = input.outpoint();
+            let spent = outpoint.as_ref().and_then(|op| utxos.get(op));
+
+            total += match spent {
+                Some(u) => count_sigops(&u.locking_script()),
+                None => outpoint
+                    .and_then(|op| created.get(&op))
+                    .map(|s| count_sigops(s))
+                    .unwrap_or(1),
+            };
+        }
+
+        for (outpoint, o) in tx.outpoints() {
+            created.insert(outpoint, o.locking_script());
+        }
+    }
+
+    total
+}
+
+/// Block validation cost: bytes and signature checks combined
+pub fn block_weight(block: &Block, sigops: usize) -> usize {
+    block.serialized_size() * WEIGHT_PER_BYTE + sigops * WEIGHT_PER_SIGOP
+}
+
+/// Size, sigop and weight limits
+pub fn check_block_limits(block: &Block, utxos: &UTXOSet) -> Result<(), BlockError> {
+    if block.serialized_size() > MAX_BLOCK_SIZE {
+        return Err(BlockError::Oversized);
+    }
+
+    let sigops = block_sigops(block, utxos);
+    if sigops > MAX_BLOCK_SIGOPS {
+        return Err(BlockError::TooManySigops);
+    }
+
+    if block_weight(block, sigops) > MAX_BLOCK_WEIGHT {
+        return Err(BlockError::Overweight);
+    }
+
+    Ok(())
+}
+
+/// The coinbase commits to the block's height
+/// (`TxInput::coinbase`), so no two coinbases share a txid
+pub fn check_coinbase_height(block: &Block) -> bool {
+    block.transactions.first().and_then(Transaction::coinbase_height) == Some(block.header.height)
+}
+
+/// Duplicate txid protection (BIP30-style)
+///
+/// A block may not contain the same txid twice, nor a txid
+/// that still has unspent outputs in the UTXO set: the new
+/// outputs would silently overwrite the existing ones.
+pub fn check_unique_txids(block: &Block, utxos: &UTXOSet) -> bool {
+    let mut seen = HashSet::new();
+
+    for tx in &block.transactions {
+        if !seen.insert(tx.txid()) {
+            return false;
+        }
+
+        if tx.outpoints().any(|(outpoint, _)| utxos.contains_key(&outpoint)) {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockHeader;
+    use crate::core::transaction::TxOutput;
+    use crate::core::utxo::UTXOSet;
+
+    fn coinbase(value: u64) -> Transaction {
+        Transaction {
+            inputs: vec![],
+            outputs: vec![TxOutput {
+                value,
+                pubkey_hash: vec![1; 32],
+                script_pubkey: vec![],
+            }],
+        }
+    }
+
+    fn block(transactions: Vec<Transaction>) -> Block {
+        Block {
+            header: BlockHeader {
+                height: 1,
+                timestamp: 0,
+                prev_hash: vec![0; 32],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![0; 32],
+                utxo_commitment: vec![],
+            },
+            transactions,
+            hash: vec![],
+        }
+    }
+
+    #[test]
+    fn test_money_range() {
+        assert!(check_money_range(&coinbase(MAX_MONEY)));
+        assert!(!check_money_range(&coinbase(MAX_MONEY + 1)));
+
+        let mut tx = coinbase(MAX_MONEY);
+        tx.outputs.push(tx.outputs[0].clone());
+        assert!(!check_money_range(&tx));
+    }
+
+    #[test]
+    fn test_duplicate_txids_rejected() {
+        let utxos = UTXOSet::new();
+        assert!(check_unique_txids(&block(vec![coinbase(1)]), &utxos));
+        assert!(!check_unique_txids(&block(vec![coinbase(1), coinbase(1)]), &utxos));
+    }
+
+    /// Transaction spending a coin locked by `sigops` OP_CHECKSIGs
+    fn sigop_spend(sigops: usize) -> (Transaction, UTXOSet) {
+        use crate::core::script::OP_CHECKSIG;
+
+        let outpoint = OutPoint::new(&[7; 32], 0).unwrap();
+        let mut utxos = UTXOSet::new();
+        utxos.insert(
+            outpoint,
+            UTXO {
+                value: 1,
+                pubkey_hash: vec![1; 32],
+                height: 0,
+                is_coinbase: false,
+                script_pubkey: vec![OP_CHECKSIG; sigops],
+            },
+        );
+
+        let tx = Transaction {
+            inputs: vec![TxInput {
+                txid: outpoint.txid.to_vec(),
+                index: 0,
+                pubkey: vec![],
+                signature: vec![],
+                address_index: 0,
+                script_sig: vec![],
+            }],
+            outputs: vec![],
+        };
+        (tx, utxos)
+    }
+
+    /// Grow the coinbase output script until `block` is `size` bytes
+    fn pad_to(block: &mut Block, size: usize) {
+        loop {
+            let diff = size as isize - block.serialized_size() as isize;
+            if diff == 0 {
+                return;
+            }
+            let script = &mut block.transactions[0].outputs[0].script_pubkey;
+            script.resize((script.len() as isize + diff) as usize, 0);
+        }
+    }
+
+    #[test]
+    fn test_sigop_limit() {
+        let (tx, utxos) = sigop_spend(MAX_BLOCK_SIGOPS);
+        assert_eq!(check_block_limits(&block(vec![coinbase(1), tx]), &utxos), Ok(()));
+
+        let (tx, utxos) = sigop_spend(MAX_BLOCK_SIGOPS + 1);
+        assert_eq!(
+            check_block_limits(&block(vec![coinbase(1), tx]), &utxos),
+            Err(BlockError::TooManySigops)
+        );
+    }
+
+    #[test]
+    fn test_size_and_weight_limits() {
+        let utxos = UTXOSet::new();
+
+        // Without sigops the size limit is exactly the weight limit
+        let mut b = block(vec![coinbase(1)]);
+        pad_to(&mut b, MAX_BLOCK_SIZE);
+        assert_eq!(check_block_limits(&b, &utxos), Ok(()));
+        pad_to(&mut b, MAX_BLOCK_SIZE + 1);
+        assert_eq!(check_block_limits(&b, &utxos), Err(BlockError::Oversized));
+
+        // Sigops use up weight a smaller block would have had
+        let sigops = 1_000;
+        let (tx, utxos) = sigop_spend(sigops);
+        let fits = (MAX_BLOCK_WEIGHT - sigops * WEIGHT_PER_SIGOP) / WEIGHT_PER_BYTE;
+
+        let mut b = block(vec![coinbase(1), tx]);
+        pad_to(&mut b, fits);
+        assert_eq!(block_weight(&b, sigops), MAX_BLOCK_WEIGHT);
+        assert_eq!(check_block_limits(&b, &utxos), Ok(()));
+        pad_to(&mut b, fits + 1);
+        assert_eq!(check_block_limits(&b, &utxos), Err(BlockError::Overweight));
+    }
+
+    #[test]
+    fn test_coinbase_commits_to_height() {
+        use crate::core::transaction::TxInput;
+
+        // Pre-v5 coinbase without an input
+        assert!(!check_coinbase_height(&block(vec![coinbase(1)])));
+
+        let mut tx = coinbase(1);
+        tx.inputs = vec![TxInput::coinbase(1)];
+        assert!(check_coinbase_height(&block(vec![tx.clone()])));
+
+        tx.inputs = vec![TxInput::coinbase(2)];
+        assert!(!check_coinbase_height(&block(vec![tx])));
+    }
+}
@@ -0,0 +1,60 @@
+// ─────────────────────────────────────────────
+// CONSENSUS v6 — HEIGHT-GATED (CONSENSUS_V6_HEIGHT)
+//
+// All v5 rules, plus:
+// - header commits to the UTXO set it builds on
+// ─────────────────────────────────────────────
+
+use crate::core::block::{Block, BlockHeader};
+use crate::core::error::{BlockError, ValidationError};
+use crate::core::transaction::{Transaction, TxInput};
+use crate::core::utxo::{utxo_stats, UTXOSet, UTXO};
+
+use super::{ConsensusRules, ConsensusV5};
+
+pub struct ConsensusV6;
+
+impl ConsensusRules for ConsensusV6 {
+    fn version(&self) -> u32 {
+        6
+    }
+
+    fn next_target(&self, headers: &[BlockHeader]) -> [u8; 32] {
+        ConsensusV5.next_target(headers)
+    }
+
+    fn check_transaction(&self, tx: &Transaction) -> Result<(), ValidationError> {
+        ConsensusV5.check_transaction(tx)
+    }
+
+    fn sighash(&self, tx: &Transaction) -> Vec<u8> {
+        ConsensusV5.sighash(tx)
+    }
+
+    fn verify_input(&self, input: &TxInput, utxo: &UTXO, sighash: &[u8]) -> bool {
+        ConsensusV5.verify_input(input, utxo, sighash)
+    }
+
+    fn max_input_total(&self) -> Option<u64> {
+        ConsensusV5.max_input_total()
+    }
+
+    fn check_block(&self, block: &Block, utxos: &UTXOSet) -> Result<(), BlockError> {
+        ConsensusV5.check_block(block, utxos)
+    }
+
+    /// Hash of the parent's UTXO set (see `utxo_stats`)
+    fn utxo_commitment(&self, utxos: &UTXOSet) -> Vec<u8> {
+        utxo_stats(utxos).hash
+    }
+}
+
+/// Check a downloaded UTXO snapshot against a v6 header
+///
+/// `header` is the block built on the snapshot's tip, so
+/// a snapshot of the state after block h is verified with
+/// header h + 1.
+pub fn verify_utxo_snapshot(snapshot: &UTXOSet, header: &BlockHeader) -> bool {
+    !header.utxo_commitment.is_empty()
+        && utxo_stats(snapshot).hash == header.utxo_commitment
+}
@@ -0,0 +1,104 @@
+// ─────────────────────────────────────────────
+// CONSENSUS v7 — HEIGHT-GATED (CONSENSUS_V7_HEIGHT)
+//
+// All v6 rules, plus:
+// - coinbase pays at most block reward + transaction fees
+// ─────────────────────────────────────────────
+
+use crate::core::block::{Block, BlockHeader};
+use crate::core::error::{BlockError, ValidationError};
+use crate::core::transaction::{Transaction, TxInput};
+use crate::core::utxo::{UTXOSet, UTXO};
+use crate::reward::block_reward;
+
+use super::{ConsensusRules, ConsensusV6};
+
+pub struct ConsensusV7;
+
+impl ConsensusRules for ConsensusV7 {
+    fn version(&self) -> u32 {
+        7
+    }
+
+    fn next_target(&self, headers: &[BlockHeader]) -> [u8; 32] {
+        ConsensusV6.next_target(headers)
+    }
+
+    fn check_transaction(&self, tx: &Transaction) -> Result<(), ValidationError> {
+        ConsensusV6.check_transaction(tx)
+    }
+
+    fn sighash(&self, tx: &Transaction) -> Vec<u8> {
+        ConsensusV6.sighash(tx)
+    }
+
+    fn verify_input(&self, input: &TxInput, utxo: &UTXO, sighash: &[u8]) -> bool {
+        ConsensusV6.verify_input(input, utxo, sighash)
+    }
+
+    fn max_input_total(&self) -> Option<u64> {
+        ConsensusV6.max_input_total()
+    }
+
+    fn check_block(&self, block: &Block, utxos: &UTXOSet) -> Result<(), BlockError> {
+        ConsensusV6.check_block(block, utxos)
+    }
+
+    fn utxo_commitment(&self, utxos: &UTXOSet) -> Vec<u8> {
+        ConsensusV6.utxo_commitment(utxos)
+    }
+
+    /// Fees are not destroyed: the miner may claim them, but
+    /// nothing more
+    fn check_coinbase_value(&self, block: &Block, fees: u64) -> Result<(), BlockError> {
+        let paid: u64 = block
+            .transactions
+            .first()
+            .filter(|tx| tx.is_coinbase())
+            .map_or(0, |tx| tx.outputs.iter().map(|o| o.value).sum());
+
+        if paid > block_reward(block.header.height).saturating_add(fees) {
+            return Err(BlockError::BadCoinbaseValue);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::params::CONSENSUS_V7_HEIGHT;
+    use crate::core::transaction::TxOutput;
+
+    fn block_paying(value: u64) -> Block {
+        Block {
+            header: BlockHeader {
+                height: CONSENSUS_V7_HEIGHT,
+                timestamp: 0,
+                prev_hash: vec![],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![],
+                utxo_commitment: vec![],
+            },
+            transactions: vec![Transaction {
+                inputs: vec![],
+                outputs: vec![TxOutput { value, pubkey_hash: vec![1; 32], script_pubkey: vec![] }],
+            }],
+            hash: vec![],
+        }
+    }
+
+    #[test]
+    fn coinbase_may_claim_reward_and_fees_only() {
+        let reward = block_reward(CONSENSUS_V7_HEIGHT);
+
+        assert!(ConsensusV7.check_coinbase_value(&block_paying(reward + 500), 500).is_ok());
+        assert!(ConsensusV7.check_coinbase_value(&block_paying(reward), 500).is_ok());
+        assert_eq!(
+            ConsensusV7.check_coinbase_value(&block_paying(reward + 501), 500),
+            Err(BlockError::BadCoinbaseValue)
+        );
+    }
+}
@@ -6,6 +6,7 @@
 
 use crate::core::block::BlockHeader;
 use crate::core::transaction::{Transaction, TxInput, TxOutput};
+use crate::core::utxo::{OutPoint, UTXO};
 
 fn write_u64_le(v: u64, out: &mut Vec<u8>) {
     out.extend_from_slice(&v.to_le_bytes());
@@ -24,6 +25,14 @@ fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) {
     out.extend_from_slice(bytes);
 }
 
+/// Opens the v5 transaction encoding, in place of the legacy
+/// input count; no transaction has this many inputs, so the two
+/// encodings never collide
+const SCRIPTED_TX_MARKER: u32 = u32::MAX;
+
+/// Precedes the header's UTXO commitment (v6)
+const COMMITMENT_FLAG: u8 = 1;
+
 /// Serialize block header EXACTLY for hashing (CONSENSUS)
 pub fn serialize_block_header(header: &BlockHeader) -> Vec<u8> {
     let mut out = Vec::with_capacity(128);
@@ -35,35 +44,107 @@ pub fn serialize_block_header(header: &BlockHeader) -> Vec<u8> {
     out.extend_from_slice(&header.target);
     write_bytes(&header.merkle_root, &mut out);
 
+    // v6: only present when set, so legacy header hashes are unchanged;
+    // flagged, so the field is never confused with trailing bytes
+    if !header.utxo_commitment.is_empty() {
+        out.push(COMMITMENT_FLAG);
+        write_bytes(&header.utxo_commitment, &mut out);
+    }
+
     out
 }
 
 /// Serialize transaction EXACTLY for txid / sighash (CONSENSUS)
+///
+/// Transactions without scripts keep the legacy encoding (and
+/// txids). Any script switches the whole transaction to the v5
+/// encoding: SCRIPTED_TX_MARKER, then every script written with
+/// its length, empty or not.
 pub fn serialize_transaction(tx: &Transaction) -> Vec<u8> {
     let mut out = Vec::new();
 
+    let scripted = tx.inputs.iter().any(|i| !i.script_sig.is_empty())
+        || tx.outputs.iter().any(|o| !o.script_pubkey.is_empty());
+    if scripted {
+        write_u32_le(SCRIPTED_TX_MARKER, &mut out);
+    }
+
     write_u32_le(tx.inputs.len() as u32, &mut out);
     for i in &tx.inputs {
-        serialize_input(i, &mut out);
+        serialize_input(i, scripted, &mut out);
     }
 
     write_u32_le(tx.outputs.len() as u32, &mut out);
     for o in &tx.outputs {
-        serialize_output(o, &mut out);
+        serialize_output(o, scripted, &mut out);
     }
 
     out
 }
 
-fn serialize_input(i: &TxInput, out: &mut Vec<u8>) {
+fn serialize_input(i: &TxInput, scripted: bool, out: &mut Vec<u8>) {
     write_bytes(&i.txid, out);
     write_u32_le(i.index, out);
     write_bytes(&i.pubkey, out);
     write_bytes(&i.signature, out);
     write_u32_le(i.address_index, out);
+
+    if scripted {
+        write_bytes(&i.script_sig, out);
+    }
 }
 
-fn serialize_output(o: &TxOutput, out: &mut Vec<u8>) {
+fn serialize_output(o: &TxOutput, scripted: bool, out: &mut Vec<u8>) {
     write_u64_le(o.value, out);
     write_bytes(&o.pubkey_hash, out);
+
+    if scripted {
+        write_bytes(&o.script_pubkey, out);
+    }
+}
+
+/// Serialize one UTXO set entry for set hashing
+pub fn serialize_utxo_entry(outpoint: &OutPoint, utxo: &UTXO) -> Vec<u8> {
+    let mut out = Vec::with_capacity(128);
+
+    out.extend_from_slice(&outpoint.txid);
+    write_u32_le(outpoint.vout, &mut out);
+    write_u64_le(utxo.value, &mut out);
+    write_bytes(&utxo.pubkey_hash, &mut out);
+    write_u64_le(utxo.height, &mut out);
+    out.push(utxo.is_coinbase as u8);
+    write_bytes(&utxo.script_pubkey, &mut out);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(script_sig: Vec<u8>) -> TxInput {
+        TxInput {
+            txid: vec![7; 32],
+            index: 0,
+            pubkey: vec![2; 33],
+            signature: vec![],
+            address_index: 0,
+            script_sig,
+        }
+    }
+
+    #[test]
+    fn scripts_are_always_delimited() {
+        let legacy = Transaction { inputs: vec![input(vec![])], outputs: vec![] };
+        assert_eq!(serialize_transaction(&legacy)[..4], 1u32.to_le_bytes());
+
+        // A script in the first input must not read as the start
+        // of a second one
+        let one = Transaction { inputs: vec![input(vec![9]), input(vec![])], outputs: vec![] };
+        let two = Transaction { inputs: vec![input(vec![]), input(vec![9])], outputs: vec![] };
+        let (a, b) = (serialize_transaction(&one), serialize_transaction(&two));
+        assert_eq!(a[..4], SCRIPTED_TX_MARKER.to_le_bytes());
+        assert_ne!(a, b);
+        assert_eq!(a.len(), b.len());
+    }
 }
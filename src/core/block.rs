@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 use super::transaction::Transaction;
-use crate::consensus::serialize::serialize_block_header;
+use crate::consensus::serialize::{serialize_block_header, serialize_transaction};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BlockHeader {
@@ -10,6 +10,16 @@ pub struct BlockHeader {
     pub nonce: u64,
     pub target: [u8; 32],
     pub merkle_root: Vec<u8>,
+    #[serde(default)]
+    pub utxo_commitment: Vec<u8>, // v6: hash of the parent UTXO set
+}
+
+impl BlockHeader {
+    /// Header hash (CONSENSUS)
+    pub fn hash(&self) -> Vec<u8> {
+        let bytes = serialize_block_header(self);
+        crate::crypto::sha256(&crate::crypto::sha256(&bytes))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -22,8 +32,27 @@ pub struct Block {
 impl Block {
     /// Block header hash (CONSENSUS)
     pub fn hash_header(&self) -> Vec<u8> {
-        let bytes = serialize_block_header(&self.header);
-        crate::crypto::sha256(&crate::crypto::sha256(&bytes))
+        self.header.hash()
+    }
+
+    /// Consensus-serialized size in bytes
+    pub fn serialized_size(&self) -> usize {
+        serialize_block_header(&self.header).len()
+            + self
+                .transactions
+                .iter()
+                .map(|tx| serialize_transaction(tx).len())
+                .sum::<usize>()
+    }
+
+    /// Consensus serialization: the header, then every
+    /// transaction in order
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = serialize_block_header(&self.header);
+        for tx in &self.transactions {
+            out.extend(serialize_transaction(tx));
+        }
+        out
     }
 
     pub fn verify_pow(&self) -> bool {
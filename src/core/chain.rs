@@ -6,8 +6,10 @@ use std::collections::HashMap;
 use std::fs;
 use std::env;
 use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
 
 use time::OffsetDateTime;
+use num_bigint::BigUint;
 
 use crate::consensus::{
     difficulty::calculate_next_target,
@@ -16,14 +18,22 @@ use crate::consensus::{
 
 use crate::{
     block::{Block, BlockHeader},
-    utxo::{UTXOSet, UTXO},
+    utxo::{utxo_stats, UTXOSet, UtxoStats, UTXO},
+    core::utxo_view::{UtxoSource, UtxoView},
     transaction::{Transaction, TxInput, TxOutput},
     revelation::revelation_tx,
     merkle::merkle_root,
+    validation::{
+        check_block_structure,
+        check_failure_error,
+        verify_input_checks,
+        InputCheck,
+        ValidationContext,
+    },
+    core::checkqueue::CheckQueue,
+    error::BlockError,
 };
 
-#[allow(dead_code)]
-const COINBASE_MATURITY: u64 = 100;
 const _CONSENSUS_V2_HEIGHT: u64 = 1000;
 
 // ─────────────────────────────────────────────
@@ -47,6 +57,34 @@ pub struct Blockchain {
     pub blocks: Vec<Block>,
     pub utxos: UTXOSet,
     pub mempool: Vec<Transaction>,
+    /// Clean UTXO entries kept cached while connecting blocks
+    utxo_cache_limit: usize,
+    /// Blocks dropped from the best chain by fork choice,
+    /// not yet handed to the mempool
+    disconnected: Vec<Block>,
+}
+
+/// Block that passed structural checks, awaiting signatures
+pub struct PendingBlock {
+    pub block: Block,
+    tip_hash: Option<Vec<u8>>,
+    checks: Vec<InputCheck>,
+}
+
+/// Validate and connect a block, holding the chain lock only
+/// for the structural and connect phases. Signature checks run
+/// on the `CheckQueue` workers in between.
+pub fn process_block(
+    chain: &Mutex<Blockchain>,
+    block: Block,
+    queue: &CheckQueue,
+) -> Result<(), BlockError> {
+    let mut pending = chain.lock().unwrap().prepare_block(block)?;
+    let checks = std::mem::take(&mut pending.checks);
+
+    queue.verify(checks).map_err(check_failure_error)?;
+
+    chain.lock().unwrap().connect_pending(pending)
 }
 
 /* ───────── Wallet layer (NON-CONSENSUS) ───────── */
@@ -61,20 +99,17 @@ impl Blockchain {
         let mut accumulated = 0;
         let mut inputs = Vec::new();
 
-        for (key, utxo) in &self.utxos {
+        for (outpoint, utxo) in &self.utxos {
             if utxo.pubkey_hash == from {
-                let parts: Vec<&str> = key.split(':').collect();
-                let txid = hex::decode(parts[0]).map_err(|_| "Bad txid")?;
-                let index: u32 = parts[1].parse().map_err(|_| "Bad index")?;
-
                 accumulated += utxo.value;
 
                 inputs.push(TxInput {
-                    txid,
-                    index,
+                    txid: outpoint.txid.to_vec(),
+                    index: outpoint.vout,
                     pubkey: vec![],
                     signature: vec![],
                     address_index: 0,
+                    script_sig: vec![],
                 });
 
                 if accumulated >= amount {
@@ -90,12 +125,14 @@ impl Blockchain {
         let mut outputs = vec![TxOutput {
             value: amount,
             pubkey_hash: to,
+            script_pubkey: vec![],
         }];
 
         if accumulated > amount {
             outputs.push(TxOutput {
                 value: accumulated - amount,
                 pubkey_hash: from,
+                script_pubkey: vec![],
             });
         }
 
@@ -113,11 +150,23 @@ impl Blockchain {
 
 /* ───────── Persistence helpers ───────── */
 
-fn data_dir() -> PathBuf {
-    let mut path = env::current_exe().unwrap();
-    path.pop();
-    path.push("data");
-    path
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keep chain data in `dir` instead of `data/` next to the
+/// executable; must be called before the chain is loaded
+pub fn set_data_dir(dir: PathBuf) {
+    let _ = DATA_DIR.set(dir);
+}
+
+pub(crate) fn data_dir() -> PathBuf {
+    DATA_DIR
+        .get_or_init(|| {
+            let mut path = env::current_exe().unwrap();
+            path.pop();
+            path.push("data");
+            path
+        })
+        .clone()
 }
 
 fn blocks_file() -> PathBuf {
@@ -132,18 +181,46 @@ fn utxos_file() -> PathBuf {
     path
 }
 
-fn median_time_past(chain: &[Block]) -> i64 {
-    let mut times: Vec<i64> = chain
-        .iter()
-        .rev()
-        .take(MTP_WINDOW)
-        .map(|b| b.header.timestamp)
-        .collect();
+/// Median of the last MTP_WINDOW timestamps (oldest first)
+///
+/// Shared by full blocks and bare header chains.
+pub fn median_time_past<I>(timestamps: I) -> i64
+where
+    I: IntoIterator<Item = i64>,
+    I::IntoIter: DoubleEndedIterator,
+{
+    let mut times: Vec<i64> = timestamps.into_iter().rev().take(MTP_WINDOW).collect();
 
     times.sort();
     times[times.len() / 2]
 }
 
+/// Spend the inputs and add the outputs of `block`
+fn apply_block<B: UtxoSource>(view: &mut UtxoView<B>, block: &Block) {
+    for (tx_index, tx) in block.transactions.iter().enumerate() {
+        for input in &tx.inputs {
+            if let Some(outpoint) = input.outpoint() {
+                view.spend(&outpoint);
+            }
+        }
+
+        let is_coinbase = tx_index == 0 && tx.is_coinbase();
+
+        for (outpoint, o) in tx.outpoints() {
+            view.add(
+                outpoint,
+                UTXO {
+                    value: o.value,
+                    pubkey_hash: o.pubkey_hash.clone(),
+                    height: block.header.height,
+                    is_coinbase,
+                    script_pubkey: o.script_pubkey.clone(),
+                },
+            );
+        }
+    }
+}
+
 /* ───────── Blockchain implementation ───────── */
 
 impl Blockchain {
@@ -152,13 +229,108 @@ impl Blockchain {
             blocks: Vec::new(),
             utxos: HashMap::new(),
             mempool: Vec::new(),
+            utxo_cache_limit: usize::MAX,
+            disconnected: Vec::new(),
         }
     }
 
+    /// Cumulative proof-of-work of the best chain
+    pub fn chain_work(&self) -> BigUint {
+        self.blocks
+            .iter()
+            .map(crate::consensus::fork_choice::block_work)
+            .sum()
+    }
+
+    /// Block locator for the best chain
+    ///
+    /// The last 10 block hashes, then hashes at doubling
+    /// distances back, always ending with genesis.
+    pub fn block_locator(&self) -> Vec<Vec<u8>> {
+        let mut locator = Vec::new();
+        let mut index = self.blocks.len() as i64 - 1;
+        let mut step = 1;
+
+        while index > 0 {
+            locator.push(self.blocks[index as usize].hash.clone());
+            if locator.len() >= 10 {
+                step *= 2;
+            }
+            index -= step;
+        }
+
+        if let Some(genesis) = self.blocks.first() {
+            locator.push(genesis.hash.clone());
+        }
+
+        locator
+    }
+
+    /// Height of the first locator entry on our best chain
+    pub fn find_fork(&self, locator: &[Vec<u8>]) -> Option<u64> {
+        locator.iter().find_map(|hash| {
+            self.blocks
+                .iter()
+                .position(|b| &b.hash == hash)
+                .map(|i| i as u64)
+        })
+    }
+
+    /// Up to `max` best-chain headers after the fork point with
+    /// `locator`, ending early at `stop_hash`
+    pub fn headers_after(&self, locator: &[Vec<u8>], stop_hash: &[u8], max: usize) -> Vec<BlockHeader> {
+        let start = match self.find_fork(locator) {
+            Some(height) => height as usize + 1,
+            None => 0,
+        };
+
+        let mut headers = Vec::new();
+        for block in self.blocks.iter().skip(start).take(max) {
+            headers.push(block.header.clone());
+            if block.hash == stop_hash {
+                break;
+            }
+        }
+
+        headers
+    }
+
+    /// Take blocks disconnected by reorgs since the last call
+    pub fn take_disconnected(&mut self) -> Vec<Block> {
+        std::mem::take(&mut self.disconnected)
+    }
+
+    /// UTXO set statistics at the current tip
+    pub fn utxo_stats(&self) -> UtxoStats {
+        utxo_stats(&self.utxos)
+    }
+
+    /// Bound the UTXO cache (RAM-first mobile policy)
+    pub fn set_utxo_cache_limit(&mut self, limit: usize) {
+        self.utxo_cache_limit = limit;
+    }
+
     pub fn height(&self) -> u64 {
         self.blocks.len() as u64
     }
 
+    /// Context for validating transactions for the next block
+    pub fn next_block_context(&self) -> ValidationContext {
+        let mtp = if self.blocks.is_empty() {
+            0
+        } else {
+            median_time_past(self.blocks.iter().map(|b| b.header.timestamp))
+        };
+        ValidationContext::new(self.height(), mtp)
+    }
+
+    /// Context for validating `block` on top of the current tip
+    fn block_context(&self, block: &Block) -> ValidationContext {
+        let mut ctx = self.next_block_context();
+        ctx.height = block.header.height;
+        ctx
+    }
+
     /// Load chain from disk or create genesis
     pub fn initialize(&mut self) {
         fs::create_dir_all(data_dir()).unwrap();
@@ -181,6 +353,7 @@ impl Blockchain {
                     nonce: GENESIS_NONCE,
                     target: GENESIS_TARGET,
                     merkle_root: hex::decode(GENESIS_MERKLE).unwrap(),
+                    utxo_commitment: vec![],
                 },
                 transactions: vec![revelation_tx()],
                 hash: hex::decode(GENESIS_HASH).unwrap(),
@@ -203,105 +376,204 @@ impl Blockchain {
         self.save_all();
     }
 
-pub fn validate_and_add_block(&mut self, block: Block) -> bool {
-    use crate::consensus::fork_choice;
+pub fn validate_and_add_block(&mut self, block: Block) -> Result<(), BlockError> {
+    let mut pending = self.prepare_block(block)?;
+    let checks = std::mem::take(&mut pending.checks);
 
-    // Basic height sanity
-    if block.header.height > self.height() + 1 {
-        return false;
-    }
+    verify_input_checks(&checks).map_err(check_failure_error)?;
+
+    self.accept_block(pending.block)
+}
+
+    /// Phase 1: every block rule except transaction signatures
+    ///
+    /// The returned checks can be verified without the chain
+    /// lock (see `process_block`).
+    pub fn prepare_block(&self, block: Block) -> Result<PendingBlock, BlockError> {
+        let ctx = self.block_context(&block);
 
-    // Timestamp rules
-    if !self.blocks.is_empty() {
-        let mtp = median_time_past(&self.blocks);
-        if block.header.timestamp <= mtp {
-            return false;
+        // Basic height sanity
+        if block.header.height > self.height() + 1 {
+            return Err(BlockError::BadHeight(block.header.height));
         }
 
-        if block.header.timestamp >
-            OffsetDateTime::now_utc().unix_timestamp() + MAX_FUTURE_DRIFT
-        {
-            return false;
+        // Timestamp rules
+        if !self.blocks.is_empty() {
+            if block.header.timestamp <= ctx.mtp {
+                return Err(BlockError::TimeTooOld);
+            }
+
+            if ctx.flags.check_future_drift
+                && block.header.timestamp >
+                    OffsetDateTime::now_utc().unix_timestamp() + ctx.params.max_future_drift
+            {
+                return Err(BlockError::TimeTooNew);
+            }
         }
-    }
 
-    // Difficulty must match expected target
-    if block.header.target != calculate_next_target(&self.blocks) {
-        return false;
-    }
+        // Difficulty must match expected target
+        if block.header.target != calculate_next_target(&self.blocks) {
+            return Err(BlockError::BadTarget);
+        }
+
+        // PoW validity
+        if !block.verify_pow() {
+            return Err(BlockError::BadProofOfWork);
+        }
+
+        // Merkle root
+        if merkle_root(&block.transactions) != block.header.merkle_root {
+            return Err(BlockError::BadMerkleRoot);
+        }
+
+        // Version-specific block rules and transactions need the
+        // UTXO set of the parent; side-branch blocks get them in
+        // `check_branch` before a reorg can connect them
+        let tip_hash = self.blocks.last().map(|tip| tip.hash.clone());
+        let extends_tip = tip_hash.as_ref() == Some(&block.header.prev_hash);
+
+        let checks = if extends_tip {
+            ctx.rules().check_block(&block, &self.utxos)?;
 
-    // PoW validity
-    if !block.verify_pow() {
-        return false;
+            if block.header.utxo_commitment != ctx.rules().utxo_commitment(&self.utxos) {
+                return Err(BlockError::BadUtxoCommitment);
+            }
+
+            // Transactions are consensus-checked only from v5;
+            // earlier blocks were accepted without it
+            if ctx.height >= CONSENSUS_V5_HEIGHT {
+                check_block_structure(&block, &self.utxos, &ctx)?
+            } else {
+                Vec::new()
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(PendingBlock { block, tip_hash, checks })
     }
 
-    // Merkle root
-    if merkle_root(&block.transactions) != block.header.merkle_root {
-        return false;
+    /// Phase 3: connect a prepared block whose checks passed
+    ///
+    /// If the tip moved while the checks ran, the block is
+    /// validated again from scratch.
+    pub fn connect_pending(&mut self, pending: PendingBlock) -> Result<(), BlockError> {
+        let tip_hash = self.blocks.last().map(|tip| tip.hash.clone());
+
+        if tip_hash != pending.tip_hash {
+            return self.validate_and_add_block(pending.block);
+        }
+
+        self.accept_block(pending.block)
     }
 
-    // Accept block (side branches allowed)
-    self.blocks.push(block);
-
-    // ─────────────────────────────────────────
-    // 🔒 CONSENSUS v4 FORK CHOICE
-    // Select chain with highest cumulative work
-    // ─────────────────────────────────────────
-    if let Some(best_hash) = fork_choice::best_tip(&self.blocks) {
-        let best_chain: Vec<Block> = {
-            let mut chain = Vec::new();
-            let mut current = best_hash;
-
-            while let Some(b) = self.blocks.iter().find(|x| x.hash == current) {
-                chain.push(b.clone());
-                if b.header.height == 0 {
-                    break;
+    /// Store a fully validated block and apply fork choice
+    fn accept_block(&mut self, block: Block) -> Result<(), BlockError> {
+        use crate::consensus::fork_choice;
+
+        let tip_len = self.blocks.len();
+
+        // Accept block (side branches allowed)
+        self.blocks.push(block);
+
+        // ─────────────────────────────────────────
+        // 🔒 CONSENSUS v4 FORK CHOICE
+        // Select chain with highest cumulative work
+        // ─────────────────────────────────────────
+        if let Some(best_hash) = fork_choice::best_tip(&self.blocks) {
+            let best_chain: Vec<Block> = {
+                let mut chain = Vec::new();
+                let mut current = best_hash;
+
+                while let Some(b) = self.blocks.iter().find(|x| x.hash == current) {
+                    chain.push(b.clone());
+                    if b.header.height == 0 {
+                        break;
+                    }
+                    current = b.header.prev_hash.clone();
+                }
+
+                chain.into_iter().rev().collect()
+            };
+
+            // A reorg connects side-branch blocks whose transactions
+            // were never checked; refuse it if any of them is invalid
+            let fork = best_chain
+                .iter()
+                .zip(&self.blocks[..tip_len])
+                .take_while(|(new, old)| new.hash == old.hash)
+                .count();
+
+            if fork < tip_len {
+                if let Err(e) = Self::check_branch(&best_chain, fork) {
+                    self.blocks.pop();
+                    return Err(e);
                 }
-                current = b.header.prev_hash.clone();
             }
 
-            chain.into_iter().rev().collect()
-        };
+            let old_chain = std::mem::replace(&mut self.blocks, best_chain);
+            for old in old_chain {
+                if !self.blocks.iter().any(|b| b.hash == old.hash) {
+                    self.disconnected.push(old);
+                }
+            }
 
-        self.blocks = best_chain;
-        self.rebuild_utxos();
-        self.save_all();
-        return true;
+            self.rebuild_utxos();
+            self.save_all();
+            return Ok(());
+        }
+
+        Err(BlockError::NoBestTip)
     }
 
-    false
-}
+    /// ⚠️ CONSENSUS — validate `chain[fork..]` block by block
+    /// against the UTXO set at each block's parent
+    ///
+    /// Runs every check `prepare_block` defers for blocks that
+    /// do not extend the tip, signatures included.
+    fn check_branch(chain: &[Block], fork: usize) -> Result<(), BlockError> {
+        let mut utxos = UTXOSet::new();
+
+        {
+            let mut view = UtxoView::new(&mut utxos);
+            for block in &chain[..fork] {
+                apply_block(&mut view, block);
+            }
+            view.flush();
+        }
+
+        for i in fork..chain.len() {
+            let block = &chain[i];
+            let mtp = median_time_past(chain[..i].iter().map(|b| b.header.timestamp));
+            let ctx = ValidationContext::new(block.header.height, mtp);
 
+            ctx.rules().check_block(block, &utxos)?;
+
+            if block.header.utxo_commitment != ctx.rules().utxo_commitment(&utxos) {
+                return Err(BlockError::BadUtxoCommitment);
+            }
+
+            if ctx.height >= CONSENSUS_V5_HEIGHT {
+                let checks = check_block_structure(block, &utxos, &ctx)?;
+                verify_input_checks(&checks).map_err(check_failure_error)?;
+            }
+
+            let mut view = UtxoView::new(&mut utxos);
+            apply_block(&mut view, block);
+            view.flush();
+        }
+
+        Ok(())
+    }
 
     pub fn rebuild_utxos(&mut self) {
         self.utxos.clear();
 
-        for block in &self.blocks {
-            for (tx_index, tx) in block.transactions.iter().enumerate() {
-                let txid = hex::encode(tx.txid());
-
-                for input in &tx.inputs {
-                    self.utxos.remove(&format!(
-                        "{}:{}",
-                        hex::encode(&input.txid),
-                        input.index
-                    ));
-                }
+        let mut view = UtxoView::with_limit(&mut self.utxos, self.utxo_cache_limit);
 
-                let is_coinbase = tx_index == 0 && tx.inputs.is_empty();
-
-                for (i, o) in tx.outputs.iter().enumerate() {
-                    self.utxos.insert(
-                        format!("{}:{}", txid, i),
-                        UTXO {
-                            value: o.value,
-                            pubkey_hash: o.pubkey_hash.clone(),
-                            height: block.header.height,
-                            is_coinbase,
-                        },
-                    );
-                }
-            }
+        for block in &self.blocks {
+            apply_block(&mut view, block);
+            view.flush();
         }
     }
 
@@ -0,0 +1,135 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::validation::{CheckFailure, InputCheck};
+
+/// Batch of checks shared by all workers
+struct Batch {
+    checks: Vec<InputCheck>,
+    /// Next unclaimed check
+    next: AtomicUsize,
+    /// Lowest failing check index (usize::MAX = none yet)
+    first_failure: AtomicUsize,
+}
+
+struct Job {
+    batch: Arc<Batch>,
+    done: Sender<()>,
+}
+
+/// Worker pool for deferred signature / script checks
+///
+/// Block connect runs its structural pass under the chain
+/// lock, then hands the input checks to this queue. Workers
+/// claim checks in small chunks; once a failure is known,
+/// checks positioned after it are skipped. The reported
+/// failure is always the lowest (tx, input) position.
+pub struct CheckQueue {
+    jobs: Mutex<Sender<Job>>,
+    workers: usize,
+}
+
+const CHUNK: usize = 16;
+
+impl CheckQueue {
+    /// Start a queue with `workers` threads (at least one)
+    pub fn new(workers: usize) -> Self {
+        let workers = workers.max(1);
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+
+        for _ in 0..workers {
+            let rx = Arc::clone(&rx);
+            thread::spawn(move || worker_loop(rx));
+        }
+
+        Self {
+            jobs: Mutex::new(tx),
+            workers,
+        }
+    }
+
+    /// Queue sized to the machine's cores
+    pub fn with_available_cores() -> Self {
+        let cores = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        Self::new(cores)
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Run every check, returning the first failing position
+    pub fn verify(&self, checks: Vec<InputCheck>) -> Result<(), CheckFailure> {
+        if checks.is_empty() {
+            return Ok(());
+        }
+
+        let batch = Arc::new(Batch {
+            checks,
+            next: AtomicUsize::new(0),
+            first_failure: AtomicUsize::new(usize::MAX),
+        });
+
+        let (done_tx, done_rx) = mpsc::channel();
+        {
+            let jobs = self.jobs.lock().unwrap();
+            for _ in 0..self.workers {
+                let _ = jobs.send(Job {
+                    batch: Arc::clone(&batch),
+                    done: done_tx.clone(),
+                });
+            }
+        }
+        drop(done_tx);
+
+        // Wait for every worker to finish this batch
+        while done_rx.recv().is_ok() {}
+
+        match batch.first_failure.load(Ordering::SeqCst) {
+            usize::MAX => Ok(()),
+            i => {
+                let c = &batch.checks[i];
+                Err((c.tx_index, c.input_index))
+            }
+        }
+    }
+}
+
+fn worker_loop(rx: Arc<Mutex<Receiver<Job>>>) {
+    loop {
+        let job = match rx.lock().unwrap().recv() {
+            Ok(j) => j,
+            Err(_) => return, // queue dropped
+        };
+
+        run_batch(&job.batch);
+        let _ = job.done.send(());
+    }
+}
+
+fn run_batch(batch: &Batch) {
+    let len = batch.checks.len();
+
+    loop {
+        let start = batch.next.fetch_add(CHUNK, Ordering::SeqCst);
+        if start >= len {
+            return;
+        }
+
+        for i in start..(start + CHUNK).min(len) {
+            // A lower failure is already known
+            if i > batch.first_failure.load(Ordering::SeqCst) {
+                return;
+            }
+
+            if !batch.checks[i].verify() {
+                batch.first_failure.fetch_min(i, Ordering::SeqCst);
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use thiserror::Error;
+
+/// Why a transaction failed consensus validation
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    #[error("script fields are not allowed before v5")]
+    ScriptBeforeActivation,
+
+    #[error("output value out of range")]
+    ValueOutOfRange,
+
+    #[error("script output hash does not match its script")]
+    ScriptHashMismatch,
+
+    #[error("input {0} spends the same outpoint twice")]
+    DuplicateInput(usize),
+
+    #[error("input {0} references a missing or spent output")]
+    MissingUtxo(usize),
+
+    #[error("input {0} spends an immature coinbase")]
+    ImmatureCoinbase(usize),
+
+    #[error("input {0} has an invalid signature or script")]
+    BadSignature(usize),
+
+    #[error("outputs exceed inputs")]
+    InsufficientInputs,
+}
+
+impl ValidationError {
+    /// Misbehavior points for the peer that relayed the transaction
+    ///
+    /// Missing or immature inputs may only mean our view of the
+    /// chain differs from the peer's, so they score nothing.
+    pub fn ban_score(&self) -> u32 {
+        match self {
+            ValidationError::MissingUtxo(_)
+            | ValidationError::ImmatureCoinbase(_)
+            | ValidationError::ScriptBeforeActivation => 0,
+            _ => 100,
+        }
+    }
+}
+
+/// Why a header chain was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HeaderError {
+    #[error("empty header chain")]
+    Empty,
+
+    #[error("first header is not a genesis header")]
+    BadGenesis,
+
+    #[error("header {0} has a non-sequential height")]
+    BadHeight(u64),
+
+    #[error("header {0} does not link to its predecessor")]
+    BadLinkage(u64),
+
+    #[error("header {0} timestamp not after median time past")]
+    TimeTooOld(u64),
+
+    #[error("header {0} has an unexpected difficulty target")]
+    BadTarget(u64),
+
+    #[error("header {0} has invalid proof-of-work")]
+    BadProofOfWork(u64),
+}
+
+/// Why a block was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BlockError {
+    #[error("block height {0} does not connect")]
+    BadHeight(u64),
+
+    #[error("timestamp not after median time past")]
+    TimeTooOld,
+
+    #[error("timestamp too far in the future")]
+    TimeTooNew,
+
+    #[error("unexpected difficulty target")]
+    BadTarget,
+
+    #[error("invalid proof-of-work")]
+    BadProofOfWork,
+
+    #[error("merkle root mismatch")]
+    BadMerkleRoot,
+
+    #[error("UTXO commitment mismatch")]
+    BadUtxoCommitment,
+
+    #[error("block exceeds size limit")]
+    Oversized,
+
+    #[error("block exceeds signature operation limit")]
+    TooManySigops,
+
+    #[error("block exceeds weight limit")]
+    Overweight,
+
+    #[error("block outputs exceed MAX_MONEY")]
+    ValueOutOfRange,
+
+    #[error("duplicate or overwriting txid")]
+    DuplicateTxid,
+
+    #[error("transaction {0} is an unexpected coinbase")]
+    UnexpectedCoinbase(usize),
+
+    #[error("coinbase pays more than the block reward plus fees")]
+    BadCoinbaseValue,
+
+    #[error("coinbase does not commit to the block height")]
+    BadCoinbaseHeight,
+
+    #[error("transaction {tx_index} invalid: {error}")]
+    InvalidTransaction {
+        tx_index: usize,
+        error: ValidationError,
+    },
+
+    #[error("no valid chain tip")]
+    NoBestTip,
+}
+
+impl BlockError {
+    /// Misbehavior points for the peer that relayed the block
+    ///
+    /// Timing and connection issues can happen to honest peers
+    /// (clock skew, races) and score nothing.
+    pub fn ban_score(&self) -> u32 {
+        match self {
+            BlockError::BadHeight(_)
+            | BlockError::TimeTooNew
+            | BlockError::NoBestTip => 0,
+            BlockError::TimeTooOld => 10,
+            BlockError::InvalidTransaction { error, .. } => error.ban_score(),
+            _ => 100,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// ─────────────────────────────────────────────
+// HEADER-CHAIN VALIDATION (STATELESS)
+//
+// Checks everything a header commits to without
+// block bodies or a UTXO set. Used by headers-first
+// sync and SPV clients.
+// ─────────────────────────────────────────────
+
+use num_bigint::BigUint;
+
+use super::block::BlockHeader;
+use super::chain::median_time_past;
+use super::error::HeaderError;
+use crate::consensus::difficulty::calculate_next_target_headers;
+use crate::consensus::fork_choice::header_work;
+use crate::pow::valid_pow;
+
+/// Cumulative proof-of-work of a header chain
+pub type ChainWork = BigUint;
+
+/// Validate a header chain starting at genesis
+///
+/// Checks height sequence, prev_hash linkage, PoW, MTP
+/// and the retarget of every header. The genesis header
+/// itself is only checked for shape and PoW; callers pin
+/// its hash.
+///
+/// Returns the total work of the chain.
+pub fn validate_header_chain(headers: &[BlockHeader]) -> Result<ChainWork, HeaderError> {
+    let genesis = headers.first().ok_or(HeaderError::Empty)?;
+
+    if genesis.height != 0 || genesis.prev_hash != vec![0u8; 32] {
+        return Err(HeaderError::BadGenesis);
+    }
+
+    if !valid_pow(&genesis.hash(), &genesis.target) {
+        return Err(HeaderError::BadProofOfWork(0));
+    }
+
+    Ok(header_work(genesis) + validate_headers_after(&headers[..1], &headers[1..])?)
+}
+
+/// Validate `headers` extending the known header chain
+/// `parents` (genesis first, already validated)
+///
+/// Same per-header checks as `validate_header_chain`; the
+/// first header must follow the last of `parents`.
+///
+/// Returns the work added by `headers`.
+pub fn validate_headers_after(
+    parents: &[BlockHeader],
+    headers: &[BlockHeader],
+) -> Result<ChainWork, HeaderError> {
+    let mut prev_hash = parents.last().ok_or(HeaderError::Empty)?.hash();
+    let mut chain = parents.to_vec();
+    let mut work = ChainWork::default();
+
+    for header in headers {
+        let height = chain.len() as u64;
+
+        if header.height != height {
+            return Err(HeaderError::BadHeight(header.height));
+        }
+
+        if header.prev_hash != prev_hash {
+            return Err(HeaderError::BadLinkage(header.height));
+        }
+
+        if header.timestamp <= median_time_past(chain.iter().map(|h| h.timestamp)) {
+            return Err(HeaderError::TimeTooOld(header.height));
+        }
+
+        if header.target != calculate_next_target_headers(&chain) {
+            return Err(HeaderError::BadTarget(header.height));
+        }
+
+        let hash = header.hash();
+        if !valid_pow(&hash, &header.target) {
+            return Err(HeaderError::BadProofOfWork(header.height));
+        }
+
+        work += header_work(header);
+        prev_hash = hash;
+        chain.push(header.clone());
+    }
+
+    Ok(work)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::params::{MAX_TARGET, MTP_WINDOW};
+
+    fn header_chain(len: usize) -> Vec<BlockHeader> {
+        let mut headers: Vec<BlockHeader> = Vec::new();
+
+        for height in 0..len as u64 {
+            let prev_hash = headers
+                .last()
+                .map(|h| h.hash())
+                .unwrap_or_else(|| vec![0u8; 32]);
+
+            headers.push(BlockHeader {
+                height,
+                timestamp: 1_730_000_000 + height as i64 * 60,
+                prev_hash,
+                nonce: 0,
+                target: MAX_TARGET,
+                merkle_root: vec![0u8; 32],
+                utxo_commitment: vec![],
+            });
+        }
+
+        headers
+    }
+
+    #[test]
+    fn accepts_linked_chain() {
+        let headers = header_chain(5);
+        let work = validate_header_chain(&headers).unwrap();
+
+        assert_eq!(work, header_work(&headers[0]) * BigUint::from(5u32));
+    }
+
+    #[test]
+    fn rejects_broken_linkage_and_old_timestamp() {
+        let mut headers = header_chain(5);
+        headers[3].prev_hash = vec![1u8; 32];
+        assert_eq!(validate_header_chain(&headers), Err(HeaderError::BadLinkage(3)));
+
+        let mut headers = header_chain(5);
+        headers[4].timestamp = headers[0].timestamp;
+        assert_eq!(validate_header_chain(&headers), Err(HeaderError::TimeTooOld(4)));
+    }
+
+    #[test]
+    fn rejects_bad_genesis_and_height() {
+        let mut headers = header_chain(3);
+        headers[0].prev_hash = vec![1u8; 32];
+        assert_eq!(validate_header_chain(&headers), Err(HeaderError::BadGenesis));
+
+        let mut headers = header_chain(5);
+        headers[2].height = 5;
+        assert_eq!(validate_header_chain(&headers), Err(HeaderError::BadHeight(5)));
+    }
+
+    #[test]
+    fn rejects_bad_target_and_proof_of_work() {
+        let mut headers = header_chain(5);
+        headers[2].target = [0x0f; 32];
+        assert_eq!(validate_header_chain(&headers), Err(HeaderError::BadTarget(2)));
+
+        let mut headers = header_chain(3);
+        headers[0].target = [0u8; 32];
+        assert_eq!(validate_header_chain(&headers), Err(HeaderError::BadProofOfWork(0)));
+    }
+
+    #[test]
+    fn validates_headers_after_known_parent() {
+        let headers = header_chain(6);
+        let work = validate_headers_after(&headers[..3], &headers[3..]).unwrap();
+        assert_eq!(work, header_work(&headers[0]) * BigUint::from(3u32));
+
+        assert_eq!(
+            validate_headers_after(&headers[..2], &headers[3..]),
+            Err(HeaderError::BadHeight(3))
+        );
+    }
+
+    #[test]
+    fn median_time_past_uses_last_window() {
+        let headers = header_chain(MTP_WINDOW + 5);
+        let mtp = median_time_past(headers.iter().map(|h| h.timestamp));
+
+        assert_eq!(mtp, headers[headers.len() - 1 - MTP_WINDOW / 2].timestamp);
+    }
+}
@@ -21,4 +21,72 @@ pub fn merkle_root(txs: &[Transaction]) -> Vec<u8> {
     }
 
     hashes[0].clone()
+}
+
+/// Sibling hashes from the txid at `index` up to the root,
+/// for SPV inclusion proofs (None if out of range)
+pub fn merkle_branch(txs: &[Transaction], mut index: usize) -> Option<Vec<Vec<u8>>> {
+    if index >= txs.len() {
+        return None;
+    }
+
+    let mut hashes: Vec<Vec<u8>> =
+        txs.iter().map(|t| t.txid()).collect();
+    let mut branch = Vec::new();
+
+    while hashes.len() > 1 {
+        if hashes.len() % 2 == 1 {
+            hashes.push(hashes.last().unwrap().clone());
+        }
+
+        branch.push(hashes[index ^ 1].clone());
+        index /= 2;
+
+        hashes = hashes
+            .chunks(2)
+            .map(|pair| sha256(&[pair[0].clone(), pair[1].clone()].concat()))
+            .collect();
+    }
+
+    Some(branch)
+}
+
+/// Whether `branch` links `txid` at `index` to `root`
+pub fn verify_merkle_branch(txid: &[u8], mut index: usize, branch: &[Vec<u8>], root: &[u8]) -> bool {
+    let mut hash = txid.to_vec();
+
+    for sibling in branch {
+        hash = if index % 2 == 0 {
+            sha256(&[hash, sibling.clone()].concat())
+        } else {
+            sha256(&[sibling.clone(), hash].concat())
+        };
+        index /= 2;
+    }
+
+    index == 0 && hash == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transaction::TxOutput;
+
+    #[test]
+    fn branches_verify_against_the_root() {
+        let txs: Vec<Transaction> = (0..5)
+            .map(|n| Transaction {
+                inputs: vec![],
+                outputs: vec![TxOutput { value: n, pubkey_hash: vec![1; 32], script_pubkey: vec![] }],
+            })
+            .collect();
+        let root = merkle_root(&txs);
+
+        for (i, tx) in txs.iter().enumerate() {
+            let branch = merkle_branch(&txs, i).unwrap();
+            assert!(verify_merkle_branch(&tx.txid(), i, &branch, &root));
+            assert!(!verify_merkle_branch(&tx.txid(), i ^ 1, &branch, &root));
+        }
+        assert!(merkle_branch(&txs, 5).is_none());
+    }
 }
\ No newline at end of file
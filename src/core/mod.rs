@@ -1,6 +1,11 @@
 pub mod block;
+pub mod headers;
 pub mod transaction;
+pub mod script;
 pub mod merkle;
 pub mod utxo;
+pub mod utxo_view;
+pub mod error;
 pub mod validation;
+pub mod checkqueue;
 pub mod chain;
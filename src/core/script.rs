@@ -0,0 +1,421 @@
+// ─────────────────────────────────────────────
+// CONSENSUS v5 — OUTPUT SCRIPTS
+//
+// Minimal stack-based script engine. Active only
+// from CONSENSUS_V5_HEIGHT; before that outputs are
+// locked by the implicit SHA256(pubkey) rule.
+// Adding or changing an opcode is a HARD FORK.
+// ─────────────────────────────────────────────
+
+use crate::crypto::{sha256, verify_signature};
+
+/// Script size / execution limits
+pub const MAX_SCRIPT_SIZE: usize = 10_000;
+pub const MAX_STACK_SIZE: usize = 1_000;
+pub const MAX_PUBKEYS_PER_MULTISIG: usize = 16;
+
+/// Push opcodes
+pub const OP_0: u8 = 0x00;
+pub const OP_PUSHBYTES_MAX: u8 = 0x4b;
+pub const OP_PUSHDATA1: u8 = 0x4c;
+pub const OP_PUSHDATA2: u8 = 0x4d;
+pub const OP_1: u8 = 0x51;
+pub const OP_16: u8 = 0x60;
+
+/// Flow / stack / crypto opcodes
+pub const OP_VERIFY: u8 = 0x69;
+pub const OP_DUP: u8 = 0x76;
+pub const OP_EQUAL: u8 = 0x87;
+pub const OP_EQUALVERIFY: u8 = 0x88;
+pub const OP_HASH: u8 = 0xa8; // SHA256, matches pubkey_hash()
+pub const OP_CHECKSIG: u8 = 0xac;
+pub const OP_CHECKMULTISIG: u8 = 0xae;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction<'a> {
+    Push(&'a [u8]),
+    Op(u8),
+}
+
+/// Split a script into instructions (None = truncated push)
+pub fn instructions(script: &[u8]) -> Option<Vec<Instruction<'_>>> {
+    let mut out = Vec::new();
+    let mut i = 0;
+
+    while i < script.len() {
+        let op = script[i];
+        i += 1;
+
+        let len = match op {
+            OP_0 => 0,
+            1..=OP_PUSHBYTES_MAX => op as usize,
+            OP_PUSHDATA1 => {
+                let n = *script.get(i)? as usize;
+                i += 1;
+                n
+            }
+            OP_PUSHDATA2 => {
+                let b = script.get(i..i + 2)?;
+                i += 2;
+                u16::from_le_bytes([b[0], b[1]]) as usize
+            }
+            _ => {
+                out.push(Instruction::Op(op));
+                continue;
+            }
+        };
+
+        out.push(Instruction::Push(script.get(i..i + len)?));
+        i += len;
+    }
+
+    Some(out)
+}
+
+/* ───────── Script builders ───────── */
+
+/// Append a minimal data push
+pub fn push_data(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        0 => script.push(OP_0),
+        n if n <= OP_PUSHBYTES_MAX as usize => script.push(n as u8),
+        n if n <= 0xff => {
+            script.push(OP_PUSHDATA1);
+            script.push(n as u8);
+        }
+        n => {
+            script.push(OP_PUSHDATA2);
+            script.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+    }
+    script.extend_from_slice(data);
+}
+
+/// Push a small integer (0..=16)
+pub fn push_int(script: &mut Vec<u8>, n: u8) {
+    if n == 0 {
+        script.push(OP_0);
+    } else {
+        script.push(OP_1 + n - 1);
+    }
+}
+
+/// OP_DUP OP_HASH <pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG
+///
+/// Equivalent to the pre-v5 implicit lock.
+pub fn pubkey_hash_script(pubkey_hash: &[u8]) -> Vec<u8> {
+    let mut s = vec![OP_DUP, OP_HASH];
+    push_data(&mut s, pubkey_hash);
+    s.push(OP_EQUALVERIFY);
+    s.push(OP_CHECKSIG);
+    s
+}
+
+/// <signature> <pubkey>
+pub fn pubkey_hash_unlock(signature: &[u8], pubkey: &[u8]) -> Vec<u8> {
+    let mut s = Vec::new();
+    push_data(&mut s, signature);
+    push_data(&mut s, pubkey);
+    s
+}
+
+/// <m> <pubkey_1> .. <pubkey_n> <n> OP_CHECKMULTISIG
+pub fn multisig_script(m: u8, pubkeys: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let n = pubkeys.len();
+    if m == 0 || m as usize > n || n > MAX_PUBKEYS_PER_MULTISIG {
+        return None;
+    }
+
+    let mut s = Vec::new();
+    push_int(&mut s, m);
+    for pk in pubkeys {
+        push_data(&mut s, pk);
+    }
+    push_int(&mut s, n as u8);
+    s.push(OP_CHECKMULTISIG);
+    Some(s)
+}
+
+/// <sig_1> .. <sig_m>, ordered as their pubkeys
+pub fn multisig_unlock(signatures: &[Vec<u8>]) -> Vec<u8> {
+    let mut s = Vec::new();
+    for sig in signatures {
+        push_data(&mut s, sig);
+    }
+    s
+}
+
+/// Recognise a multisig template, returning (m, pubkeys)
+pub fn parse_multisig(script: &[u8]) -> Option<(usize, Vec<Vec<u8>>)> {
+    let ins = instructions(script)?;
+    if ins.len() < 4 || ins.last()? != &Instruction::Op(OP_CHECKMULTISIG) {
+        return None;
+    }
+
+    let small = |i: &Instruction| match i {
+        Instruction::Op(op) if (OP_1..=OP_16).contains(op) => {
+            Some((op - OP_1 + 1) as usize)
+        }
+        _ => None,
+    };
+
+    let m = small(&ins[0])?;
+    let n = small(&ins[ins.len() - 2])?;
+
+    let pubkeys: Vec<Vec<u8>> = ins[1..ins.len() - 2]
+        .iter()
+        .map(|i| match i {
+            Instruction::Push(pk) => Some(pk.to_vec()),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    if pubkeys.len() != n || m > n {
+        return None;
+    }
+
+    Some((m, pubkeys))
+}
+
+/// Data pushes of a push-only script (e.g. collected signatures)
+pub fn pushed_data(script: &[u8]) -> Option<Vec<Vec<u8>>> {
+    instructions(script)?
+        .into_iter()
+        .map(|i| match i {
+            Instruction::Push(d) => Some(d.to_vec()),
+            Instruction::Op(_) => None,
+        })
+        .collect()
+}
+
+/// True if the script only pushes data
+pub fn is_push_only(script: &[u8]) -> bool {
+    match instructions(script) {
+        Some(ins) => ins.iter().all(|i| match i {
+            Instruction::Push(_) => true,
+            Instruction::Op(op) => (OP_1..=OP_16).contains(op),
+        }),
+        None => false,
+    }
+}
+
+/// Signature verifications a locking script can require
+///
+/// Multisig counts its declared key count; an unparsable
+/// or non-literal count is charged the maximum.
+pub fn count_sigops(script: &[u8]) -> usize {
+    let ins = match instructions(script) {
+        Some(i) => i,
+        None => return 0,
+    };
+
+    let mut count = 0;
+    let mut last_small: Option<usize> = None;
+
+    for ins in ins {
+        match ins {
+            Instruction::Op(OP_CHECKSIG) => count += 1,
+            Instruction::Op(OP_CHECKMULTISIG) => {
+                count += last_small.unwrap_or(MAX_PUBKEYS_PER_MULTISIG);
+            }
+            _ => {}
+        }
+
+        last_small = match ins {
+            Instruction::Op(op) if (OP_1..=OP_16).contains(&op) => {
+                Some((op - OP_1 + 1) as usize)
+            }
+            _ => None,
+        };
+    }
+
+    count
+}
+
+/* ───────── Interpreter ───────── */
+
+fn cast_to_bool(v: &[u8]) -> bool {
+    v.iter().any(|b| *b != 0)
+}
+
+fn small_int(v: &[u8]) -> Option<usize> {
+    match v {
+        [] => Some(0),
+        [n] if *n <= 16 => Some(*n as usize),
+        _ => None,
+    }
+}
+
+/// Execute one script against the shared stack
+fn eval(
+    script: &[u8],
+    stack: &mut Vec<Vec<u8>>,
+    sighash: &[u8],
+) -> Option<()> {
+    for ins in instructions(script)? {
+        match ins {
+            Instruction::Push(data) => stack.push(data.to_vec()),
+
+            Instruction::Op(op) if (OP_1..=OP_16).contains(&op) => {
+                stack.push(vec![op - OP_1 + 1]);
+            }
+
+            Instruction::Op(OP_DUP) => {
+                let top = stack.last()?.clone();
+                stack.push(top);
+            }
+
+            Instruction::Op(OP_HASH) => {
+                let top = stack.pop()?;
+                stack.push(sha256(&top));
+            }
+
+            Instruction::Op(OP_EQUAL) => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                stack.push(vec![(a == b) as u8]);
+            }
+
+            Instruction::Op(OP_EQUALVERIFY) => {
+                let a = stack.pop()?;
+                let b = stack.pop()?;
+                if a != b {
+                    return None;
+                }
+            }
+
+            Instruction::Op(OP_VERIFY) => {
+                if !cast_to_bool(&stack.pop()?) {
+                    return None;
+                }
+            }
+
+            Instruction::Op(OP_CHECKSIG) => {
+                let pubkey = stack.pop()?;
+                let sig = stack.pop()?;
+                let ok = verify_signature(sighash, &sig, &pubkey);
+                stack.push(vec![ok as u8]);
+            }
+
+            Instruction::Op(OP_CHECKMULTISIG) => {
+                let n = small_int(&stack.pop()?)?;
+                if n > MAX_PUBKEYS_PER_MULTISIG || stack.len() < n {
+                    return None;
+                }
+                let mut pubkeys = stack.split_off(stack.len() - n);
+
+                let m = small_int(&stack.pop()?)?;
+                if m > n || stack.len() < m {
+                    return None;
+                }
+                let sigs = stack.split_off(stack.len() - m);
+
+                // Signatures must appear in the same order as pubkeys
+                let mut ok = true;
+                for sig in &sigs {
+                    match pubkeys
+                        .iter()
+                        .position(|pk| verify_signature(sighash, sig, pk))
+                    {
+                        Some(pos) => {
+                            pubkeys.drain(..=pos);
+                        }
+                        None => {
+                            ok = false;
+                            break;
+                        }
+                    }
+                }
+
+                stack.push(vec![ok as u8]);
+            }
+
+            Instruction::Op(_) => return None,
+        }
+
+        if stack.len() > MAX_STACK_SIZE {
+            return None;
+        }
+    }
+
+    Some(())
+}
+
+/// Verify an input's unlocking script against the spent output
+///
+/// ⚠️ CONSENSUS CRITICAL (v5)
+pub fn verify_script(
+    script_sig: &[u8],
+    script_pubkey: &[u8],
+    sighash: &[u8],
+) -> bool {
+    if script_sig.len() > MAX_SCRIPT_SIZE
+        || script_pubkey.len() > MAX_SCRIPT_SIZE
+    {
+        return false;
+    }
+
+    // Unlocking scripts may only push data
+    if !is_push_only(script_sig) {
+        return false;
+    }
+
+    let mut stack = Vec::new();
+
+    if eval(script_sig, &mut stack, sighash).is_none() {
+        return false;
+    }
+
+    if eval(script_pubkey, &mut stack, sighash).is_none() {
+        return false;
+    }
+
+    matches!(stack.last(), Some(top) if cast_to_bool(top))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{secret_key_from_seed, public_key, pubkey_hash, sign};
+
+    #[test]
+    fn test_pubkey_hash_script_roundtrip() {
+        let sk = secret_key_from_seed(&[7u8; 32]);
+        let pk = public_key(&sk).serialize().to_vec();
+        let sighash = sha256(b"spend");
+
+        let lock = pubkey_hash_script(&pubkey_hash(&public_key(&sk)));
+        let unlock = pubkey_hash_unlock(&sign(&sighash, &sk), &pk);
+
+        assert!(verify_script(&unlock, &lock, &sighash));
+        assert!(!verify_script(&unlock, &lock, &sha256(b"other")));
+    }
+
+    #[test]
+    fn test_multisig_two_of_three() {
+        let keys: Vec<_> = (1u8..=3).map(|i| secret_key_from_seed(&[i; 32])).collect();
+        let pubkeys: Vec<Vec<u8>> = keys
+            .iter()
+            .map(|sk| public_key(sk).serialize().to_vec())
+            .collect();
+        let sighash = sha256(b"multisig");
+
+        let lock = multisig_script(2, &pubkeys).unwrap();
+        assert_eq!(parse_multisig(&lock), Some((2, pubkeys.clone())));
+
+        let sig = |i: usize| sign(&sighash, &keys[i]);
+
+        assert!(verify_script(&multisig_unlock(&[sig(0), sig(2)]), &lock, &sighash));
+        // Out of order signatures are rejected
+        assert!(!verify_script(&multisig_unlock(&[sig(2), sig(0)]), &lock, &sighash));
+        // Not enough signatures
+        assert!(!verify_script(&multisig_unlock(&[sig(1)]), &lock, &sighash));
+    }
+
+    #[test]
+    fn test_unlocking_script_must_be_push_only() {
+        let lock = vec![OP_1];
+        assert!(!verify_script(&[OP_DUP], &lock, &[]));
+        assert!(verify_script(&[], &lock, &[]));
+    }
+}
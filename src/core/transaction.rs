@@ -1,6 +1,8 @@
 use serde::{Serialize, Deserialize};
 use crate::crypto::sha256;
 use crate::consensus::serialize::serialize_transaction;
+use super::script::{pubkey_hash_script, pubkey_hash_unlock, multisig_script, push_data, pushed_data};
+use super::utxo::OutPoint;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TxInput {
@@ -9,12 +11,71 @@ pub struct TxInput {
     pub pubkey: Vec<u8>,       // compressed pubkey bytes
     pub signature: Vec<u8>,    // compact 64-byte sig
     pub address_index: u32,
+    #[serde(default)]
+    pub script_sig: Vec<u8>,   // v5: empty = <signature> <pubkey>
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TxOutput {
     pub value: u64,
     pub pubkey_hash: Vec<u8>,
+    #[serde(default)]
+    pub script_pubkey: Vec<u8>, // v5: empty = implicit pubkey_hash lock
+}
+
+impl TxInput {
+    /// Sole input of a v5 coinbase (BIP34-style): spends nothing,
+    /// its script_sig pushes `height` so every coinbase txid is new
+    pub fn coinbase(height: u64) -> Self {
+        let mut script_sig = Vec::new();
+        push_data(&mut script_sig, &height.to_le_bytes());
+
+        TxInput {
+            txid: vec![],
+            index: 0,
+            pubkey: vec![],
+            signature: vec![],
+            address_index: 0,
+            script_sig,
+        }
+    }
+
+    /// Output being spent (None for a malformed txid)
+    pub fn outpoint(&self) -> Option<OutPoint> {
+        OutPoint::new(&self.txid, self.index)
+    }
+
+    /// Unlocking script executed under v5 rules
+    pub fn unlocking_script(&self) -> Vec<u8> {
+        if self.script_sig.is_empty() {
+            pubkey_hash_unlock(&self.signature, &self.pubkey)
+        } else {
+            self.script_sig.clone()
+        }
+    }
+}
+
+impl TxOutput {
+    /// m-of-n multisig output (v5)
+    ///
+    /// `pubkey_hash` identifies the output as SHA256(script).
+    pub fn multisig(value: u64, m: u8, pubkeys: &[Vec<u8>]) -> Option<Self> {
+        let script_pubkey = multisig_script(m, pubkeys)?;
+        Some(TxOutput {
+            value,
+            pubkey_hash: sha256(&script_pubkey),
+            script_pubkey,
+        })
+    }
+
+    /// Locking script executed under v5 rules
+    pub fn locking_script(&self) -> Vec<u8> {
+        if self.script_pubkey.is_empty() {
+            pubkey_hash_script(&self.pubkey_hash)
+        } else {
+            self.script_pubkey.clone()
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,9 +90,49 @@ impl Transaction {
         sha256(&serialize_transaction(self))
     }
 
+    /// No inputs, or (v5) just `TxInput::coinbase`
+    pub fn is_coinbase(&self) -> bool {
+        match self.inputs.as_slice() {
+            [] => true,
+            [only] => only.txid.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Height committed by a v5 coinbase
+    pub fn coinbase_height(&self) -> Option<u64> {
+        let [input] = self.inputs.as_slice() else {
+            return None;
+        };
+        if !input.txid.is_empty() {
+            return None;
+        }
+
+        let pushes = pushed_data(&input.script_sig)?;
+        let height: [u8; 8] = pushes.first()?.as_slice().try_into().ok()?;
+        Some(u64::from_le_bytes(height))
+    }
+
+    /// Outputs created by this transaction, with their outpoints
+    pub fn outpoints(&self) -> impl Iterator<Item = (OutPoint, &TxOutput)> {
+        let txid: [u8; 32] = self.txid().try_into().expect("txid is 32 bytes");
+        self.outputs
+            .iter()
+            .enumerate()
+            .map(move |(i, o)| (OutPoint { txid, vout: i as u32 }, o))
+    }
+
     /// Message signed by each input (CONSENSUS)
-    pub fn sighash(&self) -> Vec<u8> {
-        sha256(&serialize_transaction(self))
+    ///
+    /// Same preimage as the txid, with every signature and
+    /// unlocking script blanked so signers can commit to it.
+    pub fn signature_hash(&self) -> Vec<u8> {
+        let mut stripped = self.clone();
+        for input in &mut stripped.inputs {
+            input.signature.clear();
+            input.script_sig.clear();
+        }
+        sha256(&serialize_transaction(&stripped))
     }
 
     /// Estimated serialized size (POLICY ONLY)
@@ -1,5 +1,59 @@
 use std::collections::HashMap;
-use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use super::script::pubkey_hash_script;
+use crate::consensus::serialize::serialize_utxo_entry;
+use crate::crypto::sha256;
+
+/// Reference to a transaction output
+///
+/// Serialized as "<hex txid>:<vout>" so it can key a JSON map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OutPoint {
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+impl OutPoint {
+    /// None unless `txid` is exactly 32 bytes
+    pub fn new(txid: &[u8], vout: u32) -> Option<Self> {
+        Some(OutPoint {
+            txid: txid.try_into().ok()?,
+            vout,
+        })
+    }
+}
+
+impl fmt::Display for OutPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", hex::encode(self.txid), self.vout)
+    }
+}
+
+impl FromStr for OutPoint {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (txid, vout) = s.split_once(':').ok_or("missing ':'")?;
+        let txid = hex::decode(txid).map_err(|_| "bad txid")?;
+        let vout = vout.parse().map_err(|_| "bad vout")?;
+        OutPoint::new(&txid, vout).ok_or("bad txid length")
+    }
+}
+
+impl Serialize for OutPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for OutPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct UTXO {
@@ -7,6 +61,54 @@ pub struct UTXO {
     pub pubkey_hash: Vec<u8>,
     pub height: u64,      // block height where this UTXO was created
     pub is_coinbase: bool,
+    #[serde(default)]
+    pub script_pubkey: Vec<u8>,
+}
+
+impl UTXO {
+    /// Locking script executed under v5 rules
+    pub fn locking_script(&self) -> Vec<u8> {
+        if self.script_pubkey.is_empty() {
+            pubkey_hash_script(&self.pubkey_hash)
+        } else {
+            self.script_pubkey.clone()
+        }
+    }
 }
 
-pub type UTXOSet = HashMap<String, UTXO>;
+pub type UTXOSet = HashMap<OutPoint, UTXO>;
+
+/// UTXO set summary (`gettxoutsetinfo`)
+#[derive(Debug, Clone, Serialize)]
+pub struct UtxoStats {
+    pub count: usize,
+    pub total_amount: u64,
+    /// Bytes of all entries in set-hash serialization
+    pub serialized_size: usize,
+    /// SHA256 over entries sorted by outpoint
+    pub hash: Vec<u8>,
+}
+
+/// Count, supply, size and deterministic hash of a UTXO set
+///
+/// Entries are hashed in outpoint order, so two nodes with
+/// the same set always report the same hash.
+pub fn utxo_stats(utxos: &UTXOSet) -> UtxoStats {
+    let mut entries: Vec<(&OutPoint, &UTXO)> = utxos.iter().collect();
+    entries.sort_by_key(|(outpoint, _)| **outpoint);
+
+    let mut preimage = Vec::new();
+    let mut total_amount = 0u64;
+
+    for (outpoint, utxo) in &entries {
+        preimage.extend(serialize_utxo_entry(outpoint, utxo));
+        total_amount = total_amount.saturating_add(utxo.value);
+    }
+
+    UtxoStats {
+        count: entries.len(),
+        total_amount,
+        serialized_size: preimage.len(),
+        hash: sha256(&preimage),
+    }
+}
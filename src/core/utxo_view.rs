@@ -0,0 +1,235 @@
+// ─────────────────────────────────────────────
+// UTXO VIEW — LAYERED CACHE (NON-CONSENSUS)
+//
+// An in-memory delta over a backing UTXO store.
+// Reads fall through to the base; writes stay in the
+// view until `flush()`. Views stack: a block view can
+// sit on top of the chain's view.
+// ─────────────────────────────────────────────
+
+use std::collections::HashMap;
+
+use super::utxo::{OutPoint, UTXOSet, UTXO};
+
+/// Read access to a UTXO store
+pub trait UtxoSource {
+    fn fetch(&self, outpoint: &OutPoint) -> Option<UTXO>;
+}
+
+/// Write access to a UTXO store (`None` = spent)
+pub trait UtxoSink: UtxoSource {
+    fn write(&mut self, outpoint: OutPoint, utxo: Option<UTXO>);
+}
+
+impl UtxoSource for UTXOSet {
+    fn fetch(&self, outpoint: &OutPoint) -> Option<UTXO> {
+        self.get(outpoint).cloned()
+    }
+}
+
+impl UtxoSink for UTXOSet {
+    fn write(&mut self, outpoint: OutPoint, utxo: Option<UTXO>) {
+        match utxo {
+            Some(u) => self.insert(outpoint, u),
+            None => self.remove(&outpoint),
+        };
+    }
+}
+
+impl<T: UtxoSource + ?Sized> UtxoSource for &T {
+    fn fetch(&self, outpoint: &OutPoint) -> Option<UTXO> {
+        (**self).fetch(outpoint)
+    }
+}
+
+impl<T: UtxoSource + ?Sized> UtxoSource for &mut T {
+    fn fetch(&self, outpoint: &OutPoint) -> Option<UTXO> {
+        (**self).fetch(outpoint)
+    }
+}
+
+impl<T: UtxoSink + ?Sized> UtxoSink for &mut T {
+    fn write(&mut self, outpoint: OutPoint, utxo: Option<UTXO>) {
+        (**self).write(outpoint, utxo)
+    }
+}
+
+struct CacheEntry {
+    /// None = spent in this view
+    utxo: Option<UTXO>,
+    /// Differs from the base
+    dirty: bool,
+}
+
+/// Delta cache over a UTXO store
+pub struct UtxoView<B> {
+    base: B,
+    cache: HashMap<OutPoint, CacheEntry>,
+    /// Clean entries beyond this are evicted on flush
+    max_entries: usize,
+}
+
+impl<B: UtxoSource> UtxoView<B> {
+    /// Unbounded view (block validation)
+    pub fn new(base: B) -> Self {
+        Self::with_limit(base, usize::MAX)
+    }
+
+    /// View keeping at most `max_entries` clean entries
+    pub fn with_limit(base: B, max_entries: usize) -> Self {
+        Self {
+            base,
+            cache: HashMap::new(),
+            max_entries,
+        }
+    }
+
+    /// Current coin at `outpoint`, None if missing or spent
+    pub fn get(&self, outpoint: &OutPoint) -> Option<UTXO> {
+        match self.cache.get(outpoint) {
+            Some(entry) => entry.utxo.clone(),
+            None => self.base.fetch(outpoint),
+        }
+    }
+
+    pub fn contains(&self, outpoint: &OutPoint) -> bool {
+        self.get(outpoint).is_some()
+    }
+
+    /// Create a coin
+    pub fn add(&mut self, outpoint: OutPoint, utxo: UTXO) {
+        self.cache.insert(outpoint, CacheEntry { utxo: Some(utxo), dirty: true });
+    }
+
+    /// Spend a coin, returning it if it existed
+    pub fn spend(&mut self, outpoint: &OutPoint) -> Option<UTXO> {
+        let prev = self.get(outpoint)?;
+        self.cache.insert(*outpoint, CacheEntry { utxo: None, dirty: true });
+        Some(prev)
+    }
+
+    /// Entries not yet written to the base
+    pub fn dirty_count(&self) -> usize {
+        self.cache.values().filter(|e| e.dirty).count()
+    }
+
+    pub fn cached_count(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Drop clean entries until the cache fits its limit
+    fn evict(&mut self) {
+        if self.cache.len() <= self.max_entries {
+            return;
+        }
+
+        let excess = self.cache.len() - self.max_entries;
+        let clean: Vec<OutPoint> = self
+            .cache
+            .iter()
+            .filter(|(_, e)| !e.dirty)
+            .map(|(op, _)| *op)
+            .take(excess)
+            .collect();
+
+        for op in clean {
+            self.cache.remove(&op);
+        }
+    }
+
+    /// Discard the view, returning its base untouched
+    pub fn into_base(self) -> B {
+        self.base
+    }
+}
+
+impl<B: UtxoSink> UtxoView<B> {
+    /// Write dirty entries to the base
+    ///
+    /// Spent entries are dropped; created ones stay cached
+    /// (clean) up to the memory limit. Returns the number
+    /// of entries written.
+    pub fn flush(&mut self) -> usize {
+        let mut written = 0;
+
+        self.cache.retain(|outpoint, entry| {
+            if entry.dirty {
+                self.base.write(*outpoint, entry.utxo.clone());
+                entry.dirty = false;
+                written += 1;
+            }
+            entry.utxo.is_some()
+        });
+
+        self.evict();
+        written
+    }
+}
+
+impl<B: UtxoSource> UtxoSource for UtxoView<B> {
+    fn fetch(&self, outpoint: &OutPoint) -> Option<UTXO> {
+        self.get(outpoint)
+    }
+}
+
+impl<B: UtxoSink> UtxoSink for UtxoView<B> {
+    fn write(&mut self, outpoint: OutPoint, utxo: Option<UTXO>) {
+        match utxo {
+            Some(u) => self.add(outpoint, u),
+            None => {
+                self.cache.insert(outpoint, CacheEntry { utxo: None, dirty: true });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin(value: u64) -> UTXO {
+        UTXO {
+            value,
+            pubkey_hash: vec![0u8; 32],
+            height: 1,
+            is_coinbase: false,
+            script_pubkey: vec![],
+        }
+    }
+
+    fn outpoint(n: u8) -> OutPoint {
+        OutPoint { txid: [n; 32], vout: 0 }
+    }
+
+    #[test]
+    fn writes_stay_in_view_until_flush() {
+        let mut base = UTXOSet::new();
+        base.insert(outpoint(1), coin(10));
+
+        let mut view = UtxoView::new(&mut base);
+        assert_eq!(view.spend(&outpoint(1)).map(|u| u.value), Some(10));
+        view.add(outpoint(2), coin(20));
+
+        assert!(!view.contains(&outpoint(1)));
+        assert_eq!(view.dirty_count(), 2);
+        assert_eq!(view.flush(), 2);
+        assert_eq!(view.dirty_count(), 0);
+
+        assert!(!base.contains_key(&outpoint(1)));
+        assert_eq!(base.get(&outpoint(2)).map(|u| u.value), Some(20));
+    }
+
+    #[test]
+    fn flush_evicts_clean_entries_over_limit() {
+        let mut base = UTXOSet::new();
+        let mut view = UtxoView::with_limit(&mut base, 2);
+
+        for n in 0..5 {
+            view.add(outpoint(n), coin(n as u64));
+        }
+
+        view.flush();
+        assert_eq!(view.cached_count(), 2);
+        assert_eq!(view.get(&outpoint(4)).map(|u| u.value), Some(4));
+    }
+}
@@ -1,74 +1,357 @@
-use super::transaction::Transaction;
-use super::utxo::UTXOSet;
-use crate::crypto::{verify_signature, pubkey_hash};
+use super::block::Block;
+use super::error::{BlockError, ValidationError};
+use super::transaction::{Transaction, TxInput};
+use super::utxo::{OutPoint, UTXOSet, UTXO};
+use super::utxo_view::UtxoView;
+use crate::consensus::rules::{rules_for_height, ConsensusRules};
+use crate::consensus::params::{NetworkParams, MAINNET};
 
-use secp256k1::PublicKey;
 use std::collections::HashSet;
 
-const COINBASE_MATURITY: u64 = 100;
+/// Optional checks, for replay and tests
+#[derive(Debug, Clone, Copy)]
+pub struct ValidationFlags {
+    /// Run deferred signature / script checks
+    pub verify_signatures: bool,
+    /// Enforce the MAX_FUTURE_DRIFT timestamp rule
+    pub check_future_drift: bool,
+}
+
+impl Default for ValidationFlags {
+    fn default() -> Self {
+        Self {
+            verify_signatures: true,
+            check_future_drift: true,
+        }
+    }
+}
+
+/// Everything a validator needs to know about where a
+/// transaction or block sits: the height it is validated
+/// at, the median time past of its parent, the network and
+/// any relaxed checks.
+#[derive(Debug, Clone, Copy)]
+pub struct ValidationContext {
+    pub height: u64,
+    pub mtp: i64,
+    pub params: &'static NetworkParams,
+    pub flags: ValidationFlags,
+}
+
+impl ValidationContext {
+    /// Mainnet context with all checks enabled
+    pub fn new(height: u64, mtp: i64) -> Self {
+        Self {
+            height,
+            mtp,
+            params: &MAINNET,
+            flags: ValidationFlags::default(),
+        }
+    }
+
+    pub fn with_flags(mut self, flags: ValidationFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Consensus rules active at this height
+    pub fn rules(&self) -> &'static dyn ConsensusRules {
+        rules_for_height(self.height)
+    }
+}
+
+/// Position of a failed signature check: (transaction index, input index)
+pub type CheckFailure = (usize, usize);
+
+/// Deferred signature / script check for one input
+#[derive(Clone)]
+pub struct InputCheck {
+    pub tx_index: usize,
+    pub input_index: usize,
+    pub height: u64,
+    pub input: TxInput,
+    pub utxo: UTXO,
+    pub sighash: Vec<u8>,
+}
+
+impl InputCheck {
+    pub fn verify(&self) -> bool {
+        rules_for_height(self.height)
+            .verify_input(&self.input, &self.utxo, &self.sighash)
+    }
+}
 
 /// ⚠️ CONSENSUS — MUST NOT CHANGE WITHOUT A VERSIONED FORK
 ///
 /// Transaction validation rules enforced by consensus.
+/// Version-specific rules come from `consensus::rules`.
 pub fn validate_transaction(
     tx: &Transaction,
     utxos: &UTXOSet,
-    current_height: u64,
-) -> bool {
+    ctx: &ValidationContext,
+) -> Result<(), ValidationError> {
+    let lookup = |outpoint: &OutPoint| utxos.get(outpoint).cloned();
+    validate_transaction_with(tx, &lookup, ctx)
+}
+
+/// `validate_transaction` against coins from `lookup` (e.g. the
+/// UTXO set plus unconfirmed outputs in the mempool)
+pub fn validate_transaction_with(
+    tx: &Transaction,
+    lookup: &dyn Fn(&OutPoint) -> Option<UTXO>,
+    ctx: &ValidationContext,
+) -> Result<(), ValidationError> {
+    let checks = check_transaction_structure(tx, 0, lookup, ctx)?;
+
+    if !ctx.flags.verify_signatures {
+        return Ok(());
+    }
+
+    match checks.iter().find(|c| !c.verify()) {
+        Some(c) => Err(ValidationError::BadSignature(c.input_index)),
+        None => Ok(()),
+    }
+}
+
+/// Structural phase: every rule except signatures
+///
+/// Returns the deferred signature checks.
+pub fn check_transaction_structure(
+    tx: &Transaction,
+    tx_index: usize,
+    lookup: &dyn Fn(&OutPoint) -> Option<UTXO>,
+    ctx: &ValidationContext,
+) -> Result<Vec<InputCheck>, ValidationError> {
+    let rules = ctx.rules();
+
+    rules.check_transaction(tx)?;
+
     // Coinbase tx
-    if tx.inputs.is_empty() {
-        return true;
+    if tx.is_coinbase() {
+        return Ok(Vec::new());
     }
 
-    let sighash = tx.sighash();
+    let sighash = rules.sighash(tx);
     let mut input_sum: u64 = 0;
     let mut output_sum: u64 = 0;
 
     let mut seen_outpoints = HashSet::new();
+    let mut checks = Vec::with_capacity(tx.inputs.len());
 
-    for input in &tx.inputs {
-        let key = format!("{}:{}", hex::encode(&input.txid), input.index);
+    for (input_index, input) in tx.inputs.iter().enumerate() {
+        let outpoint = input
+            .outpoint()
+            .ok_or(ValidationError::MissingUtxo(input_index))?;
 
         // Prevent same-UTXO double spend inside tx
-        if !seen_outpoints.insert(key.clone()) {
-            return false;
+        if !seen_outpoints.insert(outpoint) {
+            return Err(ValidationError::DuplicateInput(input_index));
         }
 
-        let utxo = match utxos.get(&key) {
+        let utxo = match lookup(&outpoint) {
             Some(u) => u,
-            None => return false,
+            None => return Err(ValidationError::MissingUtxo(input_index)),
         };
 
         // Coinbase maturity rule
         if utxo.is_coinbase {
-            if current_height < utxo.height + COINBASE_MATURITY {
-                return false;
+            if ctx.height < utxo.height + ctx.params.coinbase_maturity {
+                return Err(ValidationError::ImmatureCoinbase(input_index));
             }
         }
 
-        let pubkey = match PublicKey::from_slice(&input.pubkey) {
-            Ok(pk) => pk,
-            Err(_) => return false,
-        };
-
-        if pubkey_hash(&pubkey) != utxo.pubkey_hash {
-            return false;
-        }
+        input_sum = input_sum.saturating_add(utxo.value);
 
-        if !verify_signature(
-            &sighash,
-            &input.signature,
-            &pubkey.serialize(),
-        ) {
-            return false;
+        if matches!(rules.max_input_total(), Some(max) if input_sum > max) {
+            return Err(ValidationError::ValueOutOfRange);
         }
 
-        input_sum = input_sum.saturating_add(utxo.value);
+        checks.push(InputCheck {
+            tx_index,
+            input_index,
+            height: ctx.height,
+            input: input.clone(),
+            utxo,
+            sighash: sighash.clone(),
+        });
     }
 
     for output in &tx.outputs {
         output_sum = output_sum.saturating_add(output.value);
     }
 
-    input_sum >= output_sum
+    if input_sum < output_sum {
+        return Err(ValidationError::InsufficientInputs);
+    }
+
+    Ok(checks)
+}
+
+/// Run deferred input checks
+///
+/// With the `parallel-verify` feature checks are spread over
+/// all cores. Either way the reported failure is the lowest
+/// (tx, input) position, so results are deterministic.
+pub fn verify_input_checks(checks: &[InputCheck]) -> Result<(), CheckFailure> {
+    #[cfg(feature = "parallel-verify")]
+    let failed = {
+        use rayon::prelude::*;
+
+        checks
+            .par_iter()
+            .filter(|c| !c.verify())
+            .map(|c| (c.tx_index, c.input_index))
+            .min()
+    };
+
+    #[cfg(not(feature = "parallel-verify"))]
+    let failed = checks
+        .iter()
+        .find(|c| !c.verify())
+        .map(|c| (c.tx_index, c.input_index));
+
+    match failed {
+        Some(pos) => Err(pos),
+        None => Ok(()),
+    }
+}
+
+/// Map a failed deferred check to the block error it causes
+pub fn check_failure_error((tx_index, input_index): CheckFailure) -> BlockError {
+    BlockError::InvalidTransaction {
+        tx_index,
+        error: ValidationError::BadSignature(input_index),
+    }
+}
+
+/// ⚠️ CONSENSUS — structural phase of block transaction
+/// validation (from CONSENSUS_V5_HEIGHT)
+///
+/// Transactions are applied in order over `utxos`, so later
+/// transactions may spend outputs created earlier in the block.
+/// Everything except signatures; the returned checks can be
+/// run later (e.g. on a `CheckQueue`) without holding the chain.
+pub fn check_block_structure(
+    block: &Block,
+    utxos: &UTXOSet,
+    ctx: &ValidationContext,
+) -> Result<Vec<InputCheck>, BlockError> {
+    let height = ctx.height;
+    let mut view = UtxoView::new(utxos);
+    let mut checks = Vec::new();
+    let mut fees = 0u64;
+
+    for (tx_index, tx) in block.transactions.iter().enumerate() {
+        let is_coinbase = tx_index == 0 && tx.is_coinbase();
+
+        // Only the first transaction may be a coinbase
+        if tx.is_coinbase() && !is_coinbase {
+            return Err(BlockError::UnexpectedCoinbase(tx_index));
+        }
+
+        {
+            let lookup = |outpoint: &OutPoint| view.get(outpoint);
+
+            let tx_checks = check_transaction_structure(tx, tx_index, &lookup, ctx)
+                .map_err(|error| BlockError::InvalidTransaction { tx_index, error })?;
+
+            if !is_coinbase {
+                let input = tx_checks.iter().fold(0u64, |sum, c| sum.saturating_add(c.utxo.value));
+                let output = tx.outputs.iter().fold(0u64, |sum, o| sum.saturating_add(o.value));
+                fees = fees.saturating_add(input.saturating_sub(output));
+            }
+            checks.extend(tx_checks);
+        }
+
+        for outpoint in tx.inputs.iter().filter_map(|input| input.outpoint()) {
+            view.spend(&outpoint);
+        }
+
+        for (outpoint, o) in tx.outpoints() {
+            view.add(
+                outpoint,
+                UTXO {
+                    value: o.value,
+                    pubkey_hash: o.pubkey_hash.clone(),
+                    height,
+                    is_coinbase,
+                    script_pubkey: o.script_pubkey.clone(),
+                },
+            );
+        }
+    }
+
+    ctx.rules().check_coinbase_value(block, fees)?;
+
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transaction::TxOutput;
+
+    fn spend(outpoint: &OutPoint, value: u64) -> Transaction {
+        Transaction {
+            inputs: vec![TxInput {
+                txid: outpoint.txid.to_vec(),
+                index: outpoint.vout,
+                pubkey: vec![],
+                signature: vec![],
+                address_index: 0,
+                script_sig: vec![],
+            }],
+            outputs: vec![TxOutput {
+                value,
+                pubkey_hash: vec![1; 32],
+                script_pubkey: vec![],
+            }],
+        }
+    }
+
+    fn coin(value: u64, height: u64, is_coinbase: bool) -> UTXO {
+        UTXO {
+            value,
+            pubkey_hash: vec![1; 32],
+            height,
+            is_coinbase,
+            script_pubkey: vec![],
+        }
+    }
+
+    fn context(height: u64) -> ValidationContext {
+        ValidationContext::new(height, 0).with_flags(ValidationFlags {
+            verify_signatures: false,
+            check_future_drift: false,
+        })
+    }
+
+    #[test]
+    fn outputs_may_not_exceed_inputs() {
+        let outpoint = OutPoint::new(&[7; 32], 0).unwrap();
+        let mut utxos = UTXOSet::new();
+        utxos.insert(outpoint, coin(50_000, 0, false));
+        let ctx = context(10);
+
+        assert_eq!(validate_transaction(&spend(&outpoint, 50_000), &utxos, &ctx), Ok(()));
+        assert_eq!(
+            validate_transaction(&spend(&outpoint, 50_001), &utxos, &ctx),
+            Err(ValidationError::InsufficientInputs)
+        );
+    }
+
+    #[test]
+    fn coinbase_matures_after_context_maturity() {
+        let outpoint = OutPoint::new(&[7; 32], 0).unwrap();
+        let mut utxos = UTXOSet::new();
+        utxos.insert(outpoint, coin(50_000, 10, true));
+        let tx = spend(&outpoint, 50_000);
+        let mature = 10 + context(0).params.coinbase_maturity;
+
+        assert_eq!(
+            validate_transaction(&tx, &utxos, &context(mature - 1)),
+            Err(ValidationError::ImmatureCoinbase(0))
+        );
+        assert_eq!(validate_transaction(&tx, &utxos, &context(mature)), Ok(()));
+    }
 }
@@ -0,0 +1,112 @@
+use bech32::{FromBase32, ToBase32, Variant};
+
+use crate::consensus::params::MAINNET;
+
+/// Bech32 address of a 32-byte pubkey hash (or multisig
+/// script hash) on this network, e.g. `bc21…`
+pub fn encode_address(pubkey_hash: &[u8]) -> String {
+    bech32::encode(MAINNET.bech32_hrp, pubkey_hash.to_base32(), Variant::Bech32)
+        .expect("valid human-readable part")
+}
+
+/// Legacy-style Base58Check address: the network version
+/// byte, then the 32-byte hash
+pub fn encode_base58_address(pubkey_hash: &[u8]) -> String {
+    bs58::encode(pubkey_hash)
+        .with_check_version(MAINNET.base58_version)
+        .into_string()
+}
+
+/// Pubkey hash from an address in any supported encoding
+///
+/// Detects bech32 by its network prefix, then tries
+/// Base58Check. Raw hex is still accepted for compatibility,
+/// including the hex multisig scripts `payment_output` pays to.
+pub fn decode_address(address: &str) -> Result<Vec<u8>, &'static str> {
+    let address = address.trim();
+    let prefix = format!("{}1", MAINNET.bech32_hrp);
+
+    if address.to_lowercase().starts_with(&prefix) {
+        return decode_bech32(address);
+    }
+
+    if let Ok(data) = bs58::decode(address).with_check(None).into_vec() {
+        return decode_base58_payload(&data);
+    }
+
+    if let Ok(bytes) = hex::decode(address) {
+        return Ok(bytes);
+    }
+
+    // Well-formed, but for another network
+    decode_bech32(address)
+}
+
+fn decode_bech32(address: &str) -> Result<Vec<u8>, &'static str> {
+    let (hrp, data, variant) = bech32::decode(address).map_err(|_| "invalid address")?;
+    if hrp != MAINNET.bech32_hrp {
+        return Err("address is for another network");
+    }
+    if variant != Variant::Bech32 {
+        return Err("invalid address");
+    }
+
+    let hash = Vec::<u8>::from_base32(&data).map_err(|_| "invalid address")?;
+    if hash.len() != 32 {
+        return Err("invalid address length");
+    }
+
+    Ok(hash)
+}
+
+/// `data`: checksummed payload, version byte first
+fn decode_base58_payload(data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let (version, hash) = data.split_first().ok_or("invalid address")?;
+    if *version != MAINNET.base58_version {
+        return Err("address is for another network");
+    }
+    if hash.len() != 32 {
+        return Err("invalid address length");
+    }
+
+    Ok(hash.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_bad_checksums() {
+        let hash = vec![0x5a; 32];
+        let address = encode_address(&hash);
+
+        assert!(address.starts_with("bc21"));
+        assert_eq!(decode_address(&address), Ok(hash.clone()));
+        assert_eq!(decode_address(&address.to_uppercase()), Ok(hash.clone()));
+        assert_eq!(decode_address(&hex::encode(&hash)), Ok(hash));
+
+        // One character changed
+        let mut typo = address.into_bytes();
+        let last = typo.len() - 1;
+        typo[last] = if typo[last] == b'q' { b'p' } else { b'q' };
+        assert!(decode_address(&String::from_utf8(typo).unwrap()).is_err());
+
+        let other = bech32::encode("tb2", [1u8; 32].to_base32(), Variant::Bech32).unwrap();
+        assert!(decode_address(&other).is_err());
+    }
+
+    #[test]
+    fn detects_base58_addresses() {
+        let hash = vec![0x3c; 32];
+        let address = encode_base58_address(&hash);
+        assert_eq!(decode_address(&address), Ok(hash.clone()));
+
+        let other = bs58::encode(&hash).with_check_version(0x6f).into_string();
+        assert_eq!(decode_address(&other), Err("address is for another network"));
+
+        let mut typo = address.into_bytes();
+        typo[5] = if typo[5] == b'2' { b'3' } else { b'2' };
+        assert!(decode_address(&String::from_utf8(typo).unwrap()).is_err());
+    }
+}
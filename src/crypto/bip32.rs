@@ -0,0 +1,265 @@
+use hmac::{Hmac, Mac};
+use ripemd::Ripemd160;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::crypto::public_key;
+
+/// Child indexes at or above this are hardened
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// Mainnet xpub version bytes
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+/// BIP32 extended private key (key + chain code)
+#[derive(Clone)]
+pub struct ExtendedPrivKey {
+    pub secret_key: SecretKey,
+    pub chain_code: [u8; 32],
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+}
+
+/// BIP32 extended public key; derives non-hardened children only
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtendedPubKey {
+    pub public_key: PublicKey,
+    pub chain_code: [u8; 32],
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+}
+
+/// First 4 bytes of RIPEMD160(SHA256(pubkey))
+fn fingerprint(pk: &PublicKey) -> [u8; 4] {
+    let hash = Ripemd160::digest(Sha256::digest(pk.serialize()));
+    let mut out = [0u8; 4];
+    out.copy_from_slice(&hash[..4]);
+    out
+}
+
+fn hmac_sha512(key: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key size");
+    for p in parts {
+        mac.update(p);
+    }
+
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&mac.finalize().into_bytes());
+    out
+}
+
+impl ExtendedPrivKey {
+    /// Master key from a BIP39 seed
+    pub fn master(seed: &[u8]) -> Result<Self, &'static str> {
+        let i = hmac_sha512(b"Bitcoin seed", &[seed]);
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&i[32..]);
+
+        Ok(Self {
+            secret_key: SecretKey::from_slice(&i[..32]).map_err(|_| "invalid master key")?,
+            chain_code,
+            depth: 0,
+            parent_fingerprint: [0u8; 4],
+            child_number: 0,
+        })
+    }
+
+    /// CKDpriv; `index >= HARDENED` derives a hardened child
+    pub fn derive_child(&self, index: u32) -> Result<Self, &'static str> {
+        let i = if index >= HARDENED {
+            hmac_sha512(
+                &self.chain_code,
+                &[&[0u8], &self.secret_key.secret_bytes(), &index.to_be_bytes()],
+            )
+        } else {
+            hmac_sha512(
+                &self.chain_code,
+                &[&public_key(&self.secret_key).serialize(), &index.to_be_bytes()],
+            )
+        };
+
+        let mut tweak = [0u8; 32];
+        tweak.copy_from_slice(&i[..32]);
+        let tweak = Scalar::from_be_bytes(tweak).map_err(|_| "invalid child key")?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&i[32..]);
+
+        Ok(Self {
+            secret_key: self.secret_key.add_tweak(&tweak).map_err(|_| "invalid child key")?,
+            chain_code,
+            depth: self.depth.checked_add(1).ok_or("derivation too deep")?,
+            parent_fingerprint: fingerprint(&public_key(&self.secret_key)),
+            child_number: index,
+        })
+    }
+
+    /// Derive along a path such as `m/44'/0'/0'/0/5`
+    pub fn derive_path(&self, path: &str) -> Result<Self, &'static str> {
+        let mut key = self.clone();
+
+        for step in parse_path(path)? {
+            key = key.derive_child(step)?;
+        }
+
+        Ok(key)
+    }
+
+    /// Neutered (public) counterpart
+    pub fn public(&self) -> ExtendedPubKey {
+        ExtendedPubKey {
+            public_key: public_key(&self.secret_key),
+            chain_code: self.chain_code,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+        }
+    }
+
+    /// Best-effort wipe of key material (on wallet lock)
+    pub fn wipe(&mut self) {
+        self.secret_key.non_secure_erase();
+        self.chain_code = [0u8; 32];
+    }
+}
+
+impl ExtendedPubKey {
+    /// CKDpub (non-hardened `index` only)
+    pub fn derive_child(&self, index: u32) -> Result<Self, &'static str> {
+        if index >= HARDENED {
+            return Err("cannot derive hardened child from xpub");
+        }
+
+        let i = hmac_sha512(&self.chain_code, &[&self.public_key.serialize(), &index.to_be_bytes()]);
+
+        let mut tweak = [0u8; 32];
+        tweak.copy_from_slice(&i[..32]);
+        let tweak = Scalar::from_be_bytes(tweak).map_err(|_| "invalid child key")?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&i[32..]);
+
+        let secp = Secp256k1::verification_only();
+
+        Ok(Self {
+            public_key: self
+                .public_key
+                .add_exp_tweak(&secp, &tweak)
+                .map_err(|_| "invalid child key")?,
+            chain_code,
+            depth: self.depth.checked_add(1).ok_or("derivation too deep")?,
+            parent_fingerprint: fingerprint(&self.public_key),
+            child_number: index,
+        })
+    }
+
+    /// Base58Check `xpub...` string
+    pub fn to_base58(&self) -> String {
+        let mut data = Vec::with_capacity(78);
+        data.extend_from_slice(&XPUB_VERSION);
+        data.push(self.depth);
+        data.extend_from_slice(&self.parent_fingerprint);
+        data.extend_from_slice(&self.child_number.to_be_bytes());
+        data.extend_from_slice(&self.chain_code);
+        data.extend_from_slice(&self.public_key.serialize());
+
+        bs58::encode(data).with_check().into_string()
+    }
+
+    pub fn from_base58(s: &str) -> Result<Self, &'static str> {
+        let data = bs58::decode(s.trim())
+            .with_check(None)
+            .into_vec()
+            .map_err(|_| "invalid xpub encoding")?;
+
+        if data.len() != 78 {
+            return Err("invalid xpub length");
+        }
+        if data[..4] != XPUB_VERSION {
+            return Err("not a mainnet xpub");
+        }
+
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&data[5..9]);
+        let mut child_number = [0u8; 4];
+        child_number.copy_from_slice(&data[9..13]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&data[13..45]);
+
+        Ok(Self {
+            public_key: PublicKey::from_slice(&data[45..]).map_err(|_| "invalid xpub key")?,
+            chain_code,
+            depth: data[4],
+            parent_fingerprint,
+            child_number: u32::from_be_bytes(child_number),
+        })
+    }
+}
+
+/// `m/44'/0'/0'` → [44 | HARDENED, HARDENED, HARDENED]
+pub fn parse_path(path: &str) -> Result<Vec<u32>, &'static str> {
+    let mut steps = path.split('/');
+
+    if steps.next() != Some("m") {
+        return Err("path must start with m");
+    }
+
+    steps
+        .map(|s| {
+            let (num, hardened) = match s.strip_suffix('\'').or_else(|| s.strip_suffix('h')) {
+                Some(n) => (n, true),
+                None => (s, false),
+            };
+
+            let index: u32 = num.parse().map_err(|_| "invalid path index")?;
+            if index >= HARDENED {
+                return Err("path index out of range");
+            }
+
+            Ok(if hardened { index | HARDENED } else { index })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP32 test vector 1
+    #[test]
+    fn matches_bip32_test_vector() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedPrivKey::master(&seed).unwrap();
+
+        assert_eq!(
+            hex::encode(master.secret_key.secret_bytes()),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+
+        let child = master.derive_path("m/0'/1/2'").unwrap();
+        assert_eq!(
+            hex::encode(child.secret_key.secret_bytes()),
+            "cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca"
+        );
+        assert_eq!(
+            hex::encode(child.chain_code),
+            "04466b9cc8e161e966409ca52986c584f07e9dc81f735db683c3ff6ec7b1503f"
+        );
+        assert_eq!(child.depth, 3);
+
+        assert_eq!(
+            master.derive_path("m/0'").unwrap().public().to_base58(),
+            "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw"
+        );
+
+        // Public derivation agrees with private derivation
+        let account = master.derive_path("m/0'").unwrap();
+        let xpub = ExtendedPubKey::from_base58(&account.public().to_base58()).unwrap();
+        assert_eq!(xpub.derive_child(1).unwrap(), account.derive_child(1).unwrap().public());
+
+        assert!(parse_path("44'/0'").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use secp256k1::{
+    Secp256k1, SecretKey, Message,
+    ecdsa::{RecoverableSignature, RecoveryId},
+};
+
+use super::signature::{pubkey_hash, sha256};
+
+/// Domain separator: a signed message can never be a valid
+/// transaction signature, since sighashes never start with it
+pub const MESSAGE_PREFIX: &[u8] = b"Bitcoin Revelation Signed Message:\n";
+
+/// Recovery id byte + 64-byte compact signature
+pub const MESSAGE_SIGNATURE_LEN: usize = 65;
+
+/// Digest signed for `message`: double SHA256 of the prefix,
+/// the message length (u64 LE) and the message
+fn message_digest(message: &str) -> Vec<u8> {
+    let mut data = MESSAGE_PREFIX.to_vec();
+    data.extend_from_slice(&(message.len() as u64).to_le_bytes());
+    data.extend_from_slice(message.as_bytes());
+    sha256(&sha256(&data))
+}
+
+/// Sign `message` with `sk`; the public key is recoverable from
+/// the signature, so verifying needs only the address
+pub fn sign_message(message: &str, sk: &SecretKey) -> Vec<u8> {
+    let secp = Secp256k1::new();
+    let digest = Message::from_digest_slice(&message_digest(message)).expect("32 bytes");
+
+    let (recovery_id, compact) = secp.sign_ecdsa_recoverable(&digest, sk).serialize_compact();
+
+    let mut sig = vec![recovery_id.to_i32() as u8];
+    sig.extend_from_slice(&compact);
+    sig
+}
+
+/// True if `sig` signs `message` with the key behind
+/// `address_hash` (the decoded address)
+pub fn verify_message(address_hash: &[u8], message: &str, sig: &[u8]) -> bool {
+    if sig.len() != MESSAGE_SIGNATURE_LEN {
+        return false;
+    }
+
+    let recovered = RecoveryId::from_i32(sig[0] as i32)
+        .and_then(|id| RecoverableSignature::from_compact(&sig[1..], id))
+        .and_then(|s| {
+            let digest = Message::from_digest_slice(&message_digest(message))?;
+            Secp256k1::verification_only().recover_ecdsa(&digest, &s)
+        });
+
+    match recovered {
+        Ok(pubkey) => pubkey_hash(&pubkey) == address_hash,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{public_key, secret_key_from_seed};
+
+    #[test]
+    fn verifies_only_the_signed_message_and_address() {
+        let sk = secret_key_from_seed(&[7u8; 32]);
+        let address = pubkey_hash(&public_key(&sk));
+        let sig = sign_message("I own this address", &sk);
+
+        assert!(verify_message(&address, "I own this address", &sig));
+        assert!(!verify_message(&address, "I own that address", &sig));
+        assert!(!verify_message(&[0u8; 32], "I own this address", &sig));
+        assert!(!verify_message(&address, "I own this address", &sig[1..]));
+    }
+}
@@ -1,4 +1,7 @@
 pub mod signature;
+pub mod bip32;
+pub mod address;
+pub mod message;
 
 pub use signature::{
     sha256,
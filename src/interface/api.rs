@@ -1,42 +1,175 @@
 use tokio::net::TcpListener;
 use serde::{Serialize, Deserialize};
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use axum::{
     Router,
     Json,
+    middleware,
     routing::{get, post},
-    extract::{State, Path},
-    http::StatusCode,
+    extract::{State, Path, Query},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
+    response::sse::{Event, KeepAlive, Sse},
 };
+use axum_server::tls_rustls::RustlsConfig;
+use futures::stream::{self, Stream};
 
+use crate::block::{Block, BlockHeader};
 use crate::chain::Blockchain;
+use crate::config::{load_network_config, NodeConfig};
+use crate::consensus::difficulty::calculate_next_target;
+use crate::consensus::params::COINBASE_MATURITY;
+use crate::consensus::serialize::serialize_transaction;
+use crate::crypto::address::{decode_address, encode_address};
+use crate::crypto::message::verify_message;
+use crate::node::fee_estimator::FALLBACK_FEE_RATE;
+use crate::interface::auth::{require_auth, ApiAuth};
+use crate::interface::cors::{cors, Cors};
+use crate::interface::explorer;
+use crate::interface::rate_limit::{rate_limit, RateLimiter};
+use crate::merkle::merkle_branch;
+use crate::node::address_index::AddressIndex;
+use crate::node::bus::{block_events, BusEvent, EventBus};
+use crate::node::events::{EventLog, NodeEvent, EVENT_LOG_CAPACITY};
+use crate::node::mempool::{Mempool, MempoolError};
+use crate::node::miner::{self, block_template, HashrateStats, PayoutShare};
+use crate::node::p2p::{P2PNetwork, PeerStats};
+use crate::node::thermal::ThermalState;
+use crate::node::RuntimePolicy;
+use crate::pow::valid_pow;
 use crate::reward::block_reward;
+use crate::transaction::Transaction;
+use crate::wallet::{calculate_balances, FeeRate, DEFAULT_AUTO_LOCK};
+use crate::wallet_history::{HistoryEntry, WalletHistory};
+use crate::wallet_manager::WalletManager;
 
-const COINBASE_MATURITY: u64 = 100;
+/// Confirmation target for the estimated fee of wallet sends
+const DEFAULT_CONFIRM_TARGET: u64 = 6;
 
 #[derive(Clone)]
 struct AppState {
     chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+    p2p: Arc<P2PNetwork>,
+    history: Arc<Mutex<WalletHistory>>,
+    wallets: Arc<Mutex<WalletManager>>,
+    /// Templates handed to external miners, by merkle root (hex)
+    templates: Arc<Mutex<HashMap<String, Block>>>,
+    /// Where accepted transactions and submitted blocks are published
+    bus: Arc<EventBus>,
+    events: Arc<EventLog>,
+    /// Caught up with the chain by `/address/:address/txs`
+    addresses: Arc<Mutex<AddressIndex>>,
+    /// Supply and rich-list figures for the tip they were computed at
+    stats: Arc<Mutex<Option<StatsCache>>>,
+    policy: RuntimePolicy,
 }
 
-pub async fn start_api(chain: Arc<Mutex<Blockchain>>, port: u16) {
-    let state = AppState { chain };
+pub async fn start_api(
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+    p2p: Arc<P2PNetwork>,
+    history: Arc<Mutex<WalletHistory>>,
+    wallets: Arc<Mutex<WalletManager>>,
+    bus: Arc<EventBus>,
+    node_config: NodeConfig,
+) {
+    let events = Arc::new(EventLog::new());
+    events.subscribe(&bus);
+
+    let state = AppState {
+        chain,
+        mempool,
+        p2p,
+        history,
+        wallets,
+        templates: Arc::new(Mutex::new(HashMap::new())),
+        bus,
+        events,
+        addresses: Arc::new(Mutex::new(AddressIndex::new())),
+        stats: Arc::new(Mutex::new(None)),
+        policy: RuntimePolicy::from_config(&node_config),
+    };
+
+    let html = explorer::router(
+        Arc::clone(&state.chain),
+        Arc::clone(&state.mempool),
+        Arc::clone(&state.addresses),
+    );
+
+    let config = load_network_config();
+    let limiter = Arc::new(RateLimiter::new(&config.api_rate_limit));
+    let cors_policy = Arc::new(Cors::new(&config.api_cors_origins));
+    let auth = Arc::new(ApiAuth::new(node_config.api.auth_token.clone()));
+    let addr = SocketAddr::new(auth.bind_ip(), node_config.ports.api);
 
     let app = Router::new()
         .route("/status", get(status))
         .route("/blocks", get(blocks))
+        .route("/blocks/tip", get(blocks_tip))
         .route("/block/height/:height", get(block_by_height))
+        .route("/block/:hash/raw", get(block_raw))
+        .route("/block/:hash/verbose", get(block_verbose))
         .route("/tx/:txid", get(tx_by_id))
-        .route("/address/:hash", get(address_info))
+        .route("/tx/:txid/raw", get(tx_raw))
+        .route("/tx/:txid/verbose", get(tx_verbose))
+        .route("/tx/:txid/merkleproof", get(tx_merkle_proof))
+        .route("/txoutsetinfo", get(txoutset_info))
+        .route("/stats/supply", get(stats_supply))
+        .route("/stats/richlist", get(stats_richlist))
+        .route("/address/:address", get(address_info))
+        .route("/address/:address/txs", get(address_txs))
+        .route("/mempool", get(mempool_info))
+        .route("/mempool/info", get(mempool_info))
+        .route("/mempool/txids", get(mempool_txids))
+        .route("/mempool/tx/:txid", get(mempool_tx))
+        .route("/estimatefee/:target_blocks", get(estimate_fee))
+        .route("/peers", get(peers))
+        .route("/health", get(health))
+        .route("/sync", get(sync_status))
+        .route("/events", get(events))
+        .route("/events/recent", get(events_recent))
+        .route("/thermal", get(thermal))
+        .route("/wallet/history", get(wallet_history))
+        .route("/wallets", get(wallet_list))
+        .route("/wallet/:name/unlock", post(wallet_unlock))
+        .route("/wallet/:name/lock", post(wallet_lock))
+        .route("/wallet/:name/address", get(wallet_address))
+        .route("/wallet/:name/balance", get(wallet_balance))
+        .route("/wallet/:name/send", post(wallet_send))
+        .route("/wallet/:name/signmessage", post(wallet_sign_message))
+        .route("/verifymessage", post(verify_message_handler))
         .route("/transactions/new", post(new_transaction))
-        .with_state(state);
+        .route("/transactions/submit", post(submit_transaction))
+        .route("/mining/template/:address", get(mining_template))
+        .route("/mining/submit", post(mining_submit))
+        .route("/mining/info", get(mining_info))
+        .with_state(state)
+        .merge(html)
+        .layer(middleware::from_fn_with_state(auth, require_auth))
+        .layer(middleware::from_fn_with_state(limiter, rate_limit))
+        .layer(middleware::from_fn_with_state(cors_policy, cors));
+
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    let listener = TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // 🔐 Phones reach the API over untrusted networks
+    match config.api_tls {
+        Some(tls) => {
+            let rustls = RustlsConfig::from_pem_file(&tls.cert, &tls.key)
+                .await
+                .expect("invalid API TLS certificate or key");
+            axum_server::bind_rustls(addr, rustls).serve(service).await.unwrap();
+        }
+        None => {
+            let listener = TcpListener::bind(addr).await.unwrap();
+            axum::serve(listener, service).await.unwrap();
+        }
+    }
 }
 
 //
@@ -88,7 +221,7 @@ async fn status(State(state): State<AppState>) -> Json<StatusResponse> {
         height,
         blocks: c.blocks.len(),
         utxos: c.utxos.len(),
-        mempool: c.mempool.len(),
+        mempool: state.mempool.lock().unwrap().size(),
 
         total_issued,
         utxo_supply,
@@ -96,6 +229,149 @@ async fn status(State(state): State<AppState>) -> Json<StatusResponse> {
     })
 }
 
+//
+// ─── SUPPLY STATS ─────────────────────────────────
+//
+
+/// Addresses kept in the cached rich list
+const MAX_RICHLIST: usize = 1_000;
+
+const DEFAULT_RICHLIST_LIMIT: usize = 100;
+
+#[derive(Serialize, Clone)]
+struct SupplyResponse {
+    height: u64,
+    /// Sum of the block subsidies up to the tip
+    expected_emission: u64,
+    /// Value of all unspent outputs
+    utxo_supply: u64,
+    /// Unspent value minus immature coinbase
+    circulating_supply: u64,
+    immature_supply: u64,
+    /// Subsidy never claimed or burnt (expected - UTXO supply)
+    missing: u64,
+}
+
+#[derive(Serialize, Clone)]
+struct RichListEntry {
+    address: String,
+    balance: u64,
+    utxos: usize,
+}
+
+/// Whole-UTXO-set scans, redone only when the tip changes
+struct StatsCache {
+    tip: Vec<u8>,
+    supply: SupplyResponse,
+    /// Largest balances first, at most MAX_RICHLIST
+    richlist: Vec<RichListEntry>,
+}
+
+impl StatsCache {
+    fn compute(c: &Blockchain) -> Self {
+        let height = c.height();
+
+        let expected_emission = c
+            .blocks
+            .iter()
+            .fold(0u64, |sum, b| sum.saturating_add(block_reward(b.header.height)));
+
+        let mut utxo_supply = 0u64;
+        let mut immature_supply = 0u64;
+        let mut balances: HashMap<&[u8], (u64, usize)> = HashMap::new();
+
+        for u in c.utxos.values() {
+            utxo_supply = utxo_supply.saturating_add(u.value);
+            if u.is_coinbase && height < u.height + COINBASE_MATURITY {
+                immature_supply = immature_supply.saturating_add(u.value);
+            }
+
+            let (balance, count) = balances.entry(&u.pubkey_hash).or_default();
+            *balance = balance.saturating_add(u.value);
+            *count += 1;
+        }
+
+        let mut richlist: Vec<RichListEntry> = balances
+            .into_iter()
+            .map(|(hash, (balance, utxos))| RichListEntry { address: encode_address(hash), balance, utxos })
+            .collect();
+        richlist.sort_by(|a, b| b.balance.cmp(&a.balance).then_with(|| a.address.cmp(&b.address)));
+        richlist.truncate(MAX_RICHLIST);
+
+        Self {
+            tip: c.blocks.last().map(|b| b.hash.clone()).unwrap_or_default(),
+            supply: SupplyResponse {
+                height,
+                expected_emission,
+                utxo_supply,
+                circulating_supply: utxo_supply - immature_supply,
+                immature_supply,
+                missing: expected_emission.saturating_sub(utxo_supply),
+            },
+            richlist,
+        }
+    }
+}
+
+/// Run `f` on the stats for the current tip, recomputing them
+/// if the tip moved
+fn with_stats<T>(state: &AppState, f: impl FnOnce(&StatsCache) -> T) -> T {
+    let c = state.chain.lock().unwrap();
+    let tip = c.blocks.last().map(|b| b.hash.as_slice()).unwrap_or_default();
+
+    let mut cache = state.stats.lock().unwrap();
+    if cache.as_ref().map(|s| s.tip.as_slice()) != Some(tip) {
+        *cache = Some(StatsCache::compute(&c));
+    }
+    f(cache.as_ref().unwrap())
+}
+
+async fn stats_supply(State(state): State<AppState>) -> Json<SupplyResponse> {
+    Json(with_stats(&state, |s| s.supply.clone()))
+}
+
+#[derive(Deserialize)]
+struct RichListQuery {
+    limit: Option<usize>,
+}
+
+/// Top addresses by unspent value
+async fn stats_richlist(
+    State(state): State<AppState>,
+    Query(q): Query<RichListQuery>,
+) -> Json<Vec<RichListEntry>> {
+    let limit = q.limit.unwrap_or(DEFAULT_RICHLIST_LIMIT).min(MAX_RICHLIST);
+    Json(with_stats(&state, |s| s.richlist[..limit.min(s.richlist.len())].to_vec()))
+}
+
+//
+// ─── UTXO SET INFO ────────────────────────────────
+//
+
+#[derive(Serialize)]
+struct TxOutSetInfoResponse {
+    height: u64,
+    best_block: String,
+    txouts: usize,
+    total_amount: u64,
+    serialized_size: usize,
+    hash: String,
+}
+
+async fn txoutset_info(State(state): State<AppState>) -> Json<TxOutSetInfoResponse> {
+    let c = state.chain.lock().unwrap();
+    let stats = c.utxo_stats();
+
+    Json(TxOutSetInfoResponse {
+        height: c.height(),
+        best_block: c.blocks.last().map(|b| hex(&b.hash)).unwrap_or_default(),
+        txouts: stats.count,
+        total_amount: stats.total_amount,
+        serialized_size: stats.serialized_size,
+        hash: hex(&stats.hash),
+    })
+}
+
 //
 // ─── BLOCKS ───────────────────────────────────────
 //
@@ -107,18 +383,70 @@ struct BlockResponse {
     txs: usize,
 }
 
-async fn blocks(State(state): State<AppState>) -> Json<Vec<BlockResponse>> {
+impl BlockResponse {
+    fn new(b: &Block) -> Self {
+        Self {
+            height: b.header.height,
+            hash: hex(&b.hash),
+            txs: b.transactions.len(),
+        }
+    }
+}
+
+/// Blocks per page unless `limit` is given
+const DEFAULT_BLOCKS_LIMIT: usize = 20;
+/// Largest page served
+const MAX_BLOCKS_LIMIT: usize = 500;
+
+#[derive(Deserialize, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Order {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Deserialize)]
+struct BlocksQuery {
+    /// First height returned (default: genesis ascending, tip
+    /// descending)
+    from: Option<u64>,
+    limit: Option<usize>,
+    #[serde(default)]
+    order: Order,
+}
+
+/// A page of blocks, newest first by default
+async fn blocks(
+    State(state): State<AppState>,
+    Query(q): Query<BlocksQuery>,
+) -> Json<Vec<BlockResponse>> {
+    let limit = q.limit.unwrap_or(DEFAULT_BLOCKS_LIMIT).min(MAX_BLOCKS_LIMIT);
+
+    let c = state.chain.lock().unwrap();
+    let page: Vec<BlockResponse> = match q.order {
+        Order::Asc => {
+            let from = q.from.unwrap_or(0) as usize;
+            c.blocks.iter().skip(from).take(limit).map(BlockResponse::new).collect()
+        }
+        Order::Desc => {
+            let end = match q.from {
+                Some(from) => (from as usize).saturating_add(1).min(c.blocks.len()),
+                None => c.blocks.len(),
+            };
+            c.blocks[..end].iter().rev().take(limit).map(BlockResponse::new).collect()
+        }
+    };
+
+    Json(page)
+}
+
+async fn blocks_tip(State(state): State<AppState>) -> impl IntoResponse {
     let c = state.chain.lock().unwrap();
-    Json(
-        c.blocks
-            .iter()
-            .map(|b| BlockResponse {
-                height: b.header.height,
-                hash: hex(&b.hash),
-                txs: b.transactions.len(),
-            })
-            .collect(),
-    )
+    match c.blocks.last() {
+        Some(b) => Json(BlockResponse::new(b)).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
 }
 
 async fn block_by_height(
@@ -127,16 +455,71 @@ async fn block_by_height(
 ) -> impl IntoResponse {
     let c = state.chain.lock().unwrap();
     match c.blocks.iter().find(|b| b.header.height == height) {
-        Some(b) => Json(BlockResponse {
-            height,
-            hash: hex(&b.hash),
-            txs: b.transactions.len(),
-        })
-        .into_response(),
+        Some(b) => Json(BlockResponse::new(b)).into_response(),
         None => StatusCode::NOT_FOUND.into_response(),
     }
 }
 
+/// Consensus-serialized block (hex)
+async fn block_raw(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> impl IntoResponse {
+    let c = state.chain.lock().unwrap();
+    match c.blocks.iter().find(|b| hex(&b.hash) == hash) {
+        Some(b) => hex(&b.serialize()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[derive(Serialize)]
+struct VerboseBlockResponse {
+    hash: String,
+    height: u64,
+    confirmations: u64,
+    timestamp: i64,
+    prev_hash: String,
+    nonce: u64,
+    target: String,
+    merkle_root: String,
+    utxo_commitment: String,
+    size: usize,
+    transactions: Vec<VerboseTxResponse>,
+}
+
+async fn block_verbose(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> impl IntoResponse {
+    let c = state.chain.lock().unwrap();
+    let b = match c.blocks.iter().find(|b| hex(&b.hash) == hash) {
+        Some(b) => b,
+        None => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    let confirmations = c.height() - b.header.height;
+    let h = &b.header;
+
+    Json(VerboseBlockResponse {
+        hash,
+        height: h.height,
+        confirmations,
+        timestamp: h.timestamp,
+        prev_hash: hex(&h.prev_hash),
+        nonce: h.nonce,
+        target: hex(&h.target),
+        merkle_root: hex(&h.merkle_root),
+        utxo_commitment: hex(&h.utxo_commitment),
+        size: b.serialized_size(),
+        transactions: b
+            .transactions
+            .iter()
+            .map(|tx| verbose_tx(tx, Some((&b.hash, h.height)), confirmations))
+            .collect(),
+    })
+    .into_response()
+}
+
 //
 // ─── TRANSACTIONS ─────────────────────────────────
 //
@@ -168,6 +551,410 @@ async fn tx_by_id(
     StatusCode::NOT_FOUND.into_response()
 }
 
+#[derive(Serialize)]
+struct VerboseInput {
+    /// Spent output
+    txid: String,
+    vout: u32,
+    pubkey: String,
+    signature: String,
+    script_sig: String,
+}
+
+#[derive(Serialize)]
+struct VerboseOutput {
+    value: u64,
+    address: String,
+    script_pubkey: String,
+}
+
+#[derive(Serialize)]
+struct VerboseTxResponse {
+    txid: String,
+    size: usize,
+    /// Containing block; null while in the mempool
+    block_hash: Option<String>,
+    height: Option<u64>,
+    confirmations: u64,
+    inputs: Vec<VerboseInput>,
+    outputs: Vec<VerboseOutput>,
+}
+
+/// `tx` as JSON; `block` is the (hash, height) it was mined in
+fn verbose_tx(tx: &Transaction, block: Option<(&[u8], u64)>, confirmations: u64) -> VerboseTxResponse {
+    VerboseTxResponse {
+        txid: hex(&tx.txid()),
+        size: tx.serialized_size(),
+        block_hash: block.map(|(hash, _)| hex(hash)),
+        height: block.map(|(_, height)| height),
+        confirmations,
+        inputs: tx
+            .inputs
+            .iter()
+            .map(|i| VerboseInput {
+                txid: hex(&i.txid),
+                vout: i.index,
+                pubkey: hex(&i.pubkey),
+                signature: hex(&i.signature),
+                script_sig: hex(&i.script_sig),
+            })
+            .collect(),
+        outputs: tx
+            .outputs
+            .iter()
+            .map(|o| VerboseOutput {
+                value: o.value,
+                address: encode_address(&o.pubkey_hash),
+                script_pubkey: hex(&o.script_pubkey),
+            })
+            .collect(),
+    }
+}
+
+/// Confirmed or mempool transaction, with the (hash, height)
+/// of its block and its confirmations
+fn find_tx(state: &AppState, txid: &str) -> Option<(Transaction, Option<(Vec<u8>, u64)>, u64)> {
+    {
+        let c = state.chain.lock().unwrap();
+        for block in &c.blocks {
+            if let Some(tx) = block.transactions.iter().find(|tx| hex(&tx.txid()) == txid) {
+                let confirmations = c.height() - block.header.height;
+                let location = (block.hash.clone(), block.header.height);
+                return Some((tx.clone(), Some(location), confirmations));
+            }
+        }
+    }
+
+    let txid = hex::decode(txid).ok()?;
+    let m = state.mempool.lock().unwrap();
+    m.get_entry(&txid).map(|e| (e.tx.clone(), None, 0))
+}
+
+/// Consensus-serialized transaction (hex)
+async fn tx_raw(
+    State(state): State<AppState>,
+    Path(txid): Path<String>,
+) -> impl IntoResponse {
+    match find_tx(&state, &txid) {
+        Some((tx, _, _)) => hex(&serialize_transaction(&tx)).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn tx_verbose(
+    State(state): State<AppState>,
+    Path(txid): Path<String>,
+) -> impl IntoResponse {
+    match find_tx(&state, &txid) {
+        Some((tx, block, confirmations)) => {
+            let block = block.as_ref().map(|(hash, height)| (hash.as_slice(), *height));
+            Json(verbose_tx(&tx, block, confirmations)).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[derive(Serialize)]
+struct MerkleProofResponse {
+    txid: String,
+    block_hash: String,
+    header: BlockHeader,
+    /// Position of the transaction in the block
+    index: usize,
+    /// Sibling hashes, leaf level first
+    branch: Vec<String>,
+}
+
+/// Inclusion proof of a confirmed transaction; check it with
+/// `merkle::verify_merkle_branch` against `header.merkle_root`
+async fn tx_merkle_proof(
+    State(state): State<AppState>,
+    Path(txid): Path<String>,
+) -> impl IntoResponse {
+    let c = state.chain.lock().unwrap();
+
+    for block in &c.blocks {
+        let Some(index) = block.transactions.iter().position(|tx| hex(&tx.txid()) == txid) else {
+            continue;
+        };
+        let branch = merkle_branch(&block.transactions, index).expect("index in range");
+
+        return Json(MerkleProofResponse {
+            txid,
+            block_hash: hex(&block.hash),
+            header: block.header.clone(),
+            index,
+            branch: branch.iter().map(|h| hex(h)).collect(),
+        })
+        .into_response();
+    }
+
+    (StatusCode::NOT_FOUND, "transaction not in the active chain").into_response()
+}
+
+//
+// ─── MEMPOOL INFO ─────────────────────────────────
+//
+
+#[derive(Serialize)]
+struct MempoolInfoResponse {
+    size: usize,
+    bytes: usize,
+    /// Fee per 1000 bytes currently required to enter
+    min_fee_rate: i64,
+}
+
+async fn mempool_info(State(state): State<AppState>) -> Json<MempoolInfoResponse> {
+    let m = state.mempool.lock().unwrap();
+
+    Json(MempoolInfoResponse {
+        size: m.size(),
+        bytes: m.bytes(),
+        min_fee_rate: m.min_fee_rate(),
+    })
+}
+
+/// Ids of every mempool transaction, highest fee rate first
+async fn mempool_txids(State(state): State<AppState>) -> Json<Vec<String>> {
+    let m = state.mempool.lock().unwrap();
+
+    let mut entries: Vec<_> = m.txids().into_iter().filter_map(|id| m.get_entry(&id)).collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.fee_rate()));
+
+    Json(entries.iter().map(|e| hex(&e.txid)).collect())
+}
+
+#[derive(Serialize)]
+struct MempoolTxResponse {
+    txid: String,
+    fee: i64,
+    size: usize,
+    fee_rate: i64,
+    /// Including unconfirmed ancestors
+    package_fee_rate: i64,
+    time: i64,
+    ancestors: Vec<String>,
+    descendants: Vec<String>,
+}
+
+async fn mempool_tx(
+    State(state): State<AppState>,
+    Path(txid): Path<String>,
+) -> impl IntoResponse {
+    let txid = match hex::decode(&txid) {
+        Ok(v) => v,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let m = state.mempool.lock().unwrap();
+    let entry = match m.get_entry(&txid) {
+        Some(e) => e,
+        None => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    Json(MempoolTxResponse {
+        txid: hex(&txid),
+        fee: entry.fee,
+        size: entry.size,
+        fee_rate: entry.fee_rate(),
+        package_fee_rate: m.package_fee_rate(&txid).unwrap_or(entry.fee_rate()),
+        time: entry.timestamp,
+        ancestors: m.ancestors(&txid).iter().map(|e| hex(&e.tx.txid())).collect(),
+        descendants: m.descendants(&txid).iter().map(|e| hex(&e.tx.txid())).collect(),
+    })
+    .into_response()
+}
+
+#[derive(Serialize)]
+struct EstimateFeeResponse {
+    blocks: u64,
+    /// Fee per 1000 bytes; null until enough blocks were seen
+    fee_rate: Option<i64>,
+    /// Fee per 1000 bytes currently required to enter the mempool
+    min_fee_rate: i64,
+    /// `fee_rate`, or the fallback the node's own wallets use,
+    /// never below `min_fee_rate`
+    suggested_fee_rate: i64,
+}
+
+/// Fee rate for confirmation within `target_blocks`
+async fn estimate_fee(
+    State(state): State<AppState>,
+    Path(target_blocks): Path<u64>,
+) -> Json<EstimateFeeResponse> {
+    let m = state.mempool.lock().unwrap();
+    let fee_rate = m.estimate_feerate(target_blocks.max(1));
+    let min_fee_rate = m.min_fee_rate();
+
+    Json(EstimateFeeResponse {
+        blocks: target_blocks,
+        fee_rate,
+        min_fee_rate,
+        suggested_fee_rate: fee_rate.unwrap_or(FALLBACK_FEE_RATE).max(min_fee_rate),
+    })
+}
+
+//
+// ─── PEERS (DIAGNOSTICS) ──────────────────────────
+//
+
+/// Connected peers (getpeerinfo)
+async fn peers(State(state): State<AppState>) -> Json<Vec<PeerStats>> {
+    Json(state.p2p.peer_stats())
+}
+
+//
+// ─── HEALTH / SYNC ────────────────────────────────
+//
+
+#[derive(Serialize)]
+struct SyncResponse {
+    initial_block_download: bool,
+    /// Best block height against the best known height, 0-100
+    progress: f64,
+    best_block_height: u64,
+    /// Best block plus the headers whose blocks are still queued
+    best_header_height: u64,
+    best_peer_height: u64,
+    /// Timestamp of the best block
+    last_block_time: i64,
+}
+
+fn sync_response(state: &AppState) -> SyncResponse {
+    let (height, last_block_time) = {
+        let c = state.chain.lock().unwrap();
+        (c.height(), c.blocks.last().map(|b| b.header.timestamp).unwrap_or(0))
+    };
+
+    let initial_block_download = state.p2p.in_initial_sync();
+    let best_header_height = height + state.p2p.blocks_to_download() as u64;
+    let best_peer_height = state.p2p.best_peer_height();
+
+    let best = best_header_height.max(best_peer_height);
+    let progress = if !initial_block_download || best == 0 {
+        100.0
+    } else {
+        (height as f64 / best as f64 * 100.0).min(100.0)
+    };
+
+    SyncResponse {
+        initial_block_download,
+        progress,
+        best_block_height: height,
+        best_header_height,
+        best_peer_height,
+        last_block_time,
+    }
+}
+
+async fn sync_status(State(state): State<AppState>) -> Json<SyncResponse> {
+    Json(sync_response(&state))
+}
+
+#[derive(Serialize)]
+struct HealthResponse {
+    /// Synced and serving current data
+    ready: bool,
+    height: u64,
+    peers: usize,
+}
+
+/// 200 once the initial sync is over, 503 before (readiness)
+async fn health(State(state): State<AppState>) -> impl IntoResponse {
+    let sync = sync_response(&state);
+    let peers = state.p2p.peer_stats().iter().filter(|p| p.handshake_complete).count();
+    let ready = !sync.initial_block_download;
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(HealthResponse { ready, height: sync.best_block_height, peers }))
+}
+
+//
+// ─── EVENTS (SERVER-SENT) ─────────────────────────
+//
+
+/// How often an idle stream checks for new events
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Block, tx, reorg and peer notifications as Server-Sent Events
+///
+/// Each event carries its id; a client reconnecting with
+/// `Last-Event-ID` gets what it missed (as far as the log
+/// reaches). New clients start at the current tip.
+async fn events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let log = Arc::clone(&state.events);
+    let resume = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+    let start = resume.unwrap_or_else(|| log.last_id());
+
+    let stream = stream::unfold((log, start, VecDeque::new()), |(log, mut last, mut queue)| async move {
+        loop {
+            if let Some((id, event)) = queue.pop_front() {
+                last = id;
+                let sse = Event::default()
+                    .id(id.to_string())
+                    .event(event.kind())
+                    .json_data(&event)
+                    .unwrap_or_default();
+                return Some((Ok(sse), (log, last, queue)));
+            }
+
+            queue.extend(log.since(last));
+            if queue.is_empty() {
+                tokio::time::sleep(EVENT_POLL_INTERVAL).await;
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Events returned by /events/recent unless `limit` is given
+const DEFAULT_RECENT_EVENTS: usize = 20;
+
+#[derive(Deserialize)]
+struct RecentEventsQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct RecentEvent {
+    id: u64,
+    #[serde(flatten)]
+    event: NodeEvent,
+}
+
+/// Newest events, oldest first, for clients that poll
+async fn events_recent(
+    State(state): State<AppState>,
+    Query(query): Query<RecentEventsQuery>,
+) -> Json<Vec<RecentEvent>> {
+    let limit = query.limit.unwrap_or(DEFAULT_RECENT_EVENTS).min(EVENT_LOG_CAPACITY);
+
+    Json(
+        state
+            .events
+            .recent(limit)
+            .into_iter()
+            .map(|(id, event)| RecentEvent { id, event })
+            .collect(),
+    )
+}
+
+//
+// ─── THERMAL ──────────────────────────────────────
+//
+
+/// Device temperature against the runtime policy's limit
+async fn thermal(State(state): State<AppState>) -> Json<ThermalState> {
+    Json(ThermalState::read(state.policy.thermal_safety_threshold()))
+}
+
 //
 // ─── NEW TRANSACTION (MEMPOOL) ────────────────────
 //
@@ -185,26 +972,31 @@ async fn new_transaction(
 ) -> impl IntoResponse {
     let mut chain = state.chain.lock().unwrap();
 
-    let from = match hex::decode(&req.from) {
+    let from = match decode_address(&req.from) {
         Ok(v) => v,
         Err(_) => return (StatusCode::BAD_REQUEST, "Invalid sender").into_response(),
     };
 
-    let to = match hex::decode(&req.to) {
+    let to = match decode_address(&req.to) {
         Ok(v) => v,
         Err(_) => return (StatusCode::BAD_REQUEST, "Invalid receiver").into_response(),
     };
 
-    match chain.create_transaction(from, to, req.amount) {
-        Ok(tx) => {
-            let txid = hex(&tx.txid());
-            chain.mempool.push(tx);
-            (
-                StatusCode::OK,
-                format!("Transaction added to mempool: {}", txid),
-            )
-                .into_response()
+    let tx = match chain.create_transaction(from, to, req.amount) {
+        Ok(tx) => tx,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, format!("Transaction failed: {}", e)).into_response()
         }
+    };
+
+    let txid = hex(&tx.txid());
+
+    match accept_transaction(&state, chain, tx) {
+        Ok(_) => (
+            StatusCode::OK,
+            format!("Transaction added to mempool: {}", txid),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
             format!("Transaction failed: {}", e),
@@ -213,6 +1005,44 @@ async fn new_transaction(
     }
 }
 
+//
+// ─── SUBMIT SIGNED TRANSACTION ────────────────────
+//
+
+#[derive(Serialize)]
+struct SubmitResponse {
+    accepted: bool,
+    txid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+async fn submit_transaction(
+    State(state): State<AppState>,
+    Json(tx): Json<Transaction>,
+) -> impl IntoResponse {
+    let chain = state.chain.lock().unwrap();
+    let txid = hex(&tx.txid());
+
+    // Validated by the mempool (unconfirmed parents allowed)
+    match accept_transaction(&state, chain, tx) {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(SubmitResponse { accepted: true, txid, error: None }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(SubmitResponse {
+                accepted: false,
+                txid,
+                error: Some(e.to_string()),
+            }),
+        )
+            .into_response(),
+    }
+}
+
 //
 // ─── ADDRESS INFO ─────────────────────────────────
 //
@@ -225,10 +1055,16 @@ struct AddressResponse {
     utxos: usize,
 }
 
+/// `address`: bech32, or the pubkey hash in hex
 async fn address_info(
     State(state): State<AppState>,
-    Path(hash): Path<String>,
-) -> Json<AddressResponse> {
+    Path(address): Path<String>,
+) -> impl IntoResponse {
+    let hash = match decode_address(&address) {
+        Ok(h) => h,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
     let c = state.chain.lock().unwrap();
     let height = c.height();
 
@@ -238,7 +1074,7 @@ async fn address_info(
     let mut count = 0usize;
 
     for u in c.utxos.values() {
-        if hex(&u.pubkey_hash) != hash {
+        if u.pubkey_hash != hash {
             continue;
         }
 
@@ -260,12 +1096,475 @@ async fn address_info(
         locked,
         utxos: count,
     })
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct AddressTxsQuery {
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct AddressTxsResponse {
+    /// Mempool and confirmed transactions touching the address
+    total: usize,
+    txs: Vec<VerboseTxResponse>,
+}
+
+/// Transactions paying or spending `address`: mempool first,
+/// then confirmed, newest first
+async fn address_txs(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(q): Query<AddressTxsQuery>,
+) -> impl IntoResponse {
+    let hash = match decode_address(&address) {
+        Ok(h) => h,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let offset = q.offset.unwrap_or(0);
+    let limit = q.limit.unwrap_or(DEFAULT_BLOCKS_LIMIT).min(MAX_BLOCKS_LIMIT);
+
+    let c = state.chain.lock().unwrap();
+    let mut index = state.addresses.lock().unwrap();
+    index.sync(&c.blocks);
+
+    let m = state.mempool.lock().unwrap();
+    let mut pending: Vec<_> = m
+        .txids()
+        .into_iter()
+        .filter_map(|id| m.get_entry(&id))
+        .filter(|e| {
+            // Unconfirmed parents are not indexed
+            let spends_pending = e.tx.inputs.iter().any(|i| {
+                m.get_entry(&i.txid)
+                    .and_then(|parent| parent.tx.outputs.get(i.index as usize))
+                    .is_some_and(|o| o.pubkey_hash == hash)
+            });
+            spends_pending || index.touched(&e.tx).contains(&hash)
+        })
+        .collect();
+    pending.sort_by_key(|e| std::cmp::Reverse((e.timestamp, e.txid.clone())));
+
+    let confirmed = index.confirmed(&hash);
+    let total = pending.len() + confirmed.len();
+
+    let mut txs: Vec<VerboseTxResponse> = pending
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .map(|e| verbose_tx(&e.tx, None, 0))
+        .collect();
+
+    let skip = offset.saturating_sub(pending.len());
+    for (height, txid) in confirmed.iter().rev().skip(skip).take(limit - txs.len()) {
+        let block = &c.blocks[*height as usize];
+        if let Some(tx) = block.transactions.iter().find(|tx| tx.txid() == *txid) {
+            let location = (block.hash.as_slice(), *height);
+            txs.push(verbose_tx(tx, Some(location), c.height() - height));
+        }
+    }
+
+    Json(AddressTxsResponse { total, txs }).into_response()
+}
+
+async fn wallet_history(State(state): State<AppState>) -> Json<Vec<HistoryEntry>> {
+    let tip = state.chain.lock().unwrap().height();
+    Json(state.history.lock().unwrap().entries(tip))
+}
+
+//
+// ─── WALLETS (PER NAME) ───────────────────────────
+//
+
+#[derive(Serialize)]
+struct WalletListEntry {
+    name: String,
+    open: bool,
+}
+
+async fn wallet_list(State(state): State<AppState>) -> Json<Vec<WalletListEntry>> {
+    let w = state.wallets.lock().unwrap();
+    Json(
+        w.list()
+            .into_iter()
+            .map(|(name, open)| WalletListEntry { name, open })
+            .collect(),
+    )
+}
+
+#[derive(Deserialize)]
+struct UnlockRequest {
+    password: String,
+    /// Seconds until the keys are wiped again; omitted = the
+    /// wallet default
+    timeout: Option<u64>,
+}
+
+async fn wallet_unlock(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<UnlockRequest>,
+) -> impl IntoResponse {
+    let timeout = req.timeout.map(Duration::from_secs).unwrap_or(DEFAULT_AUTO_LOCK);
+
+    match state.wallets.lock().unwrap().open(&name, &req.password, Some(timeout)) {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(e) => (StatusCode::FORBIDDEN, e).into_response(),
+    }
+}
+
+async fn wallet_lock(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    if state.wallets.lock().unwrap().close(&name) {
+        StatusCode::OK
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+async fn wallet_address(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    let w = state.wallets.lock().unwrap();
+    let wallet = match w.get(&name) {
+        Some(w) => w,
+        None => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    match wallet.address() {
+        Ok(a) => encode_address(&a).into_response(),
+        Err(e) => (StatusCode::FORBIDDEN, e).into_response(),
+    }
+}
+
+async fn wallet_balance(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    let hashes: HashSet<Vec<u8>> = {
+        let w = state.wallets.lock().unwrap();
+        let wallet = match w.get(&name) {
+            Some(w) => w,
+            None => return StatusCode::NOT_FOUND.into_response(),
+        };
+
+        match wallet.receive_hashes() {
+            Ok(h) => h.into_iter().collect(),
+            Err(e) => return (StatusCode::FORBIDDEN, e).into_response(),
+        }
+    };
+
+    // Same figures as a local `wallet balance`
+    let c = state.chain.lock().unwrap();
+    let m = state.mempool.lock().unwrap();
+    let balances = calculate_balances(&c.utxos, m.transactions(), |h| hashes.contains(h), c.height());
+
+    Json(balances).into_response()
+}
+
+#[derive(Deserialize)]
+struct SendOutput {
+    /// Recipient address (bech32, or pubkey hash in hex)
+    to: String,
+    amount: u64,
+}
+
+#[derive(Deserialize)]
+struct SendRequest {
+    outputs: Vec<SendOutput>,
+    /// Satoshis per byte; omitted = the fee estimator's rate
+    fee_rate: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct SendResponse {
+    txid: String,
+    fee: u64,
+}
+
+/// Pay every output in one transaction
+async fn wallet_send(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<SendRequest>,
+) -> impl IntoResponse {
+    let mut recipients = Vec::new();
+    for o in &req.outputs {
+        match decode_address(&o.to) {
+            Ok(to) => recipients.push((to, o.amount)),
+            Err(_) => return (StatusCode::BAD_REQUEST, "Invalid receiver").into_response(),
+        }
+    }
+
+    let fee_rate = match req.fee_rate {
+        Some(rate) => FeeRate::SatPerByte(rate),
+        None => FeeRate::PerKilobyte(
+            state
+                .mempool
+                .lock()
+                .unwrap()
+                .estimate_feerate(DEFAULT_CONFIRM_TARGET)
+                .unwrap_or(FALLBACK_FEE_RATE),
+        ),
+    };
+
+    let chain = state.chain.lock().unwrap();
+
+    let payment = {
+        let mut w = state.wallets.lock().unwrap();
+        let wallet = match w.get_mut(&name) {
+            Some(w) => w,
+            None => return StatusCode::NOT_FOUND.into_response(),
+        };
+
+        match wallet.create_transaction(&chain.utxos, &recipients, fee_rate) {
+            Ok(p) => p,
+            Err("wallet locked") => return (StatusCode::FORBIDDEN, "wallet locked").into_response(),
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        }
+    };
+
+    let txid = hex(&payment.tx.txid());
+
+    if let Err(e) = accept_transaction(&state, chain, payment.tx) {
+        return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    }
+
+    Json(SendResponse { txid, fee: payment.fee }).into_response()
+}
+
+//
+// ─── SIGNED MESSAGES ──────────────────────────────
+//
+
+#[derive(Deserialize)]
+struct SignMessageRequest {
+    address: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct SignMessageResponse {
+    /// Recoverable signature (hex)
+    signature: String,
+}
+
+/// Prove ownership of a wallet address off-chain
+async fn wallet_sign_message(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<SignMessageRequest>,
+) -> impl IntoResponse {
+    let address = match decode_address(&req.address) {
+        Ok(a) => a,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let w = state.wallets.lock().unwrap();
+    let wallet = match w.get(&name) {
+        Some(w) => w,
+        None => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    match wallet.sign_message(&address, &req.message) {
+        Ok(sig) => Json(SignMessageResponse { signature: hex(&sig) }).into_response(),
+        Err("wallet locked") => (StatusCode::FORBIDDEN, "wallet locked").into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct VerifyMessageRequest {
+    address: String,
+    /// Hex, as returned by signmessage
+    signature: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct VerifyMessageResponse {
+    valid: bool,
+}
+
+async fn verify_message_handler(Json(req): Json<VerifyMessageRequest>) -> impl IntoResponse {
+    let (address, sig) = match (decode_address(&req.address), hex::decode(&req.signature)) {
+        (Ok(a), Ok(s)) => (a, s),
+        _ => return (StatusCode::BAD_REQUEST, "Invalid address or signature").into_response(),
+    };
+
+    Json(VerifyMessageResponse { valid: verify_message(&address, &req.message, &sig) }).into_response()
+}
+
+//
+// ─── MINING (EXTERNAL MINERS) ─────────────────────
+//
+
+#[derive(Serialize)]
+struct TemplateResponse {
+    height: u64,
+    timestamp: i64,
+    prev_hash: String,
+    target: String,
+    merkle_root: String,
+    utxo_commitment: String,
+    /// Coinbase first
+    transactions: Vec<Transaction>,
+    coinbase_value: u64,
+}
+
+/// Next block paying the coinbase to `address`, for a miner
+/// outside this process to solve
+async fn mining_template(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> impl IntoResponse {
+    let miner_pubkey_hash = match decode_address(&address) {
+        Ok(v) => v,
+        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid address").into_response(),
+    };
+
+    let packages = state.mempool.lock().unwrap().mining_packages();
+
+    let template = {
+        let c = state.chain.lock().unwrap();
+        let prev = match c.blocks.last() {
+            Some(b) => b,
+            None => return (StatusCode::SERVICE_UNAVAILABLE, "No chain yet").into_response(),
+        };
+        block_template(prev, &c.utxos, packages, &[PayoutShare::whole(miner_pubkey_hash)], &c.blocks)
+    };
+
+    let h = &template.header;
+    let response = TemplateResponse {
+        height: h.height,
+        timestamp: h.timestamp,
+        prev_hash: hex(&h.prev_hash),
+        target: hex(&h.target),
+        merkle_root: hex(&h.merkle_root),
+        utxo_commitment: hex(&h.utxo_commitment),
+        transactions: template.transactions.clone(),
+        coinbase_value: template.transactions[0].outputs.iter().map(|o| o.value).sum(),
+    };
+
+    // Templates built on an older tip can no longer be accepted
+    let mut templates = state.templates.lock().unwrap();
+    templates.retain(|_, t| t.header.prev_hash == template.header.prev_hash);
+    templates.insert(response.merkle_root.clone(), template);
+
+    Json(response).into_response()
+}
+
+#[derive(Serialize)]
+struct MiningInfoResponse {
+    height: u64,
+    /// Target of the next block (hex)
+    target: String,
+    #[serde(flatten)]
+    hashrate: HashrateStats,
+    /// Average seconds to the next block at the 5-minute hashrate
+    expected_block_secs: Option<f64>,
+}
+
+async fn mining_info(State(state): State<AppState>) -> Json<MiningInfoResponse> {
+    let (height, target) = {
+        let c = state.chain.lock().unwrap();
+        (c.height(), calculate_next_target(&c.blocks))
+    };
+
+    let hashrate = miner::stats();
+    let expected = miner::expected_time_to_block(&target, hashrate.hashrate_5m);
+
+    Json(MiningInfoResponse {
+        height,
+        target: hex(&target),
+        hashrate,
+        expected_block_secs: expected.map(|d| d.as_secs_f64()),
+    })
+}
+
+#[derive(Serialize)]
+struct BlockSubmitResponse {
+    accepted: bool,
+    hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn block_rejected(hash: String, error: &str) -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(BlockSubmitResponse { accepted: false, hash, error: Some(error.to_string()) }),
+    )
+        .into_response()
+}
+
+/// Solved header for a template from `mining_template`; only
+/// the nonce and timestamp may differ from the template
+async fn mining_submit(
+    State(state): State<AppState>,
+    Json(header): Json<BlockHeader>,
+) -> impl IntoResponse {
+    let hash = header.hash();
+    let hash_hex = hex(&hash);
+
+    let template = state.templates.lock().unwrap().get(&hex(&header.merkle_root)).cloned();
+    let mut block = match template {
+        Some(b) => b,
+        None => return block_rejected(hash_hex, "unknown or stale template"),
+    };
+
+    let t = &block.header;
+    if header.height != t.height
+        || header.prev_hash != t.prev_hash
+        || header.target != t.target
+        || header.utxo_commitment != t.utxo_commitment
+    {
+        return block_rejected(hash_hex, "header does not match template");
+    }
+
+    if !valid_pow(&hash, &header.target) {
+        return block_rejected(hash_hex, "insufficient proof of work");
+    }
+
+    block.header = header;
+    block.hash = hash;
+
+    let events = {
+        let mut c = state.chain.lock().unwrap();
+        if let Err(e) = c.validate_and_add_block(block.clone()) {
+            return block_rejected(hash_hex, &e.to_string());
+        }
+        block_events(&mut c, &block)
+    };
+
+    state.templates.lock().unwrap().clear();
+    state.bus.publish_all(events);
+
+    Json(BlockSubmitResponse { accepted: true, hash: hash_hex, error: None }).into_response()
 }
 
 //
 // ─── HELPER ───────────────────────────────────────
 //
 
+/// Admit `tx` against the chain it was built on and publish it
+/// (relay, wallets, event stream) once both locks are released
+fn accept_transaction(state: &AppState, chain: MutexGuard<Blockchain>, tx: Transaction) -> Result<(), MempoolError> {
+    let ctx = chain.next_block_context();
+    let added = state.mempool.lock().unwrap().add_transaction(tx.clone(), &chain.utxos, &ctx);
+    drop(chain);
+
+    added?;
+    state.bus.publish(BusEvent::TxAccepted(tx));
+    Ok(())
+}
+
 fn hex(bytes: &[u8]) -> String {
     bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
@@ -0,0 +1,94 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Paths answered without a token (liveness probes)
+const OPEN_PATHS: &[&str] = &["/health"];
+
+/// Bearer token the API requires (POLICY ONLY)
+///
+/// With `api.auth_token` set in node_config.json every request
+/// but OPEN_PATHS must carry `Authorization: Bearer <token>`.
+/// Without one the API stays open but only listens on
+/// localhost (`bind_ip`), since `/wallet/*` can spend.
+pub struct ApiAuth {
+    token: Option<String>,
+}
+
+impl ApiAuth {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    /// Address the API listens on: all interfaces only once
+    /// a token protects it
+    pub fn bind_ip(&self) -> IpAddr {
+        match self.token {
+            Some(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            None => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+
+    /// True if a request for `path` with Authorization header
+    /// `authorization` may go through
+    pub fn allows(&self, path: &str, authorization: Option<&str>) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        if OPEN_PATHS.contains(&path) {
+            return true;
+        }
+
+        match authorization.and_then(|a| a.strip_prefix("Bearer ")) {
+            Some(given) => constant_time_eq(given.trim().as_bytes(), token.as_bytes()),
+            None => false,
+        }
+    }
+}
+
+/// Compare without leaking how many leading bytes matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// axum middleware: 401 for requests without the token
+pub async fn require_auth(State(auth): State<Arc<ApiAuth>>, request: Request, next: Next) -> Response {
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|a| a.to_str().ok());
+
+    if auth.allows(request.uri().path(), authorization) {
+        return next.run(request).await;
+    }
+
+    let mut response = (StatusCode::UNAUTHORIZED, "API token required").into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_required_only_when_configured() {
+        let auth = ApiAuth::new(Some("s3cret".to_string()));
+        assert!(auth.allows("/status", Some("Bearer s3cret")));
+        assert!(!auth.allows("/status", Some("Bearer wrong")));
+        assert!(!auth.allows("/status", None));
+        assert!(auth.allows("/health", None));
+
+        assert!(ApiAuth::new(None).allows("/wallets", None));
+        assert!(ApiAuth::new(None).bind_ip().is_loopback());
+        assert!(!auth.bind_ip().is_loopback());
+    }
+}
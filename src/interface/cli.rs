@@ -1,115 +1,1866 @@
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use crate::core::chain::Blockchain;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::{rngs::OsRng, RngCore};
+use rpassword::read_password;
+use serde_json::{json, Value};
+
+use crate::core::chain::{set_data_dir, Blockchain};
+use crate::node::addrman::AddrMan;
+use crate::node::connman::peers_file;
 use crate::node::mempool::Mempool;
-use crate::wallet::Wallet;
-use crate::core::validation::validate_transaction;
+use crate::node::fee_estimator::FALLBACK_FEE_RATE;
+use crate::wallet::{generate_mnemonic, parse_private_key, FeeRate, DEFAULT_AUTO_LOCK};
+use crate::wallet_store::load_wallet_store;
+use crate::wallet_manager::WalletManager;
+use crate::wallet_dump::WalletDump;
+use crate::config::{
+    load_miner_config, load_node_config, load_wallet_config, pin_config_dir, set_config_dir, NodeConfig,
+};
+use crate::interface::console::{RpcRequest, Verb};
+use crate::interface::output::{self, emit, note};
+use crate::interface::rpc_client::{RpcClient, RpcError};
+use crate::watch_wallet::{load_watch_wallet, save_watch_wallet, WatchOnlyWallet};
+use crate::core::transaction::Transaction;
+use crate::core::utxo::OutPoint;
+use crate::psbt::PartiallySignedTransaction;
+use crate::signer::{
+    create_signed_transaction, sign_transaction as sign_with, ExternalSigner, FileSigner, Signer,
+};
+use crate::multisig_wallet::{load_multisig_wallets, save_multisig_wallets, MultisigWallet};
+use crate::crypto::address::{decode_address, encode_address, encode_base58_address};
+use crate::crypto::message::verify_message;
+use crate::{fail, prompt, say};
+
+/// Blocks the wallet aims to confirm within
+const DEFAULT_CONFIRM_TARGET: u64 = 6;
+
+// ───────────────── COMMAND TREE ─────────────────
+
+/// Bitcoin v0.2 — Revelation Edition node and wallet
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    /// Directory holding node_config.json, miner_config.json,
+    /// network_config.json and wallet_config.json [default: data]
+    #[arg(long, global = true, value_name = "DIR")]
+    pub config: Option<PathBuf>,
+    /// Working directory of the node; everything it stores goes
+    /// under DIR/data
+    #[arg(long, global = true, value_name = "DIR")]
+    pub datadir: Option<PathBuf>,
+    /// [default: main]
+    #[arg(long, global = true, value_enum)]
+    pub network: Option<Network>,
+    /// Wallet to open instead of the configured coinbase wallet
+    #[arg(long, global = true, value_name = "NAME")]
+    pub wallet: Option<String>,
+    /// API of the running node that chain commands and wallet
+    /// balance/send are sent to [default: this node's API port]
+    #[arg(long, global = true, value_name = "URL")]
+    pub rpc_url: Option<String>,
+    /// P2P listening port
+    #[arg(long, global = true, value_name = "PORT")]
+    pub p2p_port: Option<u16>,
+    /// API listening port
+    #[arg(long, global = true, value_name = "PORT")]
+    pub api_port: Option<u16>,
+    /// Bootstrap seed `host:port` (repeatable; replaces the
+    /// configured ones)
+    #[arg(long = "seed", global = true, value_name = "ADDR")]
+    pub seeds: Vec<String>,
+    /// Never accept inbound peers
+    #[arg(long, global = true)]
+    pub outbound_only: bool,
+    /// Relay only; don't mine
+    #[arg(long, global = true)]
+    pub no_mining: bool,
+    /// Print one JSON object per line instead of decorated text
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Defaults to `node start`
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Network {
+    Main,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the node
+    #[command(subcommand)]
+    Node(NodeCommand),
+    /// Wallet commands
+    #[command(subcommand)]
+    Wallet(WalletCommand),
+    /// Watch-only wallet (no seed needed)
+    #[command(subcommand)]
+    Watch(WatchCommand),
+    /// Shared m-of-n wallets
+    #[command(subcommand)]
+    Multisig(MultisigCommand),
+    /// External signers
+    #[command(subcommand)]
+    Signer(SignerCommand),
+    /// Inspect the local chain
+    #[command(subcommand)]
+    Chain(ChainCommand),
+    /// Manage known peer addresses
+    #[command(subcommand)]
+    Peer(PeerCommand),
+}
+
+#[derive(Subcommand)]
+pub enum NodeCommand {
+    /// Sync, mine and serve the API
+    Start,
+    /// Live terminal view of a running node (`--rpc-url`)
+    Dashboard,
+}
+
+#[derive(Subcommand)]
+pub enum WalletCommand {
+    /// Create a wallet from a new recovery phrase
+    #[command(alias = "create")]
+    New { name: String },
+    /// Recreate a wallet from its recovery phrase
+    Restore { name: String },
+    /// Create a wallet from a `wallet dumpwallet` file
+    #[command(name = "importwallet")]
+    ImportWallet { name: String, file: String },
+    /// Known wallets; `*` marks the default
+    List,
+    /// Make a wallet the default
+    Use { name: String },
+    /// Confirmed, pending and immature funds
+    Balance,
+    /// Next receiving address
+    Address {
+        #[arg(value_enum)]
+        format: Option<AddressFormat>,
+    },
+    /// Pay one or more recipients
+    Send {
+        /// `<to_address> <amount>` pairs, then an optional sat/byte fee rate
+        #[arg(required = true, num_args = 2.., value_name = "TO AMOUNT")]
+        recipients: Vec<String>,
+    },
+    /// Replace an unconfirmed payment with a higher fee (RBF)
+    #[command(name = "bumpfee")]
+    BumpFee { txid: String, sat_per_byte: Option<u64> },
+    /// Spend an unconfirmed payment's change to pay for it (CPFP)
+    Accelerate { txid: String, sat_per_byte: Option<u64> },
+    /// Move every coin of an imported key into this wallet
+    Sweep {
+        #[arg(value_name = "WIF_OR_HEX_KEY")]
+        key: String,
+        sat_per_byte: Option<u64>,
+    },
+    /// Keep a coin out of coin selection; alone, list locked coins
+    LockUtxo {
+        #[arg(value_name = "TXID:VOUT")]
+        outpoint: Option<OutPoint>,
+    },
+    /// Return a coin to coin selection
+    UnlockUtxo {
+        #[arg(value_name = "TXID:VOUT")]
+        outpoint: OutPoint,
+    },
+    /// Move a pre-BIP32 wallet to BIP32/BIP44 keys
+    Upgrade,
+    /// Change the wallet password
+    Passwd,
+    /// Extended public key for `watch import`
+    Xpub,
+    /// Sign a message with an address's key
+    #[command(name = "signmessage")]
+    SignMessage {
+        address: String,
+        #[arg(required = true)]
+        message: Vec<String>,
+    },
+    /// Write the seed and every private key to a file, unencrypted
+    #[command(name = "dumpwallet")]
+    DumpWallet { file: String },
+    /// Check a `signmessage` signature
+    #[command(name = "verifymessage")]
+    VerifyMessage {
+        address: String,
+        signature: String,
+        #[arg(required = true)]
+        message: Vec<String>,
+    },
+    /// Public key to share with multisig cosigners
+    Pubkey { index: Option<u32> },
+    /// Add this wallet's signatures to a PSBT file
+    Sign {
+        #[arg(value_name = "TX.PSBT")]
+        psbt: String,
+    },
+    /// Build an unsigned PSBT from the watch-only wallet (online)
+    CreateUnsigned(PaymentArgs),
+    /// Sign a PSBT on an air-gapped machine
+    SignOffline {
+        #[arg(value_name = "TX.PSBT | PSBT:PAYLOAD")]
+        psbt: String,
+        #[arg(value_name = "SIGNED.PSBT")]
+        out: Option<String>,
+    },
+    /// Finalize a signed PSBT and submit it (online)
+    Broadcast {
+        #[arg(value_name = "TX.PSBT | PSBT:PAYLOAD")]
+        psbt: String,
+    },
+    /// Rebuild addresses and history from the chain
+    Rescan { height: Option<u64>, gap_limit: Option<u32> },
+    /// Wallet transactions, newest first
+    History {
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+    },
+    /// Label a transaction; no text removes the label
+    Label { txid: String, text: Vec<String> },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum AddressFormat {
+    Bech32,
+    Base58,
+}
+
+/// Unsigned payment written to a PSBT file
+#[derive(Args)]
+pub struct PaymentArgs {
+    to: String,
+    amount: u64,
+    #[arg(value_name = "TX.PSBT")]
+    psbt: String,
+    sat_per_byte: Option<u64>,
+}
+
+#[derive(Subcommand)]
+pub enum WatchCommand {
+    /// Watch every address of an extended public key
+    Import { xpub: String },
+    /// Watch one more public key
+    Add {
+        #[arg(value_name = "PUBKEY_HEX")]
+        pubkey: String,
+    },
+    Balance,
+    Coins,
+    /// Build an unsigned PSBT paying `to`
+    Send(PaymentArgs),
+    /// Finalize a signed PSBT and submit it
+    Submit {
+        #[arg(value_name = "TX.PSBT")]
+        psbt: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MultisigCommand {
+    /// Register an m-of-n wallet over cosigner public keys
+    Create {
+        name: String,
+        m: u8,
+        #[arg(required = true, value_name = "PUBKEY_HEX")]
+        pubkeys: Vec<String>,
+    },
+    List,
+    Address { name: String },
+    Balance { name: String },
+    Coins { name: String },
+    /// Build an unsigned PSBT for the cosigners to sign
+    Spend {
+        name: String,
+        #[command(flatten)]
+        payment: PaymentArgs,
+    },
+    /// Merge cosigners' signatures into the first PSBT
+    Combine {
+        #[arg(value_name = "TX.PSBT")]
+        psbt: String,
+        #[arg(required = true, value_name = "OTHER.PSBT")]
+        others: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SignerCommand {
+    /// Public keys the signer offers
+    Pubkeys,
+    /// Pay one or more recipients, signed by the signer
+    Send {
+        /// `<to_address> <amount>` pairs, then an optional sat/byte fee rate
+        #[arg(required = true, num_args = 2.., value_name = "TO AMOUNT")]
+        recipients: Vec<String>,
+    },
+    /// Add the signer's signatures to a PSBT file
+    Sign {
+        #[arg(value_name = "TX.PSBT")]
+        psbt: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ChainCommand {
+    /// Height, tip and UTXO set size
+    Info,
+    /// Block by height or hash
+    Block { id: String },
+    /// Confirmed transaction by txid
+    Tx { txid: String },
+}
+
+#[derive(Subcommand)]
+pub enum PeerCommand {
+    /// Addresses the node dials from, most recently good first
+    List,
+    /// Remember an address to dial
+    Add { addr: SocketAddr },
+    /// Forget an address
+    Remove { addr: SocketAddr },
+}
+
+impl Cli {
+    /// Point config and data paths at `--config` / `--datadir`,
+    /// then load node_config.json with the flags above applied
+    /// on top; call before anything else is loaded
+    pub fn load_config(&self) -> NodeConfig {
+        // Relative to where the command was run, not `--datadir`
+        if let Some(dir) = &self.config {
+            set_config_dir(std::env::current_dir().unwrap().join(dir));
+        }
+        if let Some(dir) = &self.datadir {
+            enter_data_dir(dir);
+        }
+        pin_config_dir();
+
+        let mut config = load_node_config();
+
+        // A datadir from the file leaves the config files where
+        // they were found
+        if let (None, Some(dir)) = (&self.datadir, &config.datadir) {
+            enter_data_dir(dir);
+        }
+
+        if let Some(dir) = &self.datadir {
+            config.datadir = Some(dir.clone());
+        }
+        if let Some(Network::Main) = self.network {
+            config.network = "main".to_string();
+        }
+        if let Some(port) = self.p2p_port {
+            config.ports.p2p = port;
+        }
+        if let Some(port) = self.api_port {
+            config.ports.api = port;
+        }
+        if !self.seeds.is_empty() {
+            config.peers.seeds = self.seeds.clone();
+        }
+        if self.outbound_only {
+            config.peers.outbound_only = true;
+        }
+        if self.no_mining {
+            config.mining.enabled = false;
+        }
+
+        if let Err(e) = config.validate() {
+            fail!("Invalid node configuration: {}", e);
+            std::process::exit(1);
+        }
+        config
+    }
+
+    /// Client for `--rpc-url`, else this node's own API
+    pub fn rpc_client(&self, config: &NodeConfig) -> RpcClient {
+        let url = self.rpc_url.clone().unwrap_or_else(|| config.api_url());
+        RpcClient::new(&url).with_auth_token(config.api.auth_token.clone())
+    }
+}
+
+fn enter_data_dir(dir: &Path) {
+    std::fs::create_dir_all(dir).expect("cannot create data directory");
+    std::env::set_current_dir(dir).expect("cannot enter data directory");
+    set_data_dir(std::env::current_dir().unwrap().join("data"));
+}
+
+// ───────────────── CHAIN ─────────────────
+
+pub fn handle_chain_command(command: ChainCommand, chain: Arc<Mutex<Blockchain>>) {
+    let c = chain.lock().unwrap();
+
+    match command {
+        ChainCommand::Info => {
+            let tip = c.blocks.last().expect("genesis is always present");
+            let work = c.chain_work();
+            emit(
+                &[
+                    format!("⛓ Height:    {}", tip.header.height),
+                    format!("🔗 Tip:       {}", hex::encode(&tip.hash)),
+                    format!("🕒 Tip time:  {}", tip.header.timestamp),
+                    format!("💪 Work:      {}", work),
+                    format!("🪙 UTXOs:     {}", c.utxos.len()),
+                ]
+                .join("\n"),
+                json!({
+                    "height": tip.header.height,
+                    "tip": hex::encode(&tip.hash),
+                    "tip_time": tip.header.timestamp,
+                    "chain_work": work.to_string(),
+                    "utxos": c.utxos.len(),
+                }),
+            );
+        }
+
+        ChainCommand::Block { id } => {
+            let block = match id.parse::<usize>() {
+                Ok(height) => c.blocks.get(height),
+                Err(_) => c.blocks.iter().find(|b| hex::encode(&b.hash) == id),
+            };
+            let Some(b) = block else {
+                fail!("Block not found");
+                return;
+            };
+
+            let txids: Vec<String> = b.transactions.iter().map(|tx| hex::encode(tx.txid())).collect();
+            let mut lines = vec![
+                format!("📦 Block {}", b.header.height),
+                format!("   Hash:        {}", hex::encode(&b.hash)),
+                format!("   Previous:    {}", hex::encode(&b.header.prev_hash)),
+                format!("   Time:        {}", b.header.timestamp),
+                format!("   Merkle root: {}", hex::encode(&b.header.merkle_root)),
+                format!("   Nonce:       {}", b.header.nonce),
+            ];
+            lines.extend(txids.iter().map(|t| format!("   • {}", t)));
+
+            emit(
+                &lines.join("\n"),
+                json!({
+                    "height": b.header.height,
+                    "hash": hex::encode(&b.hash),
+                    "prev_hash": hex::encode(&b.header.prev_hash),
+                    "timestamp": b.header.timestamp,
+                    "merkle_root": hex::encode(&b.header.merkle_root),
+                    "nonce": b.header.nonce,
+                    "transactions": txids,
+                }),
+            );
+        }
+
+        ChainCommand::Tx { txid } => {
+            let found = c.blocks.iter().find_map(|b| {
+                b.transactions
+                    .iter()
+                    .find(|tx| hex::encode(tx.txid()) == txid)
+                    .map(|tx| (b.header.height, tx))
+            });
+            let Some((height, tx)) = found else {
+                fail!("Transaction not in the active chain");
+                return;
+            };
+
+            let inputs: Vec<Value> = tx
+                .inputs
+                .iter()
+                .map(|i| json!({ "txid": hex::encode(&i.txid), "vout": i.index }))
+                .collect();
+            let outputs: Vec<Value> = tx
+                .outputs
+                .iter()
+                .map(|o| json!({ "address": encode_address(&o.pubkey_hash), "value": o.value }))
+                .collect();
+            let value = json!({ "txid": txid, "height": height, "inputs": inputs, "outputs": outputs });
+            emit(&tx_lines(&value), value);
+        }
+    }
+}
+
+// ───────────────── PEER ─────────────────
+
+/// Edits `peers.json`; a running node picks changes up on restart
+pub fn handle_peer_command(command: PeerCommand) {
+    let path = peers_file();
+    let mut addrman = AddrMan::load(&path);
+
+    match command {
+        PeerCommand::List => {
+            let known = addrman.known();
+            let lines: Vec<String> = known
+                .iter()
+                .map(|(addr, failures, last_success)| match last_success {
+                    Some(t) => format!("🌐 {}  last good {}  failures {}", addr, t, failures),
+                    None => format!("🌐 {}  never connected  failures {}", addr, failures),
+                })
+                .collect();
+            let peers: Vec<Value> = known
+                .iter()
+                .map(|(addr, failures, last_success)| {
+                    json!({ "addr": addr.to_string(), "last_success": last_success, "failures": failures })
+                })
+                .collect();
+
+            let text = if lines.is_empty() { "No known peers".to_string() } else { lines.join("\n") };
+            emit(&text, Value::Array(peers));
+            return;
+        }
+        PeerCommand::Add { addr } => {
+            addrman.add(addr);
+            say!("✅ Added {}", addr);
+        }
+        PeerCommand::Remove { addr } => {
+            if !addrman.remove(&addr) {
+                fail!("Unknown peer {}", addr);
+                return;
+            }
+            say!("🗑 Removed {}", addr);
+        }
+    }
 
-const COINBASE_MATURITY: u64 = 100;
+    if let Err(e) = addrman.save(&path) {
+        fail!("Could not save peers: {}", e);
+    }
+}
+
+// ───────────────── RPC CLIENT MODE ─────────────────
+
+/// Seconds a wallet unlocked for one remote command may stay open
+const REMOTE_UNLOCK_SECS: u64 = 60;
+
+/// Commands answered by a running node rather than by a copy of
+/// the chain loaded in this process
+pub fn is_remote_command(command: &Command) -> bool {
+    match command {
+        Command::Chain(_) => true,
+        Command::Wallet(WalletCommand::Balance | WalletCommand::Send { .. }) => true,
+        _ => false,
+    }
+}
+
+/// Commands that only make sense against a running node: a
+/// payment built here would never reach the node's mempool
+pub fn requires_node(command: &Command) -> bool {
+    matches!(command, Command::Wallet(WalletCommand::Send { .. }))
+}
+
+/// Run a remote command against the node behind `client`,
+/// using wallet `wallet` for wallet commands
+pub fn handle_remote_command(client: &RpcClient, command: &Command, wallet: &str) {
+    let result = match command {
+        Command::Chain(command) => remote_chain_command(client, command),
+        Command::Wallet(command) => remote_wallet_command(client, command, wallet),
+        _ => unreachable!("checked by is_remote_command"),
+    };
+
+    if let Err(e) = result {
+        fail!("{}", e);
+    }
+}
+
+fn get(client: &RpcClient, path: String) -> Result<Value, RpcError> {
+    client.call(RpcRequest { verb: Verb::Get, path, body: None })
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or("?")
+}
+
+fn remote_chain_command(client: &RpcClient, command: &ChainCommand) -> Result<(), RpcError> {
+    match command {
+        ChainCommand::Info => {
+            let status = client.method("getstatus", &[])?;
+            let tip = get(client, "/blocks/tip".into())?;
+            emit(
+                &[
+                    format!("⛓ Height:    {}", tip["height"]),
+                    format!("🔗 Tip:       {}", text(&tip["hash"])),
+                    format!("🪙 UTXOs:     {}", status["utxos"]),
+                    format!("📥 Mempool:   {}", status["mempool"]),
+                    format!("💰 Supply:    {}", status["circulating_supply"]),
+                ]
+                .join("\n"),
+                json!({
+                    "height": tip["height"],
+                    "tip": tip["hash"],
+                    "utxos": status["utxos"],
+                    "mempool": status["mempool"],
+                    "circulating_supply": status["circulating_supply"],
+                }),
+            );
+        }
+
+        ChainCommand::Block { id } => {
+            let hash = match id.parse::<u64>() {
+                Ok(height) => get(client, format!("/block/height/{}", height))?["hash"].clone(),
+                Err(_) => Value::String(id.clone()),
+            };
+            let b = get(client, format!("/block/{}/verbose", text(&hash)))?;
+
+            let mut lines = vec![
+                format!("📦 Block {}", b["height"]),
+                format!("   Hash:        {}", text(&b["hash"])),
+                format!("   Previous:    {}", text(&b["prev_hash"])),
+                format!("   Time:        {}", b["timestamp"]),
+                format!("   Merkle root: {}", text(&b["merkle_root"])),
+                format!("   Nonce:       {}", b["nonce"]),
+            ];
+            for tx in b["transactions"].as_array().into_iter().flatten() {
+                lines.push(format!("   • {}", text(&tx["txid"])));
+            }
+            emit(&lines.join("\n"), b);
+        }
+
+        ChainCommand::Tx { txid } => {
+            let mut tx = client.method("gettransaction", &[txid.clone()])?;
+            tx["txid"] = Value::String(txid.clone());
+            emit(&tx_lines(&tx), tx);
+        }
+    }
+    Ok(())
+}
+
+/// Text form of a transaction given as `{txid, height, inputs,
+/// outputs}`; no height means it is still in the mempool
+fn tx_lines(tx: &Value) -> String {
+    let mut lines = vec![match tx["height"].as_u64() {
+        Some(height) => format!("🧾 {} (block {})", text(&tx["txid"]), height),
+        None => format!("🧾 {} (mempool)", text(&tx["txid"])),
+    }];
+    for i in tx["inputs"].as_array().into_iter().flatten() {
+        lines.push(format!("   ← {}:{}", text(&i["txid"]), i["vout"]));
+    }
+    for o in tx["outputs"].as_array().into_iter().flatten() {
+        lines.push(format!("   → {} {}", text(&o["address"]), o["value"]));
+    }
+    lines.join("\n")
+}
+
+fn remote_wallet_command(client: &RpcClient, command: &WalletCommand, wallet: &str) -> Result<(), RpcError> {
+    match command {
+        WalletCommand::Balance => {
+            let b = with_unlocked(client, wallet, || client.method("getbalance", &[wallet.to_string()]))?;
+            emit(
+                &format!(
+                    "💰 Confirmed balance: {}\n⏳ Pending balance:   {}\n🔒 Immature balance:  {}",
+                    b["confirmed"], b["pending"], b["immature"]
+                ),
+                b,
+            );
+        }
+
+        WalletCommand::Send { recipients } => {
+            let Some((recipients, fee_rate)) = recipients_arg(recipients) else {
+                return Ok(());
+            };
+
+            let outputs: Vec<Value> = recipients
+                .iter()
+                .map(|(to, amount)| json!({ "to": encode_address(to), "amount": amount }))
+                .collect();
+            let request = || {
+                client.call(RpcRequest {
+                    verb: Verb::Post,
+                    path: format!("/wallet/{}/send", wallet),
+                    body: Some(json!({ "outputs": outputs, "fee_rate": fee_rate })),
+                })
+            };
+
+            let sent = with_unlocked(client, wallet, request)?;
+            emit(
+                &format!("🧾 Fee: {}\n✅ {} sent to the node's mempool", sent["fee"], text(&sent["txid"])),
+                sent,
+            );
+        }
+
+        _ => unreachable!("checked by is_remote_command"),
+    }
+    Ok(())
+}
+
+/// Run `call`; if the node has `wallet` locked (or not open),
+/// unlock it with a prompted password and lock it again after
+fn with_unlocked(
+    client: &RpcClient,
+    wallet: &str,
+    call: impl Fn() -> Result<Value, RpcError>,
+) -> Result<Value, RpcError> {
+    match call() {
+        Err(RpcError::Status(403 | 404, _)) => {}
+        done => return done,
+    }
 
-/// CLI wallet & transaction commands
+    prompt!("🔑 Password for wallet '{}': ", wallet);
+    let password = read_password().unwrap();
+
+    client.method("unlockwallet", &[wallet.to_string(), password, REMOTE_UNLOCK_SECS.to_string()])?;
+    let result = call();
+    let _ = client.method("lockwallet", &[wallet.to_string()]);
+    result
+}
+
+/// CLI wallet & transaction commands on the open wallet `name`
 pub fn handle_command(
-    args: Vec<String>,
-    wallet: &mut Wallet,
+    command: WalletCommand,
+    wallets: &mut WalletManager,
+    name: &str,
     chain: Arc<Mutex<Blockchain>>,
     mempool: Arc<Mutex<Mempool>>,
 ) {
-    if args.len() < 3 {
-        println!("Usage:");
-        println!("  wallet balance");
-        println!("  wallet send <to_pubkey_hash_hex> <amount>");
-        return;
-    }
+    let wallet = match wallets.get_mut(name) {
+        Some(w) => w,
+        None => {
+            fail!("Wallet '{}' is not open", name);
+            return;
+        }
+    };
+
+    match command {
+        // ───────────────── ADDRESS ─────────────────
+        WalletCommand::Address { format } => match wallet.address() {
+            Ok(a) => {
+                let address = match format {
+                    Some(AddressFormat::Base58) => encode_base58_address(&a),
+                    _ => encode_address(&a),
+                };
+                emit(&format!("📥 {}", address), json!({ "address": address }))
+            }
+            Err(e) => fail!("Wallet error: {}", e),
+        },
 
-    match args[2].as_str() {
         // ───────────────── BALANCE ─────────────────
-        "balance" => {
+        WalletCommand::Balance => {
             let chain_guard = chain.lock().unwrap();
-            let my_hash = wallet.address().expect("wallet locked");
-            let current_height = chain_guard.height();
+            let mempool_guard = mempool.lock().unwrap();
 
-            let mut total = 0u64;
-            let mut spendable = 0u64;
-            let mut locked = 0u64;
-
-            for u in chain_guard.utxos.values() {
-                if u.pubkey_hash != my_hash {
-                    continue;
+            match wallet.balances(&chain_guard.utxos, mempool_guard.transactions(), chain_guard.height()) {
+                Ok(b) => {
+                    emit(
+                        &format!(
+                            "💰 Confirmed balance: {}\n⏳ Pending balance:   {}\n🔒 Immature balance:  {}",
+                            b.confirmed, b.pending, b.immature
+                        ),
+                        json!({ "confirmed": b.confirmed, "pending": b.pending, "immature": b.immature }),
+                    );
                 }
+                Err(e) => fail!("Wallet error: {}", e),
+            }
+        }
+
+        // ───────────────── SEND ─────────────────
+        WalletCommand::Send { recipients } => {
+            let (recipients, sat_per_byte) = match recipients_arg(&recipients) {
+                Some(r) => r,
+                None => return,
+            };
+            let fee_rate = fee_rate_arg(sat_per_byte, &mempool);
 
-                total += u.value;
+            let chain_guard = chain.lock().unwrap();
 
-                if !u.is_coinbase {
-                    spendable += u.value;
-                } else if current_height >= u.height + COINBASE_MATURITY {
-                    spendable += u.value;
-                } else {
-                    locked += u.value;
+            let payment = match wallet.create_transaction(
+                &chain_guard.utxos,
+                &recipients,
+                fee_rate,
+            ) {
+                Ok(p) => p,
+                Err(e) => {
+                    fail!("Wallet error: {}", e);
+                    return;
                 }
-            }
+            };
 
-            println!("💰 Total balance:     {}", total);
-            println!("💸 Spendable balance: {}", spendable);
-            println!("🔒 Locked balance:    {}", locked);
+            drop(chain_guard);
+            let size = payment.tx.serialized_size();
+            emit(
+                &format!("🧾 Fee: {} ({} bytes)", payment.fee, size),
+                json!({ "fee": payment.fee, "size": size }),
+            );
+            submit_transaction(payment.tx, &chain, &mempool);
         }
 
-        // ───────────────── SEND ─────────────────
-        "send" => {
-            if args.len() != 5 {
-                println!("Usage: wallet send <to_pubkey_hash_hex> <amount>");
-                return;
-            }
-
-            let to = match hex::decode(&args[3]) {
+        // ───────────────── BUMPFEE (RBF) ─────────────────
+        WalletCommand::BumpFee { txid: txid_hex, sat_per_byte } => {
+            let txid = match hex::decode(&txid_hex) {
                 Ok(v) => v,
                 Err(_) => {
-                    println!("Invalid pubkey hash");
+                    fail!("Invalid txid");
                     return;
                 }
             };
 
-            let amount: u64 = match args[4].parse() {
+            let original = match mempool.lock().unwrap().get_entry(&txid) {
+                Some(e) => e.tx.clone(),
+                None => {
+                    fail!("Transaction not in mempool (unknown or already confirmed)");
+                    return;
+                }
+            };
+
+            let fee_rate = fee_rate_arg(sat_per_byte, &mempool);
+
+            let chain_guard = chain.lock().unwrap();
+            let payment = match wallet.bump_fee(&original, &chain_guard.utxos, fee_rate) {
+                Ok(p) => p,
+                Err(e) => {
+                    fail!("Fee bump failed: {}", e);
+                    return;
+                }
+            };
+
+            drop(chain_guard);
+            let replacement = hex::encode(payment.tx.txid());
+            emit(
+                &format!("🚀 Replacing {} with {} (fee {})", txid_hex, replacement, payment.fee),
+                json!({ "replaces": txid_hex, "txid": replacement, "fee": payment.fee }),
+            );
+            submit_transaction(payment.tx, &chain, &mempool);
+        }
+
+        // ───────────────── ACCELERATE (CPFP) ─────────────────
+        WalletCommand::Accelerate { txid: txid_hex, sat_per_byte } => {
+            let txid = match hex::decode(&txid_hex) {
                 Ok(v) => v,
                 Err(_) => {
-                    println!("Invalid amount");
+                    fail!("Invalid txid");
+                    return;
+                }
+            };
+
+            // Parent with its own unconfirmed ancestors
+            let (parent, package_fee, package_size) = {
+                let m = mempool.lock().unwrap();
+                let entry = match m.get_entry(&txid) {
+                    Some(e) => e,
+                    None => {
+                        fail!("Transaction not in mempool (unknown or already confirmed)");
+                        return;
+                    }
+                };
+
+                let ancestors = m.ancestors(&txid);
+                let fee = entry.fee + ancestors.iter().map(|e| e.fee).sum::<i64>();
+                let size = entry.size + ancestors.iter().map(|e| e.size).sum::<usize>();
+                (entry.tx.clone(), fee.max(0) as u64, size)
+            };
+
+            let fee_rate = fee_rate_arg(sat_per_byte, &mempool);
+
+            let payment = match wallet.accelerate(&parent, package_fee, package_size, fee_rate) {
+                Ok(p) => p,
+                Err(e) => {
+                    fail!("Acceleration failed: {}", e);
                     return;
                 }
             };
 
+            let child = hex::encode(payment.tx.txid());
+            emit(
+                &format!("⚡ Child {} pays {} for parent {}", child, payment.fee, txid_hex),
+                json!({ "txid": child, "fee": payment.fee, "parent": txid_hex }),
+            );
+            submit_transaction(payment.tx, &chain, &mempool);
+        }
+
+        // ───────────────── SWEEP (imported key) ─────────────────
+        WalletCommand::Sweep { key, sat_per_byte } => {
+            let key = match parse_private_key(&key) {
+                Ok(k) => k,
+                Err(e) => {
+                    fail!("{}", e);
+                    return;
+                }
+            };
+
+            let fee_rate = fee_rate_arg(sat_per_byte, &mempool);
+
             let chain_guard = chain.lock().unwrap();
-            let current_height = chain_guard.height();
+            let payment = match wallet.sweep(&chain_guard.utxos, key, fee_rate, chain_guard.height()) {
+                Ok(p) => p,
+                Err(e) => {
+                    fail!("Sweep failed: {}", e);
+                    return;
+                }
+            };
 
-            let tx = match wallet.create_transaction(
-                &chain_guard.utxos,
-                to,
-                amount,
-            ) {
-                Ok(t) => t,
+            drop(chain_guard);
+            let (coins, amount) = (payment.tx.inputs.len(), payment.tx.outputs[0].value);
+            emit(
+                &format!("🧹 Sweeping {} coin(s): {} to this wallet, fee {}", coins, amount, payment.fee),
+                json!({ "coins": coins, "amount": amount, "fee": payment.fee }),
+            );
+            submit_transaction(payment.tx, &chain, &mempool);
+        }
+
+        // ───────────────── COIN LOCKING ─────────────────
+        WalletCommand::LockUtxo { outpoint: None } => {
+            let locked: Vec<String> = wallet.locked_coins().map(ToString::to_string).collect();
+            let lines: Vec<String> = locked.iter().map(|op| format!("🔐 {}", op)).collect();
+            emit(&lines.join("\n"), json!(locked));
+        }
+
+        WalletCommand::LockUtxo { outpoint: Some(outpoint) } => match wallet.lock_coin(outpoint) {
+            Ok(true) => say!("🔐 {} locked", outpoint),
+            Ok(false) => say!("ℹ️ No change for {}", outpoint),
+            Err(e) => fail!("Wallet error: {}", e),
+        },
+
+        WalletCommand::UnlockUtxo { outpoint } => match wallet.unlock_coin(&outpoint) {
+            Ok(true) => say!("🔓 {} unlocked", outpoint),
+            Ok(false) => say!("ℹ️ No change for {}", outpoint),
+            Err(e) => fail!("Wallet error: {}", e),
+        },
+
+        // ───────────────── RESCAN ─────────────────
+        WalletCommand::Rescan { height, gap_limit } => {
+            let from_height = height.unwrap_or(0);
+            let gap_limit = gap_limit.unwrap_or_else(|| load_wallet_config().gap_limit);
+
+            let chain_guard = chain.lock().unwrap();
+            match wallet.rescan(&chain_guard.blocks, from_height, gap_limit) {
+                Ok(n) => emit(
+                    &format!(
+                        "🔎 Rescanned from height {}: {} address(es) in use, {} transaction(s)",
+                        from_height,
+                        wallet.next_index(),
+                        n
+                    ),
+                    json!({ "from_height": from_height, "addresses": wallet.next_index(), "transactions": n }),
+                ),
+                Err(e) => fail!("Wallet error: {}", e),
+            }
+        }
+
+        // ───────────────── HISTORY ─────────────────
+        WalletCommand::History { limit } => {
+            let limit = limit.unwrap_or(usize::MAX);
+
+            let chain_guard = chain.lock().unwrap();
+            let entries = match wallet.history(&chain_guard.blocks) {
+                Ok(e) => e,
                 Err(e) => {
-                    println!("❌ Wallet error: {}", e);
+                    fail!("Wallet error: {}", e);
                     return;
                 }
             };
 
-            if !validate_transaction(&tx, &chain_guard.utxos, current_height) {
-                println!("❌ Transaction failed consensus validation");
+            let total = entries.len();
+            let shown = &entries[..limit.min(total)];
+
+            let mut lines: Vec<String> = shown
+                .iter()
+                .map(|e| {
+                    let fee = e.fee.map(|f| f.to_string()).unwrap_or_else(|| "-".to_string());
+                    let label = e.label.as_ref().map(|l| format!("  \"{}\"", l)).unwrap_or_default();
+                    format!(
+                        "{}  {:?}  {}  fee {}  height {}  ({} conf){}",
+                        e.txid, e.direction, e.amount, fee, e.height, e.confirmations, label
+                    )
+                })
+                .collect();
+            if total == 0 {
+                lines.push("No wallet transactions yet".to_string());
+            }
+            if total > limit {
+                lines.push(format!("… {} older transactions", total - limit));
+            }
+
+            emit(&lines.join("\n"), json!({ "transactions": shown, "total": total }));
+        }
+
+        // ───────────────── LABEL ─────────────────
+        WalletCommand::Label { txid, text } => {
+            let text = text.join(" ");
+
+            match wallet.set_label(&txid, &text) {
+                Ok(()) if text.is_empty() => say!("🏷 Label removed"),
+                Ok(()) => say!("🏷 Labeled {}", txid),
+                Err(e) => fail!("Wallet error: {}", e),
+            }
+        }
+
+        // ───────────────── XPUB (for watch-only) ─────────────────
+        WalletCommand::Xpub => match wallet.xpub() {
+            Ok(xpub) => emit(&format!("👁 {}", xpub), json!({ "xpub": xpub })),
+            Err(e) => fail!("{}", e),
+        },
+
+        // ───────────────── DUMP (every private key) ─────────────────
+        WalletCommand::DumpWallet { file } => {
+            say!("⚠️ The dump holds the wallet seed and every private key, unencrypted.");
+            say!("⚠️ Anyone who reads the file can spend this wallet's coins.");
+            if prompt_line("Type DUMP to continue: ") != "DUMP" {
+                fail!("Cancelled");
                 return;
             }
 
-            drop(chain_guard);
+            prompt!("🔑 Wallet password: ");
+            let password = read_password().unwrap();
+
+            let result = wallet.dump(&password).and_then(|dump| {
+                dump.save(&file)?;
+                Ok(dump.keys.len())
+            });
+            match result {
+                Ok(n) => emit(
+                    &format!("📝 Seed and {} keys written to {}", n, file),
+                    json!({ "file": file, "keys": n }),
+                ),
+                Err(e) => fail!("{}", e),
+            }
+        }
+
+        // ───────────────── SIGN MESSAGE ─────────────────
+        WalletCommand::SignMessage { address: encoded, message } => {
+            let address = match decode_address(&encoded) {
+                Ok(a) => a,
+                Err(e) => {
+                    fail!("{}", e);
+                    return;
+                }
+            };
+
+            match wallet.sign_message(&address, &message.join(" ")) {
+                Ok(sig) => {
+                    let sig = hex::encode(sig);
+                    emit(&format!("✍️ {}", sig), json!({ "address": encoded, "signature": sig }))
+                }
+                Err(e) => fail!("{}", e),
+            }
+        }
+
+        // ───────────────── SIGN (offline) ─────────────────
+        WalletCommand::Sign { psbt: path } => {
+            let mut psbt = match PartiallySignedTransaction::load(&path) {
+                Ok(p) => p,
+                Err(e) => {
+                    fail!("{}", e);
+                    return;
+                }
+            };
+
+            // Show what is being signed
+            for o in &psbt.tx.outputs {
+                say!("➡️ {}  {}", encode_address(&o.pubkey_hash), o.value);
+            }
+            say!("🧾 Fee: {}", psbt.fee().unwrap_or(0));
+
+            match wallet.sign_psbt(&mut psbt).and_then(|n| psbt.save(&path).map(|_| n)) {
+                Ok(n) => emit(
+                    &format!(
+                        "✍️ Added {} signature(s); {} of {} input(s) signed",
+                        n,
+                        psbt.signed_inputs(),
+                        psbt.tx.inputs.len()
+                    ),
+                    json!({ "added": n, "signed_inputs": psbt.signed_inputs(), "inputs": psbt.tx.inputs.len() }),
+                ),
+                Err(e) => fail!("Wallet error: {}", e),
+            }
+        }
+
+        // ───────────────── SIGN OFFLINE (air-gapped) ─────────────────
+        WalletCommand::SignOffline { psbt: source, out } => {
+            let mut psbt = match PartiallySignedTransaction::load(&source) {
+                Ok(p) => p,
+                Err(e) => {
+                    fail!("{}", e);
+                    return;
+                }
+            };
+
+            // No chain here: the amounts come from the PSBT itself
+            for o in &psbt.tx.outputs {
+                say!("➡️ {}  {}", encode_address(&o.pubkey_hash), o.value);
+            }
+            say!("🧾 Fee: {}", psbt.fee().unwrap_or(0));
+
+            prompt!("Sign? [y/N] ");
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).unwrap();
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                fail!("Cancelled");
+                return;
+            }
 
-            let mut mempool_guard = mempool.lock().unwrap();
+            if let Err(e) = wallet.sign_psbt(&mut psbt) {
+                fail!("Wallet error: {}", e);
+                return;
+            }
+
+            // Inline payloads have no file to update
+            let out = out.or_else(|| (!source.starts_with("PSBT:")).then_some(source));
+            if let Some(path) = out {
+                match psbt.save(&path) {
+                    Ok(()) => say!("📝 Signed transaction written to {}", path),
+                    Err(e) => fail!("{}", e),
+                }
+            }
+
+            let payload = psbt.to_payload();
+            emit(
+                &format!(
+                    "✍️ {} of {} input(s) signed\n📷 QR payload:\n{}",
+                    psbt.signed_inputs(),
+                    psbt.tx.inputs.len(),
+                    payload
+                ),
+                json!({ "signed_inputs": psbt.signed_inputs(), "inputs": psbt.tx.inputs.len(), "payload": payload }),
+            );
+        }
+
+        // ───────────────── COSIGNER KEY ─────────────────
+        WalletCommand::Pubkey { index } => {
+            let index = index.unwrap_or(0);
+
+            match wallet.pubkey(index) {
+                Ok(pk) => {
+                    let pk = hex::encode(pk);
+                    emit(&format!("🔑 {}", pk), json!({ "index": index, "pubkey": pk }))
+                }
+                Err(e) => fail!("Wallet error: {}", e),
+            }
+        }
+
+        // ───────────────── UPGRADE (v3 → BIP32) ─────────────────
+        WalletCommand::Upgrade => {
+            if !wallet.is_legacy() {
+                say!("✅ Wallet already uses BIP32 keys");
+                return;
+            }
+
+            prompt!("Recovery phrase: ");
+            let mnemonic = read_password().unwrap();
+
+            prompt!("Wallet password: ");
+            let password = read_password().unwrap();
+
+            // Typing the phrase may outlast the auto-lock
+            if wallet.unlock(&password, Some(DEFAULT_AUTO_LOCK)).is_err() {
+                fail!("Wrong password");
+                return;
+            }
+
+            match wallet.upgrade_to_bip32(&password, mnemonic.trim()) {
+                Ok(()) => {
+                    say!("✅ Wallet upgraded to BIP32/BIP44 keys");
+                    note("ℹ️ Coins at old addresses remain spendable");
+                }
+                Err(e) => fail!("Upgrade failed: {}", e),
+            }
+        }
+
+        // ───────────────── PASSWORD CHANGE ─────────────────
+        WalletCommand::Passwd => {
+            prompt!("Current password: ");
+            let old = read_password().unwrap();
+
+            prompt!("New password: ");
+            let new = read_password().unwrap();
+
+            prompt!("Repeat new password: ");
+            if read_password().unwrap() != new {
+                fail!("Passwords do not match");
+                return;
+            }
+
+            match wallet.change_password(&old, &new) {
+                Ok(()) => say!("✅ Wallet password changed"),
+                Err(e) => fail!("Password change failed: {}", e),
+            }
+        }
+
+        _ => unreachable!("checked by is_wallet_store_command / is_seedless_wallet_command"),
+    }
+}
+
+/// Watch-only wallet commands (no seed or password needed)
+pub fn handle_watch_command(
+    command: WatchCommand,
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+) {
+    let imported = match &command {
+        WatchCommand::Import { xpub } => Some(WatchOnlyWallet::from_xpub(xpub)),
+        WatchCommand::Add { pubkey } => {
+            let mut w = load_watch_wallet().unwrap_or_default();
+            Some(w.add_pubkey(pubkey).map(|_| w))
+        }
+        _ => None,
+    };
+
+    if let Some(result) = imported {
+        match result {
+            Ok(w) => {
+                save_watch_wallet(&w);
+                emit(&format!("👁 Watching {} key(s)", w.keys().len()), json!({ "keys": w.keys().len() }));
+            }
+            Err(e) => fail!("{}", e),
+        }
+        return;
+    }
+
+    if let WatchCommand::Submit { psbt } = &command {
+        match PartiallySignedTransaction::load(psbt).and_then(|p| p.finalize()) {
+            Ok(tx) => submit_transaction(tx, &chain, &mempool),
+            Err(e) => fail!("{}", e),
+        }
+        return;
+    }
+
+    let watch = match load_watch_wallet() {
+        Some(w) => w,
+        None => {
+            fail!("No watch-only wallet; run `watch import <xpub>` first");
+            return;
+        }
+    };
+
+    match command {
+        WatchCommand::Balance => {
             let chain_guard = chain.lock().unwrap();
+            let balance = watch.balance(&chain_guard.utxos, chain_guard.height());
+            emit_balance(balance.total, balance.spendable, balance.locked);
+        }
+
+        WatchCommand::Coins => {
+            let chain_guard = chain.lock().unwrap();
+            let coins: Vec<(String, u64, u64)> = watch
+                .coins(&chain_guard.utxos)
+                .into_iter()
+                .map(|(outpoint, utxo)| {
+                    (format!("{}:{}", hex::encode(outpoint.txid), outpoint.vout), utxo.value, utxo.height)
+                })
+                .collect();
+            emit_coins(&coins);
+        }
+
+        WatchCommand::Send(payment) => write_unsigned_payment(&watch, &payment, &chain, &mempool),
+
+        WatchCommand::Import { .. } | WatchCommand::Add { .. } | WatchCommand::Submit { .. } => {
+            unreachable!("handled above")
+        }
+    }
+}
 
-            if mempool_guard.add_transaction(tx, &chain_guard.utxos, current_height) {
-                println!("✅ Transaction added to mempool");
+/// Build an unsigned PSBT from watch-only coins
+///
+/// The PSBT is also printed as a QR payload.
+fn write_unsigned_payment(
+    watch: &WatchOnlyWallet,
+    payment: &PaymentArgs,
+    chain: &Arc<Mutex<Blockchain>>,
+    mempool: &Arc<Mutex<Mempool>>,
+) {
+    let to = match decode_address(&payment.to) {
+        Ok(to) => to,
+        Err(_) => {
+            fail!("Invalid address");
+            return;
+        }
+    };
+
+    let fee_rate = fee_rate_arg(payment.sat_per_byte, mempool);
+    let file = &payment.psbt;
+
+    let chain_guard = chain.lock().unwrap();
+    let result = watch
+        .create_unsigned_transaction(&chain_guard.utxos, to, payment.amount, fee_rate)
+        .and_then(|payment| {
+            let psbt = PartiallySignedTransaction::new(payment.tx, &chain_guard.utxos)?;
+            psbt.save(file)?;
+            Ok(psbt)
+        });
+
+    match result {
+        Ok(psbt) => {
+            let (fee, payload) = (psbt.fee().unwrap_or(0), psbt.to_payload());
+            emit(
+                &format!(
+                    "📝 Unsigned transaction written to {}\n🧾 Fee: {}\n📷 QR payload:\n{}",
+                    file, fee, payload
+                ),
+                json!({ "file": file, "fee": fee, "payload": payload }),
+            );
+            note("   Sign with `wallet sign-offline`, then `wallet broadcast`");
+        }
+        Err(e) => fail!("Wallet error: {}", e),
+    }
+}
+
+/// Wallet commands of an air-gapped setup that need no seed:
+/// the online machine builds and broadcasts, the offline one
+/// runs `wallet sign-offline`
+/// Wallet opened when `--wallet` isn't given: the one picked by
+/// `wallet use`, else the configured coinbase wallet
+pub fn default_wallet(wallets: &WalletManager) -> String {
+    wallets
+        .current()
+        .cloned()
+        .unwrap_or_else(|| load_miner_config().coinbase_wallet)
+}
+
+/// Recovery phrase words the user must type back on `wallet new`
+const CONFIRM_WORDS: usize = 3;
+
+/// Wallet registry commands; no wallet is unlocked for them
+pub fn is_wallet_store_command(command: &WalletCommand) -> bool {
+    matches!(
+        command,
+        WalletCommand::New { .. }
+            | WalletCommand::Restore { .. }
+            | WalletCommand::ImportWallet { .. }
+            | WalletCommand::List
+            | WalletCommand::Use { .. }
+    )
+}
+
+fn prompt_line(msg: &str) -> String {
+    output::prompt(msg);
+
+    let mut line = String::new();
+    io::stdin().read_line(&mut line).unwrap();
+    line.trim().to_string()
+}
+
+/// Ask for a new password twice
+fn prompt_new_password() -> Option<String> {
+    prompt!("New wallet password: ");
+    let password = read_password().unwrap();
+
+    prompt!("Repeat password: ");
+    if read_password().unwrap() != password {
+        fail!("Passwords do not match");
+        return None;
+    }
+    Some(password)
+}
+
+/// Show a new recovery phrase and have the user type back a
+/// few of its words
+fn confirm_mnemonic(mnemonic: &str) -> bool {
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
+
+    prompt!("\n⚠️ WRITE THIS DOWN — WALLET RECOVERY PHRASE ⚠️\n");
+    for (i, word) in words.iter().enumerate() {
+        prompt!("{:>3}. {}\n", i + 1, word);
+    }
+    prompt!("⚠️ ANYONE WITH THESE WORDS CAN SPEND YOUR COINS ⚠️\n\n");
+    prompt_line("Press Enter once it is written down...");
+
+    // Scroll the phrase off screen before asking
+    output::prompt("\x1b[2J\x1b[H");
+
+    let mut positions: Vec<usize> = (0..words.len()).collect();
+    for i in 0..CONFIRM_WORDS.min(words.len()) {
+        let j = i + (OsRng.next_u32() as usize) % (positions.len() - i);
+        positions.swap(i, j);
+    }
+    positions.truncate(CONFIRM_WORDS);
+    positions.sort_unstable();
+
+    positions.into_iter().all(|i| {
+        let answer = prompt_line(&format!("Word #{}: ", i + 1));
+        answer.eq_ignore_ascii_case(words[i])
+    })
+}
+
+/// `wallet new | restore | list | use`; `selected` is `--wallet`
+pub fn handle_wallet_store_command(
+    command: WalletCommand,
+    selected: Option<&str>,
+    chain: Arc<Mutex<Blockchain>>,
+) {
+    let mut wallets = WalletManager::new(load_wallet_store());
+    let current = match selected {
+        Some(name) => name.to_string(),
+        None => default_wallet(&wallets),
+    };
+
+    match command {
+        // ───────────────── LIST ─────────────────
+        WalletCommand::List => {
+            let mut lines = Vec::new();
+            let mut list = Vec::new();
+            for (wallet_name, _) in wallets.list() {
+                let marker = if wallet_name == current { "*" } else { " " };
+                let path = wallets.path(&wallet_name).cloned().unwrap_or_default();
+                lines.push(format!("{} {:<16} {}", marker, wallet_name, path));
+                list.push(json!({ "name": wallet_name, "path": path, "current": wallet_name == current }));
+            }
+            emit(&lines.join("\n"), Value::Array(list));
+        }
+
+        // ───────────────── USE ─────────────────
+        WalletCommand::Use { name } => {
+            match wallets.set_current(&name) {
+                Ok(()) => say!("👛 Wallet '{}' is now the default", name),
+                Err(e) => fail!("{}", e),
+            }
+        }
+
+        // ───────────────── NEW ─────────────────
+        WalletCommand::New { name } => {
+            let Some(password) = prompt_new_password() else {
+                return;
+            };
+
+            let mnemonic = generate_mnemonic();
+            if !confirm_mnemonic(&mnemonic) {
+                fail!("Words did not match; no wallet was created");
+                return;
+            }
+
+            match wallets.create_from_mnemonic(&name, &password, &mnemonic) {
+                Ok(w) => emit(
+                    &format!("✅ Wallet '{}' created at {}", name, w.path()),
+                    json!({ "name": name, "path": w.path() }),
+                ),
+                Err(e) => fail!("Wallet creation failed: {}", e),
+            }
+        }
+
+        // ───────────────── RESTORE ─────────────────
+        WalletCommand::Restore { name } => {
+            let mnemonic = prompt_line("Recovery phrase: ");
+            let Some(password) = prompt_new_password() else {
+                return;
+            };
+
+            let wallet = match wallets.create_from_mnemonic(&name, &password, &mnemonic) {
+                Ok(w) => w,
+                Err(e) => {
+                    fail!("Wallet restore failed: {}", e);
+                    return;
+                }
+            };
+
+            let gap_limit = load_wallet_config().gap_limit;
+            let c = chain.lock().unwrap();
+            match wallet.rescan(&c.blocks, 0, gap_limit) {
+                Ok(n) => emit(
+                    &format!("✅ Wallet '{}' restored; {} history entries found", name, n),
+                    json!({ "name": name, "transactions": n }),
+                ),
+                Err(e) => fail!("Rescan failed: {}", e),
+            }
+        }
+
+        // ───────────────── IMPORT (from dumpwallet) ─────────────────
+        WalletCommand::ImportWallet { name, file } => {
+            let dump = match WalletDump::load(&file) {
+                Ok(d) => d,
+                Err(e) => {
+                    fail!("{}", e);
+                    return;
+                }
+            };
+
+            say!("📄 Seed, next index {}, {} keys", dump.next_index, dump.keys.len());
+            prompt!("Import as wallet '{}'? [y/N] ", name);
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).unwrap();
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                fail!("Cancelled");
+                return;
+            }
+
+            let Some(password) = prompt_new_password() else {
+                return;
+            };
+
+            let wallet = match wallets.import_dump(&name, &password, &dump) {
+                Ok(w) => w,
+                Err(e) => {
+                    fail!("Wallet import failed: {}", e);
+                    return;
+                }
+            };
+
+            // Keys added to the file by hand are not derived from its seed
+            let foreign = dump.keys.iter().filter(|k| !wallet.derives_key(&k.key).unwrap_or(false)).count();
+            if foreign > 0 {
+                say!("⚠️ {} key(s) in the file do not come from its seed; move their coins with `wallet sweep`", foreign);
+            }
+
+            let gap_limit = load_wallet_config().gap_limit;
+            let c = chain.lock().unwrap();
+            match wallet.rescan(&c.blocks, 0, gap_limit) {
+                Ok(n) => emit(
+                    &format!("✅ Wallet '{}' imported; {} history entries found", name, n),
+                    json!({ "name": name, "transactions": n, "foreign_keys": foreign }),
+                ),
+                Err(e) => fail!("Rescan failed: {}", e),
+            }
+        }
+
+        _ => unreachable!("checked by is_wallet_store_command"),
+    }
+}
+
+pub fn is_seedless_wallet_command(command: &WalletCommand) -> bool {
+    matches!(
+        command,
+        WalletCommand::CreateUnsigned(_) | WalletCommand::Broadcast { .. } | WalletCommand::VerifyMessage { .. }
+    )
+}
+
+/// `wallet create-unsigned` / `wallet broadcast` /
+/// `wallet verifymessage`
+pub fn handle_seedless_wallet_command(
+    command: WalletCommand,
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+) {
+    match command {
+        // ───────────────── CREATE UNSIGNED (online) ─────────────────
+        WalletCommand::CreateUnsigned(payment) => {
+            match load_watch_wallet() {
+                Some(watch) => write_unsigned_payment(&watch, &payment, &chain, &mempool),
+                None => fail!("No watch-only wallet; export `wallet xpub` offline and run `watch import <xpub>`"),
+            }
+        }
+
+        // ───────────────── BROADCAST (online) ─────────────────
+        WalletCommand::Broadcast { psbt } => {
+            match PartiallySignedTransaction::load(&psbt).and_then(|p| p.finalize()) {
+                Ok(tx) => submit_transaction(tx, &chain, &mempool),
+                Err(e) => fail!("{}", e),
+            }
+        }
+
+        // ───────────────── VERIFY MESSAGE ─────────────────
+        WalletCommand::VerifyMessage { address: encoded, signature, message } => {
+            let (address, sig) = match (decode_address(&encoded), hex::decode(&signature)) {
+                (Ok(a), Ok(s)) => (a, s),
+                _ => {
+                    fail!("Invalid address or signature");
+                    return;
+                }
+            };
+
+            let valid = verify_message(&address, &message.join(" "), &sig);
+            let text = if valid {
+                format!("✅ Signature valid for {}", encoded)
             } else {
-                println!("❌ Transaction rejected by mempool policy");
+                "❌ Signature invalid".to_string()
+            };
+            emit(&text, json!({ "address": encoded, "valid": valid }));
+        }
+
+        _ => unreachable!("checked by is_seedless_wallet_command"),
+    }
+}
+
+/// Shared m-of-n wallet commands (public keys only)
+///
+/// Each cosigner signs the PSBT with `wallet sign`; the copies
+/// are merged with `multisig combine` and broadcast with
+/// `watch submit`.
+pub fn handle_multisig_command(
+    command: MultisigCommand,
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+) {
+    let mut wallets = load_multisig_wallets();
+
+    let name = match &command {
+        MultisigCommand::Create { name, m, pubkeys } => {
+            if wallets.iter().any(|w| &w.name == name) {
+                fail!("Multisig wallet '{}' already exists", name);
+                return;
+            }
+
+            match MultisigWallet::new(name, *m, pubkeys) {
+                Ok(w) => {
+                    let script = hex::encode(w.script_pubkey());
+                    emit(
+                        &format!(
+                            "🤝 {}-of-{} wallet '{}' created\n📥 Pay to script: {}",
+                            w.m,
+                            w.pubkeys.len(),
+                            w.name,
+                            script
+                        ),
+                        json!({ "name": w.name, "m": w.m, "n": w.pubkeys.len(), "script_pubkey": script }),
+                    );
+                    wallets.push(w);
+                    save_multisig_wallets(&wallets);
+                }
+                Err(e) => fail!("{}", e),
             }
+            return;
+        }
+
+        MultisigCommand::List => {
+            let lines: Vec<String> = wallets
+                .iter()
+                .map(|w| format!("{:<16} {}-of-{}", w.name, w.m, w.pubkeys.len()))
+                .collect();
+            let list: Vec<Value> = wallets
+                .iter()
+                .map(|w| json!({ "name": w.name, "m": w.m, "n": w.pubkeys.len() }))
+                .collect();
+            emit(&lines.join("\n"), Value::Array(list));
+            return;
+        }
+
+        MultisigCommand::Combine { psbt: path, others } => {
+            let result = PartiallySignedTransaction::load(path).and_then(|mut psbt| {
+                for other in others {
+                    psbt.combine(&PartiallySignedTransaction::load(other)?)?;
+                }
+                psbt.save(path)?;
+                Ok(psbt)
+            });
+
+            match result {
+                Ok(psbt) => {
+                    emit(
+                        &format!("🧩 {} of {} input(s) fully signed", psbt.signed_inputs(), psbt.tx.inputs.len()),
+                        json!({
+                            "signed_inputs": psbt.signed_inputs(),
+                            "inputs": psbt.tx.inputs.len(),
+                            "complete": psbt.is_complete(),
+                        }),
+                    );
+                    if psbt.is_complete() {
+                        note(&format!("   Ready: `watch submit {}`", path));
+                    }
+                }
+                Err(e) => fail!("{}", e),
+            }
+            return;
+        }
+
+        MultisigCommand::Address { name }
+        | MultisigCommand::Balance { name }
+        | MultisigCommand::Coins { name }
+        | MultisigCommand::Spend { name, .. } => name,
+    };
+
+    let wallet = match wallets.iter().find(|w| &w.name == name) {
+        Some(w) => w,
+        None => {
+            fail!("Unknown multisig wallet; see `multisig list`");
+            return;
         }
+    };
 
-        _ => {
-            println!("Unknown wallet command");
+    match &command {
+        MultisigCommand::Address { .. } => {
+            let (script, address) = (hex::encode(wallet.script_pubkey()), encode_address(&wallet.address()));
+            emit(
+                &format!("📥 Pay to script: {}\n🏷 Address:        {}", script, address),
+                json!({ "script_pubkey": script, "address": address }),
+            );
         }
+
+        MultisigCommand::Balance { .. } => {
+            let chain_guard = chain.lock().unwrap();
+            let balance = wallet.balance(&chain_guard.utxos, chain_guard.height());
+            emit_balance(balance.total, balance.spendable, balance.locked);
+        }
+
+        MultisigCommand::Coins { .. } => {
+            let chain_guard = chain.lock().unwrap();
+            let coins: Vec<(String, u64, u64)> = wallet
+                .coins(&chain_guard.utxos)
+                .into_iter()
+                .map(|(outpoint, utxo)| (outpoint.to_string(), utxo.value, utxo.height))
+                .collect();
+            emit_coins(&coins);
+        }
+
+        MultisigCommand::Spend { payment, .. } => {
+            let to = match decode_address(&payment.to) {
+                Ok(to) => to,
+                Err(_) => {
+                    fail!("Invalid address");
+                    return;
+                }
+            };
+
+            let fee_rate = fee_rate_arg(payment.sat_per_byte, &mempool);
+
+            let chain_guard = chain.lock().unwrap();
+            let result = wallet
+                .create_spend(&chain_guard.utxos, to, payment.amount, fee_rate)
+                .and_then(|psbt| psbt.save(&payment.psbt).map(|_| psbt));
+
+            match result {
+                Ok(psbt) => {
+                    let fee = psbt.fee().unwrap_or(0);
+                    emit(
+                        &format!("📝 Unsigned transaction written to {}\n🧾 Fee: {}", payment.psbt, fee),
+                        json!({ "file": payment.psbt, "fee": fee, "signatures_needed": wallet.m }),
+                    );
+                    note(&format!("   Needs {} signature(s): each cosigner runs `wallet sign`", wallet.m));
+                }
+                Err(e) => fail!("Wallet error: {}", e),
+            }
+        }
+
+        _ => unreachable!("handled above"),
+    }
+}
+
+/// Commands signed by an external signer; the seed never
+/// touches this machine
+pub fn handle_signer_command(
+    command: SignerCommand,
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+) {
+    let config = load_wallet_config();
+    let signer: Box<dyn Signer> = match (&config.external_signer, &config.signer_dir) {
+        (Some(cmd), _) => match ExternalSigner::new(cmd) {
+            Ok(s) => Box::new(s),
+            Err(e) => {
+                fail!("{}", e);
+                return;
+            }
+        },
+        (None, Some(dir)) => Box::new(FileSigner::new(dir)),
+        (None, None) => {
+            fail!("No signer configured; set external_signer or signer_dir in wallet_config.json");
+            return;
+        }
+    };
+
+    match command {
+        SignerCommand::Pubkeys => match signer.public_keys() {
+            Ok(keys) => {
+                let lines: Vec<String> = keys.iter().map(|(index, pk)| format!("{:>4}  {}", index, hex::encode(pk))).collect();
+                let list: Vec<Value> = keys
+                    .iter()
+                    .map(|(index, pk)| json!({ "index": index, "pubkey": hex::encode(pk) }))
+                    .collect();
+                emit(&lines.join("\n"), Value::Array(list));
+            }
+            Err(e) => fail!("Signer error: {}", e),
+        },
+
+        SignerCommand::Send { recipients } => {
+            let (recipients, sat_per_byte) = match recipients_arg(&recipients) {
+                Some(r) => r,
+                None => return,
+            };
+            let fee_rate = fee_rate_arg(sat_per_byte, &mempool);
+
+            let utxos = chain.lock().unwrap().utxos.clone();
+            match create_signed_transaction(signer.as_ref(), &utxos, &recipients, fee_rate) {
+                Ok(payment) => {
+                    emit(&format!("🧾 Fee: {}", payment.fee), json!({ "fee": payment.fee }));
+                    submit_transaction(payment.tx, &chain, &mempool);
+                }
+                Err(e) => fail!("Signer error: {}", e),
+            }
+        }
+
+        SignerCommand::Sign { psbt: path } => {
+            let result = PartiallySignedTransaction::load(&path).and_then(|mut psbt| {
+                let n = sign_with(&mut psbt.tx, signer.as_ref())?;
+                psbt.save(&path)?;
+                Ok((n, psbt))
+            });
+
+            match result {
+                Ok((n, psbt)) => emit(
+                    &format!("✍️ Signed {} of {} input(s)", n, psbt.tx.inputs.len()),
+                    json!({ "signed": n, "inputs": psbt.tx.inputs.len() }),
+                ),
+                Err(e) => fail!("Signer error: {}", e),
+            }
+        }
+    }
+}
+
+/// Balance of a watch-only or multisig wallet
+fn emit_balance(total: u64, spendable: u64, locked: u64) {
+    emit(
+        &format!(
+            "💰 Total balance:     {}\n💸 Spendable balance: {}\n🔒 Locked balance:    {}",
+            total, spendable, locked
+        ),
+        json!({ "total": total, "spendable": spendable, "locked": locked }),
+    );
+}
+
+/// `(outpoint, value, height)` per coin
+fn emit_coins(coins: &[(String, u64, u64)]) {
+    let lines: Vec<String> = coins
+        .iter()
+        .map(|(outpoint, value, height)| format!("{}  {}  (height {})", outpoint, value, height))
+        .collect();
+    let list: Vec<Value> = coins
+        .iter()
+        .map(|(outpoint, value, height)| json!({ "outpoint": outpoint, "value": value, "height": height }))
+        .collect();
+    emit(&lines.join("\n"), Value::Array(list));
+}
+
+/// `<to> <amount>` pairs, then an optional trailing sat/byte
+/// fee rate
+fn recipients_arg(args: &[String]) -> Option<(Vec<(Vec<u8>, u64)>, Option<u64>)> {
+    let (pairs, fee) = if args.len() % 2 == 1 {
+        (&args[..args.len() - 1], args.last())
+    } else {
+        (args, None)
+    };
+
+    let fee = match fee.map(|f| f.parse::<u64>()).transpose() {
+        Ok(fee) => fee,
+        Err(_) => {
+            fail!("Invalid fee rate");
+            return None;
+        }
+    };
+
+    let mut recipients = Vec::new();
+    for pair in pairs.chunks(2) {
+        match (decode_address(&pair[0]), pair[1].parse::<u64>()) {
+            (Ok(to), Ok(amount)) => recipients.push((to, amount)),
+            _ => {
+                fail!("Invalid address or amount: {} {}", pair[0], pair[1]);
+                return None;
+            }
+        }
+    }
+
+    Some((recipients, fee))
+}
+
+/// Explicit sat/byte rate, else the fee estimator's rate
+fn fee_rate_arg(sat_per_byte: Option<u64>, mempool: &Arc<Mutex<Mempool>>) -> FeeRate {
+    match sat_per_byte {
+        Some(rate) => FeeRate::SatPerByte(rate),
+        None => FeeRate::PerKilobyte(
+            mempool
+                .lock()
+                .unwrap()
+                .estimate_feerate(DEFAULT_CONFIRM_TARGET)
+                .unwrap_or(FALLBACK_FEE_RATE),
+        ),
+    }
+}
+
+/// Add a signed transaction to the mempool, which validates it
+/// (it may spend outputs of unconfirmed mempool transactions)
+fn submit_transaction(tx: Transaction, chain: &Arc<Mutex<Blockchain>>, mempool: &Arc<Mutex<Mempool>>) {
+    let mut mempool_guard = mempool.lock().unwrap();
+    let chain_guard = chain.lock().unwrap();
+    let ctx = chain_guard.next_block_context();
+    let txid = hex::encode(tx.txid());
+
+    match mempool_guard.add_transaction(tx, &chain_guard.utxos, &ctx) {
+        Ok(()) => emit("✅ Transaction added to mempool", json!({ "txid": txid })),
+        Err(e) => fail!("Transaction rejected by mempool: {}", e),
     }
 }
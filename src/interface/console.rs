@@ -0,0 +1,160 @@
+use serde_json::{json, Value};
+
+/// HTTP verb of an API call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verb {
+    Get,
+    Post,
+}
+
+/// Console command mapped onto the node's HTTP API (UI ONLY)
+pub struct RpcMethod {
+    pub name: &'static str,
+    /// Parameter names; `[optional]` ones last
+    pub params: &'static [&'static str],
+    pub help: &'static str,
+}
+
+/// Request to send for one console line
+#[derive(Debug, PartialEq)]
+pub struct RpcRequest {
+    pub verb: Verb,
+    /// Path and query, e.g. "/blocks?limit=5"
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+pub const METHODS: &[RpcMethod] = &[
+    RpcMethod { name: "getstatus", params: &[], help: "Height, supply and mempool size" },
+    RpcMethod { name: "getsyncinfo", params: &[], help: "Initial sync progress" },
+    RpcMethod { name: "getblock", params: &["height|hash"], help: "Block with its transactions" },
+    RpcMethod { name: "getblocks", params: &["[limit]"], help: "Latest blocks" },
+    RpcMethod { name: "gettransaction", params: &["txid"], help: "Confirmed or mempool transaction" },
+    RpcMethod { name: "getrawtransaction", params: &["txid"], help: "Serialized transaction (hex)" },
+    RpcMethod { name: "getmempoolinfo", params: &[], help: "Mempool size and minimum fee rate" },
+    RpcMethod { name: "getrawmempool", params: &[], help: "Mempool txids, best fee rate first" },
+    RpcMethod { name: "getaddressinfo", params: &["address"], help: "Balance of an address" },
+    RpcMethod { name: "getaddresstxs", params: &["address", "[offset]"], help: "Transactions of an address" },
+    RpcMethod { name: "estimatefee", params: &["target_blocks"], help: "Fee rate per 1000 bytes" },
+    RpcMethod { name: "getpeerinfo", params: &[], help: "Connected peers" },
+    RpcMethod { name: "getmininginfo", params: &[], help: "Local hashrate" },
+    RpcMethod { name: "getsupply", params: &[], help: "Circulating vs expected supply" },
+    RpcMethod { name: "listwallets", params: &[], help: "Registered wallets" },
+    RpcMethod { name: "unlockwallet", params: &["name", "password", "[timeout_secs]"], help: "Open and unlock a wallet" },
+    RpcMethod { name: "lockwallet", params: &["name"], help: "Lock a wallet" },
+    RpcMethod { name: "getnewaddress", params: &["name"], help: "Next receive address of a wallet" },
+    RpcMethod { name: "getbalance", params: &["name"], help: "Balance of a wallet" },
+    RpcMethod { name: "send", params: &["name", "to", "amount", "[sat_per_byte]"], help: "Pay from an unlocked wallet" },
+];
+
+pub fn find_method(name: &str) -> Option<&'static RpcMethod> {
+    METHODS.iter().find(|m| m.name == name)
+}
+
+/// Method names starting with `prefix`, for tab completion
+pub fn complete(prefix: &str) -> Vec<&'static str> {
+    METHODS.iter().map(|m| m.name).filter(|n| n.starts_with(prefix)).collect()
+}
+
+impl RpcMethod {
+    pub fn usage(&self) -> String {
+        std::iter::once(self.name).chain(self.params.iter().copied()).collect::<Vec<_>>().join(" ")
+    }
+
+    fn required(&self) -> usize {
+        self.params.iter().filter(|p| !p.starts_with('[')).count()
+    }
+}
+
+fn number(arg: &str, what: &'static str) -> Result<u64, String> {
+    arg.parse().map_err(|_| format!("{} must be a number", what))
+}
+
+/// Turn `method args...` into an API request
+pub fn build_request(method: &str, args: &[String]) -> Result<RpcRequest, String> {
+    let m = find_method(method).ok_or_else(|| format!("unknown method '{}' (try help)", method))?;
+
+    if args.len() < m.required() || args.len() > m.params.len() {
+        return Err(format!("usage: {}", m.usage()));
+    }
+
+    let get = |path: String| RpcRequest { verb: Verb::Get, path, body: None };
+    let post = |path: String, body: Value| RpcRequest { verb: Verb::Post, path, body: Some(body) };
+    let arg = |i: usize| args.get(i).map(String::as_str);
+
+    Ok(match m.name {
+        "getstatus" => get("/status".into()),
+        "getsyncinfo" => get("/sync".into()),
+        "getblock" => match args[0].parse::<u64>() {
+            Ok(height) => get(format!("/block/height/{}", height)),
+            Err(_) => get(format!("/block/{}/verbose", args[0])),
+        },
+        "getblocks" => match arg(0) {
+            Some(limit) => get(format!("/blocks?limit={}", number(limit, "limit")?)),
+            None => get("/blocks".into()),
+        },
+        "gettransaction" => get(format!("/tx/{}/verbose", args[0])),
+        "getrawtransaction" => get(format!("/tx/{}/raw", args[0])),
+        "getmempoolinfo" => get("/mempool".into()),
+        "getrawmempool" => get("/mempool/txids".into()),
+        "getaddressinfo" => get(format!("/address/{}", args[0])),
+        "getaddresstxs" => {
+            let offset = arg(1).map(|o| number(o, "offset")).transpose()?.unwrap_or(0);
+            get(format!("/address/{}/txs?offset={}", args[0], offset))
+        }
+        "estimatefee" => get(format!("/estimatefee/{}", number(&args[0], "target_blocks")?)),
+        "getpeerinfo" => get("/peers".into()),
+        "getmininginfo" => get("/mining/info".into()),
+        "getsupply" => get("/stats/supply".into()),
+        "listwallets" => get("/wallets".into()),
+        "unlockwallet" => {
+            let timeout = arg(2).map(|t| number(t, "timeout_secs")).transpose()?;
+            post(format!("/wallet/{}/unlock", args[0]), json!({ "password": args[1], "timeout": timeout }))
+        }
+        "lockwallet" => post(format!("/wallet/{}/lock", args[0]), json!({})),
+        "getnewaddress" => get(format!("/wallet/{}/address", args[0])),
+        "getbalance" => get(format!("/wallet/{}/balance", args[0])),
+        "send" => {
+            let amount = number(&args[2], "amount")?;
+            let fee_rate = arg(3).map(|f| number(f, "sat_per_byte")).transpose()?;
+            post(
+                format!("/wallet/{}/send", args[0]),
+                json!({ "outputs": [{ "to": args[1], "amount": amount }], "fee_rate": fee_rate }),
+            )
+        }
+        _ => unreachable!("every method is mapped"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(a: &[&str]) -> Vec<String> {
+        a.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn maps_methods_onto_api_paths() {
+        assert_eq!(build_request("getblock", &args(&["12"])).unwrap().path, "/block/height/12");
+        assert_eq!(build_request("getblock", &args(&["ab"])).unwrap().path, "/block/ab/verbose");
+
+        let send = build_request("send", &args(&["main", "bc21q", "5000"])).unwrap();
+        assert_eq!(send.verb, Verb::Post);
+        assert_eq!(send.path, "/wallet/main/send");
+        assert_eq!(send.body.unwrap()["outputs"][0]["amount"], 5000);
+
+        assert!(build_request("getblock", &[]).is_err());
+        assert!(build_request("estimatefee", &args(&["soon"])).is_err());
+        assert!(build_request("nope", &[]).is_err());
+        assert_eq!(complete("getb"), ["getblock", "getblocks", "getbalance"]);
+    }
+
+    #[test]
+    fn every_method_builds() {
+        for m in METHODS {
+            let filled: Vec<String> = m.params.iter().filter(|p| !p.starts_with('[')).map(|_| "1".to_string()).collect();
+            assert!(build_request(m.name, &filled).is_ok(), "{}", m.name);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Browser origins allowed to call the API (POLICY ONLY)
+///
+/// Explorer and wallet frontends hosted elsewhere need CORS
+/// headers to read the JSON endpoints. Origins are matched
+/// exactly ("https://explorer.example"); "*" allows any. An
+/// empty list adds no headers, so browsers keep the default
+/// same-origin policy.
+pub struct Cors {
+    origins: Vec<String>,
+}
+
+impl Cors {
+    pub fn new(origins: &[String]) -> Self {
+        Self {
+            origins: origins.iter().map(|o| o.trim_end_matches('/').to_string()).collect(),
+        }
+    }
+
+    /// Value of Access-Control-Allow-Origin for `origin`, if allowed
+    pub fn allow_origin(&self, origin: &str) -> Option<String> {
+        if self.origins.iter().any(|o| o == "*") {
+            Some("*".to_string())
+        } else if self.origins.iter().any(|o| o == origin) {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// axum middleware: answers preflights and tags responses to
+/// allowed origins
+pub async fn cors(State(cors): State<Arc<Cors>>, request: Request, next: Next) -> Response {
+    let allowed = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|o| o.to_str().ok())
+        .and_then(|o| cors.allow_origin(o))
+        .and_then(|o| HeaderValue::from_str(&o).ok());
+
+    let preflight = request.method() == Method::OPTIONS
+        && request.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+
+    let mut response = match (&allowed, preflight) {
+        (Some(_), true) => StatusCode::NO_CONTENT.into_response(),
+        _ => next.run(request).await,
+    };
+
+    if let Some(origin) = allowed {
+        let headers = response.headers_mut();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+
+        if preflight {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET, POST, OPTIONS"));
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static("Authorization, Content-Type, Last-Event-ID"));
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("600"));
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_listed_origins_or_wildcard() {
+        let cors = Cors::new(&["https://explorer.example/".to_string()]);
+        assert_eq!(cors.allow_origin("https://explorer.example").as_deref(), Some("https://explorer.example"));
+        assert_eq!(cors.allow_origin("https://evil.example"), None);
+
+        let any = Cors::new(&["*".to_string()]);
+        assert_eq!(any.allow_origin("https://evil.example").as_deref(), Some("*"));
+        assert_eq!(Cors::new(&[]).allow_origin("https://explorer.example"), None);
+    }
+}
@@ -0,0 +1,284 @@
+use std::io::{self, Stdout};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::ExecutableCommand;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Row, Table};
+use ratatui::{Frame, Terminal};
+use serde_json::Value;
+
+use crate::interface::console::{RpcRequest, Verb};
+use crate::interface::rpc_client::{RpcClient, RpcError};
+
+/// How often the node is polled
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Confirmation targets shown in the fee panel
+const FEE_TARGETS: [u64; 3] = [1, 6, 24];
+
+/// Node events listed at the bottom
+const RECENT_EVENTS: usize = 50;
+
+/// One poll of the node's API; a failed call leaves its part Null
+#[derive(Default)]
+struct Snapshot {
+    status: Value,
+    sync: Value,
+    peers: Value,
+    mempool: Value,
+    /// suggested_fee_rate per FEE_TARGETS entry
+    fees: Vec<Value>,
+    mining: Value,
+    thermal: Value,
+    events: Value,
+    /// Set when the node could not be reached
+    error: Option<String>,
+}
+
+fn poll(client: &RpcClient) -> Snapshot {
+    let mut error = None;
+    let mut get = |path: String| match client.call(RpcRequest { verb: Verb::Get, path, body: None }) {
+        Ok(v) => v,
+        Err(RpcError::Unreachable(e)) => {
+            error = Some(e);
+            Value::Null
+        }
+        Err(_) => Value::Null,
+    };
+
+    let mut snapshot = Snapshot {
+        status: get("/status".into()),
+        sync: get("/sync".into()),
+        peers: get("/peers".into()),
+        mempool: get("/mempool".into()),
+        fees: FEE_TARGETS
+            .iter()
+            .map(|t| get(format!("/estimatefee/{}", t))["suggested_fee_rate"].clone())
+            .collect(),
+        mining: get("/mining/info".into()),
+        thermal: get("/thermal".into()),
+        events: get(format!("/events/recent?limit={}", RECENT_EVENTS)),
+        error: None,
+    };
+    snapshot.error = error;
+    snapshot
+}
+
+/// `value` for display; "-" when missing, whole numbers for rates
+fn field(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Number(n) if n.is_f64() => format!("{:.0}", n.as_f64().unwrap_or_default()),
+        other => other.to_string(),
+    }
+}
+
+fn short(hash: &Value) -> String {
+    hash.as_str().map(|h| h.chars().take(16).collect()).unwrap_or_default()
+}
+
+/// Live node view in the terminal (UI ONLY)
+///
+/// For headless operators: polls the node's API and redraws
+/// every REFRESH_INTERVAL. `q` or Esc quits, `r` refreshes now.
+pub fn run(client: &RpcClient) -> io::Result<()> {
+    enable_raw_mode()?;
+    io::stdout().execute(EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let result = event_loop(&mut terminal, client);
+
+    disable_raw_mode()?;
+    io::stdout().execute(LeaveAlternateScreen)?;
+    result
+}
+
+fn event_loop(terminal: &mut Terminal<CrosstermBackend<Stdout>>, client: &RpcClient) -> io::Result<()> {
+    let mut snapshot = poll(client);
+    let mut polled = Instant::now();
+
+    loop {
+        terminal.draw(|f| draw(f, client.url(), &snapshot))?;
+
+        let mut refresh = polled.elapsed() >= REFRESH_INTERVAL;
+        if !refresh && event::poll(REFRESH_INTERVAL.saturating_sub(polled.elapsed()))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Char('r') => refresh = true,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if refresh {
+            snapshot = poll(client);
+            polled = Instant::now();
+        }
+    }
+}
+
+fn draw(f: &mut Frame, url: &str, s: &Snapshot) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(7),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .split(f.size());
+
+    draw_sync(f, rows[0], s);
+
+    let panels = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3); 3])
+        .split(rows[1]);
+    draw_node(f, panels[0], s);
+    draw_mempool(f, panels[1], s);
+    draw_device(f, panels[2], s);
+
+    let lists = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(rows[2]);
+    draw_peers(f, lists[0], s);
+    draw_events(f, lists[1], s);
+
+    let footer = match &s.error {
+        Some(e) => Line::styled(format!(" Node unreachable: {}", e), Style::default().fg(Color::Red)),
+        None => Line::from(format!(" {} · q quit · r refresh", url)),
+    };
+    f.render_widget(Paragraph::new(footer), rows[3]);
+}
+
+fn panel(title: &str) -> Block<'_> {
+    Block::default().borders(Borders::ALL).title(format!(" {} ", title))
+}
+
+fn draw_sync(f: &mut Frame, area: Rect, s: &Snapshot) {
+    let progress = s.sync["progress"].as_f64().unwrap_or(0.0);
+    let title = match s.sync["initial_block_download"].as_bool() {
+        Some(false) => "Synced",
+        _ => "Syncing",
+    };
+
+    let gauge = Gauge::default()
+        .block(panel(title))
+        .gauge_style(Style::default().fg(Color::Green))
+        .ratio((progress / 100.0).clamp(0.0, 1.0))
+        .label(format!(
+            "{:.1}%  block {} of {}",
+            progress,
+            field(&s.sync["best_block_height"]),
+            field(&s.sync["best_header_height"])
+        ));
+    f.render_widget(gauge, area);
+}
+
+fn draw_node(f: &mut Frame, area: Rect, s: &Snapshot) {
+    let peers = s.peers.as_array().map_or(0, Vec::len);
+    let lines = vec![
+        Line::from(format!("Height     {}", field(&s.status["height"]))),
+        Line::from(format!("Peers      {}", peers)),
+        Line::from(format!(
+            "Hashrate   {} / {} / {} H/s",
+            field(&s.mining["hashrate_1m"]),
+            field(&s.mining["hashrate_5m"]),
+            field(&s.mining["hashrate_15m"])
+        )),
+        Line::from(format!("Next block ~{}s", field(&s.mining["expected_block_secs"]))),
+    ];
+    f.render_widget(Paragraph::new(lines).block(panel("Node")), area);
+}
+
+fn draw_mempool(f: &mut Frame, area: Rect, s: &Snapshot) {
+    let mut lines = vec![
+        Line::from(format!("Transactions {}", field(&s.mempool["size"]))),
+        Line::from(format!("Bytes        {}", field(&s.mempool["bytes"]))),
+        Line::from(format!("Min fee/kB   {}", field(&s.mempool["min_fee_rate"]))),
+    ];
+    let fees: Vec<String> = FEE_TARGETS
+        .iter()
+        .zip(&s.fees)
+        .map(|(target, rate)| format!("{}b:{}", target, field(rate)))
+        .collect();
+    lines.push(Line::from(format!("Fee/kB       {}", fees.join(" "))));
+
+    f.render_widget(Paragraph::new(lines).block(panel("Mempool")), area);
+}
+
+fn draw_device(f: &mut Frame, area: Rect, s: &Snapshot) {
+    let t = &s.thermal;
+    let (text, style) = match (t["celsius"].as_f64(), t["over_limit"].as_bool()) {
+        (Some(c), Some(true)) => (format!("{:.1} °C  HOT", c), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        (Some(c), _) => (format!("{:.1} °C  ok", c), Style::default().fg(Color::Green)),
+        (None, _) => ("no sensor".to_string(), Style::default()),
+    };
+
+    let lines = vec![
+        Line::styled(format!("Temperature {}", text), style),
+        Line::from(format!("Limit       {} °C", field(&t["threshold"]))),
+    ];
+    f.render_widget(Paragraph::new(lines).block(panel("Device")), area);
+}
+
+fn draw_peers(f: &mut Frame, area: Rect, s: &Snapshot) {
+    let rows: Vec<Row> = s
+        .peers
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|p| {
+            let ping = p["ping_ms"].as_u64().map_or("-".to_string(), |ms| format!("{} ms", ms));
+            Row::new(vec![field(&p["addr"]), field(&p["transport"]), field(&p["height"]), ping])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Min(21),
+        Constraint::Length(9),
+        Constraint::Length(8),
+        Constraint::Length(8),
+    ];
+    let table = Table::new(rows, widths)
+        .header(Row::new(vec!["Address", "Link", "Height", "Ping"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(panel("Peers"));
+    f.render_widget(table, area);
+}
+
+fn draw_events(f: &mut Frame, area: Rect, s: &Snapshot) {
+    // Newest first
+    let items: Vec<ListItem> = s
+        .events
+        .as_array()
+        .into_iter()
+        .flatten()
+        .rev()
+        .map(|e| {
+            let text = match e["type"].as_str() {
+                Some("block") => format!("⛏ block {} {}", field(&e["height"]), short(&e["hash"])),
+                Some("tx") => format!("📥 tx {}", short(&e["txid"])),
+                Some("reorg") => format!(
+                    "⚠ reorg at {} ({} blocks)",
+                    field(&e["fork_height"]),
+                    e["disconnected"].as_array().map_or(0, Vec::len)
+                ),
+                Some("peer") => format!("🤝 peer {}", field(&e["addr"])),
+                _ => field(e),
+            };
+            ListItem::new(text)
+        })
+        .collect();
+
+    f.render_widget(List::new(items).block(panel("Recent events")), area);
+}
@@ -0,0 +1,333 @@
+use std::sync::{Arc, Mutex};
+
+use askama::Template;
+use axum::{
+    Router,
+    routing::get,
+    extract::{State, Path, Query},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+
+use crate::block::Block;
+use crate::chain::Blockchain;
+use crate::crypto::address::{decode_address, encode_address};
+use crate::node::address_index::AddressIndex;
+use crate::node::mempool::Mempool;
+use crate::transaction::Transaction;
+
+/// Blocks listed on the front page
+const RECENT_BLOCKS: usize = 20;
+
+/// Transactions listed on an address page
+const ADDRESS_TXS: usize = 50;
+
+#[derive(Clone)]
+struct ExplorerState {
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+    addresses: Arc<Mutex<AddressIndex>>,
+}
+
+/// HTML block explorer (UI ONLY)
+///
+/// Server-rendered pages over the same chain, mempool and
+/// address index as the JSON API; every page has a search box
+/// taking a height, block hash, txid or address.
+pub fn router(
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+    addresses: Arc<Mutex<AddressIndex>>,
+) -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route("/search", get(search))
+        .route("/explorer/block/:id", get(block_page))
+        .route("/explorer/tx/:txid", get(tx_page))
+        .route("/explorer/address/:address", get(address_page))
+        .with_state(ExplorerState { chain, mempool, addresses })
+}
+
+fn render(status: StatusCode, page: impl Template) -> Response {
+    match page.render() {
+        Ok(html) => (status, Html(html)).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+fn not_found(query: &str) -> Response {
+    render(StatusCode::NOT_FOUND, NotFoundPage { query: query.to_string() })
+}
+
+//
+// ─── VIEWS ────────────────────────────────────────
+//
+
+struct BlockRow {
+    height: u64,
+    hash: String,
+    timestamp: i64,
+    txs: usize,
+}
+
+impl BlockRow {
+    fn new(b: &Block) -> Self {
+        Self {
+            height: b.header.height,
+            hash: hex::encode(&b.hash),
+            timestamp: b.header.timestamp,
+            txs: b.transactions.len(),
+        }
+    }
+}
+
+struct TxRow {
+    txid: String,
+    coinbase: bool,
+    inputs: usize,
+    /// Sum of the outputs
+    value: u64,
+}
+
+impl TxRow {
+    fn new(tx: &Transaction) -> Self {
+        Self {
+            txid: hex::encode(tx.txid()),
+            coinbase: tx.is_coinbase(),
+            inputs: tx.inputs.len(),
+            value: tx.outputs.iter().map(|o| o.value).sum(),
+        }
+    }
+}
+
+struct InputRow {
+    /// Spent transaction
+    txid: String,
+    vout: u32,
+}
+
+struct OutputRow {
+    address: String,
+    value: u64,
+}
+
+struct AddressTxRow {
+    txid: String,
+    /// None while in the mempool
+    height: Option<u64>,
+}
+
+//
+// ─── PAGES ────────────────────────────────────────
+//
+
+#[derive(Template)]
+#[template(path = "explorer/index.html")]
+struct IndexPage {
+    /// Blocks in the active chain
+    blocks_total: u64,
+    mempool: usize,
+    blocks: Vec<BlockRow>,
+}
+
+#[derive(Template)]
+#[template(path = "explorer/block.html")]
+struct BlockPage {
+    block: BlockRow,
+    prev_hash: String,
+    /// Hash of the next block on the active chain
+    next_hash: Option<String>,
+    nonce: u64,
+    merkle_root: String,
+    confirmations: u64,
+    txs: Vec<TxRow>,
+}
+
+#[derive(Template)]
+#[template(path = "explorer/tx.html")]
+struct TxPage {
+    tx: TxRow,
+    /// (height, block hash); None while in the mempool
+    block: Option<(u64, String)>,
+    confirmations: u64,
+    inputs: Vec<InputRow>,
+    outputs: Vec<OutputRow>,
+}
+
+#[derive(Template)]
+#[template(path = "explorer/address.html")]
+struct AddressPage {
+    address: String,
+    balance: u64,
+    utxos: usize,
+    /// Newest first, at most ADDRESS_TXS
+    txs: Vec<AddressTxRow>,
+    total_txs: usize,
+}
+
+#[derive(Template)]
+#[template(path = "explorer/not_found.html")]
+struct NotFoundPage {
+    query: String,
+}
+
+//
+// ─── HANDLERS ─────────────────────────────────────
+//
+
+async fn index(State(state): State<ExplorerState>) -> Response {
+    let c = state.chain.lock().unwrap();
+    let mempool = state.mempool.lock().unwrap().size();
+
+    render(
+        StatusCode::OK,
+        IndexPage {
+            blocks_total: c.height(),
+            mempool,
+            blocks: c.blocks.iter().rev().take(RECENT_BLOCKS).map(BlockRow::new).collect(),
+        },
+    )
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// Redirect to the page for a height, block hash, txid or address
+async fn search(State(state): State<ExplorerState>, Query(query): Query<SearchQuery>) -> Response {
+    let q = query.q.trim();
+
+    if q.parse::<u64>().is_ok() {
+        return Redirect::to(&format!("/explorer/block/{}", q)).into_response();
+    }
+
+    if let Ok(bytes) = hex::decode(q) {
+        let c = state.chain.lock().unwrap();
+        if c.blocks.iter().any(|b| b.hash == bytes) {
+            return Redirect::to(&format!("/explorer/block/{}", q)).into_response();
+        }
+
+        let confirmed = c.blocks.iter().any(|b| b.transactions.iter().any(|tx| tx.txid() == bytes));
+        if confirmed || state.mempool.lock().unwrap().get_entry(&bytes).is_some() {
+            return Redirect::to(&format!("/explorer/tx/{}", q)).into_response();
+        }
+    }
+
+    match decode_address(q) {
+        Ok(hash) => Redirect::to(&format!("/explorer/address/{}", encode_address(&hash))).into_response(),
+        Err(_) => not_found(q),
+    }
+}
+
+/// `id`: height or block hash
+async fn block_page(State(state): State<ExplorerState>, Path(id): Path<String>) -> Response {
+    let c = state.chain.lock().unwrap();
+
+    let found = match id.parse::<usize>() {
+        Ok(height) => c.blocks.get(height),
+        Err(_) => c.blocks.iter().find(|b| hex::encode(&b.hash) == id),
+    };
+    let Some(b) = found else {
+        return not_found(&id);
+    };
+
+    let height = b.header.height;
+    render(
+        StatusCode::OK,
+        BlockPage {
+            block: BlockRow::new(b),
+            prev_hash: hex::encode(&b.header.prev_hash),
+            next_hash: c.blocks.get(height as usize + 1).map(|n| hex::encode(&n.hash)),
+            nonce: b.header.nonce,
+            merkle_root: hex::encode(&b.header.merkle_root),
+            confirmations: c.height() - height,
+            txs: b.transactions.iter().map(TxRow::new).collect(),
+        },
+    )
+}
+
+async fn tx_page(State(state): State<ExplorerState>, Path(txid): Path<String>) -> Response {
+    let Ok(id) = hex::decode(&txid) else {
+        return not_found(&txid);
+    };
+
+    let found = {
+        let c = state.chain.lock().unwrap();
+        c.blocks.iter().find_map(|b| {
+            let tx = b.transactions.iter().find(|tx| tx.txid() == id)?;
+            let location = (b.header.height, hex::encode(&b.hash));
+            Some((tx.clone(), Some(location), c.height() - b.header.height))
+        })
+    };
+    let found = found.or_else(|| {
+        let m = state.mempool.lock().unwrap();
+        m.get_entry(&id).map(|e| (e.tx.clone(), None, 0))
+    });
+    let Some((tx, block, confirmations)) = found else {
+        return not_found(&txid);
+    };
+
+    render(
+        StatusCode::OK,
+        TxPage {
+            tx: TxRow::new(&tx),
+            block,
+            confirmations,
+            inputs: tx
+                .inputs
+                .iter()
+                .map(|i| InputRow { txid: hex::encode(&i.txid), vout: i.index })
+                .collect(),
+            outputs: tx
+                .outputs
+                .iter()
+                .map(|o| OutputRow { address: encode_address(&o.pubkey_hash), value: o.value })
+                .collect(),
+        },
+    )
+}
+
+async fn address_page(State(state): State<ExplorerState>, Path(address): Path<String>) -> Response {
+    let Ok(hash) = decode_address(&address) else {
+        return not_found(&address);
+    };
+
+    let c = state.chain.lock().unwrap();
+    let mut index = state.addresses.lock().unwrap();
+    index.sync(&c.blocks);
+
+    let (balance, utxos) = c
+        .utxos
+        .values()
+        .filter(|u| u.pubkey_hash == hash)
+        .fold((0u64, 0usize), |(sum, n), u| (sum.saturating_add(u.value), n + 1));
+
+    let m = state.mempool.lock().unwrap();
+    let pending: Vec<AddressTxRow> = m
+        .transactions()
+        .filter(|tx| index.touched(tx).contains(&hash))
+        .map(|tx| AddressTxRow { txid: hex::encode(tx.txid()), height: None })
+        .collect();
+
+    let confirmed = index.confirmed(&hash);
+    let total_txs = pending.len() + confirmed.len();
+
+    let txs = pending
+        .into_iter()
+        .chain(
+            confirmed
+                .iter()
+                .rev()
+                .map(|(height, txid)| AddressTxRow { txid: hex::encode(txid), height: Some(*height) }),
+        )
+        .take(ADDRESS_TXS)
+        .collect();
+
+    render(
+        StatusCode::OK,
+        AddressPage { address: encode_address(&hash), balance, utxos, txs, total_txs },
+    )
+}
@@ -1,3 +1,11 @@
 pub mod api;
+pub mod auth;
 pub mod cli;
+pub mod console;
+pub mod cors;
+pub mod dashboard;
+pub mod explorer;
+pub mod output;
+pub mod rate_limit;
+pub mod rpc_client;
 pub mod ui;
@@ -0,0 +1,112 @@
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::{json, Value};
+
+/// Set once from `--json` before any command runs
+static JSON: AtomicBool = AtomicBool::new(false);
+
+pub fn set_json(on: bool) {
+    JSON.store(on, Ordering::Relaxed);
+}
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// `text` without its leading emoji and padding
+fn plain(text: &str) -> &str {
+    text.trim_start_matches(|c: char| !c.is_ascii() || c.is_whitespace())
+}
+
+fn print_json(value: Value) {
+    println!("{}", value);
+}
+
+/// CLI output: decorated text, or one JSON object per line under
+/// `--json` (UI ONLY)
+///
+/// Results carry their own fields (`emit`), progress and status
+/// lines become `{"message": ...}` and failures `{"error": ...}`,
+/// so scripts can read stdout line by line.
+pub fn say(text: &str) {
+    if is_json() {
+        print_json(json!({ "message": plain(text) }));
+    } else {
+        println!("{}", text);
+    }
+}
+
+/// A failed command: "❌ text", or `{"error": ...}`
+pub fn fail(text: &str) {
+    if is_json() {
+        print_json(json!({ "error": plain(text) }));
+    } else {
+        println!("❌ {}", text);
+    }
+}
+
+/// A command result: `text` for people (nothing if empty, e.g.
+/// an empty list), `value` for scripts
+pub fn emit(text: &str, value: Value) {
+    if is_json() {
+        print_json(value);
+    } else if !text.is_empty() {
+        println!("{}", text);
+    }
+}
+
+/// Hints and banners only people need; dropped under `--json`
+pub fn note(text: &str) {
+    if !is_json() {
+        println!("{}", text);
+    }
+}
+
+/// Interactive text (questions, the recovery phrase screen); goes
+/// to stderr under `--json` so stdout stays parseable
+pub fn prompt(text: &str) {
+    if is_json() {
+        eprint!("{}", text);
+        io::stderr().flush().unwrap();
+    } else {
+        print!("{}", text);
+        io::stdout().flush().unwrap();
+    }
+}
+
+/// `say` with `format!` arguments
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        $crate::interface::output::say(&format!($($arg)*))
+    };
+}
+
+/// `fail` with `format!` arguments
+#[macro_export]
+macro_rules! fail {
+    ($($arg:tt)*) => {
+        $crate::interface::output::fail(&format!($($arg)*))
+    };
+}
+
+/// `prompt` with `format!` arguments
+#[macro_export]
+macro_rules! prompt {
+    ($($arg:tt)*) => {
+        $crate::interface::output::prompt(&format!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_drops_leading_emoji() {
+        assert_eq!(plain("✅ Wallet 'a' created"), "Wallet 'a' created");
+        assert_eq!(plain("⚠️ 2 key(s)"), "2 key(s)");
+        assert_eq!(plain("Usage: wallet use <name>"), "Usage: wallet use <name>");
+    }
+}
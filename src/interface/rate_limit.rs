@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::config::ApiRateLimit;
+use crate::node::token_bucket::TokenBucket;
+
+/// Clients tracked before idle, refilled buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Per-IP token buckets for the API (POLICY ONLY)
+///
+/// Every client IP may burst up to `burst` requests, refilled
+/// at `per_second`. Most handlers take the chain lock, so an
+/// unthrottled client could stall the node.
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<IpAddr, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: &ApiRateLimit) -> Self {
+        Self {
+            per_second: limits.per_second,
+            burst: limits.burst.max(1) as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `ip` at `now`; false = over the limit
+    pub fn allow(&self, ip: IpAddr, now: Instant) -> bool {
+        if self.per_second <= 0.0 {
+            return true; // disabled
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| !bucket.is_full(now));
+        }
+
+        buckets
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::new(self.burst, self.per_second, now))
+            .take(1.0, now)
+    }
+}
+
+/// axum middleware: 429 for clients over their limit
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !limiter.allow(addr.ip(), Instant::now()) {
+        return (StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded").into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn bursts_then_refills_per_ip() {
+        let limiter = RateLimiter::new(&ApiRateLimit { per_second: 2.0, burst: 3 });
+        let (a, b): (IpAddr, IpAddr) = ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        let now = Instant::now();
+
+        assert!((0..3).all(|_| limiter.allow(a, now)));
+        assert!(!limiter.allow(a, now));
+        assert!(limiter.allow(b, now));
+
+        // Half a second refills one token
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.allow(a, later));
+        assert!(!limiter.allow(a, later));
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::interface::console::{build_request, RpcRequest, Verb};
+
+/// Default address of a local node's API
+pub const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8080";
+
+/// How long to wait for a node before taking it as not running
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Why a call failed
+#[derive(Debug)]
+pub enum RpcError {
+    /// No node answered at the URL
+    Unreachable(String),
+    /// The node answered with an error status and body
+    Status(u16, String),
+    /// Bad method or arguments, caught before sending
+    Usage(String),
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::Unreachable(e) => write!(f, "cannot reach node: {}", e),
+            RpcError::Status(code, body) => write!(f, "{}: {}", code, body),
+            RpcError::Usage(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Blocking client for a running node's HTTP API (UI ONLY)
+///
+/// CLI commands go through here so that they act on the live
+/// node (its chain, mempool and unlocked wallets) rather than on
+/// a second copy loaded from disk.
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+    /// Sent as a bearer token when the node's API requires one
+    auth_token: Option<String>,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout_connect(CONNECT_TIMEOUT).build(),
+            auth_token: None,
+        }
+    }
+
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// True if a node answers at all (a syncing node answers
+    /// /health with 503)
+    pub fn is_running(&self) -> bool {
+        match self.agent.get(&format!("{}/health", self.url)).call() {
+            Ok(_) | Err(ureq::Error::Status(..)) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Send `req`; non-JSON bodies come back as a JSON string
+    pub fn call(&self, req: RpcRequest) -> Result<Value, RpcError> {
+        let endpoint = format!("{}{}", self.url, req.path);
+
+        let mut request = match req.verb {
+            Verb::Get => self.agent.get(&endpoint),
+            Verb::Post => self.agent.post(&endpoint),
+        };
+        if let Some(token) = &self.auth_token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+
+        let result = match req.verb {
+            Verb::Get => request.call(),
+            Verb::Post => request.send_json(req.body.unwrap_or(Value::Null)),
+        };
+
+        let body = |r: ureq::Response| r.into_string().unwrap_or_default();
+        match result {
+            Ok(r) => {
+                let text = body(r);
+                Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+            }
+            Err(ureq::Error::Status(code, r)) => Err(RpcError::Status(code, body(r))),
+            Err(e) => Err(RpcError::Unreachable(format!("{} ({})", self.url, e))),
+        }
+    }
+
+    /// Call a console method, e.g. `method("getblock", &["10"])`
+    pub fn method(&self, method: &str, args: &[String]) -> Result<Value, RpcError> {
+        self.call(build_request(method, args).map_err(RpcError::Usage)?)
+    }
+}
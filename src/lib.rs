@@ -5,6 +5,14 @@ pub mod revelation;
 pub mod reward;
 pub mod wallet;
 pub mod wallet_store;
+pub mod wallet_manager;
+pub mod wallet_history;
+pub mod wallet_events;
+pub mod wallet_dump;
+pub mod watch_wallet;
+pub mod psbt;
+pub mod multisig_wallet;
+pub mod signer;
 pub mod crypto;
 pub mod consensus;
 pub mod node;        
@@ -14,9 +22,12 @@ pub mod interface;
 pub mod core;
 
 pub use core::block;
+pub use core::headers;
 pub use core::transaction;
+pub use core::script;
 pub use core::utxo;
 pub use core::merkle;
+pub use core::error;
 pub use core::validation;
 pub use core::chain;
 
@@ -2,10 +2,9 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
-use std::io::{self, Write};
-use std::env;
 use std::net::SocketAddr;
 
+use clap::Parser;
 use tokio::runtime::Runtime;
 use rpassword::read_password;
 
@@ -17,13 +16,31 @@ use bitcoin_v0_2_revelation::node::transport::satellite::SatelliteTransport;
 #[cfg(feature = "bluetooth")]
 use bitcoin_v0_2_revelation::node::transport::bluetooth::BluetoothTransport;
 use bitcoin_v0_2_revelation::node::transport::geo::GeoTransport;
-use bitcoin_v0_2_revelation::node::dedup::MessageDeduplicator;
-use bitcoin_v0_2_revelation::interface::{api::start_api, cli};
-use bitcoin_v0_2_revelation::node::mempool::Mempool;
-use bitcoin_v0_2_revelation::wallet::Wallet;
+use bitcoin_v0_2_revelation::node::transport::manager::TransportManager;
+#[cfg(feature = "quic")]
+use bitcoin_v0_2_revelation::node::transport::quic::QuicTransport;
+#[cfg(feature = "websocket")]
+use bitcoin_v0_2_revelation::node::transport::websocket::WebSocketTransport;
+use bitcoin_v0_2_revelation::node::transport::i2p::I2pTransport;
+use bitcoin_v0_2_revelation::node::transport::ipc::IpcTransport;
+use bitcoin_v0_2_revelation::interface::{api::start_api, cli, dashboard, output};
+use bitcoin_v0_2_revelation::{fail, say};
+use bitcoin_v0_2_revelation::interface::cli::{Cli, Command, NodeCommand};
+use bitcoin_v0_2_revelation::node::mempool::{self, Mempool};
+use bitcoin_v0_2_revelation::node::bus::{block_events, BusEvent, EventBus};
+use bitcoin_v0_2_revelation::core::transaction::Transaction;
+use bitcoin_v0_2_revelation::node::RuntimePolicy;
+use bitcoin_v0_2_revelation::wallet::{calculate_balances, Balances, DEFAULT_AUTO_LOCK};
+use bitcoin_v0_2_revelation::crypto::address::{decode_address, encode_address};
 use bitcoin_v0_2_revelation::wallet_store::load_wallet_store;
-use bitcoin_v0_2_revelation::config::load_miner_config;
-use bitcoin_v0_2_revelation::node::miner;
+use bitcoin_v0_2_revelation::wallet_manager::{self, WalletManager};
+use bitcoin_v0_2_revelation::wallet_history::WalletHistory;
+use bitcoin_v0_2_revelation::config::{load_miner_config, load_network_config, load_wallet_config};
+use bitcoin_v0_2_revelation::node::miner::{self, check_payout_shares, PayoutShare};
+use bitcoin_v0_2_revelation::consensus::difficulty::calculate_next_target;
+use bitcoin_v0_2_revelation::pow::MiningThreads;
+use bitcoin_v0_2_revelation::node::connman::ConnectionManager;
+use bitcoin_v0_2_revelation::node::whitelist::Whitelist;
 
 enum NodeMode {
     Syncing,
@@ -32,94 +49,329 @@ enum NodeMode {
 
 /// 🔒 Secure password input
 fn prompt_secret(msg: &str) -> String {
-    print!("{}", msg);
-    io::stdout().flush().unwrap();
+    output::prompt(msg);
     read_password().unwrap()
 }
 
-/// 🌱 Bootstrap seeds (non-consensus)
-const BOOTSTRAP_SEEDS: &[&str] = &[
-    "bitcoin-revelation-node.fly.dev:8333",
-];
+/// Report a startup error and stop
+fn exit_with(msg: &str) -> ! {
+    fail!("{}", msg);
+    std::process::exit(1)
+}
 
-fn main() {
-    println!("⛓ Bitcoin v0.4.0 — Revelation Edition (Consensus v4)");
+/// Syncing with no new block for this long switches peers
+const SYNC_STALL_TIMEOUT: Duration = Duration::from_secs(30);
 
-    let wallet_store = load_wallet_store();
-    let miner_config = load_miner_config();
+/// Sync progress is printed at most this often
+const SYNC_REPORT_INTERVAL: Duration = Duration::from_secs(10);
 
-    if wallet_store.get_path(&miner_config.coinbase_wallet).is_none() {
-        panic!("Configured wallet '{}' not found", miner_config.coinbase_wallet);
+fn main() {
+    let cli = Cli::parse();
+    // ⚙️ node_config.json plus command-line overrides, loaded once
+    let config = cli.load_config();
+    output::set_json(cli.json);
+
+    output::note("⛓ Bitcoin v0.4.0 — Revelation Edition (Consensus v4)");
+
+    // 📊 Terminal dashboard of a running node
+    if let Some(Command::Node(NodeCommand::Dashboard)) = cli.command {
+        if let Err(e) = dashboard::run(&cli.rpc_client(&config)) {
+            eprintln!("❌ Dashboard failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
     }
 
-    let _passphrase = prompt_secret("🔐 Enter wallet passphrase: ");
-    let password = prompt_secret("🔑 Enter wallet password: ");
+    // 📡 Chain commands and wallet balance/send go to a running node
+    if let Some(command) = cli.command.as_ref().filter(|c| cli::is_remote_command(c)) {
+        let client = cli.rpc_client(&config);
 
-    let mut wallet = Wallet::load_or_create(&password);
-    let miner_pubkey_hash = wallet.address().expect("wallet locked");
+        if client.is_running() {
+            let wallet = cli
+                .wallet
+                .clone()
+                .unwrap_or_else(|| cli::default_wallet(&WalletManager::new(load_wallet_store())));
+            return cli::handle_remote_command(&client, command, &wallet);
+        }
+        if cli::requires_node(command) {
+            fail!("No node running at {}; start one with `node start`", client.url());
+            std::process::exit(1);
+        }
+        output::note(&format!("📴 No node running at {}; reading the local chain", client.url()));
+    }
 
-    println!(
-        "👛 Miner pubkey hash ({}): {}",
-        miner_config.coinbase_wallet,
-        hex::encode(&miner_pubkey_hash)
-    );
+    let policy = RuntimePolicy::from_config(&config);
 
     let mut local_chain = Blockchain::new();
+    local_chain.set_utxo_cache_limit(policy.utxo_cache_limit());
     local_chain.initialize();
 
     let chain = Arc::new(Mutex::new(local_chain));
     let mempool = Arc::new(Mutex::new(Mempool::new()));
 
-    let args: Vec<String> = env::args().collect();
-    if args.len() > 1 && args[1] == "wallet" {
-        cli::handle_command(args, &mut wallet, Arc::clone(&chain), Arc::clone(&mempool));
+    // 👁 Chain, peer, watch-only, multisig and external-signer commands never touch the seed
+    let mut wallet_command = None;
+    match cli.command {
+        None | Some(Command::Node(NodeCommand::Start)) => {}
+        Some(Command::Node(NodeCommand::Dashboard)) => unreachable!("handled above"),
+        Some(Command::Chain(command)) => return cli::handle_chain_command(command, chain),
+        Some(Command::Peer(command)) => return cli::handle_peer_command(command),
+        Some(Command::Watch(command)) => return cli::handle_watch_command(command, chain, mempool),
+        Some(Command::Multisig(command)) => return cli::handle_multisig_command(command, chain, mempool),
+        Some(Command::Signer(command)) => return cli::handle_signer_command(command, chain, mempool),
+        Some(Command::Wallet(command)) => {
+            if cli::is_wallet_store_command(&command) {
+                return cli::handle_wallet_store_command(command, cli.wallet.as_deref(), chain);
+            }
+            if cli::is_seedless_wallet_command(&command) {
+                return cli::handle_seedless_wallet_command(command, chain, mempool);
+            }
+            wallet_command = Some(command);
+        }
+    }
+
+    let mut wallets = WalletManager::new(load_wallet_store());
+    let miner_config = load_miner_config();
+
+    // 👛 `--wallet <name>` (or `wallet use`) picks a wallet other than the coinbase one
+    let wallet_name = cli.wallet.unwrap_or_else(|| cli::default_wallet(&wallets));
+
+    if wallets.path(&wallet_name).is_none() {
+        exit_with(&format!("Configured wallet '{}' not found", wallet_name));
+    }
+
+    let _passphrase = prompt_secret("🔐 Enter wallet passphrase: ");
+    let password = prompt_secret("🔑 Enter wallet password: ");
+
+    let wallet = match wallets.open(&wallet_name, &password, Some(DEFAULT_AUTO_LOCK)) {
+        Ok(w) => w,
+        Err(_) => {
+            fail!("Wallet unlock failed.");
+            eprintln!("Possible reasons:");
+            eprintln!("• Incorrect password");
+            eprintln!("• Wallet was created with a different passphrase");
+            eprintln!("• Wallet file is corrupted");
+            std::process::exit(1);
+        }
+    };
+
+    // 🔎 A restored (or never used) wallet starts at index 0
+    if wallet.next_index() == 0 {
+        let gap_limit = load_wallet_config().gap_limit;
+        let chain_guard = chain.lock().unwrap();
+        if let Ok(n) = wallet.discover_used_addresses(&chain_guard.blocks, gap_limit) {
+            if n > 0 {
+                say!("🔎 Found {} used address(es) on chain", n);
+            }
+        }
+    }
+    let miner_pubkey_hash = wallet.address().expect("wallet locked");
+
+    // 🧊 Configured payout addresses take the rewards instead
+    let payouts: Vec<Vec<u8>> = miner_config
+        .payout_addresses
+        .iter()
+        .map(|a| decode_address(a).unwrap_or_else(|e| exit_with(&format!("Invalid payout address {}: {}", a, e))))
+        .collect();
+
+    // ✂️ … or split every coinbase between several
+    let split: Vec<PayoutShare> = miner_config
+        .payout_split
+        .iter()
+        .map(|s| PayoutShare {
+            pubkey_hash: decode_address(&s.address)
+                .unwrap_or_else(|e| exit_with(&format!("Invalid payout address {}: {}", s.address, e))),
+            percent: s.percent,
+        })
+        .collect();
+
+    if !split.is_empty() {
+        check_payout_shares(&split).unwrap_or_else(|e| exit_with(&format!("Invalid payout split: {}", e)));
+        for s in &split {
+            say!("✂️ {}% of mining rewards paid to {}", s.percent, encode_address(&s.pubkey_hash));
+        }
+    } else if payouts.is_empty() {
+        say!(
+            "👛 Miner address ({}): {}",
+            wallet_name,
+            encode_address(&miner_pubkey_hash)
+        );
+    } else {
+        for a in &payouts {
+            say!("🧊 Mining rewards paid to {}", encode_address(a));
+        }
+    }
+
+    if let Some(command) = wallet_command {
+        cli::handle_command(command, &mut wallets, &wallet_name, Arc::clone(&chain), Arc::clone(&mempool));
         return;
     }
 
-    let api_chain = Arc::clone(&chain);
-    thread::spawn(move || {
-        let rt = Runtime::new().expect("Tokio runtime failed");
-        rt.block_on(start_api(api_chain, 8080));
-    });
+    // 📜 Wallet history, kept in step with the active chain
+    let wallet = wallets.get(&wallet_name).expect("wallet not open");
+    let history = Arc::new(Mutex::new(WalletHistory::load(
+        &wallet.history_file(),
+        &wallet.receive_hashes().expect("wallet locked"),
+    )));
+
+    // 🔔 Wallet notifications for the console
+    if let Some(w) = wallets.get_mut(&wallet_name) {
+        w.on_received(|txid, amount| say!("📥 Received {} in {}", amount, hex::encode(txid)));
+        w.on_confirmed(|txid, height| say!("✅ {} confirmed at height {}", hex::encode(txid), height));
+        w.on_conflicted(|txid| say!("⚠️ {} dropped from the mempool", hex::encode(txid)));
+    }
+    let wallets = Arc::new(Mutex::new(wallets));
 
-    println!("🌐 Explorer running at http://127.0.0.1:8080");
+    // 📣 Subsystems publish what they did and subscribe to the rest;
+    // subscription order is delivery order (mempool before wallets)
+    let bus = Arc::new(EventBus::new());
+    mempool::follow_chain(Arc::clone(&mempool), Arc::clone(&chain), &bus);
+    miner::follow_chain(&bus);
+    wallet_manager::follow_chain(Arc::clone(&wallets), Arc::clone(&chain), Arc::clone(&mempool), &bus);
 
-    let p2p_holder: Arc<Mutex<Option<Arc<P2PNetwork>>>> =
-        Arc::new(Mutex::new(None));
+    // Catch up with the chain as loaded; the bus keeps them current
+    {
+        let c = chain.lock().unwrap();
+        let m = mempool.lock().unwrap();
+        let txs: Vec<&Transaction> = m.transactions().collect();
+        wallets.lock().unwrap().process_events(&c.blocks, &txs);
+
+        let mut h = history.lock().unwrap();
+        if h.sync(&c.blocks) {
+            h.save();
+        }
+    }
 
-    let dedup = Arc::new(Mutex::new(
-        MessageDeduplicator::new(Duration::from_secs(60))
-    ));
+    bus.subscribe({
+        let chain = Arc::clone(&chain);
+        let history = Arc::clone(&history);
 
-    let on_receive = Arc::new({
-        let p2p_holder = Arc::clone(&p2p_holder);
-        let dedup = Arc::clone(&dedup);
+        move |event| {
+            if let BusEvent::BlockConnected(_) = event {
+                let c = chain.lock().unwrap();
+                let mut h = history.lock().unwrap();
+                if h.sync(&c.blocks) {
+                    h.save();
+                }
+            }
+        }
+    });
 
-        move |addr: SocketAddr, data: Vec<u8>| {
-            let is_new = {
-                let mut d = dedup.lock().unwrap();
-                d.check_and_insert(&data)
-            };
+    // 📶 Sync progress on the console
+    bus.subscribe({
+        let reported: Mutex<Option<Instant>> = Mutex::new(None);
 
-            if !is_new {
-                return;
+        move |event| {
+            if let BusEvent::SyncProgress { height, best_peer_height } = event {
+                let mut reported = reported.lock().unwrap();
+                if reported.map_or(true, |t| t.elapsed() >= SYNC_REPORT_INTERVAL) {
+                    say!("🔄 Synced to height {} of {}", height, best_peer_height);
+                    *reported = Some(Instant::now());
+                }
             }
+        }
+    });
 
-            if let Some(p2p) = &*p2p_holder.lock().unwrap() {
-                p2p.on_receive(addr, data);
+    // 🔀 Every transport feeds P2P through the manager and the bus
+    let transports = TransportManager::new();
+
+    let on_receive = transports.receiver(Arc::new({
+        let bus = Arc::clone(&bus);
+
+        move |from: SocketAddr, data: Vec<u8>| bus.publish(BusEvent::MessageReceived { from, data })
+    }));
+
+    let mining_threads = MiningThreads {
+        count: miner_config.threads.unwrap_or_else(|| policy.mining_threads()),
+        low_priority: miner_config.low_priority,
+        cores: miner_config.cpu_cores.clone(),
+    };
+    let transport = if policy.allow_inbound_connections() {
+        TcpTransport::new(&format!("0.0.0.0:{}", config.ports.p2p), on_receive.clone())
+    } else {
+        say!("📵 Outbound-only mode: not accepting inbound peers");
+        TcpTransport::outbound_only(on_receive.clone())
+    };
+
+    let network_config = load_network_config();
+    let whitelist = Whitelist::from_entries(&network_config.whitelist)
+        .unwrap_or_else(|e| exit_with(&e));
+
+    // Connection-oriented transports P2P can reply through;
+    // lower priority = preferred route to a peer
+    transports.register("tcp", transport.clone(), 2);
+
+    // ✅ QUIC ONLY when feature is enabled
+    #[cfg(feature = "quic")]
+    transports.register("quic", QuicTransport::new("0.0.0.0:9334", on_receive.clone()), 1);
+
+    // ✅ WebSocket ONLY when feature is enabled
+    #[cfg(feature = "websocket")]
+    transports.register("websocket", WebSocketTransport::new("0.0.0.0:8335", on_receive.clone()), 3);
+
+    // 🔌 Local IPC ONLY when configured
+    if let Some(endpoint) = &network_config.ipc {
+        transports.register("ipc", IpcTransport::new(endpoint, on_receive.clone()), 0);
+    }
+
+    // 🧄 I2P ONLY when a SAM bridge is configured
+    let i2p = network_config.i2p_sam.as_ref().and_then(|sam| {
+        let sam: SocketAddr = sam.parse().expect("invalid i2p_sam address");
+        match I2pTransport::new(sam, on_receive.clone()) {
+            Ok(i2p) => Some(i2p),
+            Err(e) => {
+                say!("⚠️ I2P unavailable ({}): {}", sam, e);
+                None
             }
         }
     });
 
-    let transport = TcpTransport::new("0.0.0.0:0", on_receive.clone());
+    if let Some(i2p) = &i2p {
+        transports.register("i2p", i2p.clone(), 4);
+    }
 
     let p2p = Arc::new(
-        P2PNetwork::new(transport.clone(), Arc::clone(&chain))
+        P2PNetwork::new(transports.clone(), Arc::clone(&chain))
+            .with_mempool(Arc::clone(&mempool))
+            .with_whitelist(whitelist)
+            .with_upload_budget(policy.upload_budget())
+            .with_event_bus(Arc::clone(&bus))
     );
 
-    *p2p_holder.lock().unwrap() = Some(Arc::clone(&p2p));
+    p2p.attach();
 
-    println!("🔗 P2P TCP transport initialized");
+    if let Some(bind) = &network_config.whitebind {
+        let p2p = Arc::clone(&p2p);
+        transport.listen(bind, move |addr| p2p.whitelist_peer(addr));
+        say!("🤝 Trusted peers accepted on {}", bind);
+    }
+
+    say!("🔗 P2P TCP transport initialized");
+
+    if let Some(i2p) = &i2p {
+        for dest in &network_config.i2p_peers {
+            if i2p.connect(dest).is_none() {
+                say!("⚠️ I2P peer unreachable: {}", dest);
+            }
+        }
+    }
+
+    Arc::clone(&p2p).spawn_pinger();
+
+    let api_chain = Arc::clone(&chain);
+    let api_mempool = Arc::clone(&mempool);
+    let api_p2p = Arc::clone(&p2p);
+    let api_history = Arc::clone(&history);
+    let api_wallets = Arc::clone(&wallets);
+    let api_bus = Arc::clone(&bus);
+    let api_config = config.clone();
+    thread::spawn(move || {
+        let rt = Runtime::new().expect("Tokio runtime failed");
+        rt.block_on(start_api(api_chain, api_mempool, api_p2p, api_history, api_wallets, api_bus, api_config));
+    });
+
+    let scheme = if network_config.api_tls.is_some() { "https" } else { "http" };
+    say!("🌐 Explorer running at {}://127.0.0.1:{}", scheme, config.ports.api);
 
     SatelliteTransport::listen_udp("0.0.0.0:9999", on_receive.clone());
     GeoTransport::start("0.0.0.0:9333", on_receive.clone());
@@ -134,32 +386,48 @@ fn main() {
         });
     }
 
-    for seed in BOOTSTRAP_SEEDS {
-        if let Ok(addr) = seed.parse::<SocketAddr>() {
-            println!("🌱 Connecting to seed {}", seed);
-            transport.connect(addr);
-        }
-    }
+    let connman = Arc::new(ConnectionManager::new(Arc::clone(&transport), config.peers.seeds.clone()));
+    let seeded = connman.fill_outbound();
+    say!("🌱 Connected to {} outbound peer(s)", seeded.len());
+    connman.spawn();
 
-    println!("🔄 Requesting sync from peers");
+    say!("🔄 Requesting sync from peers");
 
     let mut mode = NodeMode::Syncing;
     let mut last_height = chain.lock().unwrap().height();
     let mut last_change = Instant::now();
-    let mut last_balance: u64 = 0;
+    let mut last_balance = Balances::default();
 
     loop {
+        // 🔒 Wallets unlocked at startup or over the API
+        wallets.lock().unwrap().lock_expired();
+
         match mode {
             NodeMode::Syncing => {
+                p2p.request_blocks();
                 let height = chain.lock().unwrap().height();
 
                 if height != last_height {
                     last_height = height;
                     last_change = Instant::now();
+                    bus.publish(BusEvent::SyncProgress { height, best_peer_height: p2p.best_peer_height() });
                 }
 
-                if last_change.elapsed() > Duration::from_secs(3) && height > 0 {
-                    println!("✅ Sync complete at height {}", height);
+                // A peer claims more blocks but none arrive
+                if last_change.elapsed() > SYNC_STALL_TIMEOUT && p2p.best_peer_height() > height {
+                    if let Some(peer) = p2p.handle_sync_stall() {
+                        say!("⏳ Sync stalled on {}, switched peer", peer);
+                    }
+                    last_change = Instant::now();
+                }
+
+                if last_change.elapsed() > Duration::from_secs(3)
+                    && height > 0
+                    && p2p.blocks_to_download() == 0
+                    && p2p.best_peer_height() <= height
+                {
+                    say!("✅ Sync complete at height {}", height);
+                    p2p.set_initial_sync(false);
                     mode = NodeMode::Normal;
                 }
 
@@ -167,45 +435,79 @@ fn main() {
             }
 
             NodeMode::Normal => {
-                let txs = mempool.lock().unwrap().sorted_for_mining();
+                // 📡 Relay-only node (`mining.enabled = false`)
+                if !policy.allow_mining_operations() {
+                    sleep(Duration::from_millis(300));
+                    continue;
+                }
+
+                // Taken before the template: a block connecting after
+                // this makes the work stale
+                let connected_before = miner::blocks_connected();
 
-                let candidate_block = {
+                let packages = mempool.lock().unwrap().mining_packages();
+
+                let template = {
                     let c = chain.lock().unwrap();
                     let prev = c.blocks.last().unwrap();
-                    miner::mine_block(
-                        prev,
-                        &c.utxos,
-                        txs,
-                        miner_pubkey_hash.clone(),
-                        &c.blocks,
-                    )
+                    let payout = if !split.is_empty() {
+                        split.clone()
+                    } else {
+                        let to = match payouts.len() {
+                            0 => &miner_pubkey_hash,
+                            n => &payouts[(prev.header.height + 1) as usize % n],
+                        };
+                        vec![PayoutShare::whole(to.clone())]
+                    };
+                    miner::block_template(prev, &c.utxos, packages, &payout, &c.blocks)
                 };
 
-                let accepted = {
-                    let mut c = chain.lock().unwrap();
-                    c.validate_and_add_block(candidate_block.clone())
+                // ⛏️ Without the chain lock, so peers' blocks still connect
+                let candidate_block = match miner::mine(template, &mining_threads, connected_before) {
+                    Some(b) => b,
+                    None => continue, // stale tip: start over on the new one
                 };
 
-                if accepted {
-                    p2p.broadcast_block(&candidate_block);
+                let events = {
+                    let mut c = chain.lock().unwrap();
+                    match c.validate_and_add_block(candidate_block.clone()) {
+                        Ok(()) => Some(block_events(&mut c, &candidate_block)),
+                        Err(e) => {
+                            say!("⚠️ Mined block rejected: {}", e);
+                            None
+                        }
+                    }
+                };
 
-                    mempool
-                        .lock()
-                        .unwrap()
-                        .remove_confirmed(&candidate_block.transactions);
+                if let Some(events) = events {
+                    bus.publish_all(events);
 
                     let c = chain.lock().unwrap();
-                    let balance: u64 = c.utxos
-                        .values()
-                        .filter(|u| u.pubkey_hash == miner_pubkey_hash)
-                        .map(|u| u.value)
-                        .sum();
-
                     let height = c.height();
+                    let balance = calculate_balances(
+                        &c.utxos,
+                        mempool.lock().unwrap().transactions(),
+                        |h| h == miner_pubkey_hash.as_slice(),
+                        height,
+                    );
+
                     if balance != last_balance {
-                        println!("💰 Wallet balance: {} (height {})", balance, height);
+                        say!(
+                            "💰 Wallet balance: {} confirmed, {} pending, {} immature (height {})",
+                            balance.confirmed, balance.pending, balance.immature, height
+                        );
                         last_balance = balance;
                     }
+
+                    let stats = miner::stats();
+                    let eta = miner::expected_time_to_block(&calculate_next_target(&c.blocks), stats.hashrate_5m);
+                    say!(
+                        "⛏️ Hashrate: {:.0} H/s (1m), {:.0} H/s (5m), {:.0} H/s (15m); next block in ~{}s",
+                        stats.hashrate_1m,
+                        stats.hashrate_5m,
+                        stats.hashrate_15m,
+                        eta.map_or("?".to_string(), |d| d.as_secs().to_string())
+                    );
                 }
 
                 sleep(Duration::from_millis(100));
@@ -0,0 +1,177 @@
+use std::fs;
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::script::multisig_script;
+use crate::core::transaction::{Transaction, TxInput, TxOutput};
+use crate::core::utxo::{OutPoint, UTXOSet, UTXO};
+use crate::crypto::sha256;
+use crate::policy::is_dust;
+use crate::psbt::PartiallySignedTransaction;
+use crate::wallet::{calculate_balance, estimated_size, payment_output, FeeRate, WalletBalance};
+
+const MULTISIG_WALLETS_FILE: &str = "data/multisig_wallets.json";
+
+/// Shared m-of-n multisig wallet (POLICY ONLY)
+///
+/// Holds only the cosigners' public keys. Every cosigner who
+/// registers the same keys and threshold gets the same locking
+/// script, since keys are kept sorted. Spends are built as a
+/// PSBT that each cosigner signs with `wallet sign` and that
+/// `multisig combine` merges.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MultisigWallet {
+    pub name: String,
+    /// Signatures required
+    pub m: u8,
+    /// Compressed cosigner public keys (hex), sorted
+    pub pubkeys: Vec<String>,
+}
+
+impl MultisigWallet {
+    pub fn new(name: &str, m: u8, pubkeys: &[String]) -> Result<Self, &'static str> {
+        let mut keys = Vec::new();
+        for pk in pubkeys {
+            let bytes = hex::decode(pk.trim()).map_err(|_| "invalid hex")?;
+            secp256k1::PublicKey::from_slice(&bytes).map_err(|_| "invalid public key")?;
+
+            let pk = hex::encode(bytes);
+            if keys.contains(&pk) {
+                return Err("duplicate public key");
+            }
+            keys.push(pk);
+        }
+        keys.sort();
+
+        let wallet = Self { name: name.to_string(), m, pubkeys: keys };
+        multisig_script(m, &wallet.key_bytes()).ok_or("invalid threshold")?;
+        Ok(wallet)
+    }
+
+    fn key_bytes(&self) -> Vec<Vec<u8>> {
+        self.pubkeys.iter().filter_map(|pk| hex::decode(pk).ok()).collect()
+    }
+
+    /// Locking script; payers send to this (see `payment_output`)
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        multisig_script(self.m, &self.key_bytes()).expect("validated on creation")
+    }
+
+    /// SHA256(script), the `pubkey_hash` of its outputs
+    pub fn address(&self) -> Vec<u8> {
+        sha256(&self.script_pubkey())
+    }
+
+    /// Unspent coins locked by the shared script
+    pub fn coins<'a>(&self, utxos: &'a UTXOSet) -> Vec<(&'a OutPoint, &'a UTXO)> {
+        let script = self.script_pubkey();
+        utxos.iter().filter(|(_, u)| u.script_pubkey == script).collect()
+    }
+
+    pub fn balance(&self, utxos: &UTXOSet, current_height: u64) -> WalletBalance {
+        let address = self.address();
+        calculate_balance(utxos, |h| h == address.as_slice(), current_height)
+    }
+
+    /// Unsigned spend of `amount` at `fee_rate`; change returns to
+    /// the shared script
+    pub fn create_spend(
+        &self,
+        utxos: &UTXOSet,
+        to: Vec<u8>,
+        amount: u64,
+        fee_rate: FeeRate,
+    ) -> Result<PartiallySignedTransaction, &'static str> {
+        if is_dust(amount) {
+            return Err("amount below dust threshold");
+        }
+
+        let mut collected = 0u64;
+        let mut inputs = Vec::new();
+        let mut change = None;
+
+        for (outpoint, utxo) in self.coins(utxos) {
+            inputs.push(TxInput {
+                txid: outpoint.txid.to_vec(),
+                index: outpoint.vout,
+                pubkey: vec![],
+                signature: vec![],
+                address_index: 0,
+                script_sig: vec![],
+            });
+            collected += utxo.value;
+
+            if collected < amount + fee_rate.fee_for(estimated_size(inputs.len(), 1)) {
+                continue;
+            }
+
+            let fee = fee_rate.fee_for(estimated_size(inputs.len(), 2));
+            change = Some(collected.saturating_sub(amount + fee));
+            break;
+        }
+
+        let change = change.ok_or("not enough funds")?;
+
+        let mut outputs = vec![payment_output(amount, to)];
+        if !is_dust(change) {
+            outputs.push(
+                TxOutput::multisig(change, self.m, &self.key_bytes()).ok_or("bad multisig script")?,
+            );
+        }
+
+        PartiallySignedTransaction::new(Transaction { inputs, outputs }, utxos)
+    }
+}
+
+pub fn load_multisig_wallets() -> Vec<MultisigWallet> {
+    fs::read_to_string(MULTISIG_WALLETS_FILE)
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_multisig_wallets(wallets: &[MultisigWallet]) {
+    fs::create_dir_all("data").unwrap();
+    fs::write(
+        MULTISIG_WALLETS_FILE,
+        serde_json::to_string_pretty(wallets).unwrap(),
+    ).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::public_key;
+    use secp256k1::SecretKey;
+
+    fn pubkey(n: u8) -> String {
+        hex::encode(public_key(&SecretKey::from_slice(&[n; 32]).unwrap()).serialize())
+    }
+
+    #[test]
+    fn cosigners_agree_on_script_and_spend_keeps_change() {
+        let a = MultisigWallet::new("a", 2, &[pubkey(1), pubkey(2), pubkey(3)]).unwrap();
+        let b = MultisigWallet::new("b", 2, &[pubkey(3), pubkey(1), pubkey(2)]).unwrap();
+        assert_eq!(a.script_pubkey(), b.script_pubkey());
+
+        assert!(MultisigWallet::new("c", 4, &[pubkey(1), pubkey(2)]).is_err());
+
+        let mut utxos = UTXOSet::new();
+        let coin = TxOutput::multisig(100_000, 2, &a.key_bytes()).unwrap();
+        utxos.insert(
+            OutPoint { txid: [9; 32], vout: 0 },
+            UTXO {
+                value: coin.value,
+                pubkey_hash: coin.pubkey_hash,
+                height: 1,
+                is_coinbase: false,
+                script_pubkey: coin.script_pubkey,
+            },
+        );
+        assert_eq!(a.balance(&utxos, 10).total, 100_000);
+
+        let psbt = a.create_spend(&utxos, vec![5; 32], 40_000, FeeRate::SatPerByte(1)).unwrap();
+        assert_eq!(psbt.tx.outputs[1].script_pubkey, a.script_pubkey());
+        assert_eq!(psbt.input_signatures(0), (0, 2));
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::block::Block;
+use crate::core::transaction::Transaction;
+use crate::node::mempool::Txid;
+
+/// Transactions touching each address (POLICY ONLY)
+///
+/// Built from the active chain and caught up on demand; a reorg
+/// drops everything above the fork before the new blocks are
+/// indexed. An address is touched by a transaction that pays it
+/// or spends one of its coins.
+#[derive(Default)]
+pub struct AddressIndex {
+    /// Active chain hashes by height, as indexed
+    chain: Vec<Vec<u8>>,
+    /// Pubkey hash → (height, txid), oldest first
+    txs: HashMap<Vec<u8>, Vec<(u64, Txid)>>,
+    /// (txid, vout) → (pubkey hash, height), to resolve spends
+    outputs: HashMap<(Txid, u32), (Vec<u8>, u64)>,
+}
+
+impl AddressIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Catch up with `blocks` (the active chain)
+    pub fn sync(&mut self, blocks: &[Block]) {
+        let fork = self
+            .chain
+            .iter()
+            .zip(blocks)
+            .take_while(|(hash, b)| **hash == b.hash)
+            .count();
+
+        if fork < self.chain.len() {
+            let fork = fork as u64;
+            for list in self.txs.values_mut() {
+                while list.last().is_some_and(|(height, _)| *height >= fork) {
+                    list.pop();
+                }
+            }
+            self.txs.retain(|_, list| !list.is_empty());
+            self.outputs.retain(|_, (_, height)| *height < fork);
+        }
+        self.chain.truncate(fork);
+
+        for block in &blocks[fork..] {
+            let height = block.header.height;
+
+            for tx in &block.transactions {
+                let txid = tx.txid();
+
+                for address in self.touched(tx) {
+                    self.txs.entry(address).or_default().push((height, txid.clone()));
+                }
+                for (vout, o) in tx.outputs.iter().enumerate() {
+                    self.outputs.insert((txid.clone(), vout as u32), (o.pubkey_hash.clone(), height));
+                }
+            }
+            self.chain.push(block.hash.clone());
+        }
+    }
+
+    /// Addresses paid by `tx` or owning the confirmed coins it spends
+    pub fn touched(&self, tx: &Transaction) -> HashSet<Vec<u8>> {
+        let spent = tx
+            .inputs
+            .iter()
+            .filter_map(|i| self.outputs.get(&(i.txid.clone(), i.index)))
+            .map(|(address, _)| address.clone());
+
+        tx.outputs.iter().map(|o| o.pubkey_hash.clone()).chain(spent).collect()
+    }
+
+    /// Confirmed (height, txid) touching `pubkey_hash`, oldest first
+    pub fn confirmed(&self, pubkey_hash: &[u8]) -> &[(u64, Txid)] {
+        self.txs.get(pubkey_hash).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockHeader;
+    use crate::core::transaction::{TxInput, TxOutput};
+
+    fn block(height: u64, tag: u8, transactions: Vec<Transaction>) -> Block {
+        Block {
+            header: BlockHeader {
+                height,
+                timestamp: 0,
+                prev_hash: vec![],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![],
+                utxo_commitment: vec![],
+            },
+            transactions,
+            hash: vec![tag; 32],
+        }
+    }
+
+    fn tx(spends: Option<&Transaction>, value: u64, to: u8) -> Transaction {
+        Transaction {
+            inputs: spends
+                .map(|prev| TxInput {
+                    txid: prev.txid(),
+                    index: 0,
+                    pubkey: vec![],
+                    signature: vec![],
+                    address_index: 0,
+                    script_sig: vec![],
+                })
+                .into_iter()
+                .collect(),
+            outputs: vec![TxOutput { value, pubkey_hash: vec![to; 32], script_pubkey: vec![] }],
+        }
+    }
+
+    #[test]
+    fn indexes_payments_and_spends_across_reorgs() {
+        let (a, b) = (vec![1u8; 32], vec![2u8; 32]);
+        let pay_a = tx(None, 50, 1);
+        let a_to_b = tx(Some(&pay_a), 40, 2);
+
+        let mut index = AddressIndex::new();
+        let mut blocks = vec![block(0, 0, vec![pay_a.clone()]), block(1, 1, vec![a_to_b.clone()])];
+        index.sync(&blocks);
+
+        assert_eq!(index.confirmed(&a), [(0, pay_a.txid()), (1, a_to_b.txid())]);
+        assert_eq!(index.confirmed(&b), [(1, a_to_b.txid())]);
+
+        // Block 1 replaced by one without the spend
+        blocks[1] = block(1, 9, vec![]);
+        index.sync(&blocks);
+
+        assert_eq!(index.confirmed(&a), [(0, pay_a.txid())]);
+        assert!(index.confirmed(&b).is_empty());
+        assert!(index.touched(&a_to_b).contains(&a));
+    }
+}
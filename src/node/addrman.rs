@@ -0,0 +1,224 @@
+//! Peer address manager (NON-CONSENSUS)
+//!
+//! Remembers addresses learned from seeds and peers, with
+//! per-address exponential backoff after failed attempts.
+//! Persisted to `peers.json` so restarts don't depend on seeds.
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Delay after the first failed attempt
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Backoff never exceeds this
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// Addresses that never connected are forgotten after this many failures
+const MAX_FAILURES: u32 = 10;
+
+/// Upper bound on remembered addresses
+const MAX_ADDRS: usize = 4_096;
+
+/// Quality metadata for one address (times are unix seconds)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AddrInfo {
+    /// Consecutive failed attempts
+    failures: u32,
+    /// Successful connections ever
+    #[serde(default)]
+    successes: u32,
+    last_try: Option<i64>,
+    last_success: Option<i64>,
+}
+
+impl AddrInfo {
+    /// Earliest time another attempt is allowed
+    fn ready_at(&self) -> Option<i64> {
+        let last = self.last_try?;
+        if self.failures == 0 {
+            return None;
+        }
+        Some(last + backoff(self.failures).as_secs() as i64)
+    }
+}
+
+/// On-disk record
+#[derive(Serialize, Deserialize)]
+struct AddrRecord {
+    addr: SocketAddr,
+    #[serde(flatten)]
+    info: AddrInfo,
+}
+
+/// Delay before retrying after `failures` consecutive failures
+pub fn backoff(failures: u32) -> Duration {
+    let shift = failures.saturating_sub(1).min(16);
+    BASE_BACKOFF
+        .checked_mul(1 << shift)
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF)
+}
+
+pub struct AddrMan {
+    addrs: HashMap<SocketAddr, AddrInfo>,
+}
+
+impl AddrMan {
+    pub fn new() -> Self {
+        Self { addrs: HashMap::new() }
+    }
+
+    /// Load addresses saved by `save`; empty if missing or corrupt
+    pub fn load(path: &Path) -> Self {
+        let records: Vec<AddrRecord> = fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
+        Self {
+            addrs: records
+                .into_iter()
+                .take(MAX_ADDRS)
+                .map(|r| (r.addr, r.info))
+                .collect(),
+        }
+    }
+
+    /// Write all addresses with their metadata
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let records: Vec<AddrRecord> = self
+            .addrs
+            .iter()
+            .map(|(addr, info)| AddrRecord { addr: *addr, info: info.clone() })
+            .collect();
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&records)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.addrs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addrs.is_empty()
+    }
+
+    /// Learn an address (no-op if known or full)
+    pub fn add(&mut self, addr: SocketAddr) {
+        if self.addrs.len() >= MAX_ADDRS {
+            return;
+        }
+        self.addrs.entry(addr).or_default();
+    }
+
+    /// Forget an address; false if unknown
+    pub fn remove(&mut self, addr: &SocketAddr) -> bool {
+        self.addrs.remove(addr).is_some()
+    }
+
+    /// Known addresses with (consecutive failures, last success),
+    /// most recently good first
+    pub fn known(&self) -> Vec<(SocketAddr, u32, Option<i64>)> {
+        let mut known: Vec<_> = self
+            .addrs
+            .iter()
+            .map(|(addr, info)| (*addr, info.failures, info.last_success))
+            .collect();
+        known.sort_by_key(|(addr, failures, last)| (std::cmp::Reverse(*last), *failures, *addr));
+        known
+    }
+
+    /// Connection attempt failed
+    pub fn mark_failed(&mut self, addr: &SocketAddr, now: i64) {
+        let forget = match self.addrs.get_mut(addr) {
+            Some(info) => {
+                info.failures += 1;
+                info.last_try = Some(now);
+                info.last_success.is_none() && info.failures >= MAX_FAILURES
+            }
+            None => false,
+        };
+
+        if forget {
+            self.addrs.remove(addr);
+        }
+    }
+
+    /// Connection succeeded
+    pub fn mark_good(&mut self, addr: &SocketAddr, now: i64) {
+        let info = self.addrs.entry(*addr).or_default();
+        info.failures = 0;
+        info.successes = info.successes.saturating_add(1);
+        info.last_try = Some(now);
+        info.last_success = Some(now);
+    }
+
+    /// Best address to try next, skipping `exclude` and
+    /// addresses still backing off.
+    ///
+    /// Prefers addresses that worked before (most often first),
+    /// then fewest failures.
+    pub fn select(&self, exclude: &[SocketAddr], now: i64) -> Option<SocketAddr> {
+        self.addrs
+            .iter()
+            .filter(|(addr, _)| !exclude.contains(addr))
+            .filter(|(_, info)| info.ready_at().map_or(true, |t| t <= now))
+            .min_by_key(|(_, info)| {
+                (info.last_success.is_none(), std::cmp::Reverse(info.successes), info.failures)
+            })
+            .map(|(addr, _)| *addr)
+    }
+}
+
+impl AddrMan {
+    /// Address that never connected, for a feeler probe
+    pub fn select_untested(&self, exclude: &[SocketAddr], now: i64) -> Option<SocketAddr> {
+        self.addrs
+            .iter()
+            .filter(|(addr, info)| info.last_success.is_none() && !exclude.contains(addr))
+            .filter(|(_, info)| info.ready_at().map_or(true, |t| t <= now))
+            .min_by_key(|(_, info)| info.failures)
+            .map(|(addr, _)| *addr)
+    }
+}
+
+impl Default for AddrMan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_addresses_back_off() {
+        let a: SocketAddr = "10.0.0.1:8333".parse().unwrap();
+        let b: SocketAddr = "10.0.0.2:8333".parse().unwrap();
+        let now = 1_730_000_000;
+
+        let mut addrman = AddrMan::new();
+        addrman.add(a);
+        addrman.add(b);
+        addrman.mark_good(&b, now);
+
+        assert_eq!(addrman.select(&[], now), Some(b));
+        assert_eq!(addrman.select(&[b], now), Some(a));
+
+        addrman.mark_failed(&a, now);
+        assert_eq!(addrman.select(&[b], now), None);
+        assert_eq!(addrman.select(&[b], now + backoff(1).as_secs() as i64), Some(a));
+
+        assert_eq!(backoff(2), BASE_BACKOFF * 2);
+        assert_eq!(backoff(100), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,116 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+use crate::core::block::Block;
+use crate::core::chain::Blockchain;
+use crate::core::transaction::Transaction;
+
+/// Something one subsystem did that others react to
+#[derive(Clone, Debug)]
+pub enum BusEvent {
+    /// Block joined the active chain
+    BlockConnected(Block),
+    /// Block left the active chain in a reorg; published tip
+    /// first, before the replacing `BlockConnected`s
+    BlockDisconnected(Block),
+    /// Transaction accepted into the mempool from a peer or a
+    /// local client (not re-admitted after a reorg)
+    TxAccepted(Transaction),
+    PeerConnected { addr: SocketAddr, inbound: bool },
+    /// Initial block download reached `height`
+    SyncProgress { height: u64, best_peer_height: u64 },
+    /// Raw message from any transport, for P2P to decode
+    MessageReceived { from: SocketAddr, data: Vec<u8> },
+}
+
+type Handler = Arc<dyn Fn(&BusEvent) + Send + Sync>;
+
+/// Publish/subscribe between node subsystems (POLICY ONLY)
+///
+/// Chain, mempool, P2P, miner, wallets and the API only publish
+/// what they did and subscribe to what they care about, instead
+/// of calling into each other. Handlers run on the publishing
+/// thread, in subscription order, so the mempool has dropped a
+/// block's transactions before wallets look at it. Handlers may
+/// publish in turn (P2P connecting a received block).
+///
+/// Handlers take the chain and mempool locks themselves: never
+/// publish while holding either.
+#[derive(Default)]
+pub struct EventBus {
+    handlers: RwLock<Vec<Handler>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `handler` with every event published from now on
+    pub fn subscribe(&self, handler: impl Fn(&BusEvent) + Send + Sync + 'static) {
+        self.handlers.write().unwrap().push(Arc::new(handler));
+    }
+
+    pub fn publish(&self, event: BusEvent) {
+        // Not under the lock: a nested publish must not wait
+        // behind a concurrent `subscribe`
+        let handlers = self.handlers.read().unwrap().clone();
+        for handler in &handlers {
+            handler(&event);
+        }
+    }
+
+    pub fn publish_all(&self, events: Vec<BusEvent>) {
+        for event in events {
+            self.publish(event);
+        }
+    }
+}
+
+/// What accepting `block` did to the active chain: blocks a
+/// reorg disconnected (tip first), then those connected
+///
+/// Call under the chain lock right after the block was
+/// accepted; publish once the lock is dropped.
+pub fn block_events(chain: &mut Blockchain, block: &Block) -> Vec<BusEvent> {
+    let mut disconnected = chain.take_disconnected();
+    disconnected.sort_by(|a, b| b.header.height.cmp(&a.header.height));
+
+    let connected: Vec<Block> = match disconnected.last() {
+        // The new branch replaces everything from the fork up
+        Some(lowest) => chain.blocks.iter().skip(lowest.header.height as usize).cloned().collect(),
+        None if chain.blocks.last().map(|b| &b.hash) == Some(&block.hash) => vec![block.clone()],
+        // Stored on a side branch
+        None => vec![],
+    };
+
+    disconnected
+        .into_iter()
+        .map(BusEvent::BlockDisconnected)
+        .chain(connected.into_iter().map(BusEvent::BlockConnected))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn handlers_run_in_subscription_order() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        for name in ["mempool", "wallet"] {
+            let seen = Arc::clone(&seen);
+            bus.subscribe(move |event| {
+                if let BusEvent::SyncProgress { height, .. } = event {
+                    seen.lock().unwrap().push((name, *height));
+                }
+            });
+        }
+
+        bus.publish(BusEvent::SyncProgress { height: 7, best_peer_height: 9 });
+        assert_eq!(*seen.lock().unwrap(), [("mempool", 7), ("wallet", 7)]);
+    }
+}
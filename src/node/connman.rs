@@ -0,0 +1,262 @@
+//! Outbound connection manager (NON-CONSENSUS)
+//!
+//! Keeps up to `target_outbound` outbound peers connected,
+//! replacing dropped ones from AddrMan and falling back to
+//! DNS seeds when no address is ready.
+//!
+//! The longest-lived outbound peers are saved as anchors and
+//! dialled first after a restart, so an attacker can't fill
+//! every slot while the node comes back up.
+
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use time::OffsetDateTime;
+
+use crate::core::chain::data_dir;
+use crate::node::addrman::AddrMan;
+use crate::node::seeds::resolve_seed;
+use crate::node::transport::tcp::TcpTransport;
+use crate::node::transport::Transport;
+
+/// Default number of outbound peers
+pub const DEFAULT_OUTBOUND_PEERS: usize = 8;
+
+/// How often the peer set is checked
+const CONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often an untested address is probed
+const FEELER_INTERVAL: Duration = Duration::from_secs(2 * 60);
+
+/// Minimum seconds between seed re-resolutions
+const SEED_REFRESH: i64 = 5 * 60;
+
+/// Outbound peers remembered across restarts
+const MAX_ANCHORS: usize = 2;
+
+/// How often the anchor file is refreshed
+const ANCHOR_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+fn anchors_file() -> PathBuf {
+    let mut path = data_dir();
+    path.push("anchors.json");
+    path
+}
+
+/// Read and delete the anchor file (anchors are used once)
+fn take_anchors() -> Vec<SocketAddr> {
+    let path = anchors_file();
+    let anchors: Vec<SocketAddr> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let _ = fs::remove_file(&path);
+    anchors.into_iter().take(MAX_ANCHORS).collect()
+}
+
+/// Where AddrMan's known addresses are kept
+pub fn peers_file() -> PathBuf {
+    let mut path = data_dir();
+    path.push("peers.json");
+    path
+}
+
+fn unix_now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+pub struct ConnectionManager {
+    transport: Arc<TcpTransport>,
+    addrman: Mutex<AddrMan>,
+    seeds: Vec<String>,
+    target_outbound: usize,
+    /// Connected outbound peers and when they connected
+    outbound: Mutex<Vec<(SocketAddr, Instant)>>,
+    /// Anchors from the previous run, not yet dialled
+    anchors: Mutex<Vec<SocketAddr>>,
+    last_seed_lookup: Mutex<Option<i64>>,
+}
+
+impl ConnectionManager {
+    /// Manager starting from the addresses saved on disk
+    pub fn new(transport: Arc<TcpTransport>, seeds: Vec<String>) -> Self {
+        let addrman = AddrMan::load(&peers_file());
+        if !addrman.is_empty() {
+            println!("📒 Loaded {} known peer address(es)", addrman.len());
+        }
+
+        Self {
+            transport,
+            addrman: Mutex::new(addrman),
+            seeds,
+            target_outbound: DEFAULT_OUTBOUND_PEERS,
+            outbound: Mutex::new(Vec::new()),
+            anchors: Mutex::new(take_anchors()),
+            last_seed_lookup: Mutex::new(None),
+        }
+    }
+
+    pub fn with_target(mut self, target_outbound: usize) -> Self {
+        self.target_outbound = target_outbound;
+        self
+    }
+
+    /// Learn a peer address (e.g. from gossip)
+    pub fn add_address(&self, addr: SocketAddr) {
+        self.addrman.lock().unwrap().add(addr);
+    }
+
+    /// Outbound peers still connected
+    pub fn outbound_count(&self) -> usize {
+        let live = self.transport.peers();
+        let mut outbound = self.outbound.lock().unwrap();
+        outbound.retain(|(a, _)| live.contains(a));
+        outbound.len()
+    }
+
+    /// Resolve seeds into AddrMan (rate limited)
+    fn refresh_seeds(&self, now: i64) {
+        let mut last = self.last_seed_lookup.lock().unwrap();
+        if last.map_or(false, |t| now - t < SEED_REFRESH) {
+            return;
+        }
+        *last = Some(now);
+
+        let mut addrman = self.addrman.lock().unwrap();
+        for seed in &self.seeds {
+            for addr in resolve_seed(seed) {
+                addrman.add(addr);
+            }
+        }
+    }
+
+    /// Open connections until the target is met or no address
+    /// is ready. Returns the newly connected peers.
+    pub fn fill_outbound(&self) -> Vec<SocketAddr> {
+        let mut connected = Vec::new();
+        let mut attempted = false;
+
+        // Anchors first, bypassing AddrMan selection
+        let anchors = std::mem::take(&mut *self.anchors.lock().unwrap());
+        for addr in anchors {
+            if self.outbound_count() >= self.target_outbound {
+                break;
+            }
+
+            println!("⚓ Connecting to anchor {}", addr);
+            if self.transport.connect(addr) {
+                self.addrman.lock().unwrap().mark_good(&addr, unix_now());
+                self.outbound.lock().unwrap().push((addr, Instant::now()));
+                connected.push(addr);
+            }
+        }
+
+        while self.outbound_count() < self.target_outbound {
+            let now = unix_now();
+            let exclude = self.transport.peers();
+
+            let next = self.addrman.lock().unwrap().select(&exclude, now);
+            let addr = match next {
+                Some(a) => a,
+                None => {
+                    self.refresh_seeds(now);
+                    match self.addrman.lock().unwrap().select(&exclude, now) {
+                        Some(a) => a,
+                        None => break,
+                    }
+                }
+            };
+
+            println!("🔌 Connecting to {}", addr);
+            if self.transport.connect(addr) {
+                self.addrman.lock().unwrap().mark_good(&addr, unix_now());
+                self.outbound.lock().unwrap().push((addr, Instant::now()));
+                connected.push(addr);
+            } else {
+                self.addrman.lock().unwrap().mark_failed(&addr, unix_now());
+            }
+            attempted = true;
+        }
+
+        if attempted {
+            self.save();
+        }
+
+        connected
+    }
+
+    /// Persist known addresses to `peers.json`
+    pub fn save(&self) {
+        if let Err(e) = self.addrman.lock().unwrap().save(&peers_file()) {
+            println!("⚠️ Could not save peers: {}", e);
+        }
+    }
+
+    /// Save the longest-connected outbound peers as anchors
+    pub fn save_anchors(&self) {
+        self.outbound_count();
+
+        let mut outbound = self.outbound.lock().unwrap().clone();
+        outbound.sort_by_key(|(_, since)| *since);
+        let anchors: Vec<SocketAddr> = outbound.iter().take(MAX_ANCHORS).map(|(a, _)| *a).collect();
+
+        if anchors.is_empty() {
+            return;
+        }
+
+        let result = serde_json::to_string(&anchors)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(anchors_file(), json));
+        if let Err(e) = result {
+            println!("⚠️ Could not save anchors: {}", e);
+        }
+    }
+
+    /// Briefly connect to one untested address to learn whether
+    /// it is reachable, then disconnect
+    pub fn feeler(&self) {
+        let exclude = self.transport.peers();
+        let addr = match self.addrman.lock().unwrap().select_untested(&exclude, unix_now()) {
+            Some(a) => a,
+            None => return,
+        };
+
+        if self.transport.connect(addr) {
+            println!("🔭 Feeler reached {}", addr);
+            self.addrman.lock().unwrap().mark_good(&addr, unix_now());
+            self.transport.disconnect(&addr);
+        } else {
+            self.addrman.lock().unwrap().mark_failed(&addr, unix_now());
+        }
+
+        self.save();
+    }
+
+    /// Maintain outbound peers in the background
+    pub fn spawn(self: Arc<Self>) {
+        thread::spawn(move || {
+            let mut last_feeler = Instant::now();
+            let mut last_anchor_save = Instant::now();
+
+            loop {
+                self.fill_outbound();
+
+                if last_feeler.elapsed() >= FEELER_INTERVAL {
+                    self.feeler();
+                    last_feeler = Instant::now();
+                }
+
+                if last_anchor_save.elapsed() >= ANCHOR_SAVE_INTERVAL {
+                    self.save_anchors();
+                    last_anchor_save = Instant::now();
+                }
+
+                thread::sleep(CONNECT_INTERVAL);
+            }
+        });
+    }
+}
@@ -0,0 +1,195 @@
+//! Parallel block download (NON-CONSENSUS)
+//!
+//! Headers-first sync queues the hashes of missing blocks in
+//! chain order. Bodies are requested from several peers at
+//! once inside a sliding window, buffered as they arrive and
+//! released strictly in order for connection.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::core::block::Block;
+
+/// Blocks ahead of the next one to connect that may be in flight
+pub const DOWNLOAD_WINDOW: usize = 256;
+
+/// Outstanding requests per peer
+pub const MAX_IN_FLIGHT_PER_PEER: usize = 16;
+
+/// A request unanswered this long is given to another peer
+pub const BLOCK_TIMEOUT: Duration = Duration::from_secs(20);
+
+pub struct BlockDownload {
+    /// Missing blocks in chain order, front = next to connect
+    queue: VecDeque<Vec<u8>>,
+    /// hash → (peer asked, when)
+    in_flight: HashMap<Vec<u8>, (SocketAddr, Instant)>,
+    /// Downloaded bodies waiting for their predecessors
+    received: HashMap<Vec<u8>, (Block, SocketAddr)>,
+}
+
+impl BlockDownload {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            in_flight: HashMap::new(),
+            received: HashMap::new(),
+        }
+    }
+
+    /// True while blocks are still queued
+    pub fn is_active(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Queue block hashes (chain order), skipping known ones
+    pub fn add(&mut self, hashes: impl IntoIterator<Item = Vec<u8>>) {
+        for hash in hashes {
+            if !self.queue.contains(&hash) {
+                self.queue.push_back(hash);
+            }
+        }
+    }
+
+    /// Assign unrequested (or timed-out) blocks in the window to
+    /// `peers` round-robin, respecting per-peer limits.
+    ///
+    /// Returns the hashes to request from each peer.
+    pub fn schedule(&mut self, peers: &[SocketAddr], now: Instant) -> Vec<(SocketAddr, Vec<Vec<u8>>)> {
+        if peers.is_empty() {
+            return Vec::new();
+        }
+
+        // Drop requests to peers that left or timed out
+        self.in_flight.retain(|_, (peer, at)| {
+            peers.contains(peer) && now.duration_since(*at) < BLOCK_TIMEOUT
+        });
+
+        let mut load: HashMap<SocketAddr, usize> = peers.iter().map(|p| (*p, 0)).collect();
+        for (peer, _) in self.in_flight.values() {
+            *load.entry(*peer).or_default() += 1;
+        }
+
+        let mut assigned: HashMap<SocketAddr, Vec<Vec<u8>>> = HashMap::new();
+        let mut next_peer = 0;
+
+        for hash in self.queue.iter().take(DOWNLOAD_WINDOW) {
+            if self.in_flight.contains_key(hash) || self.received.contains_key(hash) {
+                continue;
+            }
+
+            // Next peer with spare capacity
+            let peer = (0..peers.len())
+                .map(|i| peers[(next_peer + i) % peers.len()])
+                .find(|p| load[p] < MAX_IN_FLIGHT_PER_PEER);
+
+            let peer = match peer {
+                Some(p) => p,
+                None => break,
+            };
+
+            next_peer = (peers.iter().position(|p| *p == peer).unwrap() + 1) % peers.len();
+            *load.get_mut(&peer).unwrap() += 1;
+            self.in_flight.insert(hash.clone(), (peer, now));
+            assigned.entry(peer).or_default().push(hash.clone());
+        }
+
+        assigned.into_iter().collect()
+    }
+
+    /// Buffer a downloaded block; false if it was not requested
+    pub fn block_received(&mut self, block: Block, from: SocketAddr) -> bool {
+        if !self.queue.contains(&block.hash) {
+            return false;
+        }
+
+        self.in_flight.remove(&block.hash);
+        self.received.insert(block.hash.clone(), (block, from));
+        true
+    }
+
+    /// Blocks ready to connect, in chain order, with their sender
+    pub fn take_ready(&mut self) -> Vec<(Block, SocketAddr)> {
+        let mut ready = Vec::new();
+
+        while let Some(hash) = self.queue.front() {
+            match self.received.remove(hash) {
+                Some(entry) => {
+                    ready.push(entry);
+                    self.queue.pop_front();
+                }
+                None => break,
+            }
+        }
+
+        ready
+    }
+
+    /// Abandon the download (e.g. a queued block was invalid)
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.in_flight.clear();
+        self.received.clear();
+    }
+}
+
+impl Default for BlockDownload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockHeader;
+
+    fn block(n: u8) -> Block {
+        Block {
+            header: BlockHeader {
+                height: n as u64,
+                timestamp: 0,
+                prev_hash: vec![0u8; 32],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![0u8; 32],
+                utxo_commitment: vec![],
+            },
+            transactions: vec![],
+            hash: vec![n],
+        }
+    }
+
+    #[test]
+    fn spreads_requests_and_connects_in_order() {
+        let a: SocketAddr = "10.0.0.1:8333".parse().unwrap();
+        let b: SocketAddr = "10.0.0.2:8333".parse().unwrap();
+        let now = Instant::now();
+
+        let mut dl = BlockDownload::new();
+        dl.add((1..=4u8).map(|n| vec![n]));
+
+        let plan = dl.schedule(&[a, b], now);
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan.iter().map(|(_, h)| h.len()).sum::<usize>(), 4);
+
+        // Later block first: buffered until its parent arrives
+        assert!(dl.block_received(block(2), b));
+        assert!(dl.take_ready().is_empty());
+
+        assert!(dl.block_received(block(1), a));
+        let ready: Vec<u64> = dl.take_ready().iter().map(|(b, _)| b.header.height).collect();
+        assert_eq!(ready, vec![1, 2]);
+
+        // Timed-out requests are reassigned
+        let plan = dl.schedule(&[b], now + BLOCK_TIMEOUT);
+        assert_eq!(plan, vec![(b, vec![vec![3], vec![4]])]);
+
+        assert!(!dl.block_received(block(9), a));
+    }
+}
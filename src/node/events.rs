@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::node::bus::{BusEvent, EventBus};
+
+/// Events kept for clients resuming a stream
+pub const EVENT_LOG_CAPACITY: usize = 1_000;
+
+/// Chain or mempool change (POLICY ONLY)
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeEvent {
+    /// Block connected to the active chain
+    Block { hash: String, height: u64 },
+    /// Transaction accepted into the mempool
+    Tx { txid: String },
+    /// Blocks above `fork_height` were disconnected (tip first);
+    /// the replacing blocks follow as `Block` events
+    Reorg { fork_height: u64, disconnected: Vec<String> },
+    /// Peer connection opened
+    Peer { addr: String, inbound: bool },
+}
+
+impl NodeEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeEvent::Block { .. } => "block",
+            NodeEvent::Tx { .. } => "tx",
+            NodeEvent::Reorg { .. } => "reorg",
+            NodeEvent::Peer { .. } => "peer",
+        }
+    }
+}
+
+#[derive(Default)]
+struct LogState {
+    /// Reorg being reported: blocks disconnected so far (tip
+    /// first), numbered when the first replacement connects
+    reorg: Option<NodeEvent>,
+    /// (id, event), oldest first; ids increase by one
+    events: VecDeque<(u64, NodeEvent)>,
+    last_id: u64,
+}
+
+/// Numbered feed of node events (POLICY ONLY)
+///
+/// Fed from the event bus (see `subscribe`); stream clients
+/// read everything after the last id they saw. Only the newest
+/// EVENT_LOG_CAPACITY events are kept, so a client resuming
+/// from an older id skips ahead.
+pub struct EventLog {
+    state: Mutex<LogState>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self { state: Mutex::new(LogState::default()) }
+    }
+
+    /// Id of the newest event (0 = none yet)
+    pub fn last_id(&self) -> u64 {
+        self.state.lock().unwrap().last_id
+    }
+
+    /// Events after `id`, oldest first
+    pub fn since(&self, id: u64) -> Vec<(u64, NodeEvent)> {
+        let state = self.state.lock().unwrap();
+        state.events.iter().filter(|(i, _)| *i > id).cloned().collect()
+    }
+
+    /// Newest `limit` events, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<(u64, NodeEvent)> {
+        let state = self.state.lock().unwrap();
+        let skip = state.events.len().saturating_sub(limit);
+        state.events.iter().skip(skip).cloned().collect()
+    }
+
+    /// Record everything published on `bus` from now on
+    pub fn subscribe(self: &Arc<Self>, bus: &EventBus) {
+        let log = Arc::clone(self);
+        bus.subscribe(move |event| log.record(event));
+    }
+
+    /// Number `event` if stream clients care about it
+    pub fn record(&self, event: &BusEvent) {
+        let mut state = self.state.lock().unwrap();
+
+        let event = match event {
+            BusEvent::BlockConnected(b) => {
+                if let Some(reorg) = state.reorg.take() {
+                    state.push(reorg);
+                }
+                NodeEvent::Block { hash: hex::encode(&b.hash), height: b.header.height }
+            }
+            BusEvent::TxAccepted(tx) => NodeEvent::Tx { txid: hex::encode(tx.txid()) },
+            BusEvent::PeerConnected { addr, inbound } => NodeEvent::Peer { addr: addr.to_string(), inbound: *inbound },
+            BusEvent::BlockDisconnected(b) => {
+                let reorg = state.reorg.get_or_insert(NodeEvent::Reorg { fork_height: 0, disconnected: vec![] });
+                if let NodeEvent::Reorg { fork_height, disconnected } = reorg {
+                    *fork_height = b.header.height;
+                    disconnected.push(hex::encode(&b.hash));
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        state.push(event);
+    }
+}
+
+impl LogState {
+    fn push(&mut self, event: NodeEvent) {
+        self.last_id += 1;
+        self.events.push_back((self.last_id, event));
+        while self.events.len() > EVENT_LOG_CAPACITY {
+            self.events.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::{Block, BlockHeader};
+    use crate::core::transaction::{Transaction, TxOutput};
+
+    fn block(height: u64, tag: u8) -> Block {
+        Block {
+            header: BlockHeader {
+                height,
+                timestamp: 0,
+                prev_hash: vec![],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![],
+                utxo_commitment: vec![],
+            },
+            transactions: vec![],
+            hash: vec![tag; 32],
+        }
+    }
+
+    #[test]
+    fn numbers_blocks_txs_and_reorgs() {
+        let log = EventLog::new();
+        assert_eq!(log.last_id(), 0);
+
+        let tx = Transaction {
+            inputs: vec![],
+            outputs: vec![TxOutput { value: 1, pubkey_hash: vec![1; 32], script_pubkey: vec![] }],
+        };
+        log.record(&BusEvent::BlockConnected(block(2, 2)));
+        log.record(&BusEvent::TxAccepted(tx));
+        log.record(&BusEvent::SyncProgress { height: 2, best_peer_height: 2 });
+
+        // Block 2 replaced, then extended
+        log.record(&BusEvent::BlockDisconnected(block(2, 2)));
+        log.record(&BusEvent::BlockConnected(block(2, 9)));
+        log.record(&BusEvent::BlockConnected(block(3, 9)));
+
+        let kinds: Vec<&str> = log.since(0).iter().map(|(_, e)| e.kind()).collect();
+        assert_eq!(kinds, ["block", "tx", "reorg", "block", "block"]);
+        assert_eq!(
+            log.since(2)[0],
+            (3, NodeEvent::Reorg { fork_height: 2, disconnected: vec![hex::encode([2u8; 32])] })
+        );
+    }
+}
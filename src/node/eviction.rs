@@ -0,0 +1,82 @@
+//! Inbound peer eviction (NON-CONSENSUS)
+//!
+//! When inbound slots are full, one existing inbound peer is
+//! dropped to make room. Peers that are hard for an attacker
+//! to fake are protected: lowest latency, recent block
+//! deliverers and longest uptime. The youngest remaining peer
+//! is evicted.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Peers protected by each single criterion
+const PROTECT_PER_CRITERION: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct EvictionCandidate {
+    pub addr: SocketAddr,
+    /// How long the peer has been connected
+    pub uptime: Duration,
+    pub ping_rtt: Option<Duration>,
+    /// Unix time of the last block it delivered
+    pub last_block: Option<i64>,
+}
+
+/// Remove the `n` best candidates by `key` (smaller = better)
+fn protect<K: Ord>(candidates: &mut Vec<EvictionCandidate>, n: usize, key: impl Fn(&EvictionCandidate) -> K) {
+    candidates.sort_by_key(|c| key(c));
+    let n = n.min(candidates.len());
+    candidates.drain(..n);
+}
+
+/// Peer to evict, or None if every candidate is protected
+pub fn select_eviction(candidates: &[EvictionCandidate]) -> Option<SocketAddr> {
+    let mut remaining = candidates.to_vec();
+
+    protect(&mut remaining, PROTECT_PER_CRITERION, |c| c.ping_rtt.unwrap_or(Duration::MAX));
+
+    // Only peers that actually delivered blocks earn protection
+    let deliverers = remaining.iter().filter(|c| c.last_block.is_some()).count();
+    protect(&mut remaining, PROTECT_PER_CRITERION.min(deliverers), |c| {
+        std::cmp::Reverse(c.last_block)
+    });
+
+    // Half of the rest by uptime
+    let half = remaining.len() / 2;
+    protect(&mut remaining, half, |c| std::cmp::Reverse(c.uptime));
+
+    remaining
+        .iter()
+        .min_by_key(|c| c.uptime)
+        .map(|c| c.addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(n: u8, uptime: u64, ping_ms: u64, last_block: Option<i64>) -> EvictionCandidate {
+        EvictionCandidate {
+            addr: SocketAddr::from(([10, 0, 0, n], 8333)),
+            uptime: Duration::from_secs(uptime),
+            ping_rtt: Some(Duration::from_millis(ping_ms)),
+            last_block,
+        }
+    }
+
+    #[test]
+    fn evicts_youngest_unprotected_peer() {
+        let mut peers: Vec<EvictionCandidate> = (0..12)
+            .map(|n| candidate(n, 1_000 + n as u64, 500, None))
+            .collect();
+
+        // Fast and block-relaying peers are kept even if new
+        peers.push(candidate(20, 1, 5, None));
+        peers.push(candidate(21, 2, 500, Some(1_730_000_000)));
+        // Youngest ordinary peer
+        peers.push(candidate(22, 3, 500, None));
+
+        assert_eq!(select_eviction(&peers), Some(SocketAddr::from(([10, 0, 0, 22], 8333))));
+        assert_eq!(select_eviction(&peers[..2]), None);
+    }
+}
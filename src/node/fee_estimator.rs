@@ -0,0 +1,141 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Used when no estimate is available yet (1 per byte)
+pub const FALLBACK_FEE_RATE: i64 = 1_000;
+
+/// Confirmations remembered for estimation
+const MAX_SAMPLES: usize = 2_000;
+
+/// Unconfirmed transactions are forgotten after this many blocks
+const MAX_TRACKED_BLOCKS: u64 = 1_000;
+
+/// Minimum samples at or above a fee rate before trusting it
+const MIN_SAMPLES: usize = 10;
+
+/// Share of samples that must confirm within the target
+const SUCCESS_THRESHOLD: f64 = 0.85;
+
+/// Ratio between neighbouring fee-rate buckets
+const FEE_SPACING: f64 = 1.1;
+
+/// Fee estimation from observed confirmations (POLICY ONLY)
+///
+/// Records the fee rate (per 1000 bytes) and entry height of
+/// every mempool transaction, then how many blocks it waited
+/// once confirmed.
+pub struct FeeEstimator {
+    /// txid → (fee rate, height seen)
+    pending: HashMap<Vec<u8>, (i64, u64)>,
+    /// (fee rate, blocks waited)
+    samples: VecDeque<(i64, u64)>,
+}
+
+impl FeeEstimator {
+    pub fn new() -> Self {
+        Self {
+            pending: HashMap::new(),
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Transaction entered the mempool at `height`
+    ///
+    /// Re-admission (e.g. after a reorg) keeps the first height.
+    pub fn track(&mut self, txid: Vec<u8>, fee_rate: i64, height: u64) {
+        self.pending.entry(txid).or_insert((fee_rate, height));
+    }
+
+    /// Transactions confirmed in the block at `height`
+    pub fn confirmed<'a>(&mut self, txids: impl IntoIterator<Item = &'a Vec<u8>>, height: u64) {
+        for txid in txids {
+            if let Some((fee_rate, seen)) = self.pending.remove(txid) {
+                self.samples.push_back((fee_rate, height.saturating_sub(seen).max(1)));
+            }
+        }
+
+        while self.samples.len() > MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.pending
+            .retain(|_, (_, seen)| height.saturating_sub(*seen) <= MAX_TRACKED_BLOCKS);
+    }
+
+    /// Lowest fee rate (per 1000 bytes) that confirmed within
+    /// `target_blocks` for SUCCESS_THRESHOLD of recent
+    /// transactions paying at least that much.
+    ///
+    /// Rates are grouped into FEE_SPACING buckets and scanned
+    /// from the top; the scan stops at the first bucket that
+    /// drags the success rate below the threshold.
+    /// None until enough confirmations have been seen.
+    pub fn estimate_feerate(&self, target_blocks: u64) -> Option<i64> {
+        let mut samples: Vec<(i64, u64)> = self.samples.iter().copied().collect();
+        samples.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let mut total = 0usize;
+        let mut within = 0usize;
+        let mut estimate = None;
+
+        let mut i = 0;
+        while i < samples.len() {
+            let current = bucket(samples[i].0);
+            let mut lowest = samples[i].0;
+
+            while i < samples.len() && bucket(samples[i].0) == current {
+                total += 1;
+                if samples[i].1 <= target_blocks {
+                    within += 1;
+                }
+                lowest = samples[i].0;
+                i += 1;
+            }
+
+            if total < MIN_SAMPLES {
+                continue;
+            }
+
+            if (within as f64 / total as f64) < SUCCESS_THRESHOLD {
+                break;
+            }
+
+            estimate = Some(lowest);
+        }
+
+        estimate
+    }
+}
+
+/// Geometric fee-rate bucket index
+fn bucket(fee_rate: i64) -> i32 {
+    (fee_rate.max(1) as f64).log(FEE_SPACING).floor() as i32
+}
+
+impl Default for FeeEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_tracks_fast_confirmations() {
+        let mut est = FeeEstimator::new();
+        assert_eq!(est.estimate_feerate(1), None);
+
+        // High fee rates confirm next block, low ones wait
+        for i in 0..20u8 {
+            let (rate, height) = if i < 10 { (5_000, 100) } else { (1_000, 90) };
+            est.track(vec![i], rate, height);
+        }
+
+        let txids: Vec<Vec<u8>> = (0..20u8).map(|i| vec![i]).collect();
+        est.confirmed(&txids, 101);
+
+        assert_eq!(est.estimate_feerate(1), Some(5_000));
+        assert_eq!(est.estimate_feerate(20), Some(1_000));
+    }
+}
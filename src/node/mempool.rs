@@ -1,32 +1,122 @@
 use crate::transaction::Transaction;
-use crate::utxo::UTXOSet;
-use crate::policy::MAX_TX_SIZE;
-use crate::validation::validate_transaction;
+use crate::utxo::{OutPoint, UTXOSet, UTXO};
+use crate::policy::{is_standard_tx, PolicyError, INCREMENTAL_RELAY_FEE_RATE, MAX_TX_SIZE};
+use crate::validation::{validate_transaction_with, ValidationContext};
+use crate::error::ValidationError;
 use crate::block::Block;
+use crate::node::fee_estimator::FeeEstimator;
+use crate::node::bus::{BusEvent, EventBus};
+use crate::chain::Blockchain;
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 const MAX_MEMPOOL_TXS: usize = 50_000;
+const MAX_MEMPOOL_BYTES: usize = 50_000_000;
+
+/// Mempool capacity (POLICY ONLY)
+#[derive(Debug, Clone, Copy)]
+pub struct MempoolLimits {
+    pub max_entries: usize,
+    pub max_bytes: usize,
+}
+
+impl Default for MempoolLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: MAX_MEMPOOL_TXS,
+            max_bytes: MAX_MEMPOOL_BYTES,
+        }
+    }
+}
+
+/// Why the mempool refused a transaction (POLICY + CONSENSUS)
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MempoolError {
+    #[error("coinbase transactions are not relayed")]
+    Coinbase,
+
+    #[error("transaction too large")]
+    TooLarge,
+
+    #[error("non-standard transaction: {0}")]
+    NonStandard(#[from] PolicyError),
+
+    #[error("invalid transaction: {0}")]
+    Invalid(#[from] ValidationError),
+
+    #[error("replacement does not pay enough fee")]
+    ReplacementFeeTooLow,
+
+    #[error("fee too low")]
+    FeeTooLow,
+
+    #[error("mempool full")]
+    MempoolFull,
+}
+
+impl MempoolError {
+    /// Misbehavior points for the peer that relayed the transaction
+    ///
+    /// Only consensus failures score; policy is local.
+    pub fn ban_score(&self) -> u32 {
+        match self {
+            MempoolError::Invalid(e) => e.ban_score(),
+            _ => 0,
+        }
+    }
+}
+
+/// Transaction id as returned by `Transaction::txid`
+pub type Txid = Vec<u8>;
 
 #[derive(Clone)]
 pub struct MempoolEntry {
+    pub txid: Txid,
     pub tx: Transaction,
     pub fee: i64,
     pub size: usize,
     pub timestamp: i64,
 }
 
+impl MempoolEntry {
+    /// Fee per 1000 bytes
+    pub fn fee_rate(&self) -> i64 {
+        self.fee * 1000 / self.size.max(1) as i64
+    }
+}
+
+/// Transaction pool indexed by txid, spent outpoint,
+/// fee rate and arrival time
 pub struct Mempool {
-    entries: Vec<MempoolEntry>,
-    spent_outpoints: HashSet<(Vec<u8>, u32)>,
+    entries: HashMap<Txid, MempoolEntry>,
+    /// Outpoint → mempool transaction spending it
+    spent_outpoints: HashMap<OutPoint, Txid>,
+    by_fee_rate: BTreeSet<(i64, Txid)>,
+    by_time: BTreeSet<(i64, Txid)>,
+    limits: MempoolLimits,
+    total_bytes: usize,
+    /// Fee rate (per 1000 bytes) required after evictions
+    min_fee_rate: i64,
+    estimator: FeeEstimator,
 }
 
 impl Mempool {
     pub fn new() -> Self {
+        Self::with_limits(MempoolLimits::default())
+    }
+
+    pub fn with_limits(limits: MempoolLimits) -> Self {
         Self {
-            entries: Vec::new(),
-            spent_outpoints: HashSet::new(),
+            entries: HashMap::new(),
+            spent_outpoints: HashMap::new(),
+            by_fee_rate: BTreeSet::new(),
+            by_time: BTreeSet::new(),
+            limits,
+            total_bytes: 0,
+            min_fee_rate: 0,
+            estimator: FeeEstimator::new(),
         }
     }
 
@@ -34,127 +124,439 @@ impl Mempool {
         self.entries.len()
     }
 
+    /// Summed size of all entries
+    pub fn bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Effective minimum fee rate (per 1000 bytes) to enter
+    pub fn min_fee_rate(&self) -> i64 {
+        self.min_fee_rate
+    }
+
+    /// Fee rate (per 1000 bytes) expected to confirm within
+    /// `target_blocks`, never below the mempool minimum
+    pub fn estimate_feerate(&self, target_blocks: u64) -> Option<i64> {
+        self.estimator
+            .estimate_feerate(target_blocks)
+            .map(|rate| rate.max(self.min_fee_rate))
+    }
+
+    pub fn get_entry(&self, txid: &[u8]) -> Option<&MempoolEntry> {
+        self.entries.get(txid)
+    }
+
+    /// Every transaction in the pool, in no particular order
+    pub fn transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.entries.values().map(|e| &e.tx)
+    }
+
+    /// Ids of every transaction in the pool
+    pub fn txids(&self) -> Vec<Txid> {
+        self.entries.keys().cloned().collect()
+    }
+
+    /// In-mempool parents of `txid`, transitively
+    pub fn ancestors(&self, txid: &[u8]) -> Vec<&MempoolEntry> {
+        let mut found: HashSet<&[u8]> = HashSet::new();
+        let mut result = Vec::new();
+        let mut stack: Vec<&MempoolEntry> = self.get_entry(txid).into_iter().collect();
+
+        while let Some(child) = stack.pop() {
+            for input in &child.tx.inputs {
+                if let Some(parent) = self.entries.get(&input.txid) {
+                    if found.insert(parent.txid.as_slice()) {
+                        result.push(parent);
+                        stack.push(parent);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// In-mempool children of `txid`, transitively
+    pub fn descendants(&self, txid: &[u8]) -> Vec<&MempoolEntry> {
+        let mut found = self.with_descendants(txid);
+        found.remove(txid);
+
+        found.iter().filter_map(|t| self.entries.get(t)).collect()
+    }
+
+    /// Fee rate (per 1000 bytes) of `txid` with its ancestors,
+    /// i.e. what a miner earns for including it
+    pub fn package_fee_rate(&self, txid: &[u8]) -> Option<i64> {
+        let entry = self.get_entry(txid)?;
+        let ancestors = self.ancestors(txid);
+
+        let fee = entry.fee + ancestors.iter().map(|e| e.fee).sum::<i64>();
+        let size = entry.size + ancestors.iter().map(|e| e.size).sum::<usize>();
+
+        Some(fee * 1000 / size.max(1) as i64)
+    }
+
     pub fn add_transaction(
         &mut self,
         tx: Transaction,
         utxos: &UTXOSet,
-        chain_height: u64,
-    ) -> bool {
+        ctx: &ValidationContext,
+    ) -> Result<(), MempoolError> {
         // Coinbase not allowed in mempool
-        if tx.inputs.is_empty() {
-            return false;
+        if tx.is_coinbase() {
+            return Err(MempoolError::Coinbase);
+        }
+
+        let txid = tx.txid();
+        if self.entries.contains_key(&txid) {
+            return Ok(());
         }
 
         let size = tx.serialized_size();
         if size > MAX_TX_SIZE {
-            return false;
+            return Err(MempoolError::TooLarge);
         }
 
-        if !validate_transaction(&tx, utxos, chain_height) {
-            return false;
-        }
+        is_standard_tx(&tx)?;
 
-        // Prevent double-spend inside mempool
-        for input in &tx.inputs {
-            let key = (input.txid.clone(), input.index);
-            if self.spent_outpoints.contains(&key) {
-                return false;
-            }
-        }
+        // Children may spend outputs of unconfirmed parents
+        let lookup = |outpoint: &OutPoint| {
+            utxos.get(outpoint).cloned().or_else(|| self.unconfirmed_coin(outpoint, ctx.height))
+        };
+
+        validate_transaction_with(&tx, &lookup, ctx)?;
+
+        // Double-spends of mempool entries are replacements (RBF)
+        let conflicts = self.conflicts_of(&tx);
 
-        let fee = match calculate_fee(&tx, utxos) {
+        let fee = match calculate_fee(&tx, &lookup) {
             Some(f) if f > 0 => f,
-            _ => return false,
+            _ => return Err(MempoolError::FeeTooLow),
         };
 
-        for input in &tx.inputs {
-            self.spent_outpoints
-                .insert((input.txid.clone(), input.index));
-        }
-
-        self.entries.push(MempoolEntry {
+        let entry = MempoolEntry {
+            txid,
             tx,
             fee,
             size,
             timestamp: now(),
-        });
+        };
+
+        if entry.fee_rate() < self.min_fee_rate {
+            return Err(MempoolError::FeeTooLow);
+        }
+
+        if !conflicts.is_empty() {
+            self.check_replacement(&entry, &conflicts)?;
+            for txid in &conflicts {
+                self.remove_entry(txid);
+            }
+        }
+
+        let txid = entry.txid.clone();
+        self.estimator.track(txid.clone(), entry.fee_rate(), ctx.height);
+        self.insert_entry(entry);
 
         // 🔒 MEMPOOL SIZE CAP + EVICTION (POLICY ONLY)
-        if self.entries.len() > MAX_MEMPOOL_TXS {
-            // Evict lowest fee-rate first
-            self.entries.sort_by(|a, b| {
-                let lhs = a.fee * b.size as i64;
-                let rhs = b.fee * a.size as i64;
-                lhs.cmp(&rhs)
-            });
+        self.trim_to_limits();
+
+        // The new entry may have been the cheapest one
+        if !self.entries.contains_key(&txid) {
+            return Err(MempoolError::MempoolFull);
+        }
 
-            self.entries.truncate(MAX_MEMPOOL_TXS);
-            self.rebuild_spent_outpoints();
+        Ok(())
+    }
+
+    fn insert_entry(&mut self, entry: MempoolEntry) {
+        for outpoint in entry.tx.inputs.iter().filter_map(|i| i.outpoint()) {
+            self.spent_outpoints.insert(outpoint, entry.txid.clone());
         }
 
-        true
+        self.by_fee_rate.insert((entry.fee_rate(), entry.txid.clone()));
+        self.by_time.insert((entry.timestamp, entry.txid.clone()));
+        self.total_bytes += entry.size;
+        self.entries.insert(entry.txid.clone(), entry);
     }
 
-    /// Transactions sorted by fee-rate for mining
-    pub fn sorted_for_mining(&self) -> Vec<Transaction> {
-        let mut entries = self.entries.clone();
+    fn remove_entry(&mut self, txid: &[u8]) -> Option<MempoolEntry> {
+        let entry = self.entries.remove(txid)?;
 
-        entries.sort_by(|a, b| {
-            let lhs = a.fee * b.size as i64;
-            let rhs = b.fee * a.size as i64;
-            rhs.cmp(&lhs)
-        });
+        for outpoint in entry.tx.inputs.iter().filter_map(|i| i.outpoint()) {
+            self.spent_outpoints.remove(&outpoint);
+        }
 
-        entries.into_iter().map(|e| e.tx).collect()
+        self.by_fee_rate.remove(&(entry.fee_rate(), entry.txid.clone()));
+        self.by_time.remove(&(entry.timestamp, entry.txid.clone()));
+        self.total_bytes -= entry.size;
+
+        Some(entry)
     }
 
-    /// Remove confirmed transactions after block acceptance
-    pub fn remove_confirmed(&mut self, confirmed: &[Transaction]) {
-        self.entries.retain(|entry| {
-            !confirmed
-                .iter()
-                .any(|tx| tx.txid() == entry.tx.txid())
-        });
+    /// Evict lowest fee-rate entries (with descendants) until
+    /// within limits, raising the minimum fee rate past each
+    /// evicted entry.
+    fn trim_to_limits(&mut self) {
+        while self.entries.len() > self.limits.max_entries
+            || self.total_bytes > self.limits.max_bytes
+        {
+            let (fee_rate, txid) = match self.by_fee_rate.iter().next() {
+                Some(lowest) => lowest.clone(),
+                None => break,
+            };
+
+            self.min_fee_rate = self.min_fee_rate.max(fee_rate + 1);
 
-        self.rebuild_spent_outpoints();
+            for evicted in self.with_descendants(&txid) {
+                self.remove_entry(&evicted);
+            }
+        }
     }
 
-    /// Re-add transactions from orphaned blocks
-    pub fn resurrect_from_orphans(
+    /// Drop entries older than `max_age` seconds, with descendants
+    pub fn remove_expired(&mut self, max_age: i64) -> usize {
+        let cutoff = now() - max_age;
+        let expired: Vec<Txid> = self
+            .by_time
+            .iter()
+            .take_while(|(time, _)| *time < cutoff)
+            .map(|(_, txid)| txid.clone())
+            .collect();
+
+        let mut removed = 0;
+        for txid in expired {
+            for t in self.with_descendants(&txid) {
+                if self.remove_entry(&t).is_some() {
+                    removed += 1;
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Output of a mempool transaction, as the coin it becomes
+    /// once mined at `height`
+    fn unconfirmed_coin(&self, outpoint: &OutPoint, height: u64) -> Option<UTXO> {
+        let entry = self.entries.get(outpoint.txid.as_slice())?;
+        let output = entry.tx.outputs.get(outpoint.vout as usize)?;
+
+        Some(UTXO {
+            value: output.value,
+            pubkey_hash: output.pubkey_hash.clone(),
+            height,
+            is_coinbase: false,
+            script_pubkey: output.script_pubkey.clone(),
+        })
+    }
+
+    /// Entries double-spent by `tx`, with their descendants
+    fn conflicts_of(&self, tx: &Transaction) -> HashSet<Txid> {
+        let mut conflicts = HashSet::new();
+
+        for outpoint in tx.inputs.iter().filter_map(|i| i.outpoint()) {
+            if let Some(spender) = self.spent_outpoints.get(&outpoint) {
+                conflicts.extend(self.with_descendants(spender));
+            }
+        }
+
+        conflicts
+    }
+
+    /// Replace-by-fee rules (POLICY ONLY)
+    ///
+    /// The replacement must beat the fee rate of every
+    /// transaction it evicts, and pay their summed fees plus
+    /// its own relay at INCREMENTAL_RELAY_FEE_RATE.
+    fn check_replacement(
+        &self,
+        replacement: &MempoolEntry,
+        conflicts: &HashSet<Txid>,
+    ) -> Result<(), MempoolError> {
+        let replaced: Vec<&MempoolEntry> =
+            conflicts.iter().filter_map(|t| self.entries.get(t)).collect();
+
+        if replaced.iter().any(|e| replacement.fee_rate() <= e.fee_rate()) {
+            return Err(MempoolError::ReplacementFeeTooLow);
+        }
+
+        let replaced_fees: i64 = replaced.iter().map(|e| e.fee).sum();
+        let relay_fee = INCREMENTAL_RELAY_FEE_RATE * replacement.size as i64 / 1000;
+
+        if replacement.fee < replaced_fees + relay_fee {
+            return Err(MempoolError::ReplacementFeeTooLow);
+        }
+
+        Ok(())
+    }
+
+    /// `txid` and every entry spending its outputs, transitively
+    fn with_descendants(&self, txid: &[u8]) -> HashSet<Txid> {
+        let mut found = HashSet::new();
+        let mut stack = vec![txid.to_vec()];
+
+        while let Some(parent) = stack.pop() {
+            let outputs = match self.entries.get(&parent) {
+                Some(e) => e.tx.outputs.len(),
+                None => continue,
+            };
+
+            if !found.insert(parent.clone()) {
+                continue;
+            }
+
+            for vout in 0..outputs as u32 {
+                let child = OutPoint::new(&parent, vout)
+                    .and_then(|op| self.spent_outpoints.get(&op));
+                if let Some(child) = child {
+                    stack.push(child.clone());
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Ancestor packages for mining (POLICY ONLY)
+    ///
+    /// Entries by descending `package_fee_rate`, each preceded
+    /// by its ancestors not already taken, parents first. A
+    /// low-fee parent is thus mined along with a child paying
+    /// for both (CPFP). Package rates are computed once, up
+    /// front.
+    pub fn mining_packages(&self) -> Vec<Vec<Transaction>> {
+        let mut by_package_rate: Vec<(i64, &MempoolEntry)> = self
+            .entries
+            .values()
+            .map(|e| (self.package_fee_rate(&e.txid).unwrap_or(0), e))
+            .collect();
+        by_package_rate.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let mut taken: HashSet<&[u8]> = HashSet::new();
+        let mut packages = Vec::new();
+
+        for (_, entry) in by_package_rate {
+            if taken.contains(entry.txid.as_slice()) {
+                continue;
+            }
+
+            let mut package: Vec<&MempoolEntry> = self
+                .ancestors(&entry.txid)
+                .into_iter()
+                .filter(|a| !taken.contains(a.txid.as_slice()))
+                .collect();
+
+            // A parent always has fewer ancestors than its child
+            package.sort_by_key(|a| self.ancestors(&a.txid).len());
+            package.push(entry);
+
+            taken.extend(package.iter().map(|&e| e.txid.as_slice()));
+            packages.push(package.into_iter().map(|e| e.tx.clone()).collect());
+        }
+
+        packages
+    }
+
+    /// Remove confirmed transactions after block acceptance,
+    /// along with entries (and their descendants) spending the
+    /// same outpoints
+    pub fn remove_confirmed(&mut self, confirmed: &[Transaction], height: u64) {
+        let txids: Vec<Txid> = confirmed.iter().map(|tx| tx.txid()).collect();
+        self.estimator.confirmed(&txids, height);
+
+        for txid in &txids {
+            self.remove_entry(txid);
+        }
+
+        let mut conflicts = HashSet::new();
+        for tx in confirmed {
+            conflicts.extend(self.conflicts_of(tx));
+        }
+        for txid in &conflicts {
+            self.remove_entry(txid);
+        }
+    }
+
+    /// Revalidate after the best chain changed (POLICY ONLY)
+    ///
+    /// Transactions from `disconnected` blocks are resurrected
+    /// first, then every existing entry is re-admitted against
+    /// `utxos`. Entries that are now confirmed, double-spent
+    /// or otherwise invalid are dropped.
+    ///
+    /// Arrival order need not put parents first (a child may
+    /// arrive in the same second), so rejected transactions
+    /// are retried until a pass admits nothing new.
+    pub fn on_chain_changed(
         &mut self,
-        orphaned: Vec<Block>,
         utxos: &UTXOSet,
-        chain_height: u64,
+        ctx: &ValidationContext,
+        mut disconnected: Vec<Block>,
     ) {
-        for block in orphaned {
-            for tx in block.transactions.into_iter().skip(1) {
-                let _ = self.add_transaction(tx, utxos, chain_height);
+        // Blocks arrive tip first; a block may spend its parent's
+        // outputs, so resurrect from the fork up
+        disconnected.sort_by_key(|b| b.header.height);
+
+        // Oldest first, so parents are re-admitted before children
+        let previous: Vec<Transaction> = std::mem::take(&mut self.by_time)
+            .into_iter()
+            .filter_map(|(_, txid)| self.entries.remove(&txid))
+            .map(|e| e.tx)
+            .collect();
+
+        self.entries.clear();
+        self.spent_outpoints.clear();
+        self.by_fee_rate.clear();
+        self.total_bytes = 0;
+
+        let resurrected = disconnected
+            .into_iter()
+            .flat_map(|block| block.transactions.into_iter().skip(1));
+
+        let mut pending: Vec<Transaction> = resurrected.chain(previous).collect();
+        loop {
+            let before = pending.len();
+            pending.retain(|tx| self.add_transaction(tx.clone(), utxos, ctx).is_err());
+
+            if pending.is_empty() || pending.len() == before {
+                break;
             }
         }
     }
+}
 
-    fn rebuild_spent_outpoints(&mut self) {
-        self.spent_outpoints.clear();
-        for entry in &self.entries {
-            for input in &entry.tx.inputs {
-                self.spent_outpoints
-                    .insert((input.txid.clone(), input.index));
+/// Keep `mempool` in step with the active chain (POLICY ONLY)
+///
+/// Drops what each connected block confirms; after a reorg,
+/// resurrects the disconnected blocks' transactions and
+/// revalidates the rest against the new tip.
+pub fn follow_chain(mempool: Arc<Mutex<Mempool>>, chain: Arc<Mutex<Blockchain>>, bus: &EventBus) {
+    // Disconnects arrive (tip first) before the blocks that
+    // replace them
+    let disconnected = Mutex::new(Vec::new());
+
+    bus.subscribe(move |event| match event {
+        BusEvent::BlockDisconnected(block) => disconnected.lock().unwrap().push(block.clone()),
+        BusEvent::BlockConnected(block) => {
+            let c = chain.lock().unwrap();
+            let mut m = mempool.lock().unwrap();
+            m.remove_confirmed(&block.transactions, block.header.height);
+
+            let blocks = std::mem::take(&mut *disconnected.lock().unwrap());
+            if !blocks.is_empty() {
+                m.on_chain_changed(&c.utxos, &c.next_block_context(), blocks);
             }
         }
-    }
+        _ => {}
+    });
 }
 
-fn calculate_fee(tx: &Transaction, utxos: &UTXOSet) -> Option<i64> {
+fn calculate_fee(tx: &Transaction, lookup: &dyn Fn(&OutPoint) -> Option<UTXO>) -> Option<i64> {
     let mut input_sum = 0i64;
     let mut output_sum = 0i64;
 
     for input in &tx.inputs {
-        let key = format!(
-            "{}:{}",
-            hex::encode(&input.txid),
-            input.index
-        );
-        let utxo = utxos.get(&key)?;
+        let utxo = lookup(&input.outpoint()?)?;
         input_sum += utxo.value as i64;
     }
 
@@ -171,3 +573,150 @@ fn now() -> i64 {
         .expect("system time")
         .as_secs() as i64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockHeader;
+    use crate::core::transaction::{TxInput, TxOutput};
+    use crate::validation::ValidationFlags;
+
+    fn spend(txid: &[u8], value: u64) -> Transaction {
+        Transaction {
+            inputs: vec![TxInput {
+                txid: txid.to_vec(),
+                index: 0,
+                pubkey: vec![],
+                signature: vec![],
+                address_index: 0,
+                script_sig: vec![],
+            }],
+            outputs: vec![TxOutput {
+                value,
+                pubkey_hash: vec![1; 32],
+                script_pubkey: vec![],
+            }],
+        }
+    }
+
+    fn block(height: u64, tx: Transaction) -> Block {
+        let coinbase = Transaction { inputs: vec![], outputs: vec![] };
+        Block {
+            header: BlockHeader {
+                height,
+                timestamp: 0,
+                prev_hash: vec![0; 32],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![0; 32],
+                utxo_commitment: vec![],
+            },
+            transactions: vec![coinbase, tx],
+            hash: vec![],
+        }
+    }
+
+    fn funded_utxos(outpoint: OutPoint, value: u64) -> UTXOSet {
+        let mut utxos = UTXOSet::new();
+        utxos.insert(
+            outpoint,
+            UTXO {
+                value,
+                pubkey_hash: vec![1; 32],
+                height: 0,
+                is_coinbase: false,
+                script_pubkey: vec![],
+            },
+        );
+        utxos
+    }
+
+    fn context() -> ValidationContext {
+        ValidationContext::new(3, 0).with_flags(ValidationFlags {
+            verify_signatures: false,
+            check_future_drift: false,
+        })
+    }
+
+    #[test]
+    fn reorg_resurrects_parent_before_child() {
+        let funding = OutPoint::new(&[7; 32], 0).unwrap();
+        let utxos = funded_utxos(funding, 100_000);
+
+        let parent = spend(&funding.txid, 90_000);
+        let child = spend(&parent.txid(), 80_000);
+
+        // Published tip first, as `block_events` does
+        let mut mempool = Mempool::new();
+        let disconnected = vec![block(2, child.clone()), block(1, parent.clone())];
+        mempool.on_chain_changed(&utxos, &context(), disconnected);
+
+        assert!(mempool.get_entry(&parent.txid()).is_some());
+        assert!(mempool.get_entry(&child.txid()).is_some());
+    }
+
+    #[test]
+    fn re_admits_children_arriving_before_parents() {
+        let funding = OutPoint::new(&[7; 32], 0).unwrap();
+        let utxos = funded_utxos(funding, 100_000);
+
+        let parent = spend(&funding.txid, 90_000);
+        let child = spend(&parent.txid(), 80_000);
+
+        let mut mempool = Mempool::new();
+        mempool.add_transaction(parent.clone(), &utxos, &context()).unwrap();
+        mempool.add_transaction(child.clone(), &utxos, &context()).unwrap();
+
+        // Same second: `by_time` falls back to txid order
+        let time = mempool.get_entry(&parent.txid()).unwrap().timestamp;
+        for txid in [parent.txid(), child.txid()] {
+            let mut entry = mempool.remove_entry(&txid).unwrap();
+            entry.timestamp = time;
+            mempool.insert_entry(entry);
+        }
+
+        mempool.on_chain_changed(&utxos, &context(), vec![]);
+        assert_eq!(mempool.size(), 2);
+    }
+
+    #[test]
+    fn confirmed_double_spend_evicts_conflicts() {
+        let funding = OutPoint::new(&[7; 32], 0).unwrap();
+        let utxos = funded_utxos(funding, 100_000);
+
+        let parent = spend(&funding.txid, 90_000);
+        let child = spend(&parent.txid(), 80_000);
+
+        let mut mempool = Mempool::new();
+        mempool.add_transaction(parent, &utxos, &context()).unwrap();
+        mempool.add_transaction(child, &utxos, &context()).unwrap();
+
+        // A block confirms a different spend of the same coin
+        let rival = spend(&funding.txid, 95_000);
+        mempool.remove_confirmed(&[rival], 1);
+
+        assert_eq!(mempool.size(), 0);
+        assert_eq!(mempool.bytes(), 0);
+    }
+
+    #[test]
+    fn rejects_transaction_evicted_by_its_own_admission() {
+        let first = OutPoint::new(&[7; 32], 0).unwrap();
+        let second = OutPoint::new(&[8; 32], 0).unwrap();
+        let mut utxos = funded_utxos(first, 100_000);
+        utxos.extend(funded_utxos(second, 100_000));
+
+        let limits = MempoolLimits { max_entries: 1, ..Default::default() };
+        let mut mempool = Mempool::with_limits(limits);
+        let rich = spend(&first.txid, 90_000);
+        mempool.add_transaction(rich.clone(), &utxos, &context()).unwrap();
+
+        let cheap = spend(&second.txid, 99_000);
+        assert_eq!(
+            mempool.add_transaction(cheap.clone(), &utxos, &context()),
+            Err(MempoolError::MempoolFull)
+        );
+        assert!(mempool.get_entry(&rich.txid()).is_some());
+        assert!(mempool.get_entry(&cheap.txid()).is_none());
+    }
+}
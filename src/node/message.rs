@@ -1,18 +1,46 @@
 use serde::{Serialize, Deserialize};
-use crate::core::block::Block;
+use crate::core::block::{Block, BlockHeader};
 use crate::core::transaction::Transaction;
+use crate::crypto::sha256;
 
-pub const PROTOCOL_VERSION: u32 = 3;
+pub const PROTOCOL_VERSION: u32 = 5;
+
+pub const USER_AGENT: &str = "/revelation:0.4.0/";
+
+/// Service bits advertised in `Hello`
+pub const NODE_NETWORK: u64 = 1 << 0; // serves full blocks
+pub const NODE_PRUNED: u64 = 1 << 1; // serves recent blocks only
+pub const NODE_SPV: u64 = 1 << 2; // serves headers to light clients
+
+/// Kind of object an inventory hash refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InvKind {
+    Block,
+    Transaction,
+}
+
+/// Announced object: block hash or txid
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InvItem {
+    pub kind: InvKind,
+    pub hash: Vec<u8>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NetworkMessage {
-    /// Initial handshake
+    /// Version handshake, answered with `Hello` + `Verack`
     Hello {
         version: u32,
+        services: u64,
         height: u64,
         agent: String,
+        /// Best-chain cumulative work (big-endian)
+        chain_work: Vec<u8>,
     },
 
+    /// Handshake acknowledged
+    Verack,
+
     /// Ask peer for known addresses
     GetAddr,
 
@@ -30,7 +58,96 @@ pub enum NetworkMessage {
     /// Transaction gossip
     Transaction(Transaction),
 
-    /// Keepalive
-    Ping,
-    Pong,
+    /// Keepalive; the nonce is echoed back to measure latency
+    Ping(u64),
+    Pong(u64),
+
+    /// Announce objects by hash (v4)
+    Inv(Vec<InvItem>),
+
+    /// Request announced objects (v4)
+    GetData(Vec<InvItem>),
+
+    /// Ask a peer to announce its whole mempool
+    MempoolRequest,
+
+    /// Request headers after the first locator hash we share (v5)
+    GetHeaders {
+        locator: Vec<Vec<u8>>,
+        /// Empty = as many as allowed
+        stop_hash: Vec<u8>,
+    },
+
+    /// Best-chain headers answering GetHeaders (v5)
+    Headers(Vec<BlockHeader>),
+}
+
+/// Most headers sent in one Headers message
+pub const MAX_HEADERS: usize = 2_000;
+
+/// Wire header: magic (4) | payload length (4, LE) | checksum (4)
+pub const HEADER_SIZE: usize = 12;
+
+/// Why a raw packet was discarded before deserialization
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WireError {
+    #[error("packet shorter than header")]
+    Truncated,
+
+    #[error("wrong network magic")]
+    BadMagic,
+
+    #[error("payload length mismatch")]
+    BadLength,
+
+    #[error("payload checksum mismatch")]
+    BadChecksum,
+
+    #[error("malformed payload")]
+    Malformed,
+}
+
+/// First 4 bytes of SHA256(SHA256(payload))
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    let hash = sha256(&sha256(payload));
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Frame a message for the wire
+pub fn encode_message(magic: [u8; 4], msg: &NetworkMessage) -> Option<Vec<u8>> {
+    let payload = bincode::serialize(msg).ok()?;
+
+    let mut out = Vec::with_capacity(HEADER_SIZE + payload.len());
+    out.extend_from_slice(&magic);
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(&checksum(&payload));
+    out.extend_from_slice(&payload);
+
+    Some(out)
+}
+
+/// Check framing, then deserialize
+///
+/// Magic and checksum are verified first so foreign-network
+/// and corrupted packets never reach bincode.
+pub fn decode_message(magic: [u8; 4], data: &[u8]) -> Result<NetworkMessage, WireError> {
+    if data.len() < HEADER_SIZE {
+        return Err(WireError::Truncated);
+    }
+
+    if data[0..4] != magic {
+        return Err(WireError::BadMagic);
+    }
+
+    let len = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let payload = &data[HEADER_SIZE..];
+    if payload.len() != len {
+        return Err(WireError::BadLength);
+    }
+
+    if data[8..12] != checksum(payload) {
+        return Err(WireError::BadChecksum);
+    }
+
+    bincode::deserialize(payload).map_err(|_| WireError::Malformed)
 }
@@ -1,88 +1,181 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive};
+use serde::Serialize;
 use time::OffsetDateTime;
 
 use crate::{
     block::{Block, BlockHeader},
-    transaction::{Transaction, TxOutput},
+    transaction::{Transaction, TxInput, TxOutput},
     reward::block_reward,
     consensus::difficulty::calculate_next_target,
+    consensus::rules::rules_for_height,
+    consensus::params::CONSENSUS_V5_HEIGHT,
     merkle::merkle_root,
-    pow::mine,
-    validation::validate_transaction,
-    utxo::UTXOSet,
-    policy::{MAX_BLOCK_TXS, MAX_BLOCK_TX_BYTES},
+    pow::{mine_parallel, MiningThreads},
+    validation::{validate_transaction_with, ValidationContext},
+    chain::median_time_past,
+    utxo::{OutPoint, UTXOSet, UTXO},
+    core::utxo_view::{UtxoSource, UtxoView},
+    policy::{is_standard_tx, MAX_BLOCK_TXS, MAX_BLOCK_TX_BYTES},
+    node::bus::{BusEvent, EventBus},
 };
 
 const MIN_FEE_PER_BYTE: i64 = 1; // POLICY ONLY
 
-pub fn mine_block(
+/// Coinbase recipient and its percentage of the block reward
+/// plus fees (POLICY ONLY)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutShare {
+    pub pubkey_hash: Vec<u8>,
+    pub percent: u8,
+}
+
+impl PayoutShare {
+    /// The whole coinbase to one address
+    pub fn whole(pubkey_hash: Vec<u8>) -> Self {
+        Self { pubkey_hash, percent: 100 }
+    }
+}
+
+/// Shares must add up to exactly 100%
+pub fn check_payout_shares(shares: &[PayoutShare]) -> Result<(), &'static str> {
+    let total: u32 = shares.iter().map(|s| s.percent as u32).sum();
+    if shares.is_empty() || total != 100 {
+        return Err("payout shares must add up to 100%");
+    }
+    if shares.iter().any(|s| s.percent == 0) {
+        return Err("payout share of 0%");
+    }
+    Ok(())
+}
+
+/// Split `total` by `shares`; rounding leftovers go to the
+/// first share, so the outputs add up to `total` exactly
+fn coinbase_outputs(total: u64, shares: &[PayoutShare]) -> Vec<TxOutput> {
+    let mut outputs: Vec<TxOutput> = shares
+        .iter()
+        .map(|s| TxOutput {
+            value: (total as u128 * s.percent as u128 / 100) as u64,
+            pubkey_hash: s.pubkey_hash.clone(),
+            script_pubkey: vec![],
+        })
+        .collect();
+
+    let paid: u64 = outputs.iter().map(|o| o.value).sum();
+    outputs[0].value += total - paid;
+    outputs
+}
+
+/// Blocks connected to the active chain so far, counted by
+/// `follow_chain`
+static BLOCKS_CONNECTED: AtomicU64 = AtomicU64::new(0);
+
+/// Count connected blocks (ours, peers', external miners'), so
+/// `mine` can drop work on a tip that is no longer the tip
+pub fn follow_chain(bus: &EventBus) {
+    bus.subscribe(|event| {
+        if let BusEvent::BlockConnected(_) = event {
+            BLOCKS_CONNECTED.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+}
+
+/// Current count; read before building the template passed to
+/// `mine`
+pub fn blocks_connected() -> u64 {
+    BLOCKS_CONNECTED.load(Ordering::SeqCst)
+}
+
+/// Solve `template` (see `block_template`) on the `threads`
+/// workers, without holding the chain lock
+///
+/// None if a block connected after `connected_before` (from
+/// `blocks_connected`): the template builds on a stale tip.
+pub fn mine(mut template: Block, threads: &MiningThreads, connected_before: u64) -> Option<Block> {
+    let stale = || blocks_connected() != connected_before;
+    if stale() {
+        return None;
+    }
+
+    mine_parallel(&mut template, threads, &record_hashes, &stale).then_some(template)
+}
+
+/// Unsolved next block (nonce 0, no hash), for this node's
+/// miner or an external one; the coinbase pays the block
+/// reward plus the fees of the included transactions, split
+/// by `payout` (see `check_payout_shares`)
+///
+/// `mempool_packages` come from `Mempool::mining_packages`:
+/// each is included whole or not at all, and must pay
+/// MIN_FEE_PER_BYTE across the package, so a child can pay
+/// for its parent.
+pub fn block_template(
     prev_block: &Block,
     utxos: &UTXOSet,
-    mempool_txs: Vec<Transaction>,
-    miner_pubkey_hash: Vec<u8>,
+    mempool_packages: Vec<Vec<Transaction>>,
+    payout: &[PayoutShare],
     chain: &[Block],
 ) -> Block {
     let height = prev_block.header.height + 1;
+    let mtp = median_time_past(chain.iter().map(|b| b.header.timestamp));
+    let ctx = ValidationContext::new(height, mtp);
 
+    // v5 coinbases commit to their height (see `check_coinbase_height`)
     let coinbase = Transaction {
-        inputs: vec![],
-        outputs: vec![TxOutput {
-            value: block_reward(height),
-            pubkey_hash: miner_pubkey_hash,
-        }],
+        inputs: if height >= CONSENSUS_V5_HEIGHT { vec![TxInput::coinbase(height)] } else { vec![] },
+        outputs: coinbase_outputs(block_reward(height), payout),
     };
 
     let mut selected = vec![coinbase];
     let mut total_bytes = selected[0].serialized_size();
 
-    for tx in mempool_txs {
-        if selected.len() >= MAX_BLOCK_TXS {
-            break;
+    // Coins as of the transactions selected so far
+    let mut view = UtxoView::new(utxos);
+    let mut fees = 0u64;
+
+    for package in mempool_packages {
+        if selected.len() + package.len() > MAX_BLOCK_TXS {
+            continue;
         }
 
-        let size = tx.serialized_size();
+        let size: usize = package.iter().map(|tx| tx.serialized_size()).sum();
         if total_bytes + size > MAX_BLOCK_TX_BYTES {
-            break;
+            continue;
         }
 
-        if !validate_transaction(&tx, utxos, height) {
+        if package.iter().any(|tx| is_standard_tx(tx).is_err()) {
             continue;
         }
 
-        let mut input = 0i64;
-        let mut output = 0i64;
-
-        for i in &tx.inputs {
-            let key = format!(
-                "{}:{}",
-                hex::encode(&i.txid),
-                i.index
-            );
-            if let Some(u) = utxos.get(&key) {
-                input += u.value as i64;
-            }
-        }
+        let fee = match package_fee(&package, UtxoView::new(&view), &ctx) {
+            Some(f) => f,
+            None => continue,
+        };
 
-        for o in &tx.outputs {
-            output += o.value as i64;
-        }
-
-        let fee = input - output;
-        if fee <= 0 {
+        if fee <= 0 || fee / size as i64 < MIN_FEE_PER_BYTE {
             continue;
         }
 
-        let fee_rate = fee / size as i64;
-        if fee_rate < MIN_FEE_PER_BYTE {
-            continue;
+        for tx in &package {
+            connect(&mut view, tx, height);
         }
 
         total_bytes += size;
-        selected.push(tx);
+        fees += fee as u64;
+        selected.extend(package);
     }
 
+    // The miner collects the fees of everything it includes
+    selected[0].outputs = coinbase_outputs(block_reward(height) + fees, payout);
+
     let target = calculate_next_target(chain);
 
-    let mut block = Block {
+    Block {
         header: BlockHeader {
             height,
             timestamp: OffsetDateTime::now_utc().unix_timestamp(),
@@ -90,11 +183,207 @@ pub fn mine_block(
             nonce: 0,
             target,
             merkle_root: merkle_root(&selected),
+            utxo_commitment: rules_for_height(height).utxo_commitment(utxos),
         },
         transactions: selected,
         hash: vec![],
-    };
+    }
+}
+
+/// Validate `package` in order on top of `view`, returning
+/// its total fee
+fn package_fee<B: UtxoSource>(
+    package: &[Transaction],
+    mut view: UtxoView<B>,
+    ctx: &ValidationContext,
+) -> Option<i64> {
+    let mut fee = 0i64;
+
+    for tx in package {
+        let lookup = |outpoint: &OutPoint| view.get(outpoint);
+        validate_transaction_with(tx, &lookup, ctx).ok()?;
+
+        let input: i64 = tx
+            .inputs
+            .iter()
+            .filter_map(|i| i.outpoint())
+            .filter_map(|op| view.get(&op))
+            .map(|u| u.value as i64)
+            .sum();
+        let output: i64 = tx.outputs.iter().map(|o| o.value as i64).sum();
+
+        fee += input - output;
+        connect(&mut view, tx, ctx.height);
+    }
+
+    Some(fee)
+}
+
+/// Spend the inputs of `tx` and add its outputs to `view`
+fn connect<B: UtxoSource>(view: &mut UtxoView<B>, tx: &Transaction, height: u64) {
+    for outpoint in tx.inputs.iter().filter_map(|i| i.outpoint()) {
+        view.spend(&outpoint);
+    }
+
+    for (outpoint, o) in tx.outpoints() {
+        view.add(
+            outpoint,
+            UTXO {
+                value: o.value,
+                pubkey_hash: o.pubkey_hash.clone(),
+                height,
+                is_coinbase: false,
+                script_pubkey: o.script_pubkey.clone(),
+            },
+        );
+    }
+}
+
+/* ───────── Hashrate ───────── */
+
+/// Hashes tried by this process's miner
+static HASHES: AtomicU64 = AtomicU64::new(0);
+
+/// (time, HASHES) samples, oldest first, covering the longest
+/// averaging window
+static SAMPLES: Mutex<VecDeque<(Instant, u64)>> = Mutex::new(VecDeque::new());
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const AVERAGE_WINDOWS: [Duration; 3] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(15 * 60),
+];
+
+/// Miner hashrate (UI ONLY)
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct HashrateStats {
+    /// Hashes tried since the node started
+    pub total_hashes: u64,
+    /// Hashes per second over the last 1, 5 and 15 minutes
+    pub hashrate_1m: f64,
+    pub hashrate_5m: f64,
+    pub hashrate_15m: f64,
+}
+
+fn record_hashes(n: u64) {
+    let total = HASHES.fetch_add(n, Ordering::Relaxed) + n;
+
+    // Workers never wait on each other for bookkeeping
+    if let Ok(mut samples) = SAMPLES.try_lock() {
+        push_sample(&mut samples, Instant::now(), total);
+    }
+}
+
+fn push_sample(samples: &mut VecDeque<(Instant, u64)>, now: Instant, total: u64) {
+    if samples.back().map_or(true, |(t, _)| now.duration_since(*t) >= SAMPLE_INTERVAL) {
+        samples.push_back((now, total));
+    }
+
+    let longest = AVERAGE_WINDOWS[AVERAGE_WINDOWS.len() - 1];
+    while samples.len() > 1 && now.duration_since(samples[1].0) >= longest {
+        samples.pop_front();
+    }
+}
+
+/// Hashes per second between the oldest sample inside `window`
+/// and (`now`, `total`)
+fn average(samples: &VecDeque<(Instant, u64)>, now: Instant, total: u64, window: Duration) -> f64 {
+    let start = samples
+        .iter()
+        .find(|(t, _)| now.duration_since(*t) <= window)
+        .or(samples.back());
+
+    match start {
+        Some((t, hashes)) if now > *t => {
+            total.saturating_sub(*hashes) as f64 / now.duration_since(*t).as_secs_f64()
+        }
+        _ => 0.0,
+    }
+}
 
-    mine(&mut block);
-    block
+/// Rolling hashrate averages of this node's miner
+pub fn stats() -> HashrateStats {
+    let now = Instant::now();
+    let total = HASHES.load(Ordering::Relaxed);
+
+    let mut samples = SAMPLES.lock().unwrap();
+    push_sample(&mut samples, now, total);
+
+    let [m1, m5, m15] = AVERAGE_WINDOWS.map(|w| average(&samples, now, total, w));
+    HashrateStats { total_hashes: total, hashrate_1m: m1, hashrate_5m: m5, hashrate_15m: m15 }
+}
+
+/// Average time to find a block at `target` hashing at
+/// `hashrate` (hashes per second); None without a hashrate
+pub fn expected_time_to_block(target: &[u8; 32], hashrate: f64) -> Option<Duration> {
+    if hashrate <= 0.0 {
+        return None;
+    }
+
+    // 2^256 / (target + 1), as for chain work
+    let target = BigUint::from_bytes_be(target);
+    let expected_hashes = ((BigUint::one() << 256u32) / (target + BigUint::one())).to_f64()?;
+
+    Duration::try_from_secs_f64(expected_hashes / hashrate).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_coinbase_by_percentage() {
+        let shares = [
+            PayoutShare { pubkey_hash: vec![1; 32], percent: 90 },
+            PayoutShare { pubkey_hash: vec![2; 32], percent: 7 },
+            PayoutShare { pubkey_hash: vec![3; 32], percent: 3 },
+        ];
+        assert!(check_payout_shares(&shares).is_ok());
+        assert!(check_payout_shares(&shares[..2]).is_err());
+
+        let outputs = coinbase_outputs(1_001, &shares);
+        let values: Vec<u64> = outputs.iter().map(|o| o.value).collect();
+        assert_eq!(values, [901, 70, 30]);
+    }
+
+    #[test]
+    fn consecutive_v5_coinbases_have_distinct_txids() {
+        use crate::consensus::rules::v5::{check_coinbase_height, check_unique_txids};
+
+        let payout = [PayoutShare { pubkey_hash: vec![1; 32], percent: 100 }];
+        let mut prev = crate::consensus::genesis::genesis_block();
+        prev.header.height = CONSENSUS_V5_HEIGHT - 1;
+
+        let mut utxos = UTXOSet::new();
+        let first = block_template(&prev, &utxos, vec![], &payout, &[prev.clone()]);
+        assert!(check_coinbase_height(&first));
+        assert!(check_unique_txids(&first, &utxos));
+
+        // Same payout one block later, first coinbase still unspent
+        let mut view = UtxoView::new(&mut utxos);
+        connect(&mut view, &first.transactions[0], first.header.height);
+        view.flush();
+
+        let second = block_template(&first, &utxos, vec![], &payout, &[prev, first.clone()]);
+        assert!(check_coinbase_height(&second));
+        assert!(check_unique_txids(&second, &utxos));
+    }
+
+    #[test]
+    fn averages_hashrate_and_estimates_block_time() {
+        let start = Instant::now();
+        let mut samples = VecDeque::new();
+        push_sample(&mut samples, start, 0);
+        push_sample(&mut samples, start + Duration::from_secs(10), 5_000);
+
+        let now = start + Duration::from_secs(20);
+        assert_eq!(average(&samples, now, 10_000, AVERAGE_WINDOWS[0]), 500.0);
+
+        // Target of 2^248 - 1: one block per 256 hashes
+        let mut target = [0xff; 32];
+        target[0] = 0;
+        assert_eq!(expected_time_to_block(&target, 128.0), Some(Duration::from_secs(2)));
+        assert_eq!(expected_time_to_block(&target, 0.0), None);
+    }
 }
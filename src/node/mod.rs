@@ -13,10 +13,29 @@
 //! ✅ Outbound-only networking
 //! ✅ RAM-first operation hints
 
+pub mod bus;
+pub mod message;
+pub mod p2p;
+pub mod miner;
+pub mod mempool;
+pub mod fee_estimator;
+pub mod dedup;
+pub mod events;
+pub mod address_index;
+pub mod seeds;
+pub mod addrman;
+pub mod connman;
+pub mod download;
+pub mod eviction;
+pub mod whitelist;
+pub mod thermal;
+pub mod token_bucket;
+
+// transport is a submodule directory
+pub mod transport;
+
 use std::sync::Arc;
 
-// Conditional import - config module might not exist
-#[cfg(feature = "config")]
 use crate::config::NodeConfig;
 
 /// Runtime execution environment
@@ -79,6 +98,8 @@ pub struct RuntimePolicy {
     pub thermal_threshold: f32,
     /// Enable RAM-first operations
     pub ram_first: bool,
+    /// Daily upload budget in bytes (None = unlimited)
+    pub max_upload_per_day: Option<u64>,
 }
 
 impl Default for RuntimePolicy {
@@ -91,6 +112,7 @@ impl Default for RuntimePolicy {
             battery_safe_level: 20, // Default: 20% battery safety
             thermal_threshold: 40.0, // Default: 40°C threshold
             ram_first: false,
+            max_upload_per_day: None,
         }
     }
 }
@@ -106,6 +128,7 @@ impl RuntimePolicy {
             battery_safe_level: 10, // Less restrictive on desktop
             thermal_threshold: 70.0, // Higher threshold for desktop
             ram_first: false,
+            max_upload_per_day: None,
         }
     }
     
@@ -119,43 +142,34 @@ impl RuntimePolicy {
             battery_safe_level: 20, // CRITERIA: 20% battery safety
             thermal_threshold: 40.0, // CRITERIA: 40°C thermal safety
             ram_first: true, // CRITERIA: RAM-first operations
+            max_upload_per_day: Some(250 * 1024 * 1024), // Metered data
         }
     }
     
-    /// Derive runtime policy from node configuration (if config exists)
-    #[cfg(feature = "config")]
+    /// Derive runtime policy from node configuration
     pub fn from_config(cfg: &NodeConfig) -> Self {
-        if cfg.is_mobile() {
+        let mut policy = if cfg.is_mobile() {
             let mut policy = Self::mobile();
-            
-            // Override with config values if provided
-            if cfg.outbound_only() {
-                policy.allow_inbound = false;
-            }
-            
-            policy.max_cpu_percent = cfg.mining.max_cpu_percent;
-            policy.battery_safe_level = cfg.mobile.battery_warning_percent as u8;
+            policy.battery_safe_level = cfg.mobile.battery_warning_percent;
             policy.thermal_threshold = cfg.mobile.thermal_limit_celsius;
             policy.ram_first = cfg.mobile.ram_first;
-            
             policy
         } else {
-            let mut policy = Self::desktop();
-            policy.max_cpu_percent = cfg.mining.max_cpu_percent;
-            policy
+            Self::desktop()
+        };
+
+        if cfg.outbound_only() {
+            policy.allow_inbound = false;
         }
-    }
-    
-    /// Fallback when config is not available
-    #[cfg(not(feature = "config"))]
-    pub fn from_config(_cfg: &()) -> Self {
-        Self::default()
+        policy.allow_mining = cfg.mining.enabled;
+        policy.max_cpu_percent = cfg.mining.max_cpu_percent;
+        policy
     }
     
     /// Check if inbound networking should be allowed
     pub fn allow_inbound_connections(&self) -> bool {
-        // CRITERIA: Mobile = outbound-only
-        !self.mode.is_mobile() || self.allow_inbound
+        // CRITERIA: Mobile = outbound-only (set by mobile())
+        self.allow_inbound
     }
     
     /// Check if mining should be allowed
@@ -168,6 +182,16 @@ impl RuntimePolicy {
         self.max_cpu_percent
     }
     
+    /// Mining worker threads: the CPU limit's share of the
+    /// machine's cores (at least one)
+    pub fn mining_threads(&self) -> usize {
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+
+        (cores * self.cpu_limit().min(100) as usize / 100).max(1)
+    }
+    
     /// Get battery safety level
     pub fn battery_safety_level(&self) -> u8 {
         self.battery_safe_level
@@ -183,6 +207,20 @@ impl RuntimePolicy {
         self.ram_first && self.mode.is_mobile()
     }
     
+    /// Maximum cached UTXO entries (RAM-first caps memory)
+    pub fn utxo_cache_limit(&self) -> usize {
+        if self.ram_first_enabled() {
+            50_000
+        } else {
+            1_000_000
+        }
+    }
+    
+    /// Daily upload budget for serving historical blocks
+    pub fn upload_budget(&self) -> Option<u64> {
+        self.max_upload_per_day
+    }
+    
     /// Validate policy settings
     pub fn validate(&self) -> Result<(), String> {
         if self.max_cpu_percent == 0 || self.max_cpu_percent > 100 {
@@ -298,5 +336,25 @@ mod tests {
         let policy_ref = context.policy();
         assert!(policy_ref.validate().is_ok());
     }
+
+    #[test]
+    fn test_policy_from_config() {
+        let mut cfg = NodeConfig::default();
+        cfg.mode = crate::config::NodeMode::Mobile;
+        cfg.mobile.thermal_limit_celsius = 38.5;
+        cfg.mining.max_cpu_percent = 50;
+
+        let policy = RuntimePolicy::from_config(&cfg);
+        assert!(!policy.allow_inbound_connections());
+        assert_eq!(policy.thermal_safety_threshold(), 38.5);
+        assert_eq!(policy.cpu_limit(), 50);
+
+        cfg.mode = crate::config::NodeMode::Desktop;
+        cfg.peers.outbound_only = true;
+        cfg.mining.enabled = false;
+        let policy = RuntimePolicy::from_config(&cfg);
+        assert!(!policy.allow_inbound_connections());
+        assert!(!policy.allow_mining_operations());
+    }
   }
 
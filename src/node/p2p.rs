@@ -1,18 +1,210 @@
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use num_bigint::BigUint;
 
 // Required imports from the project structure
-use crate::core::block::Block;
-use crate::core::chain::Blockchain;
-use crate::validation::validate_transaction;
-use crate::node::message::{NetworkMessage, PROTOCOL_VERSION};
+use crate::core::block::{Block, BlockHeader};
+use crate::core::transaction::Transaction;
+use crate::core::chain::{process_block, Blockchain};
+use crate::core::checkqueue::CheckQueue;
+use crate::core::headers::validate_headers_after;
+use crate::consensus::params::MAINNET;
+use crate::node::message::{
+    decode_message,
+    encode_message,
+    InvItem,
+    InvKind,
+    NetworkMessage,
+    MAX_HEADERS,
+    NODE_NETWORK,
+    PROTOCOL_VERSION,
+    USER_AGENT,
+};
+use crate::node::mempool::Mempool;
+use crate::node::bus::{block_events, BusEvent, EventBus};
+use crate::node::dedup::MessageDeduplicator;
+use crate::node::download::BlockDownload;
+use crate::node::connman::DEFAULT_OUTBOUND_PEERS;
+use crate::node::eviction::{select_eviction, EvictionCandidate};
+use crate::node::token_bucket::TokenBucket;
+use crate::node::whitelist::Whitelist;
 use crate::node::transport::Transport;
 
+/// Misbehavior score at which a peer is ignored
+const BAN_THRESHOLD: u32 = 100;
+
+/// Largest Inv we send in one message
+const MAX_INV_ITEMS: usize = 1_000;
+
+/// How often peers are pinged
+const PING_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Upload budget accounting period
+const UPLOAD_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Blocks this deep below the tip count as historical
+const HISTORICAL_BLOCK_DEPTH: u64 = 144;
+
+/// Inbound slots (total peers minus outbound)
+const MAX_INBOUND_PEERS: usize = 125 - DEFAULT_OUTBOUND_PEERS;
+
+/// Services this node offers
+const LOCAL_SERVICES: u64 = NODE_NETWORK;
+
+/// What a peer told us during the handshake, plus traffic counters
+#[derive(Debug, Clone, Default)]
+pub struct PeerInfo {
+    pub version: u32,
+    pub services: u64,
+    pub height: u64,
+    pub agent: String,
+    pub chain_work: Vec<u8>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Round-trip time of the last answered Ping
+    pub ping_rtt: Option<Duration>,
+    /// Unix time of the last block accepted from this peer
+    pub last_block: Option<i64>,
+    hello_sent: bool,
+    hello_received: bool,
+    verack_received: bool,
+    /// Peer connected to us (we did not send the first Hello)
+    pub inbound: bool,
+    /// Outstanding Ping nonce and when it was sent
+    ping_sent: Option<(u64, Instant)>,
+    connected_at: Option<Instant>,
+}
+
+/// Peer diagnostics snapshot (operator-facing)
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerStats {
+    pub addr: String,
+    /// Link in use ("tcp", "quic", ...), if known
+    pub transport: Option<&'static str>,
+    pub agent: String,
+    pub version: u32,
+    pub services: u64,
+    pub height: u64,
+    pub handshake_complete: bool,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub ping_ms: Option<u64>,
+    pub last_block: Option<i64>,
+    /// Misbehavior points; banned at BAN_THRESHOLD
+    pub ban_score: u32,
+}
+
+impl PeerInfo {
+    /// Both sides exchanged Hello and Verack
+    pub fn handshake_complete(&self) -> bool {
+        self.hello_received && self.verack_received
+    }
+}
+
+/// Daily upload allowance; once spent, only recent blocks are served
+#[derive(Debug)]
+struct UploadBudget {
+    limit: Option<u64>,
+    used: u64,
+    window_start: Instant,
+}
+
+impl UploadBudget {
+    fn new(limit: Option<u64>, now: Instant) -> Self {
+        Self { limit, used: 0, window_start: now }
+    }
+
+    fn roll(&mut self, now: Instant) {
+        if now.duration_since(self.window_start) >= UPLOAD_WINDOW {
+            self.used = 0;
+            self.window_start = now;
+        }
+    }
+
+    fn record(&mut self, bytes: u64, now: Instant) {
+        self.roll(now);
+        self.used = self.used.saturating_add(bytes);
+    }
+
+    fn exhausted(&mut self, now: Instant) -> bool {
+        self.roll(now);
+        self.limit.map_or(false, |limit| self.used >= limit)
+    }
+}
+
+/// Message groups sharing a rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MessageClass {
+    Handshake,
+    Sync,
+    Announce,
+    Request,
+    Block,
+    Transaction,
+    Other,
+}
+
+impl MessageClass {
+    /// Class and token cost of a message
+    fn of(msg: &NetworkMessage) -> (Self, f64) {
+        match msg {
+            NetworkMessage::Hello { .. } | NetworkMessage::Verack => (Self::Handshake, 1.0),
+            NetworkMessage::SyncRequest { .. }
+            | NetworkMessage::GetHeaders { .. }
+            | NetworkMessage::MempoolRequest => (Self::Sync, 1.0),
+            NetworkMessage::Headers(_) => (Self::Block, 1.0),
+            NetworkMessage::Inv(items) => (Self::Announce, items.len().max(1) as f64),
+            NetworkMessage::GetData(items) => (Self::Request, items.len().max(1) as f64),
+            NetworkMessage::Block(_) => (Self::Block, 1.0),
+            NetworkMessage::Transaction(_) => (Self::Transaction, 1.0),
+            _ => (Self::Other, 1.0),
+        }
+    }
+
+    /// (burst, refill per second)
+    fn limit(self) -> (f64, f64) {
+        match self {
+            Self::Handshake => (4.0, 0.1),
+            Self::Sync => (3.0, 10.0 / 60.0), // 10 per minute
+            Self::Announce => (1_000.0, 100.0), // 100 items per second
+            Self::Request => (1_000.0, 100.0),
+            Self::Block => (32.0, 4.0),
+            Self::Transaction => (200.0, 50.0),
+            Self::Other => (10.0, 1.0),
+        }
+    }
+}
+
 /// The P2P Network Layer
 /// Handles peer communication and message broadcasting
 pub struct P2PNetwork {
     transport: Arc<dyn Transport>,
     chain: Arc<Mutex<Blockchain>>,
+    misbehavior: Mutex<HashMap<SocketAddr, u32>>,
+    peers: Mutex<HashMap<SocketAddr, PeerInfo>>,
+    check_queue: CheckQueue,
+    mempool: Option<Arc<Mutex<Mempool>>>,
+    dedup: Mutex<MessageDeduplicator>,
+    rate_limits: Mutex<HashMap<(SocketAddr, MessageClass), TokenBucket>>,
+    /// Where connected blocks, accepted transactions and new
+    /// peers are published
+    bus: Arc<EventBus>,
+    download: Mutex<BlockDownload>,
+    /// Peer headers sync is currently following
+    sync_peer: Mutex<Option<SocketAddr>>,
+    whitelist: Mutex<Whitelist>,
+    upload: Mutex<UploadBudget>,
+    /// Initial block download; cleared by the node loop
+    initial_sync: AtomicBool,
 }
 
 impl P2PNetwork {
@@ -27,83 +219,813 @@ impl P2PNetwork {
         println!("> [INFO] Protocol Version: {}", PROTOCOL_VERSION);
         println!("> [STATUS] Node is active and listening...");
 
-        Self { transport, chain }
+        Self {
+            transport,
+            chain,
+            misbehavior: Mutex::new(HashMap::new()),
+            peers: Mutex::new(HashMap::new()),
+            check_queue: CheckQueue::with_available_cores(),
+            mempool: None,
+            dedup: Mutex::new(MessageDeduplicator::new(Duration::from_secs(60))),
+            rate_limits: Mutex::new(HashMap::new()),
+            bus: Arc::new(EventBus::new()),
+            download: Mutex::new(BlockDownload::new()),
+            sync_peer: Mutex::new(None),
+            whitelist: Mutex::new(Whitelist::default()),
+            upload: Mutex::new(UploadBudget::new(None, Instant::now())),
+            initial_sync: AtomicBool::new(true),
+        }
+    }
+
+    /// Admit, serve and relay transactions through `mempool`
+    pub fn with_mempool(mut self, mempool: Arc<Mutex<Mempool>>) -> Self {
+        self.mempool = Some(mempool);
+        self
+    }
+
+    /// Publish to `bus` (and relay what other subsystems
+    /// publish there; see `attach`)
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    /// Cap daily upload (metered connections); new tips are
+    /// still relayed once the budget is spent
+    pub fn with_upload_budget(mut self, bytes_per_day: Option<u64>) -> Self {
+        self.upload = Mutex::new(UploadBudget::new(bytes_per_day, Instant::now()));
+        self
+    }
+
+    /// False if `height` is historical and the upload budget is spent
+    fn may_serve_block(&self, addr: &SocketAddr, height: u64) -> bool {
+        let tip = self.chain.lock().unwrap().height();
+        if height + HISTORICAL_BLOCK_DEPTH >= tip || self.is_whitelisted(addr) {
+            return true;
+        }
+        !self.upload.lock().unwrap().exhausted(Instant::now())
+    }
+
+    /// Trust configured subnets
+    pub fn with_whitelist(mut self, whitelist: Whitelist) -> Self {
+        self.whitelist = Mutex::new(whitelist);
+        self
+    }
+
+    /// Trust a single connection (whitebind listener)
+    pub fn whitelist_peer(&self, addr: SocketAddr) {
+        self.whitelist.lock().unwrap().add_peer(addr);
+    }
+
+    fn is_whitelisted(&self, addr: &SocketAddr) -> bool {
+        self.whitelist.lock().unwrap().contains(addr)
+    }
+
+    /// Add misbehavior points to a peer
+    fn misbehaving(&self, addr: SocketAddr, score: u32, reason: &str) {
+        if score == 0 || self.is_whitelisted(&addr) {
+            return;
+        }
+
+        let mut m = self.misbehavior.lock().unwrap();
+        let total = m.entry(addr).or_insert(0);
+        *total = total.saturating_add(score);
+
+        if *total >= BAN_THRESHOLD {
+            println!("> [BAN] Peer {} banned: {}", addr, reason);
+        }
+    }
+
+    /// True if the peer crossed the ban threshold
+    pub fn is_banned(&self, addr: &SocketAddr) -> bool {
+        self.misbehavior
+            .lock()
+            .unwrap()
+            .get(addr)
+            .map(|s| *s >= BAN_THRESHOLD)
+            .unwrap_or(false)
+    }
+
+    /// Charge a message against the peer's bucket for its class
+    fn within_rate_limit(&self, addr: SocketAddr, msg: &NetworkMessage) -> bool {
+        let (class, cost) = MessageClass::of(msg);
+        let (capacity, per_sec) = class.limit();
+        let now = Instant::now();
+
+        self.rate_limits
+            .lock()
+            .unwrap()
+            .entry((addr, class))
+            .or_insert_with(|| TokenBucket::new(capacity, per_sec, now))
+            .take(cost, now)
+    }
+
+    /// Update the stats of a connected peer
+    fn with_peer(&self, addr: SocketAddr, f: impl FnOnce(&mut PeerInfo)) {
+        f(self.peers.lock().unwrap().entry(addr).or_default());
+    }
+
+    /// Diagnostics for every connected peer
+    pub fn peer_stats(&self) -> Vec<PeerStats> {
+        let peers = self.peers.lock().unwrap();
+        let misbehavior = self.misbehavior.lock().unwrap();
+
+        self.transport
+            .peers()
+            .into_iter()
+            .map(|addr| {
+                let info = peers.get(&addr).cloned().unwrap_or_default();
+                PeerStats {
+                    addr: addr.to_string(),
+                    transport: self.transport.link_name(&addr),
+                    handshake_complete: info.handshake_complete(),
+                    agent: info.agent,
+                    version: info.version,
+                    services: info.services,
+                    height: info.height,
+                    bytes_sent: info.bytes_sent,
+                    bytes_received: info.bytes_received,
+                    ping_ms: info.ping_rtt.map(|d| d.as_millis() as u64),
+                    last_block: info.last_block,
+                    ban_score: misbehavior.get(&addr).copied().unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    /// Ping every connected peer and forget disconnected ones
+    pub fn ping_peers(&self) {
+        let live = self.transport.peers();
+
+        self.peers.lock().unwrap().retain(|addr, _| live.contains(addr));
+        self.rate_limits
+            .lock()
+            .unwrap()
+            .retain(|(addr, _), _| live.contains(addr));
+
+        let now = Instant::now();
+        for addr in live {
+            let nonce = OsRng.next_u64();
+            self.with_peer(addr, |p| p.ping_sent = Some((nonce, now)));
+            self.send(addr, &NetworkMessage::Ping(nonce));
+        }
+    }
+
+    /// Ping peers every PING_INTERVAL in the background
+    pub fn spawn_pinger(self: Arc<Self>) {
+        thread::spawn(move || loop {
+            thread::sleep(PING_INTERVAL);
+            self.ping_peers();
+        });
+    }
+
+    /// Free an inbound slot for `new_peer` by evicting the least
+    /// valuable inbound peer; false if all are protected
+    fn make_room_for_inbound(&self, new_peer: SocketAddr) -> bool {
+        let candidates: Vec<EvictionCandidate> = self
+            .peers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(addr, p)| p.inbound && **addr != new_peer && !self.is_whitelisted(addr))
+            .map(|(addr, p)| EvictionCandidate {
+                addr: *addr,
+                uptime: p.connected_at.map(|t| t.elapsed()).unwrap_or_default(),
+                ping_rtt: p.ping_rtt,
+                last_block: p.last_block,
+            })
+            .collect();
+
+        if candidates.len() < MAX_INBOUND_PEERS {
+            return true;
+        }
+
+        match select_eviction(&candidates) {
+            Some(victim) => {
+                println!("> [NET] Evicting inbound peer {}", victim);
+                self.transport.disconnect(&victim);
+                self.peers.lock().unwrap().remove(&victim);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// React to the transport's connection events (handshake
+    /// with peers we dialled, clean up after departed ones) and
+    /// to the bus: decode received messages, announce connected
+    /// blocks and accepted transactions
+    pub fn attach(self: &Arc<Self>) {
+        let p2p = Arc::clone(self);
+        self.transport.on_connect(Arc::new(move |addr, inbound| p2p.on_peer_connected(addr, inbound)));
+
+        let p2p = Arc::clone(self);
+        self.transport.on_disconnect(Arc::new(move |addr| p2p.on_peer_disconnected(addr)));
+
+        let p2p = Arc::clone(self);
+        self.bus.subscribe(move |event| match event {
+            BusEvent::MessageReceived { from, data } => p2p.on_receive(*from, data.clone()),
+            BusEvent::BlockConnected(block) => p2p.broadcast_block(block),
+            BusEvent::TxAccepted(tx) => p2p.broadcast_transaction(tx),
+            _ => {}
+        });
+    }
+
+    fn on_peer_connected(&self, addr: SocketAddr, inbound: bool) {
+        println!("> [NET] {} peer {} connected", if inbound { "Inbound" } else { "Outbound" }, addr);
+
+        self.with_peer(addr, |p| {
+            p.inbound = inbound;
+            p.connected_at = Some(Instant::now());
+        });
+
+        // The dialling side speaks first
+        if !inbound {
+            self.start_handshake(addr);
+        }
+
+        self.bus.publish(BusEvent::PeerConnected { addr, inbound });
+    }
+
+    fn on_peer_disconnected(&self, addr: SocketAddr) {
+        println!("> [NET] Peer {} disconnected", addr);
+
+        self.peers.lock().unwrap().remove(&addr);
+        self.rate_limits
+            .lock()
+            .unwrap()
+            .retain(|(peer, _), _| *peer != addr);
+
+        let was_sync_peer = {
+            let mut sync_peer = self.sync_peer.lock().unwrap();
+            if *sync_peer == Some(addr) {
+                *sync_peer = None;
+                true
+            } else {
+                false
+            }
+        };
+
+        // Blocks in flight to this peer go to the others
+        self.request_blocks();
+
+        if was_sync_peer {
+            self.handle_sync_stall();
+        }
+    }
+
+    /// Send our Hello to a newly connected peer
+    pub fn start_handshake(&self, addr: SocketAddr) {
+        let (height, chain_work) = {
+            let c = self.chain.lock().unwrap();
+            (c.height(), c.chain_work().to_bytes_be())
+        };
+
+        self.with_peer(addr, |p| {
+            p.hello_sent = true;
+            p.connected_at.get_or_insert_with(Instant::now);
+        });
+
+        self.send(
+            addr,
+            &NetworkMessage::Hello {
+                version: PROTOCOL_VERSION,
+                services: LOCAL_SERVICES,
+                height,
+                agent: USER_AGENT.to_string(),
+                chain_work,
+            },
+        );
+    }
+
+    fn handshake_complete(&self, addr: &SocketAddr) -> bool {
+        self.peers
+            .lock()
+            .unwrap()
+            .get(addr)
+            .map(|p| p.handshake_complete())
+            .unwrap_or(false)
+    }
+
+    /// Handshake info for a connected peer
+    pub fn peer_info(&self, addr: &SocketAddr) -> Option<PeerInfo> {
+        self.peers.lock().unwrap().get(addr).cloned()
+    }
+
+    /// Once the handshake completes, sync from peers with more work
+    fn on_handshake_progress(&self, addr: SocketAddr) {
+        let peer = match self.peer_info(&addr) {
+            Some(p) if p.handshake_complete() => p,
+            _ => return,
+        };
+
+        let (locator, local_work) = {
+            let c = self.chain.lock().unwrap();
+            (c.block_locator(), c.chain_work())
+        };
+
+        if BigUint::from_bytes_be(&peer.chain_work) > local_work {
+            println!("> [SYNC] Peer {} has more work. Requesting headers...", addr);
+            self.sync_peer.lock().unwrap().get_or_insert(addr);
+            self.send(addr, &NetworkMessage::GetHeaders { locator, stop_hash: vec![] });
+        }
+
+        if self.mempool.is_some() {
+            self.send(addr, &NetworkMessage::MempoolRequest);
+        }
     }
 
     /// Handle incoming messages from peers
     pub fn on_receive(&self, addr: SocketAddr, data: Vec<u8>) {
+        if self.is_banned(&addr) {
+            return;
+        }
+
         // Deserialize message safely
-        let msg: NetworkMessage = match bincode::deserialize(&data) {
+        let msg = match decode_message(MAINNET.magic, &data) {
             Ok(m) => m,
-            Err(_) => {
-                println!("> [WARN] Invalid packet received from {}", addr);
+            Err(e) => {
+                println!("> [WARN] Invalid packet received from {}: {}", addr, e);
                 return;
             }
         };
 
+        let payload = matches!(msg, NetworkMessage::Block(_) | NetworkMessage::Transaction(_));
+
+        // Blocks and txs may arrive over several transports at once
+        if payload && !self.dedup.lock().unwrap().check_and_insert(&data) {
+            return;
+        }
+
+        // Broadcast-only transports (satellite, geo) never handshake
+        // and may only deliver payloads
+        if !self.transport.peers().contains(&addr) {
+            if !payload {
+                return;
+            }
+        } else {
+            self.with_peer(addr, |p| p.bytes_received += data.len() as u64);
+
+            if !self.is_whitelisted(&addr) && !self.within_rate_limit(addr, &msg) {
+                println!("> [WARN] Rate limit exceeded by {}, dropped", addr);
+                self.misbehaving(addr, 1, "message flood");
+                return;
+            }
+
+            // Nothing but the handshake until it completes
+            let handshake = matches!(msg, NetworkMessage::Hello { .. } | NetworkMessage::Verack);
+            if !handshake && !self.handshake_complete(&addr) {
+                println!("> [WARN] Message from {} before handshake, ignored", addr);
+                return;
+            }
+        }
+
         // Process message with system logging
         match msg {
-            NetworkMessage::Hello { version, height, .. } => {
-                println!("> [NET] Handshake request from {} (Height: {})", addr, height);
+            NetworkMessage::Hello { version, services, height, agent, chain_work } => {
+                println!(
+                    "> [NET] Handshake from {} ({}, height {})",
+                    addr, agent, height
+                );
 
                 if version != PROTOCOL_VERSION {
                     println!("> [DENY] Protocol mismatch with {}", addr);
+                    self.transport.disconnect(&addr);
+                    self.peers.lock().unwrap().remove(&addr);
                     return;
                 }
 
-                let local_height = self.chain.lock().unwrap().height();
-                if height > local_height {
-                    println!("> [SYNC] Peer is ahead. Requesting blocks...");
-                    self.send(addr, &NetworkMessage::SyncRequest { from_height: local_height });
+                let reply_hello = {
+                    let mut peers = self.peers.lock().unwrap();
+                    let peer = peers.entry(addr).or_default();
+                    peer.connected_at.get_or_insert_with(Instant::now);
+                    peer.version = version;
+                    peer.services = services;
+                    peer.height = height;
+                    peer.agent = agent;
+                    peer.chain_work = chain_work;
+                    peer.hello_received = true;
+                    !peer.hello_sent
+                };
+
+                if reply_hello {
+                    self.with_peer(addr, |p| p.inbound = true);
+                    if !self.make_room_for_inbound(addr) {
+                        println!("> [DENY] Inbound slots full, dropping {}", addr);
+                        self.transport.disconnect(&addr);
+                        self.peers.lock().unwrap().remove(&addr);
+                        return;
+                    }
+                    self.start_handshake(addr);
                 }
+                self.send(addr, &NetworkMessage::Verack);
+                self.on_handshake_progress(addr);
+            }
+
+            NetworkMessage::Verack => {
+                if let Some(peer) = self.peers.lock().unwrap().get_mut(&addr) {
+                    peer.verack_received = true;
+                }
+                self.on_handshake_progress(addr);
             }
 
             NetworkMessage::SyncRequest { from_height } => {
                 println!("> [QUERY] Serving blocks from height {}", from_height);
-                let c = self.chain.lock().unwrap();
-                for b in c.blocks.iter().skip(from_height as usize) {
-                    self.send(addr, &NetworkMessage::Block(b.clone()));
+                let blocks: Vec<Block> = {
+                    let c = self.chain.lock().unwrap();
+                    c.blocks.iter().skip(from_height as usize).cloned().collect()
+                };
+                for b in blocks {
+                    if !self.may_serve_block(&addr, b.header.height) {
+                        println!("> [NET] Upload budget spent, not serving old blocks to {}", addr);
+                        break;
+                    }
+                    self.send(addr, &NetworkMessage::Block(b));
+                }
+            }
+
+            NetworkMessage::GetHeaders { locator, stop_hash } => {
+                let headers = self
+                    .chain
+                    .lock()
+                    .unwrap()
+                    .headers_after(&locator, &stop_hash, MAX_HEADERS);
+                self.send(addr, &NetworkMessage::Headers(headers));
+            }
+
+            NetworkMessage::Headers(headers) => self.on_headers(addr, headers),
+
+            NetworkMessage::Inv(items) => {
+                let wanted: Vec<InvItem> =
+                    items.into_iter().filter(|i| !self.have(i)).collect();
+
+                if !wanted.is_empty() {
+                    self.send(addr, &NetworkMessage::GetData(wanted));
+                }
+            }
+
+            NetworkMessage::GetData(items) => {
+                for item in items {
+                    if let Some(msg) = self.lookup(&item) {
+                        if let NetworkMessage::Block(b) = &msg {
+                            if !self.may_serve_block(&addr, b.header.height) {
+                                println!("> [NET] Upload budget spent, not serving old blocks to {}", addr);
+                                break;
+                            }
+                        }
+                        self.send(addr, &msg);
+                    }
                 }
             }
 
             NetworkMessage::Block(block) => {
-                println!("> [BLOCK] New block received. Validating...");
-                self.chain.lock().unwrap().validate_and_add_block(block);
-                println!("> [SUCCESS] Block added to chain.");
+                // Part of a headers-first download: connect in order
+                let downloaded = self.download.lock().unwrap().block_received(block.clone(), addr);
+                if downloaded {
+                    self.connect_downloaded();
+                    self.request_blocks();
+                } else {
+                    self.connect_block(addr, block);
+                }
             }
 
             NetworkMessage::Transaction(tx) => {
                 println!("> [TX] Processing incoming transaction...");
-                let c = self.chain.lock().unwrap();
-                let _ = validate_transaction(&tx, &c.utxos, c.height());
+                self.accept_transaction(addr, tx);
             }
 
-            NetworkMessage::Ping => {
-                self.send(addr, &NetworkMessage::Pong);
+            NetworkMessage::MempoolRequest => {
+                let txids = match &self.mempool {
+                    Some(m) => m.lock().unwrap().txids(),
+                    None => return,
+                };
+
+                for chunk in txids.chunks(MAX_INV_ITEMS) {
+                    let items = chunk
+                        .iter()
+                        .map(|hash| InvItem { kind: InvKind::Transaction, hash: hash.clone() })
+                        .collect();
+                    self.send(addr, &NetworkMessage::Inv(items));
+                }
+            }
+
+            NetworkMessage::Ping(nonce) => {
+                self.send(addr, &NetworkMessage::Pong(nonce));
             }
 
+            NetworkMessage::Pong(nonce) => self.with_peer(addr, |p| {
+                if let Some((sent_nonce, sent_at)) = p.ping_sent {
+                    if sent_nonce == nonce {
+                        p.ping_rtt = Some(sent_at.elapsed());
+                        p.ping_sent = None;
+                    }
+                }
+            }),
+
             _ => {}
         }
     }
 
+    /// Fetch blocks we lack for a Headers reply, continuing
+    /// with another GetHeaders if the reply was full
+    fn on_headers(&self, addr: SocketAddr, headers: Vec<BlockHeader>) {
+        if headers.len() > MAX_HEADERS {
+            self.misbehaving(addr, 20, "oversized headers");
+            return;
+        }
+
+        let first = match headers.first() {
+            Some(h) => h,
+            None => return,
+        };
+
+        // Check PoW and difficulty from a block we know before
+        // asking for any body
+        let checked = {
+            let c = self.chain.lock().unwrap();
+            c.blocks.iter().position(|b| b.hash == first.prev_hash).map(|fork| {
+                let parents: Vec<BlockHeader> =
+                    c.blocks[..=fork].iter().map(|b| b.header.clone()).collect();
+                validate_headers_after(&parents, &headers)
+            })
+        };
+
+        match checked {
+            Some(Ok(_)) => {}
+            Some(Err(e)) => {
+                println!("> [REJECT] Headers from {}: {}", addr, e);
+                self.misbehaving(addr, 100, &e.to_string());
+                return;
+            }
+            None => {
+                self.misbehaving(addr, 20, "unconnected headers");
+                return;
+            }
+        }
+
+        let hashes: Vec<Vec<u8>> = headers.iter().map(|h| h.hash()).collect();
+
+        let wanted: Vec<Vec<u8>> = hashes
+            .iter()
+            .filter(|hash| !self.have(&InvItem { kind: InvKind::Block, hash: (*hash).clone() }))
+            .cloned()
+            .collect();
+
+        println!("> [SYNC] {} headers from {}, {} blocks missing", headers.len(), addr, wanted.len());
+
+        self.download.lock().unwrap().add(wanted);
+        self.request_blocks();
+
+        if headers.len() == MAX_HEADERS {
+            if let Some(last) = hashes.last() {
+                self.send(
+                    addr,
+                    &NetworkMessage::GetHeaders { locator: vec![last.clone()], stop_hash: vec![] },
+                );
+            }
+        }
+    }
+
+    /// Validate and connect a block from `addr`
+    fn connect_block(&self, addr: SocketAddr, block: Block) -> bool {
+        println!("> [BLOCK] New block received. Validating...");
+        match process_block(&self.chain, block.clone(), &self.check_queue) {
+            Ok(()) => {
+                println!("> [SUCCESS] Block added to chain.");
+                if self.transport.peers().contains(&addr) {
+                    let now = OffsetDateTime::now_utc().unix_timestamp();
+                    self.with_peer(addr, |p| p.last_block = Some(now));
+                }
+                let events = block_events(&mut self.chain.lock().unwrap(), &block);
+                self.bus.publish_all(events);
+                true
+            }
+            Err(e) => {
+                println!("> [REJECT] Block from {}: {}", addr, e);
+                self.misbehaving(addr, e.ban_score(), &e.to_string());
+                false
+            }
+        }
+    }
+
+    /// Connect downloaded blocks whose predecessors are in
+    fn connect_downloaded(&self) {
+        let ready = self.download.lock().unwrap().take_ready();
+
+        for (block, from) in ready {
+            if !self.connect_block(from, block) {
+                // Everything after an invalid block is useless
+                self.download.lock().unwrap().clear();
+                return;
+            }
+        }
+    }
+
+    /// Spread pending block requests over full-node peers
+    ///
+    /// Also re-requests timed-out blocks; call periodically
+    /// while syncing.
+    pub fn request_blocks(&self) {
+        let peers: Vec<SocketAddr> = self
+            .peers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, p)| p.handshake_complete() && p.services & NODE_NETWORK != 0)
+            .map(|(addr, _)| *addr)
+            .collect();
+
+        let plan = {
+            let mut download = self.download.lock().unwrap();
+            if !download.is_active() {
+                return;
+            }
+            download.schedule(&peers, Instant::now())
+        };
+
+        for (addr, hashes) in plan {
+            let items = hashes
+                .into_iter()
+                .map(|hash| InvItem { kind: InvKind::Block, hash })
+                .collect();
+            self.send(addr, &NetworkMessage::GetData(items));
+        }
+    }
+
+    /// Highest chain height claimed by a handshaken peer
+    pub fn best_peer_height(&self) -> u64 {
+        self.peers
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|p| p.handshake_complete())
+            .map(|p| p.height)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Sync made no progress: drop the sync peer and restart
+    /// headers sync with the peer claiming the most work.
+    ///
+    /// Returns the peer that was dropped, if any.
+    pub fn handle_sync_stall(&self) -> Option<SocketAddr> {
+        let stalled = self.sync_peer.lock().unwrap().take();
+
+        if let Some(addr) = stalled {
+            println!("> [SYNC] Peer {} stalled, disconnecting", addr);
+            self.transport.disconnect(&addr);
+            self.peers.lock().unwrap().remove(&addr);
+        }
+
+        let (locator, local_work) = {
+            let c = self.chain.lock().unwrap();
+            (c.block_locator(), c.chain_work())
+        };
+
+        let next = self
+            .peers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, p)| p.handshake_complete())
+            .map(|(addr, p)| (BigUint::from_bytes_be(&p.chain_work), *addr))
+            .filter(|(work, _)| *work > local_work)
+            .max()
+            .map(|(_, addr)| addr);
+
+        if let Some(addr) = next {
+            println!("> [SYNC] Switching sync to {}", addr);
+            *self.sync_peer.lock().unwrap() = Some(addr);
+            self.send(addr, &NetworkMessage::GetHeaders { locator, stop_hash: vec![] });
+        }
+
+        // In-flight blocks of the dropped peer go to the others
+        self.request_blocks();
+
+        stalled
+    }
+
+    /// Whether the node is still in initial block download
+    pub fn in_initial_sync(&self) -> bool {
+        self.initial_sync.load(Ordering::Relaxed)
+    }
+
+    pub fn set_initial_sync(&self, syncing: bool) {
+        self.initial_sync.store(syncing, Ordering::Relaxed);
+    }
+
+    /// Blocks still queued by headers-first sync
+    pub fn blocks_to_download(&self) -> usize {
+        self.download.lock().unwrap().queued()
+    }
+
     /// Helper function to send messages to a single peer
     fn send(&self, addr: SocketAddr, msg: &NetworkMessage) {
-        if let Ok(data) = bincode::serialize(msg) {
+        if let Some(data) = encode_message(MAINNET.magic, msg) {
             self.transport.send(&addr, &data);
+            self.with_peer(addr, |p| p.bytes_sent += data.len() as u64);
+            self.upload.lock().unwrap().record(data.len() as u64, Instant::now());
+        }
+    }
+
+    /// Send a message to every connected peer
+    fn broadcast(&self, msg: &NetworkMessage) {
+        if let Some(data) = encode_message(MAINNET.magic, msg) {
+            self.transport.broadcast(&data);
+
+            let targets = self.transport.peers();
+            let mut peers = self.peers.lock().unwrap();
+            for addr in &targets {
+                peers.entry(*addr).or_default().bytes_sent += data.len() as u64;
+            }
+            self.upload
+                .lock()
+                .unwrap()
+                .record((data.len() * targets.len()) as u64, Instant::now());
+        }
+    }
+
+    /// True if the announced object is already known locally
+    fn have(&self, item: &InvItem) -> bool {
+        match item.kind {
+            InvKind::Block => self
+                .chain
+                .lock()
+                .unwrap()
+                .blocks
+                .iter()
+                .any(|b| b.hash == item.hash),
+            InvKind::Transaction => self
+                .mempool
+                .as_ref()
+                .map(|m| m.lock().unwrap().get_entry(&item.hash).is_some())
+                .unwrap_or(false),
+        }
+    }
+
+    /// Body for a GetData request, if we have it
+    fn lookup(&self, item: &InvItem) -> Option<NetworkMessage> {
+        match item.kind {
+            InvKind::Block => self
+                .chain
+                .lock()
+                .unwrap()
+                .blocks
+                .iter()
+                .find(|b| b.hash == item.hash)
+                .map(|b| NetworkMessage::Block(b.clone())),
+            InvKind::Transaction => self.mempool.as_ref().and_then(|m| {
+                m.lock()
+                    .unwrap()
+                    .get_entry(&item.hash)
+                    .map(|e| NetworkMessage::Transaction(e.tx.clone()))
+            }),
         }
     }
 
-    /// ✅ FIX: Broadcast a newly mined block to all peers
+    /// Admit a transaction relayed by `addr`, scoring the peer
+    /// if it is invalid; it is announced onward when published
+    /// as accepted
+    fn accept_transaction(&self, addr: SocketAddr, tx: Transaction) {
+        let mempool = match &self.mempool {
+            Some(m) => m,
+            None => return,
+        };
+
+        let added = {
+            let c = self.chain.lock().unwrap();
+            let ctx = c.next_block_context();
+            mempool.lock().unwrap().add_transaction(tx.clone(), &c.utxos, &ctx)
+        };
+
+        match added {
+            Ok(()) => self.bus.publish(BusEvent::TxAccepted(tx)),
+            Err(e) => {
+                println!("> [REJECT] Transaction from {}: {}", addr, e);
+                self.misbehaving(addr, e.ban_score(), &e.to_string());
+            }
+        }
+    }
+
+    /// Announce an object hash to every peer
+    fn announce(&self, kind: InvKind, hash: Vec<u8>) {
+        self.broadcast(&NetworkMessage::Inv(vec![InvItem { kind, hash }]));
+    }
+
+    /// ✅ FIX: Announce a newly mined block to all peers
+    ///
+    /// Peers fetch the body with GetData if they lack it.
     pub fn broadcast_block(&self, block: &Block) {
         println!(
-            "> [NET] Broadcasting block at height {}",
+            "> [NET] Announcing block at height {}",
             block.header.height
         );
 
-        let msg = NetworkMessage::Block(block.clone());
+        self.announce(InvKind::Block, block.hash.clone());
+    }
 
-        if let Ok(data) = bincode::serialize(&msg) {
-            self.transport.broadcast(&data);
-        }
+    /// Announce a local transaction to all peers
+    pub fn broadcast_transaction(&self, tx: &Transaction) {
+        self.announce(InvKind::Transaction, tx.txid());
     }
-}
\ No newline at end of file
+}
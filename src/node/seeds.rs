@@ -0,0 +1,21 @@
+//! Bootstrap seed resolution (NON-CONSENSUS)
+//!
+//! Seeds may be literal `ip:port` or `hostname:port`.
+//! Hostnames are resolved through DNS whenever the connection
+//! manager runs out of known addresses.
+
+use std::net::{SocketAddr, ToSocketAddrs};
+
+/// Addresses tried per seed hostname
+const MAX_ADDRS_PER_SEED: usize = 8;
+
+/// Resolve a seed to socket addresses (literal or DNS)
+pub fn resolve_seed(seed: &str) -> Vec<SocketAddr> {
+    match seed.to_socket_addrs() {
+        Ok(addrs) => addrs.take(MAX_ADDRS_PER_SEED).collect(),
+        Err(e) => {
+            println!("⚠️ Seed {} did not resolve: {}", seed, e);
+            Vec::new()
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::fs;
+
+use serde::Serialize;
+
+/// Where Linux exposes temperature sensors
+const THERMAL_ZONES: &str = "/sys/class/thermal";
+
+/// Device temperature against the policy limit (POLICY ONLY)
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct ThermalState {
+    /// Hottest sensor, °C; None where no sensor is readable
+    /// (non-Linux, most containers)
+    pub celsius: Option<f32>,
+    /// `RuntimePolicy::thermal_safety_threshold`
+    pub threshold: f32,
+    pub over_limit: bool,
+}
+
+impl ThermalState {
+    /// Read the sensors now
+    pub fn read(threshold: f32) -> Self {
+        let celsius = cpu_temperature();
+        Self {
+            celsius,
+            threshold,
+            over_limit: celsius.is_some_and(|c| c >= threshold),
+        }
+    }
+}
+
+/// Hottest reading across the kernel's thermal zones, °C
+pub fn cpu_temperature() -> Option<f32> {
+    fs::read_dir(THERMAL_ZONES)
+        .ok()?
+        .flatten()
+        .filter(|zone| zone.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|zone| fs::read_to_string(zone.path().join("temp")).ok())
+        // Millidegrees Celsius
+        .filter_map(|temp| temp.trim().parse::<i64>().ok())
+        .map(|milli| milli as f32 / 1000.0)
+        .reduce(f32::max)
+}
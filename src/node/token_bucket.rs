@@ -0,0 +1,65 @@
+use std::time::Instant;
+
+/// Token bucket: bursts up to `capacity`, refills at `per_sec`
+///
+/// Shared by P2P message limits and the API rate limiter.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    per_sec: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: f64, per_sec: f64, now: Instant) -> Self {
+        Self { tokens: capacity, capacity, per_sec, last: now }
+    }
+
+    /// Spend `cost` tokens; false if not enough are left
+    pub fn take(&mut self, cost: f64, now: Instant) -> bool {
+        self.refill(now);
+
+        if self.tokens < cost {
+            return false;
+        }
+        self.tokens -= cost;
+        true
+    }
+
+    /// True once refilled to capacity, i.e. the bucket holds no
+    /// state worth keeping
+    pub fn is_full(&self, now: Instant) -> bool {
+        self.tokens + self.elapsed(now) * self.per_sec >= self.capacity
+    }
+
+    fn refill(&mut self, now: Instant) {
+        self.tokens = (self.tokens + self.elapsed(now) * self.per_sec).min(self.capacity);
+        self.last = now;
+    }
+
+    fn elapsed(&self, now: Instant) -> f64 {
+        now.duration_since(self.last).as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 1.0, start);
+
+        assert!(bucket.take(1.0, start));
+        assert!(bucket.take(1.0, start));
+        assert!(!bucket.take(1.0, start));
+        assert!(!bucket.is_full(start));
+
+        assert!(bucket.take(1.0, start + Duration::from_secs(1)));
+        assert!(!bucket.take(1.0, start + Duration::from_secs(1)));
+        assert!(bucket.is_full(start + Duration::from_secs(3)));
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::crypto::sha256;
+use crate::node::transport::tcp::TcpTransport;
+use crate::node::transport::{ConnectCallback, DisconnectCallback, Transport};
+
+/// Garlicat prefix: I2P peers get a placeholder fd60:db4d:ddb5::/48 address
+const I2P_PREFIX: [u8; 6] = [0xfd, 0x60, 0xdb, 0x4d, 0xdd, 0xb5];
+
+type OnReceive = Arc<dyn Fn(SocketAddr, Vec<u8>) + Send + Sync>;
+
+/// I2P transport over a SAM v3 bridge (e.g. i2pd on 127.0.0.1:7656)
+///
+/// Creates a transient destination, so a NAT'd node can accept
+/// inbound peers without exposing its IP. Each I2P stream is a
+/// plain byte stream once SAM has set it up, so connections
+/// are handed to an inner `TcpTransport` for reading/writing.
+///
+/// Peers are identified by a placeholder IPv6 address derived
+/// from their destination.
+pub struct I2pTransport {
+    sam: SocketAddr,
+    session_id: String,
+    /// Our public destination (base64)
+    destination: String,
+    /// Keeps the session alive; closing it ends all streams
+    _control: TcpStream,
+    streams: Arc<TcpTransport>,
+    /// placeholder address → peer destination
+    destinations: Arc<Mutex<HashMap<SocketAddr, String>>>,
+}
+
+/// Placeholder address for a destination
+pub fn placeholder_addr(destination: &str) -> SocketAddr {
+    let hash = sha256(destination.as_bytes());
+    let mut octets = [0u8; 16];
+    octets[..6].copy_from_slice(&I2P_PREFIX);
+    octets[6..].copy_from_slice(&hash[..10]);
+    SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(octets), 0, 0, 0))
+}
+
+/// True if `addr` stands for an I2P peer
+pub fn is_i2p_addr(addr: &SocketAddr) -> bool {
+    match addr {
+        SocketAddr::V6(a) => a.ip().octets()[..6] == I2P_PREFIX,
+        SocketAddr::V4(_) => false,
+    }
+}
+
+/// Open a SAM connection and complete HELLO
+fn sam_connect(sam: SocketAddr) -> std::io::Result<(TcpStream, BufReader<TcpStream>)> {
+    let mut stream = TcpStream::connect_timeout(&sam, Duration::from_secs(10))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    stream.write_all(b"HELLO VERSION MIN=3.1 MAX=3.1\n")?;
+    expect_ok(&mut reader)?;
+
+    Ok((stream, reader))
+}
+
+/// Read one SAM reply line, failing unless RESULT=OK
+fn expect_ok(reader: &mut BufReader<TcpStream>) -> std::io::Result<String> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    if line.contains("RESULT=OK") {
+        Ok(line)
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("SAM error: {}", line.trim()),
+        ))
+    }
+}
+
+/// Value of `KEY=` in a SAM reply
+fn reply_value(line: &str, key: &str) -> Option<String> {
+    line.split_whitespace()
+        .find_map(|field| field.strip_prefix(key)?.strip_prefix('='))
+        .map(str::to_string)
+}
+
+impl I2pTransport {
+    /// Create a transient destination on the SAM bridge at `sam`
+    /// and start accepting inbound I2P peers
+    pub fn new(sam: SocketAddr, on_receive: OnReceive) -> std::io::Result<Arc<Self>> {
+        let session_id = format!("revelation-{}", std::process::id());
+
+        let (mut control, mut reader) = sam_connect(sam)?;
+        control.write_all(
+            format!(
+                "SESSION CREATE STYLE=STREAM ID={} DESTINATION=TRANSIENT SIGNATURE_TYPE=7\n",
+                session_id
+            )
+            .as_bytes(),
+        )?;
+        expect_ok(&mut reader)?;
+
+        control.write_all(b"NAMING LOOKUP NAME=ME\n")?;
+        let reply = expect_ok(&mut reader)?;
+        let destination = reply_value(&reply, "VALUE").unwrap_or_default();
+
+        println!("🧄 I2P destination: {}", destination);
+
+        let transport = Arc::new(Self {
+            sam,
+            session_id,
+            destination,
+            _control: control,
+            streams: TcpTransport::outbound_only(on_receive),
+            destinations: Arc::new(Mutex::new(HashMap::new())),
+        });
+
+        let acceptor = Arc::clone(&transport);
+        thread::spawn(move || loop {
+            if let Err(e) = acceptor.accept_one() {
+                println!("⚠️ I2P accept failed: {}", e);
+                thread::sleep(Duration::from_secs(5));
+            }
+        });
+
+        Ok(transport)
+    }
+
+    /// Our public destination, to hand to peers
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// Wait for one inbound stream and register it
+    fn accept_one(&self) -> std::io::Result<()> {
+        let (mut stream, mut reader) = sam_connect(self.sam)?;
+        stream.write_all(format!("STREAM ACCEPT ID={} SILENT=false\n", self.session_id).as_bytes())?;
+        expect_ok(&mut reader)?;
+
+        // First line of an accepted stream is the peer's destination
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let peer = line.split_whitespace().next().unwrap_or_default().to_string();
+
+        let addr = placeholder_addr(&peer);
+        self.destinations.lock().unwrap().insert(addr, peer);
+        self.streams.adopt(addr, stream, true);
+        Ok(())
+    }
+
+    /// Connect to a base64 destination or a `.i2p` name;
+    /// returns the placeholder address on success
+    pub fn connect(&self, destination: &str) -> Option<SocketAddr> {
+        let (mut stream, mut reader) = sam_connect(self.sam).ok()?;
+
+        let destination = if destination.ends_with(".i2p") {
+            stream
+                .write_all(format!("NAMING LOOKUP NAME={}\n", destination).as_bytes())
+                .ok()?;
+            reply_value(&expect_ok(&mut reader).ok()?, "VALUE")?
+        } else {
+            destination.to_string()
+        };
+
+        stream
+            .write_all(
+                format!(
+                    "STREAM CONNECT ID={} DESTINATION={} SILENT=false\n",
+                    self.session_id, destination
+                )
+                .as_bytes(),
+            )
+            .ok()?;
+        expect_ok(&mut reader).ok()?;
+
+        let addr = placeholder_addr(&destination);
+        self.destinations.lock().unwrap().insert(addr, destination);
+        self.streams.adopt(addr, stream, false);
+        Some(addr)
+    }
+}
+
+impl Transport for I2pTransport {
+    fn send(&self, addr: &SocketAddr, data: &[u8]) {
+        self.streams.send(addr, data);
+    }
+
+    fn broadcast(&self, data: &[u8]) {
+        self.streams.broadcast(data);
+    }
+
+    fn peers(&self) -> Vec<SocketAddr> {
+        self.streams.peers()
+    }
+
+    fn disconnect(&self, addr: &SocketAddr) {
+        self.streams.disconnect(addr);
+        self.destinations.lock().unwrap().remove(addr);
+    }
+
+    fn on_connect(&self, cb: ConnectCallback) {
+        self.streams.on_connect(cb);
+    }
+
+    fn on_disconnect(&self, cb: DisconnectCallback) {
+        self.streams.on_disconnect(cb);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholder_addresses_are_stable_and_tagged() {
+        let a = placeholder_addr("dest-a");
+        assert_eq!(a, placeholder_addr("dest-a"));
+        assert_ne!(a, placeholder_addr("dest-b"));
+        assert!(is_i2p_addr(&a));
+        assert!(!is_i2p_addr(&"10.0.0.1:8333".parse().unwrap()));
+
+        assert_eq!(
+            reply_value("NAMING REPLY RESULT=OK NAME=ME VALUE=abc=", "VALUE").as_deref(),
+            Some("abc=")
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::node::transport::{ConnectCallback, DisconnectCallback, PeerEvents, Transport};
+
+const MAX_MESSAGE_SIZE: usize = 1 * 1024 * 1024;
+
+/// Local peers get a placeholder fd69:7063::/32 address
+const IPC_PREFIX: [u8; 4] = [0xfd, 0x69, 0x70, 0x63];
+
+type OnReceive = Arc<dyn Fn(SocketAddr, Vec<u8>) + Send + Sync>;
+
+/// A connected local stream (Unix socket or named pipe)
+trait IpcStream: Read + Write + Send {
+    fn try_clone_box(&self) -> std::io::Result<Box<dyn IpcStream>>;
+
+    /// Unblock the reader so the connection thread exits
+    fn shutdown(&self);
+}
+
+/// Local IPC transport
+///
+/// Unix domain socket on Unix, named pipe on Windows. Lets
+/// co-located processes (mining proxy, explorer, wallet GUI)
+/// exchange full P2P messages without opening a TCP port;
+/// access is controlled by the socket file's permissions.
+///
+/// Local peers have no network address, so each connection
+/// gets a placeholder IPv6 address.
+pub struct IpcTransport {
+    peers: Arc<Mutex<HashMap<SocketAddr, Box<dyn IpcStream>>>>,
+    on_receive: OnReceive,
+    events: Arc<PeerEvents>,
+    next_id: AtomicU64,
+}
+
+/// True if `addr` stands for a local IPC peer
+pub fn is_ipc_addr(addr: &SocketAddr) -> bool {
+    match addr {
+        SocketAddr::V6(a) => a.ip().octets()[..4] == IPC_PREFIX,
+        SocketAddr::V4(_) => false,
+    }
+}
+
+impl IpcTransport {
+    fn empty(on_receive: OnReceive) -> Arc<Self> {
+        Arc::new(Self {
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            on_receive,
+            events: Arc::new(PeerEvents::default()),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    fn next_addr(&self) -> SocketAddr {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut octets = [0u8; 16];
+        octets[..4].copy_from_slice(&IPC_PREFIX);
+        octets[8..].copy_from_slice(&id.to_be_bytes());
+        SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(octets), 0, 0, 0))
+    }
+
+    /// Track a connected stream and read from it until it closes
+    fn register(&self, mut stream: Box<dyn IpcStream>, inbound: bool) -> Option<SocketAddr> {
+        let writer = stream.try_clone_box().ok()?;
+        let addr = self.next_addr();
+        self.peers.lock().unwrap().insert(addr, writer);
+
+        let peers = Arc::clone(&self.peers);
+        let on_receive = Arc::clone(&self.on_receive);
+        let events = Arc::clone(&self.events);
+
+        self.events.connected(addr, inbound);
+
+        thread::spawn(move || {
+            let mut buf = vec![0u8; MAX_MESSAGE_SIZE];
+            loop {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => (on_receive)(addr, buf[..n].to_vec()),
+                }
+            }
+            peers.lock().unwrap().remove(&addr);
+            events.disconnected(addr);
+        });
+
+        Some(addr)
+    }
+}
+
+// ───────── Unix domain sockets ─────────
+
+#[cfg(unix)]
+mod platform {
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::{IpcStream, IpcTransport, OnReceive};
+
+    impl IpcStream for UnixStream {
+        fn try_clone_box(&self) -> std::io::Result<Box<dyn IpcStream>> {
+            Ok(Box::new(self.try_clone()?))
+        }
+
+        fn shutdown(&self) {
+            let _ = UnixStream::shutdown(self, std::net::Shutdown::Both);
+        }
+    }
+
+    impl IpcTransport {
+        /// Accept local peers on the socket file `path`
+        pub fn new(path: &str, on_receive: OnReceive) -> Arc<Self> {
+            // A socket left by a previous run blocks bind
+            let _ = std::fs::remove_file(path);
+            let listener = UnixListener::bind(path).expect("IPC bind failed");
+
+            let transport = Self::empty(on_receive);
+            println!("🔌 IPC listening on {}", path);
+
+            let accept = Arc::clone(&transport);
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    accept.register(Box::new(stream), true);
+                }
+            });
+
+            transport
+        }
+
+        /// Connect to another process's socket file
+        pub fn connect(&self, path: &str) -> Option<std::net::SocketAddr> {
+            let stream = UnixStream::connect(path).ok()?;
+            self.register(Box::new(stream), false)
+        }
+    }
+}
+
+// ───────── Windows named pipes ─────────
+
+#[cfg(windows)]
+mod platform {
+    use std::fs::{File, OpenOptions};
+    use std::os::windows::io::{AsRawHandle, FromRawHandle};
+    use std::sync::Arc;
+    use std::thread;
+
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_DUPLEX;
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_READMODE_BYTE,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    use super::{IpcStream, IpcTransport, OnReceive, MAX_MESSAGE_SIZE};
+
+    impl IpcStream for File {
+        fn try_clone_box(&self) -> std::io::Result<Box<dyn IpcStream>> {
+            Ok(Box::new(self.try_clone()?))
+        }
+
+        fn shutdown(&self) {
+            // Server end only; a client end closes once the
+            // remote side notices and hangs up
+            unsafe {
+                DisconnectNamedPipe(self.as_raw_handle() as isize);
+            }
+        }
+    }
+
+    /// `\\.\pipe\<name>`
+    fn pipe_path(name: &str) -> String {
+        format!(r"\\.\pipe\{}", name)
+    }
+
+    /// Create one pipe instance and wait for a client on it
+    fn accept(path: &[u16]) -> Option<File> {
+        let handle = unsafe {
+            CreateNamedPipeW(
+                path.as_ptr(),
+                PIPE_ACCESS_DUPLEX,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                MAX_MESSAGE_SIZE as u32,
+                MAX_MESSAGE_SIZE as u32,
+                0,
+                std::ptr::null(),
+            )
+        };
+
+        if handle == INVALID_HANDLE_VALUE {
+            return None;
+        }
+
+        let pipe = unsafe { File::from_raw_handle(handle as _) };
+
+        // A client may connect between create and connect
+        let ok = unsafe { ConnectNamedPipe(handle, std::ptr::null_mut()) } != 0
+            || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED;
+
+        if ok { Some(pipe) } else { None }
+    }
+
+    impl IpcTransport {
+        /// Accept local peers on the named pipe `\\.\pipe\<name>`
+        pub fn new(name: &str, on_receive: OnReceive) -> Arc<Self> {
+            let path: Vec<u16> = pipe_path(name).encode_utf16().chain(Some(0)).collect();
+
+            let transport = Self::empty(on_receive);
+            println!("🔌 IPC listening on {}", pipe_path(name));
+
+            let accept_from = Arc::clone(&transport);
+            thread::spawn(move || loop {
+                if let Some(pipe) = accept(&path) {
+                    accept_from.register(Box::new(pipe), true);
+                }
+            });
+
+            transport
+        }
+
+        /// Connect to another process's named pipe
+        pub fn connect(&self, name: &str) -> Option<std::net::SocketAddr> {
+            let pipe = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(pipe_path(name))
+                .ok()?;
+            self.register(Box::new(pipe), false)
+        }
+    }
+}
+
+impl Transport for IpcTransport {
+    fn send(&self, addr: &SocketAddr, data: &[u8]) {
+        if let Some(s) = self.peers.lock().unwrap().get_mut(addr) {
+            let _ = s.write_all(data);
+        }
+    }
+
+    fn broadcast(&self, data: &[u8]) {
+        for s in self.peers.lock().unwrap().values_mut() {
+            let _ = s.write_all(data);
+        }
+    }
+
+    fn peers(&self) -> Vec<SocketAddr> {
+        self.peers.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn disconnect(&self, addr: &SocketAddr) {
+        if let Some(s) = self.peers.lock().unwrap().remove(addr) {
+            s.shutdown();
+        }
+    }
+
+    fn on_connect(&self, cb: ConnectCallback) {
+        self.events.subscribe_connect(cb);
+    }
+
+    fn on_disconnect(&self, cb: DisconnectCallback) {
+        self.events.subscribe_disconnect(cb);
+    }
+}
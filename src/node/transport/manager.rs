@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use crate::node::dedup::MessageDeduplicator;
+use crate::node::transport::{ConnectCallback, DisconnectCallback, PeerEvents, Transport};
+
+/// Identical bytes from one peer within this window are
+/// treated as the same message arriving over two links
+const CROSS_LINK_DEDUP_TTL: Duration = Duration::from_secs(10);
+
+type OnReceive = Arc<dyn Fn(SocketAddr, Vec<u8>) + Send + Sync>;
+
+/// A registered transport
+struct Link {
+    name: &'static str,
+    transport: Arc<dyn Transport>,
+    /// Lower is preferred when a peer is reachable several ways
+    priority: u8,
+}
+
+/// One way of reaching a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Route {
+    link: usize,
+    /// The peer's address on that link
+    addr: SocketAddr,
+}
+
+/// All transports behind one `Transport` (NON-CONSENSUS)
+///
+/// A peer connected over several links (same IP, different
+/// transports — e.g. TCP and QUIC) is one peer to `P2PNetwork`,
+/// known by the address it first connected from. Sends take
+/// the preferred live route; broadcasts reach each peer once;
+/// when a link drops, traffic fails over to the peer's next
+/// route and only the last route closing disconnects the peer.
+///
+/// Peers on the same link are never merged, so several nodes
+/// behind one NAT stay distinct.
+pub struct TransportManager {
+    links: RwLock<Vec<Link>>,
+    /// peer → live routes
+    routes: Mutex<HashMap<SocketAddr, Vec<Route>>>,
+    /// link address → peer it belongs to
+    aliases: Mutex<HashMap<SocketAddr, SocketAddr>>,
+    dedup: Mutex<MessageDeduplicator>,
+    events: PeerEvents,
+}
+
+impl TransportManager {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            links: RwLock::new(Vec::new()),
+            routes: Mutex::new(HashMap::new()),
+            aliases: Mutex::new(HashMap::new()),
+            dedup: Mutex::new(MessageDeduplicator::new(CROSS_LINK_DEDUP_TTL)),
+            events: PeerEvents::default(),
+        })
+    }
+
+    /// Add a connection-oriented transport
+    pub fn register(self: &Arc<Self>, name: &'static str, transport: Arc<dyn Transport>, priority: u8) {
+        let link = {
+            let mut links = self.links.write().unwrap();
+            links.push(Link { name, transport: Arc::clone(&transport), priority });
+            links.len() - 1
+        };
+
+        let manager = Arc::clone(self);
+        transport.on_connect(Arc::new(move |addr, inbound| manager.link_up(link, addr, inbound)));
+
+        let manager = Arc::clone(self);
+        transport.on_disconnect(Arc::new(move |addr| manager.link_down(link, addr)));
+
+        println!("🔀 Transport '{}' registered (priority {})", name, priority);
+    }
+
+    /// Wrap a transport's receive callback: reports the peer's
+    /// address instead of the link's and drops copies of a
+    /// message already received over another link
+    ///
+    /// Receive-only transports (satellite, geo, BLE) go through
+    /// here too so their copies are deduplicated.
+    pub fn receiver(self: &Arc<Self>, inner: OnReceive) -> OnReceive {
+        let manager = Arc::clone(self);
+
+        Arc::new(move |addr: SocketAddr, data: Vec<u8>| {
+            let peer = manager.peer_of(&addr);
+
+            let mut key = peer.to_string().into_bytes();
+            key.extend_from_slice(&data);
+            if !manager.dedup.lock().unwrap().check_and_insert(&key) {
+                return;
+            }
+
+            (inner)(peer, data);
+        })
+    }
+
+    fn peer_of(&self, addr: &SocketAddr) -> SocketAddr {
+        self.aliases.lock().unwrap().get(addr).copied().unwrap_or(*addr)
+    }
+
+    fn link_up(&self, link: usize, addr: SocketAddr, inbound: bool) {
+        let route = Route { link, addr };
+
+        // Same host already connected over another link?
+        let existing = {
+            let mut routes = self.routes.lock().unwrap();
+            let existing = routes
+                .iter()
+                .find(|(_, rs)| rs.iter().all(|r| r.link != link) && rs.iter().any(|r| r.addr.ip() == addr.ip()))
+                .map(|(peer, _)| *peer);
+
+            routes.entry(existing.unwrap_or(addr)).or_default().push(route);
+            existing
+        };
+
+        match existing {
+            Some(peer) => {
+                self.aliases.lock().unwrap().insert(addr, peer);
+                println!("🔀 {} also reachable via {}", peer, self.link_name(link));
+            }
+            None => {
+                self.aliases.lock().unwrap().insert(addr, addr);
+                self.events.connected(addr, inbound);
+            }
+        }
+    }
+
+    fn link_down(&self, link: usize, addr: SocketAddr) {
+        let peer = match self.aliases.lock().unwrap().remove(&addr) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let gone = {
+            let mut routes = self.routes.lock().unwrap();
+            let rs = routes.entry(peer).or_default();
+            rs.retain(|r| *r != Route { link, addr });
+
+            if rs.is_empty() {
+                routes.remove(&peer);
+                true
+            } else {
+                false
+            }
+        };
+
+        if gone {
+            self.events.disconnected(peer);
+        } else {
+            println!("🔀 {} lost {}, failing over", peer, self.link_name(link));
+        }
+    }
+
+    fn link_name(&self, link: usize) -> &'static str {
+        self.links.read().unwrap()[link].name
+    }
+
+    /// Preferred live route to `peer`
+    fn best_route(&self, peer: &SocketAddr) -> Option<Route> {
+        let links = self.links.read().unwrap();
+        self.routes
+            .lock()
+            .unwrap()
+            .get(peer)?
+            .iter()
+            .min_by_key(|r| links[r.link].priority)
+            .copied()
+    }
+
+    fn send_via(&self, route: Route, data: &[u8]) {
+        let transport = Arc::clone(&self.links.read().unwrap()[route.link].transport);
+        transport.send(&route.addr, data);
+    }
+}
+
+impl Transport for TransportManager {
+    fn send(&self, addr: &SocketAddr, data: &[u8]) {
+        if let Some(route) = self.best_route(addr) {
+            self.send_via(route, data);
+        }
+    }
+
+    fn broadcast(&self, data: &[u8]) {
+        // Once per peer, not once per link
+        for peer in self.peers() {
+            self.send(&peer, data);
+        }
+    }
+
+    fn peers(&self) -> Vec<SocketAddr> {
+        self.routes.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn disconnect(&self, addr: &SocketAddr) {
+        let routes = self.routes.lock().unwrap().get(addr).cloned().unwrap_or_default();
+        for route in routes {
+            let transport = Arc::clone(&self.links.read().unwrap()[route.link].transport);
+            transport.disconnect(&route.addr);
+        }
+    }
+
+    fn on_connect(&self, cb: ConnectCallback) {
+        self.events.subscribe_connect(cb);
+    }
+
+    fn on_disconnect(&self, cb: DisconnectCallback) {
+        self.events.subscribe_disconnect(cb);
+    }
+
+    fn link_name(&self, addr: &SocketAddr) -> Option<&'static str> {
+        let route = self.best_route(addr)?;
+        Some(self.links.read().unwrap()[route.link].name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records sends; connections are driven by hand
+    #[derive(Default)]
+    struct FakeLink {
+        sent: Mutex<Vec<SocketAddr>>,
+        events: PeerEvents,
+    }
+
+    impl Transport for FakeLink {
+        fn send(&self, addr: &SocketAddr, _data: &[u8]) {
+            self.sent.lock().unwrap().push(*addr);
+        }
+        fn broadcast(&self, _data: &[u8]) {}
+        fn peers(&self) -> Vec<SocketAddr> {
+            Vec::new()
+        }
+        fn on_connect(&self, cb: ConnectCallback) {
+            self.events.subscribe_connect(cb);
+        }
+        fn on_disconnect(&self, cb: DisconnectCallback) {
+            self.events.subscribe_disconnect(cb);
+        }
+    }
+
+    #[test]
+    fn prefers_best_link_and_fails_over() {
+        let manager = TransportManager::new();
+        let tcp = Arc::new(FakeLink::default());
+        let quic = Arc::new(FakeLink::default());
+        manager.register("tcp", tcp.clone(), 1);
+        manager.register("quic", quic.clone(), 0);
+
+        let tcp_addr: SocketAddr = "10.0.0.1:8333".parse().unwrap();
+        let quic_addr: SocketAddr = "10.0.0.1:9334".parse().unwrap();
+
+        tcp.events.connected(tcp_addr, false);
+        quic.events.connected(quic_addr, false);
+        assert_eq!(manager.peers(), vec![tcp_addr]);
+
+        manager.send(&tcp_addr, b"x");
+        assert_eq!(*quic.sent.lock().unwrap(), vec![quic_addr]);
+
+        quic.events.disconnected(quic_addr);
+        manager.send(&tcp_addr, b"x");
+        assert_eq!(*tcp.sent.lock().unwrap(), vec![tcp_addr]);
+
+        tcp.events.disconnected(tcp_addr);
+        assert!(manager.peers().is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 
 // ───────── Transport implementations ─────────
 pub mod tcp;
@@ -6,10 +7,72 @@ pub mod bluetooth;
 pub mod satellite;
 pub mod geo;
 pub mod offline;
+pub mod quic;
+pub mod websocket;
+pub mod i2p;
+pub mod ipc;
+pub mod manager;
+
+// ───────── Connection lifecycle ─────────
+
+/// Peer connected; `inbound` = the peer dialled us
+pub type ConnectCallback = Arc<dyn Fn(SocketAddr, bool) + Send + Sync>;
+
+/// Peer connection closed
+pub type DisconnectCallback = Arc<dyn Fn(SocketAddr) + Send + Sync>;
+
+/// Subscribers to a transport's connection events
+#[derive(Default)]
+pub struct PeerEvents {
+    on_connect: Mutex<Vec<ConnectCallback>>,
+    on_disconnect: Mutex<Vec<DisconnectCallback>>,
+}
+
+impl PeerEvents {
+    pub fn subscribe_connect(&self, cb: ConnectCallback) {
+        self.on_connect.lock().unwrap().push(cb);
+    }
+
+    pub fn subscribe_disconnect(&self, cb: DisconnectCallback) {
+        self.on_disconnect.lock().unwrap().push(cb);
+    }
+
+    /// Notify subscribers (call after the peer is registered)
+    pub fn connected(&self, addr: SocketAddr, inbound: bool) {
+        let subscribers = self.on_connect.lock().unwrap().clone();
+        for cb in subscribers {
+            cb(addr, inbound);
+        }
+    }
+
+    /// Notify subscribers (call after the peer is removed)
+    pub fn disconnected(&self, addr: SocketAddr) {
+        let subscribers = self.on_disconnect.lock().unwrap().clone();
+        for cb in subscribers {
+            cb(addr);
+        }
+    }
+}
 
 // ───────── Transport trait ─────────
 pub trait Transport: Send + Sync {
     fn send(&self, addr: &SocketAddr, data: &[u8]);
     fn broadcast(&self, data: &[u8]);
     fn peers(&self) -> Vec<SocketAddr>;
+
+    /// Drop a connection (no-op for connectionless transports)
+    fn disconnect(&self, _addr: &SocketAddr) {}
+
+    /// Run `cb` whenever a peer connects
+    /// (never fires for connectionless transports)
+    fn on_connect(&self, _cb: ConnectCallback) {}
+
+    /// Run `cb` whenever a peer's connection closes
+    fn on_disconnect(&self, _cb: DisconnectCallback) {}
+
+    /// Name of the link currently carrying traffic to `addr`
+    /// (only known to transports multiplexing several links)
+    fn link_name(&self, _addr: &SocketAddr) -> Option<&'static str> {
+        None
+    }
 }
@@ -0,0 +1,205 @@
+#![cfg(feature = "quic")]
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use quinn::{ClientConfig, Connection, Endpoint, ServerConfig};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, PrivateKey, ServerName};
+use tokio::runtime::Runtime;
+
+use crate::node::transport::{ConnectCallback, DisconnectCallback, PeerEvents, Transport};
+
+const MAX_MESSAGE_SIZE: usize = 1 * 1024 * 1024;
+
+/// ALPN protocol id; peers speaking anything else are refused
+const ALPN: &[u8] = b"revelation-p2p";
+
+/// Name presented in the self-signed certificate
+const SERVER_NAME: &str = "revelation";
+
+type OnReceive = Arc<dyn Fn(SocketAddr, Vec<u8>) + Send + Sync>;
+
+/// QUIC transport (quinn)
+///
+/// Every P2P message travels on its own unidirectional
+/// stream, so QUIC provides the framing. Connections survive
+/// address changes (Wi-Fi ↔ cellular) and are always
+/// encrypted. Certificates are self-signed and not verified:
+/// QUIC provides privacy, peer trust is the P2P layer's job.
+///
+/// Peers are keyed by the address they first connected from,
+/// so a migrated connection keeps its identity.
+pub struct QuicTransport {
+    runtime: Runtime,
+    endpoint: Endpoint,
+    peers: Arc<Mutex<HashMap<SocketAddr, Connection>>>,
+    on_receive: OnReceive,
+    events: Arc<PeerEvents>,
+}
+
+impl QuicTransport {
+    /// Listen on `bind` (UDP) and allow outbound connections
+    pub fn new(bind: &str, on_receive: OnReceive) -> Arc<Self> {
+        let runtime = Runtime::new().expect("QUIC runtime failed");
+        let bind: SocketAddr = bind.parse().expect("invalid QUIC bind address");
+
+        let endpoint = runtime.block_on(async {
+            let mut endpoint = Endpoint::server(server_config(), bind)
+                .expect("QUIC bind failed");
+            endpoint.set_default_client_config(client_config());
+            endpoint
+        });
+
+        println!("⚡ QUIC listening on {}", bind);
+
+        let transport = Arc::new(Self {
+            runtime,
+            endpoint,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            on_receive,
+            events: Arc::new(PeerEvents::default()),
+        });
+
+        let accept = Arc::clone(&transport);
+        transport.runtime.spawn(async move {
+            while let Some(connecting) = accept.endpoint.accept().await {
+                if let Ok(conn) = connecting.await {
+                    accept.register(conn, true);
+                }
+            }
+        });
+
+        transport
+    }
+
+    /// Track a connection and read its streams until it closes
+    fn register(&self, conn: Connection, inbound: bool) {
+        let addr = conn.remote_address();
+        self.peers.lock().unwrap().insert(addr, conn.clone());
+
+        let peers = Arc::clone(&self.peers);
+        let on_receive = Arc::clone(&self.on_receive);
+        let events = Arc::clone(&self.events);
+
+        self.events.connected(addr, inbound);
+
+        self.runtime.spawn(async move {
+            while let Ok(mut stream) = conn.accept_uni().await {
+                match stream.read_to_end(MAX_MESSAGE_SIZE).await {
+                    Ok(data) => (on_receive)(addr, data),
+                    Err(_) => continue,
+                }
+            }
+            peers.lock().unwrap().remove(&addr);
+            events.disconnected(addr);
+        });
+    }
+
+    /// Open an outbound connection; false if it failed
+    ///
+    /// Blocks; must not be called from inside an async task.
+    pub fn connect(&self, addr: SocketAddr) -> bool {
+        let connecting = match self.endpoint.connect(addr, SERVER_NAME) {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+
+        match self.runtime.block_on(connecting) {
+            Ok(conn) => {
+                self.register(conn, false);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn send_on(&self, conn: Connection, data: &[u8]) {
+        let data = data.to_vec();
+        self.runtime.spawn(async move {
+            if let Ok(mut stream) = conn.open_uni().await {
+                let _ = stream.write_all(&data).await;
+                let _ = stream.finish().await;
+            }
+        });
+    }
+}
+
+impl Transport for QuicTransport {
+    fn send(&self, addr: &SocketAddr, data: &[u8]) {
+        let conn = self.peers.lock().unwrap().get(addr).cloned();
+        if let Some(conn) = conn {
+            self.send_on(conn, data);
+        }
+    }
+
+    fn broadcast(&self, data: &[u8]) {
+        let conns: Vec<Connection> = self.peers.lock().unwrap().values().cloned().collect();
+        for conn in conns {
+            self.send_on(conn, data);
+        }
+    }
+
+    fn peers(&self) -> Vec<SocketAddr> {
+        self.peers.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn disconnect(&self, addr: &SocketAddr) {
+        if let Some(conn) = self.peers.lock().unwrap().remove(addr) {
+            conn.close(0u32.into(), b"disconnect");
+        }
+    }
+
+    fn on_connect(&self, cb: ConnectCallback) {
+        self.events.subscribe_connect(cb);
+    }
+
+    fn on_disconnect(&self, cb: DisconnectCallback) {
+        self.events.subscribe_disconnect(cb);
+    }
+}
+
+/// Self-signed server identity generated at startup
+fn server_config() -> ServerConfig {
+    let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()])
+        .expect("certificate generation failed");
+    let cert_der = cert.serialize_der().expect("certificate encoding failed");
+    let key = PrivateKey(cert.serialize_private_key_der());
+
+    let mut crypto = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(vec![Certificate(cert_der)], key)
+        .expect("invalid QUIC certificate");
+    crypto.alpn_protocols = vec![ALPN.to_vec()];
+
+    ServerConfig::with_crypto(Arc::new(crypto))
+}
+
+/// Encrypt without authenticating the server certificate
+struct AcceptAnyCert;
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+fn client_config() -> ClientConfig {
+    let mut crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert))
+        .with_no_client_auth();
+    crypto.alpn_protocols = vec![ALPN.to_vec()];
+
+    ClientConfig::new(Arc::new(crypto))
+}
@@ -5,62 +5,107 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-use crate::node::transport::Transport;
+use crate::node::transport::{ConnectCallback, DisconnectCallback, PeerEvents, Transport};
 
 const MAX_MESSAGE_SIZE: usize = 1 * 1024 * 1024;
 
+type OnReceive = Arc<dyn Fn(SocketAddr, Vec<u8>) + Send + Sync>;
+
 pub struct TcpTransport {
     peers: Arc<Mutex<HashMap<SocketAddr, TcpStream>>>,
+    on_receive: OnReceive,
+    events: Arc<PeerEvents>,
 }
 
 impl TcpTransport {
-    pub fn new(
-        bind: &str,
-        on_receive: Arc<dyn Fn(SocketAddr, Vec<u8>) + Send + Sync>,
-    ) -> Arc<Self> {
+    /// Listen for inbound peers on `bind` and allow outbound ones
+    pub fn new(bind: &str, on_receive: OnReceive) -> Arc<Self> {
+        let transport = Self::outbound_only(on_receive);
+        transport.listen(bind, |_| {});
+        transport
+    }
+
+    /// Accept inbound peers on an additional address;
+    /// `on_accept` runs before the peer's first message
+    pub fn listen<F>(self: &Arc<Self>, bind: &str, on_accept: F)
+    where
+        F: Fn(SocketAddr) + Send + 'static,
+    {
         let listener = TcpListener::bind(bind).expect("TCP bind failed");
         listener.set_nonblocking(true).unwrap();
 
-        let peers = Arc::new(Mutex::new(HashMap::new()));
-        let peers_accept = Arc::clone(&peers);
-        let on_receive = Arc::clone(&on_receive);
+        let accept = Arc::clone(self);
 
         thread::spawn(move || loop {
             match listener.accept() {
-                Ok((mut stream, addr)) => {
-                    stream
-                        .set_read_timeout(Some(Duration::from_secs(30)))
-                        .ok();
-
-                    peers_accept
-                        .lock()
-                        .unwrap()
-                        .insert(addr, stream.try_clone().unwrap());
-
-                    let peers_inner = Arc::clone(&peers_accept);
-                    let on_receive = Arc::clone(&on_receive);
-
-                    thread::spawn(move || {
-                        let mut buf = vec![0u8; MAX_MESSAGE_SIZE];
-                        loop {
-                            match stream.read(&mut buf) {
-                                Ok(0) | Err(_) => break,
-                                Ok(n) => (on_receive)(addr, buf[..n].to_vec()),
-                            }
-                        }
-                        peers_inner.lock().unwrap().remove(&addr);
-                    });
+                Ok((stream, addr)) => {
+                    // Accepted sockets inherit non-blocking mode
+                    stream.set_nonblocking(false).ok();
+                    on_accept(addr);
+                    accept.register(addr, stream, true);
                 }
                 Err(_) => thread::sleep(Duration::from_millis(50)),
             }
         });
+    }
 
-        Arc::new(Self { peers })
+    /// Outbound connections only; no listening socket
+    pub fn outbound_only(on_receive: OnReceive) -> Arc<Self> {
+        Arc::new(Self {
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            on_receive,
+            events: Arc::new(PeerEvents::default()),
+        })
     }
 
-    pub fn connect(&self, addr: SocketAddr) {
-        if let Ok(stream) = TcpStream::connect(addr) {
-            self.peers.lock().unwrap().insert(addr, stream);
+    /// Track a connected stream and read from it until it closes
+    fn register(&self, addr: SocketAddr, mut stream: TcpStream, inbound: bool) {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .ok();
+
+        let writer = match stream.try_clone() {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        self.peers.lock().unwrap().insert(addr, writer);
+
+        let peers = Arc::clone(&self.peers);
+        let on_receive = Arc::clone(&self.on_receive);
+        let events = Arc::clone(&self.events);
+
+        self.events.connected(addr, inbound);
+
+        thread::spawn(move || {
+            let mut buf = vec![0u8; MAX_MESSAGE_SIZE];
+            loop {
+                match stream.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => (on_receive)(addr, buf[..n].to_vec()),
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock
+                        || e.kind() == std::io::ErrorKind::TimedOut => continue,
+                    Err(_) => break,
+                }
+            }
+            peers.lock().unwrap().remove(&addr);
+            events.disconnected(addr);
+        });
+    }
+
+    /// Take over a stream set up elsewhere (e.g. by an I2P
+    /// SAM bridge) under a caller-chosen address
+    pub(crate) fn adopt(&self, addr: SocketAddr, stream: TcpStream, inbound: bool) {
+        self.register(addr, stream, inbound);
+    }
+
+    /// Open an outbound connection; false if it failed
+    pub fn connect(&self, addr: SocketAddr) -> bool {
+        match TcpStream::connect_timeout(&addr, Duration::from_secs(10)) {
+            Ok(stream) => {
+                self.register(addr, stream, false);
+                true
+            }
+            Err(_) => false,
         }
     }
 }
@@ -81,4 +126,19 @@ impl Transport for TcpTransport {
     fn peers(&self) -> Vec<SocketAddr> {
         self.peers.lock().unwrap().keys().cloned().collect()
     }
+
+    fn disconnect(&self, addr: &SocketAddr) {
+        // The reader thread sees the shutdown and reports it
+        if let Some(s) = self.peers.lock().unwrap().remove(addr) {
+            let _ = s.shutdown(std::net::Shutdown::Both);
+        }
+    }
+
+    fn on_connect(&self, cb: ConnectCallback) {
+        self.events.subscribe_connect(cb);
+    }
+
+    fn on_disconnect(&self, cb: DisconnectCallback) {
+        self.events.subscribe_disconnect(cb);
+    }
 }
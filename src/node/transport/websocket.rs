@@ -0,0 +1,158 @@
+#![cfg(feature = "websocket")]
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::{Message, WebSocket};
+
+use crate::node::transport::{ConnectCallback, DisconnectCallback, PeerEvents, Transport};
+
+/// How long a connection thread blocks on reads before
+/// flushing queued outbound messages
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+type OnReceive = Arc<dyn Fn(SocketAddr, Vec<u8>) + Send + Sync>;
+
+/// WebSocket transport
+///
+/// Carries the same framed P2P messages as TCP, one per
+/// binary WebSocket message, so browsers and clients behind
+/// HTTP-only firewalls can reach a node on a standard port.
+pub struct WebSocketTransport {
+    /// Outbound queue of each connection's thread
+    peers: Arc<Mutex<HashMap<SocketAddr, Sender<Vec<u8>>>>>,
+    on_receive: OnReceive,
+    events: Arc<PeerEvents>,
+}
+
+impl WebSocketTransport {
+    /// Accept WebSocket peers on `bind`
+    pub fn new(bind: &str, on_receive: OnReceive) -> Arc<Self> {
+        let listener = TcpListener::bind(bind).expect("WebSocket bind failed");
+
+        let transport = Arc::new(Self {
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            on_receive,
+            events: Arc::new(PeerEvents::default()),
+        });
+
+        println!("🕸 WebSocket listening on {}", bind);
+
+        let accept = Arc::clone(&transport);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let addr = match stream.peer_addr() {
+                    Ok(a) => a,
+                    Err(_) => continue,
+                };
+
+                let accept = Arc::clone(&accept);
+                thread::spawn(move || {
+                    if let Ok(ws) = tungstenite::accept(stream) {
+                        accept.register(addr, ws, true);
+                    }
+                });
+            }
+        });
+
+        transport
+    }
+
+    /// Open an outbound WebSocket connection; false if it failed
+    pub fn connect(&self, addr: SocketAddr) -> bool {
+        let stream = match TcpStream::connect_timeout(&addr, Duration::from_secs(10)) {
+            Ok(s) => s,
+            Err(_) => return false,
+        };
+
+        match tungstenite::client(format!("ws://{}/", addr), stream) {
+            Ok((ws, _response)) => {
+                self.register(addr, ws, false);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Own the socket on a dedicated thread: read incoming
+    /// messages and write queued outbound ones
+    fn register(&self, addr: SocketAddr, mut ws: WebSocket<TcpStream>, inbound: bool) {
+        ws.get_ref().set_read_timeout(Some(POLL_INTERVAL)).ok();
+
+        let (tx, rx) = channel::<Vec<u8>>();
+        self.peers.lock().unwrap().insert(addr, tx);
+
+        let peers = Arc::clone(&self.peers);
+        let on_receive = Arc::clone(&self.on_receive);
+        let events = Arc::clone(&self.events);
+
+        self.events.connected(addr, inbound);
+
+        thread::spawn(move || {
+            'conn: loop {
+                match ws.read() {
+                    Ok(Message::Binary(data)) => (on_receive)(addr, data),
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => {}
+                    Err(tungstenite::Error::Io(e))
+                        if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {}
+                    Err(_) => break,
+                }
+
+                loop {
+                    match rx.try_recv() {
+                        Ok(data) => {
+                            if ws.send(Message::Binary(data)).is_err() {
+                                break 'conn;
+                            }
+                        }
+                        Err(TryRecvError::Empty) => break,
+                        // Sender dropped: disconnect requested
+                        Err(TryRecvError::Disconnected) => {
+                            let _ = ws.close(None);
+                            break 'conn;
+                        }
+                    }
+                }
+            }
+
+            peers.lock().unwrap().remove(&addr);
+            events.disconnected(addr);
+        });
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn send(&self, addr: &SocketAddr, data: &[u8]) {
+        if let Some(tx) = self.peers.lock().unwrap().get(addr) {
+            let _ = tx.send(data.to_vec());
+        }
+    }
+
+    fn broadcast(&self, data: &[u8]) {
+        for tx in self.peers.lock().unwrap().values() {
+            let _ = tx.send(data.to_vec());
+        }
+    }
+
+    fn peers(&self) -> Vec<SocketAddr> {
+        self.peers.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn disconnect(&self, addr: &SocketAddr) {
+        // Dropping the queue makes the connection thread close
+        self.peers.lock().unwrap().remove(addr);
+    }
+
+    fn on_connect(&self, cb: ConnectCallback) {
+        self.events.subscribe_connect(cb);
+    }
+
+    fn on_disconnect(&self, cb: DisconnectCallback) {
+        self.events.subscribe_disconnect(cb);
+    }
+}
@@ -0,0 +1,105 @@
+//! Trusted peers (NON-CONSENSUS)
+//!
+//! Whitelisted addresses skip rate limits, misbehavior scoring
+//! and eviction. Entries are `ip` or `ip/prefix`; peers that
+//! connect through a whitebind listener are added one by one.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subnet {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Subnet {
+    /// Parse `ip` or `ip/prefix`
+    pub fn parse(s: &str) -> Option<Self> {
+        let (ip, prefix) = match s.split_once('/') {
+            Some((ip, p)) => (ip, Some(p.parse::<u8>().ok()?)),
+            None => (s, None),
+        };
+
+        let network: IpAddr = ip.trim().parse().ok()?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max);
+
+        if prefix > max {
+            return None;
+        }
+
+        Some(Self { network, prefix })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                prefix_match(u32::from(net) as u128, u32::from(*ip) as u128, self.prefix, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_match(u128::from(net), u128::from(*ip), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// True if the top `prefix` of `bits` bits agree
+fn prefix_match(a: u128, b: u128, prefix: u8, bits: u8) -> bool {
+    if prefix == 0 {
+        return true;
+    }
+    let shift = (bits - prefix) as u32;
+    (a >> shift) == (b >> shift)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Whitelist {
+    subnets: Vec<Subnet>,
+    peers: HashSet<SocketAddr>,
+}
+
+impl Whitelist {
+    /// Build from config entries, reporting the first invalid one
+    pub fn from_entries(entries: &[String]) -> Result<Self, String> {
+        let subnets = entries
+            .iter()
+            .map(|e| Subnet::parse(e).ok_or_else(|| format!("invalid whitelist entry '{}'", e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { subnets, peers: HashSet::new() })
+    }
+
+    /// Trust one connection (e.g. accepted on the whitebind port)
+    pub fn add_peer(&mut self, addr: SocketAddr) {
+        self.peers.insert(addr);
+    }
+
+    pub fn contains(&self, addr: &SocketAddr) -> bool {
+        self.peers.contains(addr) || self.subnets.iter().any(|s| s.contains(&addr.ip()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_subnets_and_single_hosts() {
+        let wl = Whitelist::from_entries(&[
+            "192.168.1.0/24".to_string(),
+            "10.0.0.5".to_string(),
+            "fd00::/8".to_string(),
+        ])
+        .unwrap();
+
+        assert!(wl.contains(&"192.168.1.77:8333".parse().unwrap()));
+        assert!(!wl.contains(&"192.168.2.1:8333".parse().unwrap()));
+        assert!(wl.contains(&"10.0.0.5:1".parse().unwrap()));
+        assert!(!wl.contains(&"10.0.0.6:1".parse().unwrap()));
+        assert!(wl.contains(&"[fd12::1]:8333".parse().unwrap()));
+
+        assert!(Whitelist::from_entries(&["10.0.0.0/33".to_string()]).is_err());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::transaction::Transaction;
+use crate::script::parse_multisig;
+
 /// Policy limits (NOT consensus yet)
 pub const MAX_BLOCK_SIZE: usize = 1_000_000; // 1 MB
 pub const MAX_BLOCK_TXS: usize = 2_000;
@@ -7,3 +10,87 @@ pub const MAX_BLOCK_TX_BYTES: usize = MAX_BLOCK_SIZE - 1_000;
 
 /// Mempool policy
 pub const MAX_TX_SIZE: usize = 100_000; // 100 KB
+
+/// Extra fee rate (per 1000 bytes) a replacement must pay
+/// for its own relay, on top of the fees it displaces
+pub const INCREMENTAL_RELAY_FEE_RATE: i64 = 1_000;
+
+// ─────────────────────────────────────────────
+// STANDARDNESS (POLICY ONLY)
+//
+// Applied to mempool admission and miner selection.
+// Blocks containing non-standard transactions remain
+// VALID; nothing here may be called from consensus.
+// ─────────────────────────────────────────────
+
+/// Outputs below this value cost more to spend than they carry
+pub const DUST_THRESHOLD: u64 = 546;
+
+/// Output value the mempool refuses to relay; the wallet
+/// never creates one
+pub fn is_dust(value: u64) -> bool {
+    value < DUST_THRESHOLD
+}
+
+pub const MAX_STANDARD_TX_INPUTS: usize = 500;
+pub const MAX_STANDARD_TX_OUTPUTS: usize = 500;
+
+/// Uncompressed SEC1 encoding
+pub const MAX_STANDARD_PUBKEY_SIZE: usize = 65;
+
+/// Why a transaction is not standard
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyError {
+    #[error("too many inputs")]
+    TooManyInputs,
+
+    #[error("too many outputs")]
+    TooManyOutputs,
+
+    #[error("output {0} has zero value")]
+    ZeroValueOutput(usize),
+
+    #[error("output {0} is dust")]
+    DustOutput(usize),
+
+    #[error("input {0} has an oversized pubkey")]
+    OversizedPubkey(usize),
+
+    #[error("output {0} locks to an oversized pubkey")]
+    OversizedScriptPubkey(usize),
+}
+
+/// Standardness rules for relay and mining (POLICY ONLY)
+pub fn is_standard_tx(tx: &Transaction) -> Result<(), PolicyError> {
+    if tx.inputs.len() > MAX_STANDARD_TX_INPUTS {
+        return Err(PolicyError::TooManyInputs);
+    }
+
+    if tx.outputs.len() > MAX_STANDARD_TX_OUTPUTS {
+        return Err(PolicyError::TooManyOutputs);
+    }
+
+    for (i, input) in tx.inputs.iter().enumerate() {
+        if input.pubkey.len() > MAX_STANDARD_PUBKEY_SIZE {
+            return Err(PolicyError::OversizedPubkey(i));
+        }
+    }
+
+    for (i, output) in tx.outputs.iter().enumerate() {
+        if output.value == 0 {
+            return Err(PolicyError::ZeroValueOutput(i));
+        }
+
+        if is_dust(output.value) {
+            return Err(PolicyError::DustOutput(i));
+        }
+
+        if let Some((_, pubkeys)) = parse_multisig(&output.script_pubkey) {
+            if pubkeys.iter().any(|pk| pk.len() > MAX_STANDARD_PUBKEY_SIZE) {
+                return Err(PolicyError::OversizedScriptPubkey(i));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,3 +1,7 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
 use crate::core::block::Block;
 
 /// Consensus PoW rule:
@@ -30,3 +34,183 @@ pub fn mine(block: &mut Block) {
         block.header.nonce += 1;
     }
 }
+
+/// Nonces a worker tries between checks for a solution found
+/// by another worker
+const CANCEL_CHECK_INTERVAL: u64 = 1_024;
+
+/// How mining worker threads run (POLICY ONLY)
+#[derive(Debug, Clone, Default)]
+pub struct MiningThreads {
+    /// Worker threads (at least one)
+    pub count: usize,
+    /// Run workers at the lowest OS priority, so P2P and the API
+    /// keep getting CPU time
+    pub low_priority: bool,
+    /// Cores to pin workers to, assigned round-robin
+    /// (empty = let the OS schedule them)
+    pub cores: Vec<usize>,
+}
+
+impl MiningThreads {
+    pub fn new(count: usize) -> Self {
+        Self { count, ..Self::default() }
+    }
+
+    /// Apply priority and pinning to the calling worker thread.
+    /// Best effort: unsupported platforms run unchanged.
+    fn tune_worker(&self, worker: usize) {
+        if self.low_priority {
+            lower_thread_priority();
+        }
+        if !self.cores.is_empty() {
+            pin_thread(self.cores[worker % self.cores.len()]);
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn lower_thread_priority() {
+    // Linux nice values are per thread
+    unsafe {
+        let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+        libc::setpriority(libc::PRIO_PROCESS, tid, 19);
+    }
+}
+
+#[cfg(windows)]
+fn lower_thread_priority() {
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_IDLE,
+    };
+
+    unsafe {
+        SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_IDLE);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+fn lower_thread_priority() {}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn pin_thread(core: usize) {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set);
+    }
+}
+
+#[cfg(windows)]
+fn pin_thread(core: usize) {
+    use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadAffinityMask};
+
+    if core < usize::BITS as usize {
+        unsafe {
+            SetThreadAffinityMask(GetCurrentThread(), 1 << core);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+fn pin_thread(_core: usize) {}
+
+/// `mine` on `threads.count` worker threads
+///
+/// Worker `i` tries nonces `i, i + count, i + 2·count, …` so
+/// the nonce space is split without overlap. The first valid
+/// solution is kept; the other workers then stop. Workers
+/// report hashes tried to `progress` as they go, and give up
+/// once `stop` returns true (checked every
+/// CANCEL_CHECK_INTERVAL hashes).
+///
+/// False, with `block` untouched, if stopped or the nonce
+/// space ran out.
+pub fn mine_parallel(
+    block: &mut Block,
+    threads: &MiningThreads,
+    progress: &(dyn Fn(u64) + Sync),
+    stop: &(dyn Fn() -> bool + Sync),
+) -> bool {
+    let count = threads.count.max(1) as u64;
+    let found = AtomicBool::new(false);
+    let solution: Mutex<Option<(u64, Vec<u8>)>> = Mutex::new(None);
+
+    thread::scope(|s| {
+        for worker in 0..count {
+            let mut header = block.header.clone();
+            let (found, solution) = (&found, &solution);
+
+            s.spawn(move || {
+                threads.tune_worker(worker as usize);
+
+                header.nonce = worker;
+                let mut tried = 0u64;
+
+                loop {
+                    let hash = header.hash();
+
+                    if valid_pow(&hash, &header.target) {
+                        progress(tried % CANCEL_CHECK_INTERVAL + 1);
+                        if !found.swap(true, Ordering::SeqCst) {
+                            *solution.lock().unwrap() = Some((header.nonce, hash));
+                        }
+                        return;
+                    }
+
+                    tried += 1;
+                    if tried % CANCEL_CHECK_INTERVAL == 0 {
+                        progress(CANCEL_CHECK_INTERVAL);
+                        if found.load(Ordering::Relaxed) || stop() {
+                            return;
+                        }
+                    }
+
+                    header.nonce = match header.nonce.checked_add(count) {
+                        Some(n) => n,
+                        None => return, // share of the nonce space exhausted
+                    };
+                }
+            });
+        }
+    });
+
+    match solution.into_inner().unwrap() {
+        Some((nonce, hash)) => {
+            block.header.nonce = nonce;
+            block.hash = hash;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockHeader;
+
+    #[test]
+    fn parallel_workers_find_a_valid_nonce() {
+        let mut target = [0xff; 32];
+        target[0] = 0x0f; // ~1 in 16 hashes
+
+        let mut block = Block {
+            header: BlockHeader {
+                height: 1,
+                timestamp: 0,
+                prev_hash: vec![0; 32],
+                nonce: 0,
+                target,
+                merkle_root: vec![],
+                utxo_commitment: vec![],
+            },
+            transactions: vec![],
+            hash: vec![],
+        };
+
+        assert!(mine_parallel(&mut block, &MiningThreads::new(4), &|_| {}, &|| false));
+        assert_eq!(block.hash, block.hash_header());
+        assert!(valid_pow(&block.hash, &target));
+    }
+}
@@ -0,0 +1,254 @@
+use std::fs;
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::script::{multisig_unlock, parse_multisig, pushed_data};
+use crate::core::transaction::Transaction;
+use crate::core::utxo::{UTXOSet, UTXO};
+use crate::crypto::pubkey_hash_from_bytes;
+use crate::wallet::order_multisig_signatures;
+
+/// Current container format
+const PSBT_VERSION: u32 = 1;
+
+/// Marks the text (QR) form
+const PAYLOAD_PREFIX: &str = "PSBT:";
+
+/// Partially signed transaction (POLICY ONLY)
+///
+/// An unsigned or partly signed transaction together with the
+/// coin each input spends, so an offline signer can check the
+/// amounts and fee without a copy of the UTXO set. Built by an
+/// online (watch-only) node, signed offline, then brought back
+/// and finalized for broadcast. Stored as JSON, or as a one-line
+/// text payload for QR codes.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PartiallySignedTransaction {
+    pub version: u32,
+    pub tx: Transaction,
+    /// Coin spent by `tx.inputs[i]`
+    pub inputs: Vec<UTXO>,
+}
+
+impl PartiallySignedTransaction {
+    /// Wrap `tx`, looking up the coin spent by every input
+    pub fn new(tx: Transaction, utxos: &UTXOSet) -> Result<Self, &'static str> {
+        let inputs = tx
+            .inputs
+            .iter()
+            .map(|i| {
+                i.outpoint()
+                    .and_then(|op| utxos.get(&op))
+                    .cloned()
+                    .ok_or("input coin not found")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let psbt = Self { version: PSBT_VERSION, tx, inputs };
+        psbt.check()?;
+        Ok(psbt)
+    }
+
+    /// Input metadata matches the transaction; single-key inputs
+    /// carry the key their coin is locked to
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.version != PSBT_VERSION {
+            return Err("unsupported PSBT version");
+        }
+        if self.inputs.len() != self.tx.inputs.len() {
+            return Err("input metadata does not match transaction");
+        }
+
+        for (input, coin) in self.tx.inputs.iter().zip(&self.inputs) {
+            if coin.script_pubkey.is_empty() && pubkey_hash_from_bytes(&input.pubkey) != coin.pubkey_hash {
+                return Err("input key does not match the coin it spends");
+            }
+        }
+
+        self.fee().map(|_| ())
+    }
+
+    /// Coins spent, as a UTXO set (for `Wallet::cosign`)
+    pub fn utxo_set(&self) -> UTXOSet {
+        self.tx
+            .inputs
+            .iter()
+            .zip(&self.inputs)
+            .filter_map(|(i, coin)| Some((i.outpoint()?, coin.clone())))
+            .collect()
+    }
+
+    pub fn input_total(&self) -> u64 {
+        self.inputs.iter().map(|u| u.value).sum()
+    }
+
+    pub fn output_total(&self) -> u64 {
+        self.tx.outputs.iter().map(|o| o.value).sum()
+    }
+
+    pub fn fee(&self) -> Result<u64, &'static str> {
+        self.input_total()
+            .checked_sub(self.output_total())
+            .ok_or("outputs exceed inputs")
+    }
+
+    /// Signatures on input `i` and how many it needs
+    pub fn input_signatures(&self, i: usize) -> (usize, usize) {
+        let input = &self.tx.inputs[i];
+
+        match parse_multisig(&self.inputs[i].script_pubkey) {
+            Some((m, _)) => (pushed_data(&input.script_sig).map_or(0, |s| s.len()), m),
+            None => {
+                let signed = !input.signature.is_empty() || !input.script_sig.is_empty();
+                (signed as usize, 1)
+            }
+        }
+    }
+
+    /// Inputs with all the signatures they need
+    pub fn signed_inputs(&self) -> usize {
+        (0..self.tx.inputs.len())
+            .filter(|&i| {
+                let (have, need) = self.input_signatures(i);
+                have >= need
+            })
+            .count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.signed_inputs() == self.tx.inputs.len()
+    }
+
+    /// Take signatures from another copy of the same transaction
+    /// (e.g. signed by another cosigner)
+    pub fn combine(&mut self, other: &Self) -> Result<(), &'static str> {
+        let sighash = self.tx.signature_hash();
+        if sighash != other.tx.signature_hash() {
+            return Err("different transactions");
+        }
+
+        for ((mine, theirs), coin) in self.tx.inputs.iter_mut().zip(&other.tx.inputs).zip(&self.inputs) {
+            match parse_multisig(&coin.script_pubkey) {
+                // Pool both parties' signatures
+                Some((m, pubkeys)) => {
+                    let mut sigs = pushed_data(&mine.script_sig).ok_or("bad script_sig")?;
+                    sigs.extend(pushed_data(&theirs.script_sig).ok_or("bad script_sig")?);
+
+                    mine.script_sig = multisig_unlock(&order_multisig_signatures(&sighash, &pubkeys, m, sigs));
+                }
+                None => {
+                    if mine.signature.is_empty() {
+                        mine.signature = theirs.signature.clone();
+                    }
+                    if mine.script_sig.is_empty() {
+                        mine.script_sig = theirs.script_sig.clone();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The signed transaction, once every input is signed
+    pub fn finalize(self) -> Result<Transaction, &'static str> {
+        if !self.is_complete() {
+            return Err("transaction not fully signed");
+        }
+        Ok(self.tx)
+    }
+
+    /// `PSBT:` + uppercase hex, which QR codes store in their
+    /// compact alphanumeric mode
+    pub fn to_payload(&self) -> String {
+        format!("{}{}", PAYLOAD_PREFIX, hex::encode_upper(bincode::serialize(self).unwrap()))
+    }
+
+    pub fn from_payload(payload: &str) -> Result<Self, &'static str> {
+        let data = payload
+            .trim()
+            .strip_prefix(PAYLOAD_PREFIX)
+            .ok_or("not a PSBT payload")?;
+        let bytes = hex::decode(data).map_err(|_| "invalid PSBT payload")?;
+
+        let psbt: Self = bincode::deserialize(&bytes).map_err(|_| "invalid PSBT payload")?;
+        psbt.check()?;
+        Ok(psbt)
+    }
+
+    /// A payload given inline, or a file holding JSON or a payload
+    pub fn load(source: &str) -> Result<Self, &'static str> {
+        if source.starts_with(PAYLOAD_PREFIX) {
+            return Self::from_payload(source);
+        }
+
+        let data = fs::read_to_string(source).map_err(|_| "cannot read PSBT file")?;
+        if data.trim_start().starts_with(PAYLOAD_PREFIX) {
+            return Self::from_payload(&data);
+        }
+
+        let psbt: Self = serde_json::from_str(&data).map_err(|_| "invalid PSBT file")?;
+        psbt.check()?;
+        Ok(psbt)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), &'static str> {
+        fs::write(path, serde_json::to_string_pretty(self).unwrap())
+            .map_err(|_| "cannot write PSBT file")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transaction::{TxInput, TxOutput};
+    use crate::core::utxo::OutPoint;
+
+    fn spend(pubkey: Vec<u8>) -> Transaction {
+        Transaction {
+            inputs: vec![TxInput {
+                txid: vec![7; 32],
+                index: 0,
+                pubkey,
+                signature: vec![],
+                address_index: 0,
+                script_sig: vec![],
+            }],
+            outputs: vec![TxOutput { value: 900, pubkey_hash: vec![2; 32], script_pubkey: vec![] }],
+        }
+    }
+
+    #[test]
+    fn tracks_fee_and_merges_signatures() {
+        let pubkey = vec![3u8; 33];
+        let mut utxos = UTXOSet::new();
+        utxos.insert(
+            OutPoint { txid: [7; 32], vout: 0 },
+            UTXO {
+                value: 1_000,
+                pubkey_hash: pubkey_hash_from_bytes(&pubkey),
+                height: 1,
+                is_coinbase: false,
+                script_pubkey: vec![],
+            },
+        );
+
+        let psbt = PartiallySignedTransaction::new(spend(pubkey.clone()), &utxos).unwrap();
+        assert_eq!(psbt.fee(), Ok(100));
+        assert!(!psbt.is_complete());
+
+        let mut signed = psbt.clone();
+        signed.tx.inputs[0].signature = vec![1; 64];
+
+        let mut merged = psbt.clone();
+        merged.combine(&signed).unwrap();
+        let merged_payload = merged.to_payload();
+        assert!(merged.finalize().is_ok());
+        let copy = PartiallySignedTransaction::from_payload(&merged_payload).unwrap();
+        assert!(copy.is_complete());
+        assert!(psbt.finalize().is_err());
+
+        // A key that does not own the coin is refused
+        assert!(PartiallySignedTransaction::new(spend(vec![4; 33]), &utxos).is_err());
+    }
+}
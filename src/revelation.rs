@@ -13,6 +13,7 @@ No authority. No reversal. No governance. \
 Truth revealed by computation."
                     .as_bytes(),
             ),
+            script_pubkey: vec![],
         }],
     }
 }
@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use secp256k1::SecretKey;
+use serde::{Serialize, Deserialize};
+
+use crate::core::transaction::Transaction;
+use crate::core::utxo::UTXOSet;
+use crate::crypto::{public_key, sign, verify_signature};
+use crate::wallet::{build_payment, FeeRate, OwnedKey, Payment};
+
+/// Holder of wallet keys that signs on request (POLICY ONLY)
+///
+/// Implemented by the unlocked `Wallet` and by signers that keep
+/// the seed off this machine: a helper process (e.g. a bridge to
+/// a USB hardware wallet) or a shared-folder handoff.
+pub trait Signer {
+    /// Keys available to sign with: (address index, compressed pubkey)
+    fn public_keys(&self) -> Result<Vec<(u32, Vec<u8>)>, &'static str>;
+
+    /// Compact signature of `sighash` by the key behind `pubkey`
+    fn sign(&self, sighash: &[u8], pubkey: &[u8]) -> Result<Vec<u8>, &'static str>;
+}
+
+/// Sign every single-key input whose pubkey `signer` holds.
+/// Returns the number of inputs signed.
+pub fn sign_transaction(tx: &mut Transaction, signer: &dyn Signer) -> Result<usize, &'static str> {
+    let held: Vec<Vec<u8>> = signer.public_keys()?.into_iter().map(|(_, pk)| pk).collect();
+
+    // Inputs are committed to before signing (v5 signature_hash)
+    let sighash = tx.signature_hash();
+    let mut signed = 0;
+
+    for input in &mut tx.inputs {
+        if !input.script_sig.is_empty() || !held.contains(&input.pubkey) {
+            continue;
+        }
+
+        let signature = signer.sign(&sighash, &input.pubkey)?;
+
+        // Never trust an external device blindly
+        if !verify_signature(&sighash, &signature, &input.pubkey) {
+            return Err("signer returned an invalid signature");
+        }
+
+        input.signature = signature;
+        signed += 1;
+    }
+
+    Ok(signed)
+}
+
+/// Pay `recipients` from coins `signer` holds keys for,
+/// returning change to its first key, and sign it
+pub fn create_signed_transaction(
+    signer: &dyn Signer,
+    utxos: &UTXOSet,
+    recipients: &[(Vec<u8>, u64)],
+    fee_rate: FeeRate,
+) -> Result<Payment, &'static str> {
+    let keys: Vec<OwnedKey> = signer
+        .public_keys()?
+        .into_iter()
+        .map(|(index, pk)| OwnedKey::new(index, pk))
+        .collect();
+
+    let change_to = keys.first().ok_or("signer has no keys")?.pubkey_hash.clone();
+
+    let mut payment = build_payment(utxos, &keys, recipients, fee_rate, change_to)?;
+    sign_transaction(&mut payment.tx, signer)?;
+
+    Ok(payment)
+}
+
+/* ───────── Single Key ───────── */
+
+/// Signer for one bare private key (e.g. swept from a paper wallet)
+pub struct KeySigner {
+    secret_key: SecretKey,
+}
+
+impl KeySigner {
+    pub fn new(secret_key: SecretKey) -> Self {
+        Self { secret_key }
+    }
+
+    pub fn pubkey(&self) -> Vec<u8> {
+        public_key(&self.secret_key).serialize().to_vec()
+    }
+}
+
+impl Signer for KeySigner {
+    fn public_keys(&self) -> Result<Vec<(u32, Vec<u8>)>, &'static str> {
+        Ok(vec![(0, self.pubkey())])
+    }
+
+    fn sign(&self, sighash: &[u8], pubkey: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if pubkey != self.pubkey().as_slice() {
+            return Err("unknown key");
+        }
+        Ok(sign(sighash, &self.secret_key))
+    }
+}
+
+impl Drop for KeySigner {
+    fn drop(&mut self) {
+        self.secret_key.non_secure_erase();
+    }
+}
+
+/* ───────── External Process ───────── */
+
+/// Signer behind a helper program
+///
+/// `<program> pubkeys` prints one `<index> <pubkey_hex>` per line;
+/// `<program> sign <sighash_hex> <pubkey_hex>` prints the
+/// signature in hex. The helper talks to the device (USB HID,
+/// serial, …) and may ask the user to confirm.
+pub struct ExternalSigner {
+    program: String,
+    args: Vec<String>,
+}
+
+impl ExternalSigner {
+    /// `command` is split on whitespace: program, then fixed args
+    pub fn new(command: &str) -> Result<Self, &'static str> {
+        let mut parts = command.split_whitespace().map(str::to_string);
+        let program = parts.next().ok_or("empty signer command")?;
+
+        Ok(Self { program, args: parts.collect() })
+    }
+
+    fn run(&self, extra: &[&str]) -> Result<String, &'static str> {
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .args(extra)
+            .output()
+            .map_err(|_| "cannot start signer")?;
+
+        if !output.status.success() {
+            return Err("signer refused");
+        }
+
+        String::from_utf8(output.stdout).map_err(|_| "bad signer output")
+    }
+}
+
+impl Signer for ExternalSigner {
+    fn public_keys(&self) -> Result<Vec<(u32, Vec<u8>)>, &'static str> {
+        self.run(&["pubkeys"])?
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|line| {
+                let (index, pk) = line.trim().split_once(' ').ok_or("bad signer output")?;
+                Ok((
+                    index.parse().map_err(|_| "bad signer output")?,
+                    hex::decode(pk.trim()).map_err(|_| "bad signer output")?,
+                ))
+            })
+            .collect()
+    }
+
+    fn sign(&self, sighash: &[u8], pubkey: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let out = self.run(&["sign", &hex::encode(sighash), &hex::encode(pubkey)])?;
+        hex::decode(out.trim()).map_err(|_| "bad signer output")
+    }
+}
+
+/* ───────── File Handoff ───────── */
+
+/// Give up on a file-handoff signature after this long
+const FILE_SIGNER_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Serialize, Deserialize)]
+struct SignRequest {
+    sighash: String,
+    pubkey: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+/// Signer on the other side of a shared folder (USB stick,
+/// network share) for an air-gapped machine
+///
+/// The device exports `pubkeys.json` (`[[index, "pubkey_hex"], …]`)
+/// once. For each signature this side writes `request.json` and
+/// waits for the device to answer with `response.json`.
+pub struct FileSigner {
+    dir: String,
+}
+
+impl FileSigner {
+    pub fn new(dir: &str) -> Self {
+        Self { dir: dir.to_string() }
+    }
+
+    fn file(&self, name: &str) -> String {
+        Path::new(&self.dir).join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl Signer for FileSigner {
+    fn public_keys(&self) -> Result<Vec<(u32, Vec<u8>)>, &'static str> {
+        let data = fs::read_to_string(self.file("pubkeys.json")).map_err(|_| "pubkeys.json not found")?;
+        let keys: Vec<(u32, String)> = serde_json::from_str(&data).map_err(|_| "invalid pubkeys.json")?;
+
+        keys.into_iter()
+            .map(|(index, pk)| Ok((index, hex::decode(pk).map_err(|_| "invalid pubkeys.json")?)))
+            .collect()
+    }
+
+    fn sign(&self, sighash: &[u8], pubkey: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let response = self.file("response.json");
+        let _ = fs::remove_file(&response);
+
+        let request = SignRequest { sighash: hex::encode(sighash), pubkey: hex::encode(pubkey) };
+        fs::write(self.file("request.json"), serde_json::to_string_pretty(&request).unwrap())
+            .map_err(|_| "cannot write request.json")?;
+
+        eprintln!("📤 Signature request written to {}; waiting for response.json", self.dir);
+
+        let started = Instant::now();
+        while started.elapsed() < FILE_SIGNER_TIMEOUT {
+            if let Some(r) = fs::read_to_string(&response)
+                .ok()
+                .and_then(|d| serde_json::from_str::<SignResponse>(&d).ok())
+            {
+                let _ = fs::remove_file(&response);
+                let _ = fs::remove_file(self.file("request.json"));
+                return hex::decode(r.signature).map_err(|_| "invalid response.json");
+            }
+            sleep(Duration::from_millis(500));
+        }
+
+        Err("signer timed out")
+    }
+}
@@ -79,6 +79,11 @@
 ///
 /// Any change to interval length, math, rounding,
 /// or clamping is a HARD FORK.
+///
+/// From CONSENSUS_V5_HEIGHT the target is recomputed
+/// every block by LWMA over LWMA_WINDOW blocks:
+///   new_target = avg_target × Σ(i × solvetime_i) / (T × N(N+1)/2)
+/// with each solvetime clamped to [1, 6T].
 
 /// ─────────────────────────────────────────────
 /// 4. Block Validity
@@ -108,6 +113,7 @@
 /// - signatures must verify
 /// - input sum ≥ output sum
 /// - fees are implicit
+/// - enforced in blocks from CONSENSUS_V5_HEIGHT
 ///
 /// Coinbase:
 /// - must be the first transaction in a block
@@ -168,6 +174,65 @@
 ///
 /// Differences here MUST NOT affect validity.
 
+/// ─────────────────────────────────────────────
+/// 10. Output Scripts (Consensus v5)
+/// ─────────────────────────────────────────────
+///
+/// Activation:
+/// - height ≥ CONSENSUS_V5_HEIGHT
+///
+/// From activation every output is locked by a script:
+/// - explicit `script_pubkey`, or
+/// - implicit OP_DUP OP_HASH <pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG
+///
+/// Inputs unlock with a push-only `script_sig`
+/// (implicit: <signature> <pubkey>).
+///
+/// Signatures commit to the transaction with all
+/// signatures and unlocking scripts blanked.
+///
+/// Before activation, script fields MUST be empty.
+///
+/// Block limits from activation:
+/// - serialized size ≤ MAX_BLOCK_SIZE
+/// - signature operations ≤ MAX_BLOCK_SIGOPS
+///   (each input charged by the script it spends)
+/// - 4 × bytes + WEIGHT_PER_SIGOP × sigops ≤ MAX_BLOCK_WEIGHT
+/// - txids are unique within the block
+/// - no txid matches one with unspent outputs
+/// - every output value ≤ MAX_MONEY, and per-transaction
+///   input, output and per-block output totals ≤ MAX_MONEY
+
+/// ─────────────────────────────────────────────
+/// 11. UTXO Commitment (Consensus v6)
+/// ─────────────────────────────────────────────
+///
+/// Activation:
+/// - height ≥ CONSENSUS_V6_HEIGHT
+///
+/// From activation every header carries
+/// `utxo_commitment` = SHA256 of the parent's UTXO set,
+/// entries serialized in outpoint order.
+///
+/// Before activation the field MUST be empty and is not
+/// serialized, so earlier header hashes are unchanged.
+///
+/// A UTXO snapshot is trusted once it matches the
+/// commitment of a header on the best chain.
+
+/// ─────────────────────────────────────────────
+/// 12. Coinbase Value (Consensus v7)
+/// ─────────────────────────────────────────────
+///
+/// Activation:
+/// - height ≥ CONSENSUS_V7_HEIGHT
+///
+/// From activation the coinbase outputs total at most
+/// block_reward(height) + the fees of the block's other
+/// transactions (inputs − outputs).
+///
+/// Before activation the coinbase value is unchecked.
+
 /// ─────────────────────────────────────────────
 /// Finality
 /// ─────────────────────────────────────────────
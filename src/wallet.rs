@@ -1,6 +1,8 @@
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
+use std::io::Write;
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use rand::{rngs::OsRng, RngCore};
 use zeroize::Zeroize;
@@ -18,23 +20,118 @@ use pbkdf2::pbkdf2_hmac;
 use bip39::{Mnemonic, Language};
 use hex;
 
+use secp256k1::SecretKey;
+
 use crate::crypto::{
     secret_key_from_seed,
     public_key,
     pubkey_hash,
+    pubkey_hash_from_bytes,
     sign,
+    sha256,
 };
+use crate::crypto::bip32::{ExtendedPrivKey, ExtendedPubKey};
+use crate::crypto::message::sign_message;
 
+use crate::consensus::params::COINBASE_MATURITY;
+use crate::core::block::Block;
 use crate::core::transaction::{Transaction, TxInput, TxOutput};
-use crate::core::utxo::UTXOSet;
-
-const WALLET_FILE: &str = "data/wallet.dat";
-const COINBASE_MATURITY: u64 = 100;
+use crate::core::utxo::{OutPoint, UTXOSet};
+use crate::core::script::{parse_multisig, multisig_unlock, pushed_data};
+use crate::crypto::verify_signature;
+use crate::policy::{is_dust, INCREMENTAL_RELAY_FEE_RATE};
+use crate::psbt::PartiallySignedTransaction;
+use crate::signer::{self, create_signed_transaction, KeySigner, Signer};
+use crate::wallet_events::WalletEvents;
+use crate::wallet_history::{HistoryEntry, WalletHistory};
+use crate::wallet_dump::{DumpedKey, KeyScheme, WalletDump};
+
+/// Path of the "default" wallet
+pub const DEFAULT_WALLET_FILE: &str = "data/wallet.dat";
+
+/// v3: first 32 bytes of the BIP39 seed, SHA256(master || index) keys
+const WALLET_VERSION_LEGACY: u32 = 3;
+/// v4: full 64-byte BIP39 seed, BIP32/BIP44 keys
+const WALLET_VERSION_BIP32: u32 = 4;
+/// v5: KDF recorded in the file; holds either kind of seed
+/// (32 bytes = legacy keys, 64 bytes = BIP32)
+const WALLET_VERSION: u32 = 5;
+
+/// Keys are wiped this long after unlock unless the caller
+/// asks otherwise
+pub const DEFAULT_AUTO_LOCK: Duration = Duration::from_secs(60);
+
+/// BIP44 account: m / purpose' / coin' / account'
+const BIP44_ACCOUNT: &str = "m/44'/0'/0'";
+
+/// Receive (external) chain under the account
+pub(crate) const RECEIVE_CHAIN: u32 = 0;
+
+/// Address indexes searched for keys that own a coin,
+/// beyond the last index known to be used
+pub(crate) const KEY_SEARCH_DEPTH: u32 = 20;
+
+/// BIP44 gap limit: consecutive unused addresses that end discovery
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
 
 /* ───────── Encrypted Wallet File ───────── */
 
+/// Password → encryption key derivation
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// HMAC-SHA256; all v3/v4 files
+    Pbkdf2 { iterations: u32 },
+    /// Memory-hard; `memory_kib` of RAM per guess
+    Argon2id { memory_kib: u32, iterations: u32, parallelism: u32 },
+}
+
+impl Kdf {
+    /// Used for every file written; older files are moved to it
+    /// on their next unlock
+    pub const DEFAULT: Kdf = Kdf::Argon2id {
+        memory_kib: 64 * 1024,
+        iterations: 3,
+        parallelism: 1,
+    };
+
+    /// What v3/v4 files were encrypted with
+    const LEGACY: Kdf = Kdf::Pbkdf2 { iterations: 300_000 };
+
+    fn derive_key(&self, password: &str, salt: &[u8]) -> Result<[u8; 32], &'static str> {
+        let mut key = [0u8; 32];
+
+        match *self {
+            Kdf::Pbkdf2 { iterations } => {
+                pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+            }
+            Kdf::Argon2id { memory_kib, iterations, parallelism } => {
+                let params = argon2::Params::new(memory_kib, iterations, parallelism, Some(32))
+                    .map_err(|_| "invalid Argon2 parameters")?;
+
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password.as_bytes(), salt, &mut key)
+                    .map_err(|_| "key derivation failed")?;
+            }
+        }
+
+        Ok(key)
+    }
+}
+
+/// v5 wallet file
 #[derive(serde::Serialize, serde::Deserialize)]
 struct WalletFile {
+    version: u32,
+    kdf: Kdf,
+    encrypted_master_seed: Vec<u8>,
+    password_salt: Vec<u8>,
+    nonce: Vec<u8>,
+    next_index: u32,
+}
+
+/// v3/v4 layout (no KDF field; always PBKDF2)
+#[derive(serde::Deserialize)]
+struct LegacyWalletFile {
     version: u32,
     encrypted_master_seed: Vec<u8>,
     password_salt: Vec<u8>,
@@ -42,6 +139,115 @@ struct WalletFile {
     next_index: u32,
 }
 
+/// Encrypt `seed` under `password` with the default KDF and
+/// write a current-version wallet file
+fn write_wallet_file(path: &str, password: &str, seed: &[u8], next_index: u32) -> Result<(), &'static str> {
+    if seed.len() != 32 && seed.len() != 64 {
+        return Err("invalid seed length");
+    }
+
+    let mut password_salt = [0u8; 16];
+    OsRng.fill_bytes(&mut password_salt);
+
+    let kdf = Kdf::DEFAULT;
+    let mut enc_key = kdf.derive_key(password, &password_salt)?;
+
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(&enc_key));
+    enc_key.zeroize();
+
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+
+    let encrypted_master_seed = cipher
+        .encrypt(GenericArray::from_slice(&nonce), seed)
+        .map_err(|_| "seed encryption failed")?;
+
+    let wf = WalletFile {
+        version: WALLET_VERSION,
+        kdf,
+        encrypted_master_seed,
+        password_salt: password_salt.to_vec(),
+        nonce: nonce.to_vec(),
+        next_index,
+    };
+
+    save_wallet_file(path, &wf)
+}
+
+/// Replace the wallet file atomically: a crash mid-write
+/// leaves the old seed in place, never a truncated file
+fn save_wallet_file(path: &str, wf: &WalletFile) -> Result<(), &'static str> {
+    let tmp = format!("{}.tmp", path);
+
+    let mut file = fs::File::create(&tmp).map_err(|_| "wallet write failed")?;
+    file.write_all(&bincode::serialize(wf).unwrap()).map_err(|_| "wallet write failed")?;
+    file.sync_all().map_err(|_| "wallet write failed")?;
+
+    fs::rename(&tmp, path).map_err(|_| "wallet write failed")
+}
+
+/// Read any supported version; older layouts come back as v5
+/// with the KDF they were written with
+fn read_wallet_file(path: &str) -> Result<WalletFile, &'static str> {
+    let data = fs::read(path).map_err(|_| "wallet read failed")?;
+
+    // Every layout starts with the version
+    let version: u32 = bincode::deserialize(&data).map_err(|_| "wallet file corrupted")?;
+
+    match version {
+        WALLET_VERSION_LEGACY | WALLET_VERSION_BIP32 => {
+            let old: LegacyWalletFile =
+                bincode::deserialize(&data).map_err(|_| "wallet file corrupted")?;
+
+            let expected = if old.version == WALLET_VERSION_LEGACY { 32 } else { 64 };
+            // AES-GCM adds a 16-byte tag
+            if old.encrypted_master_seed.len() != expected + 16 {
+                return Err("wallet file corrupted");
+            }
+
+            Ok(WalletFile {
+                version: WALLET_VERSION,
+                kdf: Kdf::LEGACY,
+                encrypted_master_seed: old.encrypted_master_seed,
+                password_salt: old.password_salt,
+                nonce: old.nonce,
+                next_index: old.next_index,
+            })
+        }
+        WALLET_VERSION => bincode::deserialize(&data).map_err(|_| "wallet file corrupted"),
+        _ => Err("unsupported wallet version"),
+    }
+}
+
+/// Seed stored in `wf`; Err on a wrong password
+fn decrypt_seed(wf: &WalletFile, password: &str) -> Result<Vec<u8>, ()> {
+    let mut enc_key = wf.kdf.derive_key(password, &wf.password_salt).map_err(|_| ())?;
+
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(&enc_key));
+    enc_key.zeroize();
+
+    let seed_bytes = cipher
+        .decrypt(
+            GenericArray::from_slice(&wf.nonce),
+            wf.encrypted_master_seed.as_ref(),
+        )
+        .map_err(|_| ())?;
+
+    if seed_bytes.len() != 32 && seed_bytes.len() != 64 {
+        return Err(());
+    }
+
+    Ok(seed_bytes)
+}
+
+/// Update `next_index` in the wallet file (stored unencrypted)
+fn store_next_index(path: &str, next_index: u32) -> Result<(), &'static str> {
+    let mut wf = read_wallet_file(path)?;
+
+    wf.next_index = next_index;
+    save_wallet_file(path, &wf)
+}
+
 /* ───────── Memory Lock ───────── */
 
 fn lock_memory(bytes: &mut [u8]) {
@@ -52,6 +258,7 @@ fn lock_memory(bytes: &mut [u8]) {
 
 /* ───────── HD Derivation ───────── */
 
+/// Pre-BIP32 scheme, kept so old coins stay spendable
 fn derive_child_seed(master: &[u8; 32], index: u32) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(master);
@@ -63,12 +270,92 @@ fn derive_child_seed(master: &[u8; 32], index: u32) -> [u8; 32] {
     out
 }
 
+/// Unlocked key material
+///
+/// The legacy master is the first 32 bytes of the BIP39 seed,
+/// so a wallet upgraded to BIP32 still finds coins received
+/// under the old scheme.
+struct Keychain {
+    legacy: [u8; 32],
+    /// m/44'/0'/0'/0 (None until a v3 wallet is upgraded)
+    receive: Option<ExtendedPrivKey>,
+    /// m/44'/0'/0', shared with watch-only wallets
+    account_xpub: Option<ExtendedPubKey>,
+}
+
+impl Keychain {
+    /// 64-byte BIP39 seed (v4) or 32-byte legacy master (v3)
+    fn from_seed(seed: &[u8]) -> Result<Self, &'static str> {
+        if seed.len() < 32 {
+            return Err("seed too short");
+        }
+
+        let mut legacy = [0u8; 32];
+        legacy.copy_from_slice(&seed[..32]);
+        lock_memory(&mut legacy);
+
+        let (receive, account_xpub) = if seed.len() == 64 {
+            let account = ExtendedPrivKey::master(seed)?.derive_path(BIP44_ACCOUNT)?;
+            (Some(account.derive_child(RECEIVE_CHAIN)?), Some(account.public()))
+        } else {
+            (None, None)
+        };
+
+        Ok(Self { legacy, receive, account_xpub })
+    }
+
+    /// Key for a receive address index
+    fn secret_key(&self, index: u32) -> Result<SecretKey, &'static str> {
+        match &self.receive {
+            Some(chain) => Ok(chain.derive_child(index)?.secret_key),
+            None => Ok(secret_key_from_seed(&derive_child_seed(&self.legacy, index))),
+        }
+    }
+
+    /// Keys at one address index under each scheme, BIP44 first
+    fn keys_at(&self, index: u32) -> Vec<SecretKey> {
+        let mut keys = Vec::new();
+
+        if let Some(chain) = &self.receive {
+            if let Ok(child) = chain.derive_child(index) {
+                keys.push(child.secret_key);
+            }
+        }
+        keys.push(secret_key_from_seed(&derive_child_seed(&self.legacy, index)));
+
+        keys
+    }
+
+    /// Every key coins may have been received to in the
+    /// first `depth` indexes
+    fn search_keys(&self, depth: u32) -> Vec<(u32, SecretKey)> {
+        (0..depth)
+            .flat_map(|index| self.keys_at(index).into_iter().map(move |sk| (index, sk)))
+            .collect()
+    }
+
+    fn wipe(&mut self) {
+        self.legacy.zeroize();
+        if let Some(chain) = &mut self.receive {
+            chain.wipe();
+        }
+    }
+}
+
 /* ───────── Wallet Struct ───────── */
 
 pub struct Wallet {
-    master_seed: Option<[u8; 32]>,
+    /// Encrypted wallet file
+    path: String,
+    keys: Option<Keychain>,
     last_unlock: Option<Instant>,
+    /// None = stay unlocked until `lock()`
+    auto_lock: Option<Duration>,
     next_index: u32,
+    /// Coins never picked by automatic coin selection
+    locked_coins: BTreeSet<OutPoint>,
+    /// Subscribers; addresses stay watched while locked
+    events: WalletEvents,
 }
 
 /* ───────── Balance Struct (UI ONLY) ───────── */
@@ -83,13 +370,22 @@ pub fn calculate_wallet_balance(
     utxos: &UTXOSet,
     my_pubkey_hash: &[u8],
     current_height: u64,
+) -> WalletBalance {
+    calculate_balance(utxos, |h| h == my_pubkey_hash, current_height)
+}
+
+/// Balance of every UTXO whose pubkey hash satisfies `owns`
+pub fn calculate_balance(
+    utxos: &UTXOSet,
+    owns: impl Fn(&[u8]) -> bool,
+    current_height: u64,
 ) -> WalletBalance {
     let mut total = 0;
     let mut spendable = 0;
     let mut locked = 0;
 
     for u in utxos.values() {
-        if u.pubkey_hash != my_pubkey_hash {
+        if !owns(&u.pubkey_hash) {
             continue;
         }
 
@@ -107,225 +403,1204 @@ pub fn calculate_wallet_balance(
     WalletBalance { total, spendable, locked }
 }
 
-/* ───────── Wallet Impl ───────── */
+/// Wallet funds by when they can be spent (UI ONLY)
+#[derive(serde::Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Balances {
+    /// Mature coins not spent by a mempool transaction
+    pub confirmed: u64,
+    /// Unconfirmed outputs paying the wallet: incoming
+    /// payments and change of its own mempool spends
+    pub pending: u64,
+    /// Coinbase outputs younger than COINBASE_MATURITY
+    pub immature: u64,
+}
 
-impl Wallet {
-    pub fn load_or_create(password: &str) -> Self {
-        fs::create_dir_all("data").unwrap();
-
-        if Path::new(WALLET_FILE).exists() {
-            let mut w = Wallet {
-                master_seed: None,
-                last_unlock: None,
-                next_index: 0,
-            };
+/// Funds of every pubkey hash satisfying `owns`, taking the
+/// `unconfirmed` (mempool) transactions into account
+pub fn calculate_balances<'a>(
+    utxos: &UTXOSet,
+    unconfirmed: impl IntoIterator<Item = &'a Transaction>,
+    owns: impl Fn(&[u8]) -> bool,
+    current_height: u64,
+) -> Balances {
+    let unconfirmed: Vec<&Transaction> = unconfirmed.into_iter().collect();
+    let spent: HashSet<OutPoint> = unconfirmed
+        .iter()
+        .flat_map(|tx| tx.inputs.iter().filter_map(|i| i.outpoint()))
+        .collect();
+
+    let mut balances = Balances::default();
+
+    for (outpoint, u) in utxos {
+        if !owns(&u.pubkey_hash) || spent.contains(outpoint) {
+            continue;
+        }
 
-            if let Err(_) = w.unlock(password) {
-                eprintln!("❌ Wallet unlock failed.");
-                eprintln!("Possible reasons:");
-                eprintln!("• Incorrect password");
-                eprintln!("• Wallet was created with a different passphrase");
-                eprintln!("• Wallet file is corrupted");
-                std::process::exit(1);
+        if u.is_coinbase && current_height < u.height + COINBASE_MATURITY {
+            balances.immature += u.value;
+        } else {
+            balances.confirmed += u.value;
+        }
+    }
+
+    for tx in unconfirmed {
+        for (outpoint, o) in tx.outpoints() {
+            if owns(&o.pubkey_hash) && !spent.contains(&outpoint) {
+                balances.pending += o.value;
             }
+        }
+    }
+
+    balances
+}
+
+/* ───────── Wallet Impl ───────── */
 
-            w
+impl Wallet {
+    /// Open the wallet at `path`, creating it if missing
+    pub fn load_or_create(path: &str, password: &str) -> Self {
+        if Path::new(path).exists() {
+            match Self::open(path, password, Some(DEFAULT_AUTO_LOCK)) {
+                Ok(w) => w,
+                Err(_) => {
+                    eprintln!("❌ Wallet unlock failed.");
+                    eprintln!("Possible reasons:");
+                    eprintln!("• Incorrect password");
+                    eprintln!("• Wallet was created with a different passphrase");
+                    eprintln!("• Wallet file is corrupted");
+                    std::process::exit(1);
+                }
+            }
         } else {
-            Self::create_new(password)
+            Self::create_new(path, password)
         }
     }
 
-    fn create_new(password: &str) -> Self {
-        let mut entropy = [0u8; 16];
-        OsRng.fill_bytes(&mut entropy);
+    /// Unlock an existing wallet file
+    pub fn open(path: &str, password: &str, auto_lock: Option<Duration>) -> Result<Self, &'static str> {
+        let mut w = Wallet {
+            path: path.to_string(),
+            keys: None,
+            last_unlock: None,
+            auto_lock: None,
+            next_index: 0,
+            locked_coins: load_locked_coins(path),
+            events: WalletEvents::default(),
+        };
+
+        w.unlock(password, auto_lock).map_err(|_| "wallet unlock failed")?;
+        Ok(w)
+    }
 
-        let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy)
-            .expect("mnemonic generation failed");
+    /// New wallet at `path`; shows the recovery phrase on stderr
+    /// (stdout may be `--json` output)
+    pub fn create_new(path: &str, password: &str) -> Self {
+        let mnemonic = generate_mnemonic();
 
-        println!("\n⚠️ WRITE THIS DOWN — WALLET RECOVERY PHRASE ⚠️");
-        println!("{}", mnemonic.to_string());
-        println!("⚠️ ANYONE WITH THESE WORDS CAN SPEND YOUR COINS ⚠️\n");
+        eprintln!("\n⚠️ WRITE THIS DOWN — WALLET RECOVERY PHRASE ⚠️");
+        eprintln!("{}", mnemonic);
+        eprintln!("⚠️ ANYONE WITH THESE WORDS CAN SPEND YOUR COINS ⚠️\n");
 
-        Self::create_from_mnemonic(password, &mnemonic.to_string())
+        Self::create_from_mnemonic(path, password, &mnemonic)
             .expect("wallet creation failed")
     }
 
     pub fn create_from_mnemonic(
+        path: &str,
         password: &str,
         mnemonic_phrase: &str,
     ) -> Result<Self, &'static str> {
         let mnemonic = Mnemonic::parse_in_normalized(Language::English, mnemonic_phrase)
             .map_err(|_| "invalid mnemonic")?;
 
-        let seed = mnemonic.to_seed("");
-        let mut master_seed = [0u8; 32];
-        master_seed.copy_from_slice(&seed[..32]);
+        let mut seed = mnemonic.to_seed("");
+        lock_memory(&mut seed);
 
-        let mut password_salt = [0u8; 16];
-        OsRng.fill_bytes(&mut password_salt);
+        let wallet = Self::create_from_seed(path, password, &seed, 0);
+        seed.zeroize();
+        wallet
+    }
 
-        let mut enc_key = [0u8; 32];
-        pbkdf2_hmac::<Sha256>(
-            password.as_bytes(),
-            &password_salt,
-            300_000,
-            &mut enc_key,
-        );
+    /// New wallet file at `path` holding `seed` (64-byte BIP39
+    /// seed or 32-byte legacy master), e.g. from a wallet dump
+    pub fn create_from_seed(
+        path: &str,
+        password: &str,
+        seed: &[u8],
+        next_index: u32,
+    ) -> Result<Self, &'static str> {
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir).map_err(|_| "wallet write failed")?;
+        }
+
+        write_wallet_file(path, password, seed, next_index)?;
+        let keys = Keychain::from_seed(seed)?;
+
+        let mut wallet = Wallet {
+            path: path.to_string(),
+            keys: Some(keys),
+            last_unlock: Some(Instant::now()),
+            auto_lock: Some(DEFAULT_AUTO_LOCK),
+            next_index,
+            locked_coins: load_locked_coins(path),
+            events: WalletEvents::default(),
+        };
+        wallet.watch_addresses();
 
-        let cipher = Aes256Gcm::new(GenericArray::from_slice(&enc_key));
+        Ok(wallet)
+    }
 
-        let mut nonce = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce);
+    /// Move a v3 wallet to BIP32 keys
+    ///
+    /// v3 files only hold the first half of the BIP39 seed, so the
+    /// recovery phrase is needed to rebuild the full seed. Coins at
+    /// legacy addresses stay spendable; new addresses are BIP44.
+    pub fn upgrade_to_bip32(
+        &mut self,
+        password: &str,
+        mnemonic_phrase: &str,
+    ) -> Result<(), &'static str> {
+        let keys = self.keychain()?;
+        if keys.receive.is_some() {
+            return Err("wallet already uses BIP32");
+        }
 
-        let encrypted_master_seed = cipher
-            .encrypt(GenericArray::from_slice(&nonce), &master_seed[..])
-            .map_err(|_| "seed encryption failed")?;
+        // The file is re-encrypted, so refuse a mistyped password
+        let mut current = decrypt_seed(&read_wallet_file(&self.path)?, password).map_err(|_| "wrong password")?;
+        current.zeroize();
 
-        let wf = WalletFile {
-            version: 3,
-            encrypted_master_seed,
-            password_salt: password_salt.to_vec(),
-            nonce: nonce.to_vec(),
-            next_index: 0,
-        };
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, mnemonic_phrase)
+            .map_err(|_| "invalid mnemonic")?;
 
-        fs::write(WALLET_FILE, bincode::serialize(&wf).unwrap()).unwrap();
-        lock_memory(&mut master_seed);
+        let mut seed = mnemonic.to_seed("");
+        lock_memory(&mut seed);
 
-        Ok(Wallet {
-            master_seed: Some(master_seed),
-            last_unlock: Some(Instant::now()),
-            next_index: 0,
-        })
-    }
+        if seed[..32] != keys.legacy {
+            seed.zeroize();
+            return Err("mnemonic does not match this wallet");
+        }
 
-    pub fn unlock(&mut self, password: &str) -> Result<(), ()> {
-        let data = fs::read(WALLET_FILE).map_err(|_| ())?;
-        let wf: WalletFile = bincode::deserialize(&data).map_err(|_| ())?;
+        write_wallet_file(&self.path, password, &seed, self.next_index)?;
+        let upgraded = Keychain::from_seed(&seed)?;
+        seed.zeroize();
 
-        let mut enc_key = [0u8; 32];
-        pbkdf2_hmac::<Sha256>(
-            password.as_bytes(),
-            &wf.password_salt,
-            300_000,
-            &mut enc_key,
-        );
+        if let Some(mut old) = self.keys.replace(upgraded) {
+            old.wipe();
+        }
+        self.watch_addresses();
+
+        Ok(())
+    }
 
-        let cipher = Aes256Gcm::new(GenericArray::from_slice(&enc_key));
+    /// Wallet file this wallet was opened from
+    pub fn path(&self) -> &str {
+        &self.path
+    }
 
-        let seed_bytes = cipher
-            .decrypt(
-                GenericArray::from_slice(&wf.nonce),
-                wf.encrypted_master_seed.as_ref(),
-            )
-            .map_err(|_| ())?;
+    /// True until the wallet is upgraded to BIP32 keys
+    pub fn is_legacy(&self) -> bool {
+        matches!(&self.keys, Some(k) if k.receive.is_none())
+    }
 
-        let mut master_seed = [0u8; 32];
-        master_seed.copy_from_slice(&seed_bytes[..32]);
+    /// Decrypt the keys; they are wiped again after `auto_lock`
+    /// (None keeps them until `lock()`)
+    pub fn unlock(&mut self, password: &str, auto_lock: Option<Duration>) -> Result<(), ()> {
+        let wf = read_wallet_file(&self.path).map_err(|_| ())?;
+        let mut seed_bytes = decrypt_seed(&wf, password)?;
+
+        // Re-encrypt files from before the current KDF, keeping
+        // the old file as .bak
+        if wf.kdf != Kdf::DEFAULT {
+            let backup = format!("{}.bak", self.path);
+            let upgraded = fs::copy(&self.path, &backup)
+                .map_err(|_| "wallet backup failed")
+                .and_then(|_| write_wallet_file(&self.path, password, &seed_bytes, wf.next_index));
+
+            match upgraded {
+                Ok(()) => eprintln!("🔐 Wallet encryption upgraded to Argon2id (previous file kept as {})", backup),
+                Err(e) => eprintln!("⚠️ Wallet encryption upgrade failed: {}", e),
+            }
+        }
 
-        lock_memory(&mut master_seed);
+        let keys = Keychain::from_seed(&seed_bytes).map_err(|_| ())?;
+        seed_bytes.zeroize();
 
-        self.master_seed = Some(master_seed);
+        if let Some(mut old) = self.keys.replace(keys) {
+            old.wipe();
+        }
         self.last_unlock = Some(Instant::now());
+        self.auto_lock = auto_lock;
         self.next_index = wf.next_index;
+        self.watch_addresses();
 
         Ok(())
     }
 
+    /// Re-encrypt the seed under a new password; the recovery
+    /// phrase and keys are unchanged
+    pub fn change_password(&mut self, old: &str, new: &str) -> Result<(), &'static str> {
+        let wf = read_wallet_file(&self.path)?;
+        let mut seed = decrypt_seed(&wf, old).map_err(|_| "wrong password")?;
+
+        let result = write_wallet_file(&self.path, new, &seed, wf.next_index);
+        seed.zeroize();
+        result
+    }
+
+    /// Wipe the keys if the auto-lock period has passed
+    pub fn lock_if_expired(&mut self) {
+        if self.keys.is_some() && self.expired() {
+            self.lock();
+        }
+    }
+
+    fn expired(&self) -> bool {
+        match (self.last_unlock, self.auto_lock) {
+            (Some(t), Some(limit)) => t.elapsed() >= limit,
+            _ => false,
+        }
+    }
+
+    /// Unlocked keys, unless locked or past the auto-lock period
+    fn keychain(&self) -> Result<&Keychain, &'static str> {
+        if self.expired() {
+            return Err("wallet locked");
+        }
+        self.keys.as_ref().ok_or("wallet locked")
+    }
+
     pub fn lock(&mut self) {
-        if let Some(mut s) = self.master_seed.take() {
-            s.zeroize();
+        if let Some(mut k) = self.keys.take() {
+            k.wipe();
         }
         self.last_unlock = None;
     }
 
     pub fn address(&self) -> Result<Vec<u8>, &'static str> {
-        let master = self.master_seed.ok_or("wallet locked")?;
-        let child = derive_child_seed(&master, 0);
-        let sk = secret_key_from_seed(&child);
-        let pk = public_key(&sk);
+        let keys = self.keychain()?;
+        let pk = public_key(&keys.secret_key(0)?);
         Ok(pubkey_hash(&pk))
     }
 
+    /// Pay every (pubkey hash, amount) in `recipients` in one
+    /// transaction at `fee_rate`, returning change to address 0;
+    /// the result reports the fee paid. Locked coins are never
+    /// selected.
     pub fn create_transaction(
         &mut self,
         utxos: &UTXOSet,
-        to_pubkey_hash: Vec<u8>,
-        amount: u64,
-    ) -> Result<Transaction, &'static str> {
-        let master_seed = self.master_seed.ok_or("wallet locked")?;
+        recipients: &[(Vec<u8>, u64)],
+        fee_rate: FeeRate,
+    ) -> Result<Payment, &'static str> {
+        let owned: HashSet<Vec<u8>> = self.receive_hashes()?.into_iter().collect();
+
+        let selectable: UTXOSet = utxos
+            .iter()
+            .filter(|(op, u)| owned.contains(&u.pubkey_hash) && !self.locked_coins.contains(*op))
+            .map(|(op, u)| (*op, u.clone()))
+            .collect();
+
+        create_signed_transaction(self, &selectable, recipients, fee_rate)
+    }
+
+    /// Every key the wallet may have received to
+    fn owned_keys(&self) -> Result<Vec<OwnedKey>, &'static str> {
+        let keys = self.keychain()?;
+        Ok(keys
+            .search_keys(self.next_index + KEY_SEARCH_DEPTH)
+            .iter()
+            .map(|(index, sk)| OwnedKey::new(*index, public_key(sk).serialize().to_vec()))
+            .collect())
+    }
+
+    /// Sign every single-key input whose pubkey belongs to this
+    /// wallet (e.g. a transaction built by a watch-only wallet).
+    /// Returns the number of inputs signed.
+    pub fn sign_transaction(&self, tx: &mut Transaction) -> Result<usize, &'static str> {
+        signer::sign_transaction(tx, self)
+    }
+
+    /// Account xpub (m/44'/0'/0') for a watch-only wallet
+    pub fn xpub(&self) -> Result<String, &'static str> {
+        let keys = self.keychain()?;
+        keys.account_xpub
+            .as_ref()
+            .map(|x| x.to_base58())
+            .ok_or("legacy wallet has no xpub; run `wallet upgrade`")
+    }
+
+    /// Sign `message` with the key of `address` (pubkey hash) to
+    /// prove ownership off-chain; see `crypto::message`
+    pub fn sign_message(&self, address: &[u8], message: &str) -> Result<Vec<u8>, &'static str> {
+        self.keychain()?
+            .search_keys(self.next_index + KEY_SEARCH_DEPTH)
+            .into_iter()
+            .find(|(_, sk)| pubkey_hash(&public_key(sk)) == address)
+            .map(|(_, sk)| sign_message(message, &sk))
+            .ok_or("address not in wallet")
+    }
+}
+
+/* ───────── Signer ───────── */
+
+/// The unlocked wallet signs with its own keys
+impl Signer for Wallet {
+    fn public_keys(&self) -> Result<Vec<(u32, Vec<u8>)>, &'static str> {
+        Ok(self.owned_keys()?.into_iter().map(|k| (k.index, k.pubkey)).collect())
+    }
+
+    fn sign(&self, sighash: &[u8], pubkey: &[u8]) -> Result<Vec<u8>, &'static str> {
+        self.keychain()?
+            .search_keys(self.next_index + KEY_SEARCH_DEPTH)
+            .into_iter()
+            .find(|(_, sk)| public_key(sk).serialize()[..] == *pubkey)
+            .map(|(_, sk)| sign(sighash, &sk))
+            .ok_or("key not in wallet")
+    }
+}
+
+/* ───────── PSBT ───────── */
+
+impl Wallet {
+    /// Sign every input of `psbt` this wallet holds a key for,
+    /// single-key and multisig. Returns the number of signatures
+    /// added.
+    pub fn sign_psbt(&self, psbt: &mut PartiallySignedTransaction) -> Result<usize, &'static str> {
+        psbt.check()?;
+        let coins = psbt.utxo_set();
+
+        Ok(self.sign_transaction(&mut psbt.tx)? + self.cosign(&mut psbt.tx, &coins)?)
+    }
+}
+
+/* ───────── Address Discovery ───────── */
+
+impl Wallet {
+    /// First address index never used
+    pub fn next_index(&self) -> u32 {
+        self.next_index
+    }
+
+    /// Find the addresses `blocks` paid to and move `next_index`
+    /// past the last used one
+    ///
+    /// Derives keys until `gap_limit` consecutive indexes never
+    /// received a coin (BIP44 account discovery), under both the
+    /// BIP44 and legacy schemes. Needed after restoring from a
+    /// mnemonic, which starts at index 0. Returns `next_index`.
+    pub fn discover_used_addresses(
+        &mut self,
+        blocks: &[Block],
+        gap_limit: u32,
+    ) -> Result<u32, &'static str> {
+        let keys = self.keychain()?;
+
+        let paid: HashSet<&[u8]> = blocks
+            .iter()
+            .flat_map(|b| &b.transactions)
+            .flat_map(|tx| &tx.outputs)
+            .map(|o| o.pubkey_hash.as_slice())
+            .collect();
+
+        let mut next = 0;
+        let mut index = 0;
+
+        while index < next + gap_limit.max(1) {
+            let used = keys
+                .keys_at(index)
+                .iter()
+                .any(|sk| paid.contains(pubkey_hash(&public_key(sk)).as_slice()));
+
+            if used {
+                next = index + 1;
+            }
+            index += 1;
+        }
+
+        if next != self.next_index {
+            self.next_index = next;
+            store_next_index(&self.path, next)?;
+            self.watch_addresses();
+        }
+
+        Ok(next)
+    }
+}
 
+/* ───────── Coin Locking ───────── */
+
+/// Locked coins of the wallet at `path`, kept next to it
+fn locked_coins_file(path: &str) -> String {
+    Path::new(path).with_extension("locked.json").to_string_lossy().into_owned()
+}
+
+fn load_locked_coins(path: &str) -> BTreeSet<OutPoint> {
+    fs::read_to_string(locked_coins_file(path))
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok())
+        .unwrap_or_default()
+}
+
+impl Wallet {
+    /// Reserve `outpoint` for a manual spend: `create_transaction`
+    /// skips it until unlocked. False if it was already locked.
+    pub fn lock_coin(&mut self, outpoint: OutPoint) -> Result<bool, &'static str> {
+        let added = self.locked_coins.insert(outpoint);
+        self.save_locked_coins()?;
+        Ok(added)
+    }
+
+    /// Return `outpoint` to coin selection; false if it was not
+    /// locked
+    pub fn unlock_coin(&mut self, outpoint: &OutPoint) -> Result<bool, &'static str> {
+        let removed = self.locked_coins.remove(outpoint);
+        self.save_locked_coins()?;
+        Ok(removed)
+    }
+
+    pub fn locked_coins(&self) -> impl Iterator<Item = &OutPoint> {
+        self.locked_coins.iter()
+    }
+
+    fn save_locked_coins(&self) -> Result<(), &'static str> {
+        fs::write(
+            locked_coins_file(&self.path),
+            serde_json::to_string_pretty(&self.locked_coins).unwrap(),
+        )
+        .map_err(|_| "cannot write locked coins")
+    }
+}
+
+/* ───────── Events ───────── */
+
+impl Wallet {
+    /// Call `f(txid, amount)` when a payment to the wallet is
+    /// first seen
+    pub fn on_received(&mut self, f: impl Fn(&[u8], u64) + Send + 'static) {
+        self.events.on_received(f);
+    }
+
+    /// Call `f(txid, height)` when a wallet transaction is mined
+    pub fn on_confirmed(&mut self, f: impl Fn(&[u8], u64) + Send + 'static) {
+        self.events.on_confirmed(f);
+    }
+
+    /// Call `f(txid)` when a pending wallet transaction leaves
+    /// the mempool unmined
+    pub fn on_conflicted(&mut self, f: impl Fn(&[u8]) + Send + 'static) {
+        self.events.on_conflicted(f);
+    }
+
+    /// Fire events for `blocks` (the active chain) and the
+    /// current `mempool`
+    pub fn process_events<'a>(&mut self, blocks: &[Block], mempool: impl IntoIterator<Item = &'a Transaction>) {
+        self.events.process(blocks, mempool);
+    }
+
+    /// Remember the current addresses, for events while locked
+    fn watch_addresses(&mut self) {
+        if let Ok(hashes) = self.receive_hashes() {
+            self.events.watch(hashes);
+        }
+    }
+}
+
+/* ───────── Balances ───────── */
+
+impl Wallet {
+    /// Confirmed, pending and immature funds across every
+    /// address, with `unconfirmed` the mempool transactions
+    pub fn balances<'a>(
+        &self,
+        utxos: &UTXOSet,
+        unconfirmed: impl IntoIterator<Item = &'a Transaction>,
+        current_height: u64,
+    ) -> Result<Balances, &'static str> {
+        let hashes: HashSet<Vec<u8>> = self.receive_hashes()?.into_iter().collect();
+        Ok(calculate_balances(utxos, unconfirmed, |h| hashes.contains(h), current_height))
+    }
+}
+
+/* ───────── History ───────── */
+
+impl Wallet {
+    /// Pubkey hashes of every address the wallet searches
+    pub fn receive_hashes(&self) -> Result<Vec<Vec<u8>>, &'static str> {
+        Ok(self.owned_keys()?.into_iter().map(|k| k.pubkey_hash).collect())
+    }
+
+    /// History file kept next to the wallet file
+    pub fn history_file(&self) -> String {
+        Path::new(&self.path).with_extension("history.json").to_string_lossy().into_owned()
+    }
+
+    /// Wallet transactions on `blocks`, newest first
+    ///
+    /// Brings the persisted history up to date with the chain
+    /// before reading it.
+    pub fn history(&self, blocks: &[Block]) -> Result<Vec<HistoryEntry>, &'static str> {
+        let mut history = WalletHistory::load(&self.history_file(), &self.receive_hashes()?);
+        if history.sync(blocks) {
+            history.save();
+        }
+
+        let tip = blocks.last().map_or(0, |b| b.header.height);
+        Ok(history.entries(tip))
+    }
+
+    /// Label a wallet transaction; an empty label removes it
+    pub fn set_label(&self, txid: &str, label: &str) -> Result<(), &'static str> {
+        let mut history = WalletHistory::load(&self.history_file(), &self.receive_hashes()?);
+        history.set_label(txid, label);
+        history.save();
+        Ok(())
+    }
+
+    /// Rebuild the history by replaying `blocks` from `from_height`
+    ///
+    /// For imported keys or a restored backup: used addresses are
+    /// discovered first (see `discover_used_addresses`), so coins
+    /// paid past `next_index` count as the wallet's too. Returns
+    /// the number of history entries found.
+    pub fn rescan(&mut self, blocks: &[Block], from_height: u64, gap_limit: u32) -> Result<usize, &'static str> {
+        self.discover_used_addresses(blocks, gap_limit)?;
+
+        let mut history = WalletHistory::load(&self.history_file(), &self.receive_hashes()?);
+        history.rescan(blocks, from_height);
+        history.save();
+
+        let tip = blocks.last().map_or(0, |b| b.header.height);
+        Ok(history.entries(tip).len())
+    }
+}
+
+/// Fresh 12-word BIP39 recovery phrase
+pub fn generate_mnemonic() -> String {
+    let mut entropy = [0u8; 16];
+    OsRng.fill_bytes(&mut entropy);
+
+    let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy)
+        .expect("mnemonic generation failed");
+    entropy.zeroize();
+
+    mnemonic.to_string()
+}
+
+/* ───────── Transaction Building ───────── */
+
+/// A public key the wallet can receive to
+pub(crate) struct OwnedKey {
+    pub index: u32,
+    pub pubkey: Vec<u8>,
+    pub pubkey_hash: Vec<u8>,
+}
+
+impl OwnedKey {
+    pub fn new(index: u32, pubkey: Vec<u8>) -> Self {
+        let pubkey_hash = pubkey_hash_from_bytes(&pubkey);
+        Self { index, pubkey, pubkey_hash }
+    }
+}
+
+/// Fee target for a new transaction (POLICY ONLY)
+#[derive(Debug, Clone, Copy)]
+pub enum FeeRate {
+    /// Explicit satoshis per byte
+    SatPerByte(u64),
+    /// Per 1000 bytes, as returned by the fee estimator
+    PerKilobyte(i64),
+}
+
+impl FeeRate {
+    /// Fee for a transaction of `size` bytes (at least 1)
+    pub fn fee_for(&self, size: usize) -> u64 {
+        let fee = match *self {
+            FeeRate::SatPerByte(rate) => rate.saturating_mul(size as u64),
+            FeeRate::PerKilobyte(rate) => rate.max(0) as u64 * size as u64 / 1000,
+        };
+        fee.max(1)
+    }
+}
+
+/// A built transaction and the fee it pays
+pub struct Payment {
+    pub tx: Transaction,
+    pub fee: u64,
+}
+
+/// Same estimate as `Transaction::serialized_size`
+pub(crate) fn estimated_size(inputs: usize, outputs: usize) -> usize {
+    inputs * 148 + outputs * 34 + 10
+}
+
+/// Output paying `to`: a pubkey hash, or a multisig locking
+/// script shared by its cosigners
+pub(crate) fn payment_output(value: u64, to: Vec<u8>) -> TxOutput {
+    if to.len() != 32 && parse_multisig(&to).is_some() {
+        return TxOutput {
+            value,
+            pubkey_hash: sha256(&to),
+            script_pubkey: to,
+        };
+    }
+
+    TxOutput {
+        value,
+        pubkey_hash: to,
+        script_pubkey: vec![],
+    }
+}
+
+/// Select coins locked to `keys` and build an unsigned payment
+/// to every recipient, change going to `change_to`
+///
+/// Coins are added until they cover the amount plus the fee for
+/// the transaction's size, which grows with every input. Change
+/// that would be dust is dropped and left to the miner instead.
+pub(crate) fn build_payment(
+    utxos: &UTXOSet,
+    keys: &[OwnedKey],
+    recipients: &[(Vec<u8>, u64)],
+    fee_rate: FeeRate,
+    change_to: Vec<u8>,
+) -> Result<Payment, &'static str> {
+    if recipients.is_empty() {
+        return Err("no recipients");
+    }
+    if recipients.iter().any(|(_, value)| is_dust(*value)) {
+        return Err("amount below dust threshold");
+    }
+
+    let amount = recipients
+        .iter()
+        .try_fold(0u64, |sum, (_, value)| sum.checked_add(*value))
+        .ok_or("amount overflow")?;
+    let paid = recipients.len();
+
+    let mut collected = 0u64;
+    let mut selected = Vec::new();
+    let mut plan = None;
+
+    for (outpoint, utxo) in utxos {
+        let key = match keys.iter().find(|k| k.pubkey_hash == utxo.pubkey_hash) {
+            Some(k) => k,
+            None => continue,
+        };
+
+        selected.push((outpoint, key));
+        collected += utxo.value;
+
+        // Not even enough without a change output
+        if collected < amount + fee_rate.fee_for(estimated_size(selected.len(), paid)) {
+            continue;
+        }
+
+        let fee_with_change = fee_rate.fee_for(estimated_size(selected.len(), paid + 1));
+        let change = collected.saturating_sub(amount + fee_with_change);
+
+        // Dust change is left to the miner
+        plan = Some(if !is_dust(change) {
+            (fee_with_change, change)
+        } else {
+            (collected - amount, 0)
+        });
+        break;
+    }
+
+    let (fee, change) = plan.ok_or("not enough funds")?;
+
+    let mut outputs: Vec<TxOutput> = recipients
+        .iter()
+        .map(|(to, value)| payment_output(*value, to.clone()))
+        .collect();
+
+    if change > 0 {
+        outputs.push(TxOutput {
+            value: change,
+            pubkey_hash: change_to,
+            script_pubkey: vec![],
+        });
+    }
+
+    let inputs = selected
+        .into_iter()
+        .map(|(outpoint, key)| TxInput {
+            txid: outpoint.txid.to_vec(),
+            index: outpoint.vout,
+            signature: vec![],
+            pubkey: key.pubkey.clone(),
+            address_index: key.index,
+            script_sig: vec![],
+        })
+        .collect();
+
+    Ok(Payment {
+        tx: Transaction { inputs, outputs },
+        fee,
+    })
+}
+
+/* ───────── Fee Bumping ───────── */
+
+impl Wallet {
+    /// Rebuild the unconfirmed outgoing `original` at `fee_rate`,
+    /// taking the extra fee from its change (POLICY ONLY)
+    ///
+    /// The rebuilt transaction spends the same coins, so the
+    /// mempool takes it as a replacement (RBF) of `original`.
+    /// It pays at least the original fee plus its own relay at
+    /// INCREMENTAL_RELAY_FEE_RATE. Change too small to cover
+    /// the increase without becoming dust is dropped entirely.
+    pub fn bump_fee(
+        &self,
+        original: &Transaction,
+        utxos: &UTXOSet,
+        fee_rate: FeeRate,
+    ) -> Result<Payment, &'static str> {
+        let keys = self.owned_keys()?;
+
+        if original
+            .inputs
+            .iter()
+            .any(|i| !keys.iter().any(|k| k.pubkey == i.pubkey))
+        {
+            return Err("transaction spends coins not in this wallet");
+        }
+
+        let mut input_total = 0u64;
+        for input in &original.inputs {
+            let coin = input
+                .outpoint()
+                .and_then(|op| utxos.get(&op))
+                .ok_or("input coin not found (unconfirmed parent?)")?;
+            input_total += coin.value;
+        }
+
+        let output_total: u64 = original.outputs.iter().map(|o| o.value).sum();
+        let old_fee = input_total
+            .checked_sub(output_total)
+            .ok_or("outputs exceed inputs")?;
+
+        let change_at = original
+            .outputs
+            .iter()
+            .rposition(|o| o.script_pubkey.is_empty() && keys.iter().any(|k| k.pubkey_hash == o.pubkey_hash))
+            .ok_or("no change output to take the fee from")?;
+
+        let size = estimated_size(original.inputs.len(), original.outputs.len());
+        let relay_fee = INCREMENTAL_RELAY_FEE_RATE as u64 * size as u64 / 1000;
+        let fee = fee_rate.fee_for(size).max(old_fee + relay_fee);
+
+        let mut tx = original.clone();
+        for input in &mut tx.inputs {
+            input.signature.clear();
+            input.script_sig.clear();
+        }
+
+        let extra = fee - old_fee;
+        let change = tx.outputs[change_at].value;
+
+        let fee = if change >= extra && !is_dust(change - extra) {
+            tx.outputs[change_at].value -= extra;
+            fee
+        } else if change >= extra && tx.outputs.len() > 1 {
+            tx.outputs.remove(change_at);
+            old_fee + change
+        } else {
+            return Err("change too small to pay the higher fee");
+        };
+
+        signer::sign_transaction(&mut tx, self)?;
+
+        Ok(Payment { tx, fee })
+    }
+}
+
+/* ───────── Child Pays For Parent ───────── */
+
+impl Wallet {
+    /// Spend this wallet's outputs of the unconfirmed `parent`
+    /// back to address 0, paying enough fee to lift the whole
+    /// package to `fee_rate` (POLICY ONLY)
+    ///
+    /// `package_fee` and `package_size` cover `parent` and its
+    /// own unconfirmed ancestors. Miners select by ancestor
+    /// package, so they take the parent along with the child.
+    pub fn accelerate(
+        &self,
+        parent: &Transaction,
+        package_fee: u64,
+        package_size: usize,
+        fee_rate: FeeRate,
+    ) -> Result<Payment, &'static str> {
+        let keys = self.owned_keys()?;
+        let txid = parent.txid();
+
+        let mut inputs = Vec::new();
         let mut collected = 0u64;
-        let mut selected = Vec::new();
-
-        for (key, utxo) in utxos {
-            for index in 0..20 {
-                let child = derive_child_seed(&master_seed, index);
-                let sk = secret_key_from_seed(&child);
-                let pk = public_key(&sk);
-                let hash = pubkey_hash(&pk);
-
-                if hash == utxo.pubkey_hash {
-                    let parts: Vec<&str> = key.split(':').collect();
-                    let txid = hex::decode(parts[0]).unwrap();
-                    let vout = parts[1].parse::<u32>().unwrap();
-
-                    selected.push((txid, vout, index, utxo.value));
-                    collected += utxo.value;
-
-                    if collected >= amount {
-                        break;
-                    }
-                }
+
+        for (vout, output) in parent.outputs.iter().enumerate() {
+            if !output.script_pubkey.is_empty() {
+                continue;
             }
-            if collected >= amount {
-                break;
+
+            if let Some(key) = keys.iter().find(|k| k.pubkey_hash == output.pubkey_hash) {
+                inputs.push(TxInput {
+                    txid: txid.clone(),
+                    index: vout as u32,
+                    pubkey: key.pubkey.clone(),
+                    signature: vec![],
+                    address_index: key.index,
+                    script_sig: vec![],
+                });
+                collected += output.value;
             }
         }
 
-        if collected < amount {
-            return Err("not enough funds");
+        if inputs.is_empty() {
+            return Err("transaction pays nothing to this wallet");
         }
 
-        let mut outputs = vec![TxOutput {
-            value: amount,
-            pubkey_hash: to_pubkey_hash,
-        }];
+        let size = estimated_size(inputs.len(), 1);
+        let fee = fee_rate
+            .fee_for(package_size + size)
+            .saturating_sub(package_fee)
+            .max(fee_rate.fee_for(size));
+
+        let value = collected
+            .checked_sub(fee)
+            .filter(|v| !is_dust(*v))
+            .ok_or("wallet outputs too small to pay the child fee")?;
+
+        let mut tx = Transaction {
+            inputs,
+            outputs: vec![TxOutput {
+                value,
+                pubkey_hash: self.address()?,
+                script_pubkey: vec![],
+            }],
+        };
+        signer::sign_transaction(&mut tx, self)?;
+
+        Ok(Payment { tx, fee })
+    }
+}
+
+/* ───────── Dump / Import ───────── */
 
-        let change = collected - amount;
-        if change > 0 {
-            let change_addr = self.address()?;
-            outputs.push(TxOutput {
-                value: change,
-                pubkey_hash: change_addr,
+impl Wallet {
+    /// Seed, next index and every searched key, for
+    /// `wallet dumpwallet`
+    ///
+    /// Needs the unlocked wallet and its password again: the
+    /// full seed is only kept encrypted in the wallet file.
+    pub fn dump(&self, password: &str) -> Result<WalletDump, &'static str> {
+        let keys = self.keychain()?;
+        let seed = decrypt_seed(&read_wallet_file(&self.path)?, password).map_err(|_| "wrong password")?;
+
+        let mut dumped = Vec::new();
+        for index in 0..self.next_index + KEY_SEARCH_DEPTH {
+            if let Some(chain) = &keys.receive {
+                dumped.push(DumpedKey { index, scheme: KeyScheme::Bip44, key: chain.derive_child(index)?.secret_key });
+            }
+            dumped.push(DumpedKey {
+                index,
+                scheme: KeyScheme::Legacy,
+                key: secret_key_from_seed(&derive_child_seed(&keys.legacy, index)),
             });
         }
 
+        Ok(WalletDump { seed, next_index: self.next_index, keys: dumped })
+    }
+
+    /// True if `key` is one of the keys the wallet searches
+    pub fn derives_key(&self, key: &SecretKey) -> Result<bool, &'static str> {
+        Ok(self
+            .keychain()?
+            .search_keys(self.next_index + KEY_SEARCH_DEPTH)
+            .iter()
+            .any(|(_, sk)| sk == key))
+    }
+}
+
+/* ───────── Sweep ───────── */
+
+/// WIF (mainnet, compressed or not) or 64-char hex private key
+pub fn parse_private_key(s: &str) -> Result<SecretKey, &'static str> {
+    let s = s.trim();
+
+    if s.len() == 64 {
+        if let Ok(bytes) = hex::decode(s) {
+            return SecretKey::from_slice(&bytes).map_err(|_| "invalid private key");
+        }
+    }
+
+    let mut data = bs58::decode(s)
+        .with_check(None)
+        .into_vec()
+        .map_err(|_| "not a WIF or hex key")?;
+
+    // 0x80 || key [|| 0x01 if compressed]
+    let valid = data.first() == Some(&0x80)
+        && (data.len() == 33 || (data.len() == 34 && data[33] == 0x01));
+
+    let key = if valid {
+        SecretKey::from_slice(&data[1..33]).map_err(|_| "invalid private key")
+    } else {
+        Err("not a mainnet WIF key")
+    };
+    data.zeroize();
+    key
+}
+
+/// Compressed mainnet WIF, the form `parse_private_key` reads
+pub fn encode_private_key(key: &SecretKey) -> String {
+    let mut data = vec![0x80];
+    data.extend_from_slice(&key.secret_bytes());
+    data.push(0x01);
+
+    let wif = bs58::encode(&data).with_check().into_string();
+    data.zeroize();
+    wif
+}
+
+impl Wallet {
+    /// Move every mature coin locked to `key` to this wallet's
+    /// address in one transaction, paying the fee out of it
+    pub fn sweep(
+        &self,
+        utxos: &UTXOSet,
+        key: SecretKey,
+        fee_rate: FeeRate,
+        current_height: u64,
+    ) -> Result<Payment, &'static str> {
+        let to = self.address()?;
+        let signer = KeySigner::new(key);
+        let pubkey = signer.pubkey();
+        let hash = pubkey_hash_from_bytes(&pubkey);
+
+        let coins: Vec<_> = utxos
+            .iter()
+            .filter(|(_, u)| u.script_pubkey.is_empty() && u.pubkey_hash == hash)
+            .filter(|(_, u)| !u.is_coinbase || current_height >= u.height + COINBASE_MATURITY)
+            .collect();
+
+        if coins.is_empty() {
+            return Err("no spendable coins for this key");
+        }
+
+        let total: u64 = coins.iter().map(|(_, u)| u.value).sum();
+        let fee = fee_rate.fee_for(estimated_size(coins.len(), 1));
+        let value = total.checked_sub(fee).filter(|v| !is_dust(*v)).ok_or("coins do not cover the fee")?;
+
+        let inputs = coins
+            .iter()
+            .map(|(outpoint, _)| TxInput {
+                txid: outpoint.txid.to_vec(),
+                index: outpoint.vout,
+                signature: vec![],
+                pubkey: pubkey.clone(),
+                address_index: 0,
+                script_sig: vec![],
+            })
+            .collect();
+
         let mut tx = Transaction {
-            inputs: Vec::new(),
-            outputs,
+            inputs,
+            outputs: vec![TxOutput { value, pubkey_hash: to, script_pubkey: vec![] }],
         };
+        signer::sign_transaction(&mut tx, &signer)?;
 
-        let sighash = tx.sighash();
-
-        for (txid, vout, index, _) in selected {
-            let sig = sign(
-                &sighash,
-                &secret_key_from_seed(&derive_child_seed(&master_seed, index)),
-            );
-            let pk = public_key(&secret_key_from_seed(
-                &derive_child_seed(&master_seed, index),
-            ));
-
-            tx.inputs.push(TxInput {
-                txid,
-                index: vout,
-                signature: sig,
-                pubkey: pk.serialize().to_vec(),
-                address_index: index,
-            });
+        Ok(Payment { tx, fee })
+    }
+}
+
+/* ───────── Multisig (v5) ───────── */
+
+/// Keep valid, distinct signatures ordered by the position of
+/// the key they satisfy, at most `m` of them
+pub(crate) fn order_multisig_signatures(
+    sighash: &[u8],
+    pubkeys: &[Vec<u8>],
+    m: usize,
+    mut sigs: Vec<Vec<u8>>,
+) -> Vec<Vec<u8>> {
+    let position = |sig: &Vec<u8>| {
+        pubkeys
+            .iter()
+            .position(|pk| verify_signature(sighash, sig, pk))
+    };
+    sigs.retain(|s| position(s).is_some());
+    sigs.sort_by_key(|s| position(s));
+    sigs.dedup_by_key(|s| position(s));
+    sigs.truncate(m);
+    sigs
+}
+
+impl Wallet {
+    /// Public key at a derivation index, to share with cosigners
+    pub fn pubkey(&self, index: u32) -> Result<Vec<u8>, &'static str> {
+        let keys = self.keychain()?;
+        Ok(public_key(&keys.secret_key(index)?).serialize().to_vec())
+    }
+
+    /// Add this wallet's signatures to multisig inputs it can sign
+    ///
+    /// Signatures already present are kept and re-ordered to
+    /// match pubkey order. Returns the number of inputs signed.
+    pub fn cosign(
+        &self,
+        tx: &mut Transaction,
+        utxos: &UTXOSet,
+    ) -> Result<usize, &'static str> {
+        let keys = self
+            .keychain()?
+            .search_keys(self.next_index + KEY_SEARCH_DEPTH);
+        let sighash = tx.signature_hash();
+        let mut signed = 0;
+
+        for input in &mut tx.inputs {
+            let utxo = match input.outpoint().and_then(|op| utxos.get(&op)) {
+                Some(u) => u,
+                None => continue,
+            };
+
+            let (m, pubkeys) = match parse_multisig(&utxo.script_pubkey) {
+                Some(ms) => ms,
+                None => continue,
+            };
+
+            let mut sigs =
+                pushed_data(&input.script_sig).ok_or("bad script_sig")?;
+
+            for (_, sk) in &keys {
+                let pk = public_key(sk).serialize().to_vec();
+
+                if pubkeys.contains(&pk)
+                    && !sigs.iter().any(|s| verify_signature(&sighash, s, &pk))
+                {
+                    sigs.push(sign(&sighash, sk));
+                    signed += 1;
+                }
+            }
+
+            input.script_sig = multisig_unlock(&order_multisig_signatures(&sighash, &pubkeys, m, sigs));
+        }
+
+        Ok(signed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utxo::UTXO;
+
+    fn coin(utxos: &mut UTXOSet, n: u8, value: u64, key: &OwnedKey) {
+        utxos.insert(
+            OutPoint { txid: [n; 32], vout: 0 },
+            UTXO {
+                value,
+                pubkey_hash: key.pubkey_hash.clone(),
+                height: 1,
+                is_coinbase: false,
+                script_pubkey: vec![],
+            },
+        );
+    }
+
+    #[test]
+    fn fee_tracks_size_and_dust_change_is_dropped() {
+        let key = OwnedKey::new(0, vec![2; 33]);
+        let rate = FeeRate::SatPerByte(2);
+
+        let mut utxos = UTXOSet::new();
+        coin(&mut utxos, 1, 100_000, &key);
+
+        // One input, payment + change
+        let p = build_payment(&utxos, &[key], &[(vec![9; 32], 50_000)], rate, vec![8; 32]).unwrap();
+        assert_eq!(p.fee, rate.fee_for(estimated_size(1, 2)));
+        assert_eq!(p.tx.outputs[1].value, 100_000 - 50_000 - p.fee);
+
+        // Change would be dust: folded into the fee
+        let key = OwnedKey::new(0, vec![2; 33]);
+        let amount = 100_000 - rate.fee_for(estimated_size(1, 2)) - 100;
+        let p = build_payment(&utxos, &[key], &[(vec![9; 32], amount)], rate, vec![8; 32]).unwrap();
+        assert_eq!(p.tx.outputs.len(), 1);
+        assert_eq!(p.fee, 100_000 - amount);
+
+        let key = OwnedKey::new(0, vec![2; 33]);
+        assert!(build_payment(&utxos, &[key], &[(vec![9; 32], 100_000)], rate, vec![8; 32]).is_err());
+
+        // Dust recipients are refused, as the mempool would
+        let key = OwnedKey::new(0, vec![2; 33]);
+        let recipients = [(vec![9; 32], 20_000), (vec![7; 32], 545)];
+        assert!(build_payment(&utxos, &[key], &recipients, rate, vec![8; 32]).is_err());
+
+        // Two recipients, one change output, fee for three outputs
+        let key = OwnedKey::new(0, vec![2; 33]);
+        let recipients = [(vec![9; 32], 20_000), (vec![7; 32], 30_000)];
+        let p = build_payment(&utxos, &[key], &recipients, rate, vec![8; 32]).unwrap();
+        assert_eq!(p.tx.outputs.len(), 3);
+        assert_eq!(p.fee, rate.fee_for(estimated_size(1, 3)));
+    }
+
+    #[test]
+    fn balances_split_pending_and_immature_funds() {
+        let key = OwnedKey::new(0, vec![2; 33]);
+        let mut utxos = UTXOSet::new();
+        coin(&mut utxos, 1, 10_000, &key);
+        coin(&mut utxos, 2, 20_000, &key);
+
+        let reward = OutPoint { txid: [3; 32], vout: 0 };
+        utxos.insert(
+            reward,
+            UTXO {
+                value: 5_000,
+                pubkey_hash: key.pubkey_hash.clone(),
+                height: 50,
+                is_coinbase: true,
+                script_pubkey: vec![],
+            },
+        );
+
+        // Mempool spend of the 20k coin, 15k back as change
+        let spend = Transaction {
+            inputs: vec![TxInput {
+                txid: vec![2; 32],
+                index: 0,
+                pubkey: key.pubkey.clone(),
+                signature: vec![],
+                address_index: 0,
+                script_sig: vec![],
+            }],
+            outputs: vec![
+                TxOutput { value: 4_000, pubkey_hash: vec![9; 32], script_pubkey: vec![] },
+                TxOutput { value: 15_000, pubkey_hash: key.pubkey_hash.clone(), script_pubkey: vec![] },
+            ],
+        };
+
+        let owns = |h: &[u8]| h == key.pubkey_hash.as_slice();
+        let b = calculate_balances(&utxos, [&spend], owns, 100);
+        assert_eq!(b, Balances { confirmed: 10_000, pending: 15_000, immature: 5_000 });
+
+        let b = calculate_balances(&utxos, [&spend], owns, 150);
+        assert_eq!((b.confirmed, b.immature), (15_000, 0));
+    }
+
+    #[test]
+    fn parses_wif_and_hex_keys() {
+        let hex_key = "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d";
+        let expected = parse_private_key(hex_key).unwrap();
+
+        for wif in [
+            "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ",
+            "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617",
+        ] {
+            assert_eq!(parse_private_key(wif).unwrap(), expected);
         }
 
-        Ok(tx)
+        assert!(parse_private_key("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTK").is_err());
+        assert_eq!(encode_private_key(&expected), "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617");
     }
 }
@@ -0,0 +1,200 @@
+use std::fs;
+use std::io::Write;
+
+use secp256k1::SecretKey;
+use zeroize::Zeroize;
+
+use crate::crypto::address::{decode_address, encode_address};
+use crate::crypto::{public_key, pubkey_hash};
+use crate::wallet::{encode_private_key, parse_private_key};
+
+/// First line of every dump
+const DUMP_HEADER: &str = "# Bitcoin Revelation wallet dump v1";
+
+/// Comment block written under the header
+const DUMP_HELP: &str = "\
+# ANYONE WITH THIS FILE CAN SPEND THE WALLET'S COINS
+#
+# Lines starting with '#' are comments. Fields:
+#   seed <hex>          BIP39 seed (64 bytes) or legacy master (32 bytes)
+#   next_index <n>      first address index never used
+#   key <wif> <address> <index> <bip44|legacy>
+#
+# The recovery phrase is never stored, so it cannot be dumped;
+# the seed restores the same keys. Import with `wallet importwallet`.";
+
+/// Derivation scheme of a dumped key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyScheme {
+    /// m/44'/0'/0'/0/index
+    Bip44,
+    /// SHA256(legacy master || index)
+    Legacy,
+}
+
+impl KeyScheme {
+    fn name(self) -> &'static str {
+        match self {
+            KeyScheme::Bip44 => "bip44",
+            KeyScheme::Legacy => "legacy",
+        }
+    }
+}
+
+pub struct DumpedKey {
+    pub index: u32,
+    pub scheme: KeyScheme,
+    pub key: SecretKey,
+}
+
+impl DumpedKey {
+    pub fn pubkey_hash(&self) -> Vec<u8> {
+        pubkey_hash(&public_key(&self.key))
+    }
+}
+
+/// Plain-text export of a wallet's secrets (POLICY ONLY)
+///
+/// Everything needed to rebuild the wallet elsewhere: the seed
+/// it was created from, its next address index, and every key
+/// it searches for coins (for other software that takes WIF
+/// keys). The seed is wiped when the dump is dropped.
+pub struct WalletDump {
+    pub seed: Vec<u8>,
+    pub next_index: u32,
+    pub keys: Vec<DumpedKey>,
+}
+
+impl Drop for WalletDump {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+impl WalletDump {
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n{}\n\n", DUMP_HEADER, DUMP_HELP);
+        text += &format!("seed {}\n", hex::encode(&self.seed));
+        text += &format!("next_index {}\n", self.next_index);
+
+        for k in &self.keys {
+            text += &format!(
+                "key {} {} {} {}\n",
+                encode_private_key(&k.key),
+                encode_address(&k.pubkey_hash()),
+                k.index,
+                k.scheme.name()
+            );
+        }
+        text
+    }
+
+    /// Parse `to_text` output; every key must match its address
+    pub fn parse(text: &str) -> Result<Self, &'static str> {
+        if text.lines().next() != Some(DUMP_HEADER) {
+            return Err("not a wallet dump");
+        }
+
+        let mut dump = WalletDump { seed: Vec::new(), next_index: 0, keys: Vec::new() };
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["seed", seed] => {
+                    dump.seed = hex::decode(seed).map_err(|_| "invalid seed")?;
+                    if dump.seed.len() != 32 && dump.seed.len() != 64 {
+                        return Err("invalid seed length");
+                    }
+                }
+                ["next_index", n] => dump.next_index = n.parse().map_err(|_| "invalid next_index")?,
+                ["key", wif, address, index, scheme] => {
+                    let key = DumpedKey {
+                        index: index.parse().map_err(|_| "invalid key index")?,
+                        scheme: match *scheme {
+                            "bip44" => KeyScheme::Bip44,
+                            "legacy" => KeyScheme::Legacy,
+                            _ => return Err("unknown key scheme"),
+                        },
+                        key: parse_private_key(wif)?,
+                    };
+                    if decode_address(address)? != key.pubkey_hash() {
+                        return Err("key does not match its address");
+                    }
+                    dump.keys.push(key);
+                }
+                _ => return Err("unrecognized line in wallet dump"),
+            }
+        }
+
+        if dump.seed.is_empty() {
+            return Err("wallet dump has no seed");
+        }
+        Ok(dump)
+    }
+
+    pub fn load(path: &str) -> Result<Self, &'static str> {
+        let mut text = fs::read_to_string(path).map_err(|_| "cannot read wallet dump")?;
+        let dump = Self::parse(&text);
+        text.zeroize();
+        dump
+    }
+
+    /// Write to a new file (never overwrites), readable by the
+    /// owner only where the platform allows
+    pub fn save(&self, path: &str) -> Result<(), &'static str> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut text = self.to_text();
+        let result = options
+            .open(path)
+            .and_then(|mut f| f.write_all(text.as_bytes()))
+            .map_err(|_| "cannot write wallet dump (does the file exist?)");
+        text.zeroize();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::secret_key_from_seed;
+
+    #[test]
+    fn round_trips_and_rejects_tampered_keys() {
+        let dump = WalletDump {
+            seed: vec![3; 64],
+            next_index: 2,
+            keys: vec![
+                DumpedKey { index: 0, scheme: KeyScheme::Bip44, key: secret_key_from_seed(&[1; 32]) },
+                DumpedKey { index: 0, scheme: KeyScheme::Legacy, key: secret_key_from_seed(&[2; 32]) },
+            ],
+        };
+        let text = dump.to_text();
+
+        let parsed = WalletDump::parse(&text).unwrap();
+        assert_eq!((parsed.seed.clone(), parsed.next_index), (vec![3; 64], 2));
+        assert_eq!(parsed.keys.len(), 2);
+        assert_eq!(parsed.keys[1].scheme, KeyScheme::Legacy);
+        assert_eq!(parsed.keys[1].key, dump.keys[1].key);
+
+        // First key's address swapped for the second's
+        let swapped = text.replacen(
+            &encode_address(&dump.keys[0].pubkey_hash()),
+            &encode_address(&dump.keys[1].pubkey_hash()),
+            1,
+        );
+        assert!(WalletDump::parse(&swapped).is_err());
+        assert!(WalletDump::parse("seed 00").is_err());
+    }
+}
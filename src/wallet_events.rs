@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use crate::core::block::Block;
+use crate::core::transaction::Transaction;
+use crate::crypto::pubkey_hash_from_bytes;
+
+type ReceivedFn = Box<dyn Fn(&[u8], u64) + Send>;
+type ConfirmedFn = Box<dyn Fn(&[u8], u64) + Send>;
+type ConflictedFn = Box<dyn Fn(&[u8]) + Send>;
+
+/// Wallet transaction notifications (POLICY ONLY)
+///
+/// Fed the active chain and the mempool by the node loop; fires
+/// the subscribed callbacks for transactions paying or spending
+/// the watched addresses:
+///
+/// - received: first sight of a payment to the wallet (txid,
+///   amount), in the mempool or straight in a block
+/// - confirmed: a wallet transaction was mined (txid, height)
+/// - conflicted: a pending wallet transaction left the mempool
+///   without being mined (double-spent, replaced or evicted)
+#[derive(Default)]
+pub struct WalletEvents {
+    /// Wallet pubkey hashes
+    watched: HashSet<Vec<u8>>,
+    /// Wallet transactions in the mempool
+    pending: HashSet<Vec<u8>>,
+    /// Height and hash of the last block processed
+    tip: Option<(u64, Vec<u8>)>,
+    received: Vec<ReceivedFn>,
+    confirmed: Vec<ConfirmedFn>,
+    conflicted: Vec<ConflictedFn>,
+}
+
+impl WalletEvents {
+    /// Replace the addresses events are raised for
+    pub fn watch(&mut self, pubkey_hashes: impl IntoIterator<Item = Vec<u8>>) {
+        self.watched = pubkey_hashes.into_iter().collect();
+    }
+
+    pub fn on_received(&mut self, f: impl Fn(&[u8], u64) + Send + 'static) {
+        self.received.push(Box::new(f));
+    }
+
+    pub fn on_confirmed(&mut self, f: impl Fn(&[u8], u64) + Send + 'static) {
+        self.confirmed.push(Box::new(f));
+    }
+
+    pub fn on_conflicted(&mut self, f: impl Fn(&[u8]) + Send + 'static) {
+        self.conflicted.push(Box::new(f));
+    }
+
+    /// Paid to the wallet by `tx`, and whether `tx` concerns the
+    /// wallet at all (pays or spends it)
+    fn relevance(&self, tx: &Transaction) -> (u64, bool) {
+        let paid: u64 = tx
+            .outputs
+            .iter()
+            .filter(|o| self.watched.contains(&o.pubkey_hash))
+            .map(|o| o.value)
+            .sum();
+
+        let spends = tx
+            .inputs
+            .iter()
+            .any(|i| !i.pubkey.is_empty() && self.watched.contains(&pubkey_hash_from_bytes(&i.pubkey)));
+
+        (paid, paid > 0 || spends)
+    }
+
+    /// Catch up with `blocks` (the active chain) and the current
+    /// `mempool` contents
+    ///
+    /// The first call only records the tip; later calls report
+    /// blocks connected since. After a reorg, blocks from the
+    /// old tip's height onward are reported again.
+    pub fn process<'a>(&mut self, blocks: &[Block], mempool: impl IntoIterator<Item = &'a Transaction>) {
+        let start = match &self.tip {
+            None => blocks.len(),
+            Some((height, hash)) => match blocks.get(*height as usize) {
+                Some(b) if b.hash == *hash => *height as usize + 1,
+                _ => (*height as usize).min(blocks.len()),
+            },
+        };
+
+        for block in &blocks[start..] {
+            for tx in &block.transactions {
+                let (paid, relevant) = self.relevance(tx);
+                if !relevant {
+                    continue;
+                }
+
+                let txid = tx.txid();
+                if !self.pending.remove(&txid) && paid > 0 {
+                    self.received.iter().for_each(|f| f(&txid, paid));
+                }
+                self.confirmed.iter().for_each(|f| f(&txid, block.header.height));
+            }
+        }
+
+        if let Some(last) = blocks.last() {
+            self.tip = Some((last.header.height, last.hash.clone()));
+        }
+
+        let mut in_mempool = HashSet::new();
+        for tx in mempool {
+            let (paid, relevant) = self.relevance(tx);
+            if !relevant {
+                continue;
+            }
+
+            let txid = tx.txid();
+            if !self.pending.contains(&txid) && paid > 0 {
+                self.received.iter().for_each(|f| f(&txid, paid));
+            }
+            in_mempool.insert(txid);
+        }
+
+        // Still pending after this round's blocks, but gone
+        for txid in self.pending.difference(&in_mempool) {
+            self.conflicted.iter().for_each(|f| f(txid));
+        }
+        self.pending = in_mempool;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::core::block::BlockHeader;
+    use crate::core::transaction::TxOutput;
+
+    fn block(height: u64, transactions: Vec<Transaction>) -> Block {
+        Block {
+            header: BlockHeader {
+                height,
+                timestamp: 0,
+                prev_hash: vec![],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![],
+                utxo_commitment: vec![],
+            },
+            transactions,
+            hash: vec![height as u8; 32],
+        }
+    }
+
+    fn payment(value: u64, to: u8) -> Transaction {
+        Transaction {
+            inputs: vec![],
+            outputs: vec![TxOutput { value, pubkey_hash: vec![to; 32], script_pubkey: vec![] }],
+        }
+    }
+
+    #[test]
+    fn reports_receive_confirm_and_conflict() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut events = WalletEvents::default();
+        events.watch([vec![1u8; 32]]);
+
+        let l = Arc::clone(&log);
+        events.on_received(move |_, amount| l.lock().unwrap().push(format!("received {}", amount)));
+        let l = Arc::clone(&log);
+        events.on_confirmed(move |_, height| l.lock().unwrap().push(format!("confirmed {}", height)));
+        let l = Arc::clone(&log);
+        events.on_conflicted(move |_| l.lock().unwrap().push("conflicted".to_string()));
+
+        let mut blocks = vec![block(0, vec![payment(50, 1)])];
+        events.process(&blocks, []);
+        assert!(log.lock().unwrap().is_empty());
+
+        // Seen in the mempool, then mined
+        let tx = payment(7_000, 1);
+        events.process(&blocks, [&tx, &payment(1, 2)]);
+        blocks.push(block(1, vec![tx]));
+        events.process(&blocks, []);
+
+        // Seen, then dropped without a block
+        let lost = payment(9_000, 1);
+        events.process(&blocks, [&lost]);
+        events.process(&blocks, []);
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["received 7000", "confirmed 1", "received 9000", "conflicted"]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::block::Block;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Received,
+    Sent,
+    /// Every output came back to the wallet
+    SelfTransfer,
+}
+
+/// One wallet transaction (POLICY ONLY)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub txid: String,
+    pub direction: Direction,
+    /// Received: paid to the wallet. Sent: paid to others.
+    /// Self-transfer: 0 (only the fee left the wallet).
+    pub amount: u64,
+    /// Known when every input belonged to the wallet
+    pub fee: Option<u64>,
+    pub height: u64,
+    pub timestamp: i64,
+    /// Filled in against the current tip when read
+    #[serde(default)]
+    pub confirmations: u64,
+    /// User label, filled in when read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Persistent wallet transaction history (POLICY ONLY)
+///
+/// Blocks are applied on top of the recorded tip. If that tip
+/// was disconnected by a reorg, the history is replayed from
+/// the start height; if the wallet's address set changed, from
+/// genesis.
+#[derive(Serialize, Deserialize, Default)]
+pub struct WalletHistory {
+    /// Where the history is saved
+    #[serde(skip)]
+    file: String,
+    /// Wallet pubkey hashes (hex)
+    owned: HashSet<String>,
+    /// Height and hash (hex) of the last applied block
+    tip: Option<(u64, String)>,
+    /// First block scanned; earlier blocks are taken to hold no
+    /// wallet transactions (e.g. older than the wallet itself)
+    #[serde(default)]
+    start_height: u64,
+    entries: Vec<HistoryEntry>,
+    /// Wallet outputs not yet spent ("txid:vout" → value),
+    /// to value the inputs of later spends
+    unspent: HashMap<String, u64>,
+    /// User labels by txid (hex); kept across replays
+    #[serde(default)]
+    labels: HashMap<String, String>,
+}
+
+fn outpoint_key(txid: &[u8], vout: u32) -> String {
+    format!("{}:{}", hex::encode(txid), vout)
+}
+
+impl WalletHistory {
+    /// Load history saved at `file` for the wallet owning
+    /// `pubkey_hashes`
+    pub fn load(file: &str, pubkey_hashes: &[Vec<u8>]) -> Self {
+        let owned: HashSet<String> = pubkey_hashes.iter().map(hex::encode).collect();
+
+        let stored = fs::read_to_string(file)
+            .ok()
+            .and_then(|d| serde_json::from_str::<WalletHistory>(&d).ok());
+
+        let history = match stored {
+            Some(h) if h.owned == owned => h,
+            // Address set changed: replay, keeping the labels
+            other => Self {
+                owned,
+                labels: other.map(|h| h.labels).unwrap_or_default(),
+                ..Self::default()
+            },
+        };
+
+        Self { file: file.to_string(), ..history }
+    }
+
+    pub fn save(&self) {
+        if let Some(dir) = Path::new(&self.file).parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(
+            &self.file,
+            serde_json::to_string_pretty(self).unwrap(),
+        ).unwrap();
+    }
+
+    /// Catch up with `blocks` (the active chain); true if
+    /// anything changed
+    pub fn sync(&mut self, blocks: &[Block]) -> bool {
+        let mut changed = false;
+
+        let tip_intact = self.tip.as_ref().map(|(height, hash)| {
+            blocks
+                .get(*height as usize)
+                .map_or(false, |b| hex::encode(&b.hash) == *hash)
+        });
+
+        let start = match tip_intact {
+            None => self.start_height as usize,
+            Some(true) => self.tip.as_ref().map_or(0, |(height, _)| *height as usize + 1),
+            // Tip disconnected: replay
+            Some(false) => {
+                self.entries.clear();
+                self.unspent.clear();
+                self.tip = None;
+                changed = true;
+                self.start_height as usize
+            }
+        };
+
+        for block in blocks.iter().skip(start) {
+            self.apply_block(block);
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Forget everything and replay `blocks` from `from_height`
+    pub fn rescan(&mut self, blocks: &[Block], from_height: u64) {
+        self.entries.clear();
+        self.unspent.clear();
+        self.tip = None;
+        self.start_height = from_height;
+
+        self.sync(blocks);
+    }
+
+    fn apply_block(&mut self, block: &Block) {
+        for tx in &block.transactions {
+            let txid = tx.txid();
+
+            let mut spent = 0u64;
+            let mut all_inputs_ours = !tx.inputs.is_empty();
+            for input in &tx.inputs {
+                match self.unspent.remove(&outpoint_key(&input.txid, input.index)) {
+                    Some(value) => spent += value,
+                    None => all_inputs_ours = false,
+                }
+            }
+
+            let mut received = 0u64;
+            let mut to_others = 0u64;
+            for (vout, output) in tx.outputs.iter().enumerate() {
+                if self.owned.contains(&hex::encode(&output.pubkey_hash)) {
+                    received += output.value;
+                    self.unspent.insert(outpoint_key(&txid, vout as u32), output.value);
+                } else {
+                    to_others += output.value;
+                }
+            }
+
+            if spent == 0 && received == 0 {
+                continue;
+            }
+
+            let fee = if all_inputs_ours {
+                let out_total: u64 = tx.outputs.iter().map(|o| o.value).sum();
+                Some(spent.saturating_sub(out_total))
+            } else {
+                None
+            };
+
+            let (direction, amount) = if spent == 0 {
+                (Direction::Received, received)
+            } else if to_others == 0 {
+                (Direction::SelfTransfer, 0)
+            } else {
+                (Direction::Sent, to_others)
+            };
+
+            self.entries.push(HistoryEntry {
+                txid: hex::encode(&txid),
+                direction,
+                amount,
+                fee,
+                height: block.header.height,
+                timestamp: block.header.timestamp,
+                confirmations: 0,
+                label: None,
+            });
+        }
+
+        self.tip = Some((block.header.height, hex::encode(&block.hash)));
+    }
+
+    /// Label `txid` (hex); an empty label removes it
+    pub fn set_label(&mut self, txid: &str, label: &str) {
+        if label.is_empty() {
+            self.labels.remove(txid);
+        } else {
+            self.labels.insert(txid.to_string(), label.to_string());
+        }
+    }
+
+    /// Entries, newest first, with confirmations at `tip_height`
+    pub fn entries(&self, tip_height: u64) -> Vec<HistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|e| HistoryEntry {
+                confirmations: tip_height.saturating_sub(e.height) + 1,
+                label: self.labels.get(&e.txid).cloned(),
+                ..e.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockHeader;
+    use crate::core::transaction::{Transaction, TxInput, TxOutput};
+
+    fn output(value: u64, owner: u8) -> TxOutput {
+        TxOutput { value, pubkey_hash: vec![owner; 32], script_pubkey: vec![] }
+    }
+
+    fn block(height: u64, transactions: Vec<Transaction>) -> Block {
+        Block {
+            header: BlockHeader {
+                height,
+                timestamp: 1_700_000_000 + height as i64,
+                prev_hash: vec![],
+                nonce: 0,
+                target: [0xff; 32],
+                merkle_root: vec![],
+                utxo_commitment: vec![],
+            },
+            transactions,
+            hash: vec![height as u8; 32],
+        }
+    }
+
+    #[test]
+    fn records_receive_and_spend_and_replays_on_reorg() {
+        let coinbase = Transaction { inputs: vec![], outputs: vec![output(5_000, 1)] };
+        let spend = Transaction {
+            inputs: vec![TxInput {
+                txid: coinbase.txid(),
+                index: 0,
+                pubkey: vec![],
+                signature: vec![],
+                address_index: 0,
+                script_sig: vec![],
+            }],
+            outputs: vec![output(3_000, 2), output(1_900, 1)],
+        };
+
+        let mut blocks = vec![block(0, vec![coinbase]), block(1, vec![spend])];
+        let mut history = WalletHistory { owned: [hex::encode([1u8; 32])].into(), ..Default::default() };
+
+        assert!(history.sync(&blocks));
+        let entries = history.entries(1);
+        assert_eq!(entries[0].direction, Direction::Sent);
+        assert_eq!((entries[0].amount, entries[0].fee), (3_000, Some(100)));
+        assert_eq!(entries[1].direction, Direction::Received);
+        assert_eq!(entries[1].confirmations, 2);
+
+        assert!(!history.sync(&blocks));
+        history.set_label(&entries[1].txid, "mined");
+
+        // Block 1 replaced by an empty one
+        blocks[1] = block(1, vec![]);
+        blocks[1].hash = vec![9; 32];
+        assert!(history.sync(&blocks));
+        assert_eq!(history.entries(1).len(), 1);
+        assert_eq!(history.entries(1)[0].label.as_deref(), Some("mined"));
+
+        // Scanning from block 1 skips the coinbase
+        history.rescan(&blocks, 1);
+        assert!(history.entries(1).is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::core::block::Block;
+use crate::core::chain::Blockchain;
+use crate::core::transaction::Transaction;
+use crate::node::bus::{BusEvent, EventBus};
+use crate::node::mempool::Mempool;
+use crate::wallet::Wallet;
+use crate::wallet_dump::WalletDump;
+use crate::wallet_store::{save_wallet_store, WalletStore};
+
+/// Several named wallets open at once (POLICY ONLY)
+///
+/// Names and file paths come from the `WalletStore`; each
+/// wallet is unlocked with its own password and auto-locks on
+/// its own timer.
+pub struct WalletManager {
+    store: WalletStore,
+    open: HashMap<String, Wallet>,
+}
+
+impl WalletManager {
+    pub fn new(store: WalletStore) -> Self {
+        Self {
+            store,
+            open: HashMap::new(),
+        }
+    }
+
+    /// Registered wallet names with whether each is open, sorted
+    pub fn list(&self) -> Vec<(String, bool)> {
+        let mut names = self.store.list();
+        names.sort();
+
+        names
+            .into_iter()
+            .map(|n| {
+                let open = self.open.contains_key(&n);
+                (n, open)
+            })
+            .collect()
+    }
+
+    pub fn path(&self, name: &str) -> Option<&String> {
+        self.store.get_path(name)
+    }
+
+    /// Unlock a registered wallet; an already open wallet is
+    /// unlocked again with the new timeout
+    pub fn open(
+        &mut self,
+        name: &str,
+        password: &str,
+        auto_lock: Option<Duration>,
+    ) -> Result<&mut Wallet, &'static str> {
+        let path = self.store.get_path(name).ok_or("unknown wallet")?.clone();
+
+        if let Some(w) = self.open.get_mut(name) {
+            w.unlock(password, auto_lock).map_err(|_| "wallet unlock failed")?;
+        } else if Path::new(&path).exists() {
+            self.open.insert(name.to_string(), Wallet::open(&path, password, auto_lock)?);
+        } else {
+            // Registered but never created (the "default" entry)
+            self.open.insert(name.to_string(), Wallet::create_new(&path, password));
+        }
+
+        Ok(self.open.get_mut(name).unwrap())
+    }
+
+    /// File path for a new wallet `name`, if it is free
+    fn new_wallet_path(&self, name: &str) -> Result<String, &'static str> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err("invalid wallet name");
+        }
+        if self.store.get_path(name).is_some() {
+            return Err("wallet already exists");
+        }
+
+        let path = format!("data/wallet_{}.dat", name);
+        if Path::new(&path).exists() {
+            return Err("wallet file already exists");
+        }
+        Ok(path)
+    }
+
+    fn register(&mut self, name: &str, path: String, wallet: Wallet) -> &mut Wallet {
+        self.store.wallets.insert(name.to_string(), path);
+        save_wallet_store(&self.store);
+
+        self.open.insert(name.to_string(), wallet);
+        self.open.get_mut(name).unwrap()
+    }
+
+    /// Register and create a new wallet at `data/wallet_<name>.dat`
+    pub fn create(&mut self, name: &str, password: &str) -> Result<&mut Wallet, &'static str> {
+        let path = self.new_wallet_path(name)?;
+        let wallet = Wallet::create_new(&path, password);
+        Ok(self.register(name, path, wallet))
+    }
+
+    /// Register a wallet rebuilt from its recovery phrase (also
+    /// used for new wallets whose phrase the user confirmed)
+    pub fn create_from_mnemonic(
+        &mut self,
+        name: &str,
+        password: &str,
+        mnemonic: &str,
+    ) -> Result<&mut Wallet, &'static str> {
+        let path = self.new_wallet_path(name)?;
+        let wallet = Wallet::create_from_mnemonic(&path, password, mnemonic)?;
+        Ok(self.register(name, path, wallet))
+    }
+
+    /// Register a wallet rebuilt from a `wallet dumpwallet` file
+    pub fn import_dump(
+        &mut self,
+        name: &str,
+        password: &str,
+        dump: &WalletDump,
+    ) -> Result<&mut Wallet, &'static str> {
+        let path = self.new_wallet_path(name)?;
+        let wallet = Wallet::create_from_seed(&path, password, &dump.seed, dump.next_index)?;
+        Ok(self.register(name, path, wallet))
+    }
+
+    /// Wallet picked by `use`, if still registered
+    pub fn current(&self) -> Option<&String> {
+        self.store.current.as_ref().filter(|n| self.store.get_path(n).is_some())
+    }
+
+    /// Make `name` the wallet opened by default
+    pub fn set_current(&mut self, name: &str) -> Result<(), &'static str> {
+        if self.store.get_path(name).is_none() {
+            return Err("unknown wallet");
+        }
+        self.store.current = Some(name.to_string());
+        save_wallet_store(&self.store);
+        Ok(())
+    }
+
+    /// Lock and unload a wallet
+    pub fn close(&mut self, name: &str) -> bool {
+        match self.open.remove(name) {
+            Some(mut w) => {
+                w.lock();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Wallet> {
+        self.open.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Wallet> {
+        self.open.get_mut(name)
+    }
+
+    /// Fire wallet events of every open wallet
+    pub fn process_events(&mut self, blocks: &[Block], mempool: &[&Transaction]) {
+        for w in self.open.values_mut() {
+            w.process_events(blocks, mempool.iter().copied());
+        }
+    }
+
+    /// Wipe the keys of every wallet past its auto-lock period
+    pub fn lock_expired(&mut self) {
+        for w in self.open.values_mut() {
+            w.lock_if_expired();
+        }
+    }
+}
+
+/// Run wallet notifications (`process_events`) whenever a block
+/// connects or a transaction enters the mempool
+///
+/// Subscribe after the mempool, so confirmed transactions have
+/// already left it.
+pub fn follow_chain(
+    wallets: Arc<Mutex<WalletManager>>,
+    chain: Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<Mempool>>,
+    bus: &EventBus,
+) {
+    bus.subscribe(move |event| {
+        if !matches!(event, BusEvent::BlockConnected(_) | BusEvent::TxAccepted(_)) {
+            return;
+        }
+
+        let c = chain.lock().unwrap();
+        let m = mempool.lock().unwrap();
+        let txs: Vec<&Transaction> = m.transactions().collect();
+        wallets.lock().unwrap().process_events(&c.blocks, &txs);
+    });
+}
@@ -3,6 +3,8 @@ use std::fs;
 
 use serde::{Serialize, Deserialize};
 
+use crate::wallet::DEFAULT_WALLET_FILE;
+
 const WALLET_FILE: &str = "data/wallets.json";
 
 /// Wallet registry (POLICY ONLY)
@@ -10,12 +12,16 @@ const WALLET_FILE: &str = "data/wallets.json";
 pub struct WalletStore {
     /// wallet name → wallet file path
     pub wallets: HashMap<String, String>,
+    /// Picked by `wallet use`; opened when `--wallet` isn't given
+    #[serde(default)]
+    pub current: Option<String>,
 }
 
 impl WalletStore {
     pub fn new() -> Self {
         Self {
             wallets: HashMap::new(),
+            current: None,
         }
     }
 
@@ -45,7 +51,7 @@ pub fn load_wallet_store() -> WalletStore {
     if !store.wallets.contains_key("default") {
         store.wallets.insert(
             "default".to_string(),
-            "data/wallet.dat".to_string(),
+            DEFAULT_WALLET_FILE.to_string(),
         );
         save_wallet_store(&store);
     }
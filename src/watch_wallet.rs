@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+use std::fs;
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::utxo::{OutPoint, UTXOSet, UTXO};
+use crate::crypto::bip32::ExtendedPubKey;
+use crate::wallet::{
+    build_payment, calculate_balance, FeeRate, OwnedKey, Payment, WalletBalance, KEY_SEARCH_DEPTH,
+    RECEIVE_CHAIN,
+};
+
+const WATCH_WALLET_FILE: &str = "data/watch_wallet.json";
+
+/// Watch-only wallet (POLICY ONLY)
+///
+/// Built from an account xpub and/or bare public keys; holds no
+/// secrets. Tracks balances and builds unsigned transactions
+/// that the offline wallet signs with `Wallet::sign_psbt`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct WatchOnlyWallet {
+    /// Account xpub (m/44'/0'/0'); receive keys are xpub/0/i
+    pub xpub: Option<String>,
+    /// Individually imported compressed public keys (hex)
+    pub pubkeys: Vec<String>,
+}
+
+impl WatchOnlyWallet {
+    pub fn from_xpub(xpub: &str) -> Result<Self, &'static str> {
+        ExtendedPubKey::from_base58(xpub)?;
+
+        Ok(Self {
+            xpub: Some(xpub.trim().to_string()),
+            pubkeys: Vec::new(),
+        })
+    }
+
+    /// Watch one more compressed public key (hex)
+    pub fn add_pubkey(&mut self, pubkey_hex: &str) -> Result<(), &'static str> {
+        let bytes = hex::decode(pubkey_hex.trim()).map_err(|_| "invalid hex")?;
+        secp256k1::PublicKey::from_slice(&bytes).map_err(|_| "invalid public key")?;
+
+        let pubkey_hex = hex::encode(bytes);
+        if !self.pubkeys.contains(&pubkey_hex) {
+            self.pubkeys.push(pubkey_hex);
+        }
+        Ok(())
+    }
+
+    /// Watched keys: xpub receive chain first, then imports
+    pub(crate) fn keys(&self) -> Vec<OwnedKey> {
+        let mut keys = Vec::new();
+
+        let chain = self
+            .xpub
+            .as_deref()
+            .and_then(|x| ExtendedPubKey::from_base58(x).ok())
+            .and_then(|x| x.derive_child(RECEIVE_CHAIN).ok());
+
+        if let Some(chain) = chain {
+            for index in 0..KEY_SEARCH_DEPTH {
+                if let Ok(child) = chain.derive_child(index) {
+                    keys.push(OwnedKey::new(index, child.public_key.serialize().to_vec()));
+                }
+            }
+        }
+
+        for pk in &self.pubkeys {
+            if let Ok(bytes) = hex::decode(pk) {
+                keys.push(OwnedKey::new(0, bytes));
+            }
+        }
+
+        keys
+    }
+
+    /// Receive address (first watched key)
+    pub fn address(&self) -> Result<Vec<u8>, &'static str> {
+        self.keys()
+            .into_iter()
+            .next()
+            .map(|k| k.pubkey_hash)
+            .ok_or("no keys to watch")
+    }
+
+    pub fn balance(&self, utxos: &UTXOSet, current_height: u64) -> WalletBalance {
+        let hashes: HashSet<Vec<u8>> = self.keys().into_iter().map(|k| k.pubkey_hash).collect();
+        calculate_balance(utxos, |h| hashes.contains(h), current_height)
+    }
+
+    /// Unspent coins locked to watched keys
+    pub fn coins<'a>(&self, utxos: &'a UTXOSet) -> Vec<(&'a OutPoint, &'a UTXO)> {
+        let hashes: HashSet<Vec<u8>> = self.keys().into_iter().map(|k| k.pubkey_hash).collect();
+        utxos.iter().filter(|(_, u)| hashes.contains(&u.pubkey_hash)).collect()
+    }
+
+    /// Unsigned payment of `amount` at `fee_rate`; change returns
+    /// to the first watched key
+    pub fn create_unsigned_transaction(
+        &self,
+        utxos: &UTXOSet,
+        to_pubkey_hash: Vec<u8>,
+        amount: u64,
+        fee_rate: FeeRate,
+    ) -> Result<Payment, &'static str> {
+        build_payment(utxos, &self.keys(), &[(to_pubkey_hash, amount)], fee_rate, self.address()?)
+    }
+}
+
+/// Load the watch-only wallet, if one was imported
+pub fn load_watch_wallet() -> Option<WatchOnlyWallet> {
+    let data = fs::read_to_string(WATCH_WALLET_FILE).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn save_watch_wallet(wallet: &WatchOnlyWallet) {
+    fs::create_dir_all("data").unwrap();
+    fs::write(
+        WATCH_WALLET_FILE,
+        serde_json::to_string_pretty(wallet).unwrap(),
+    ).unwrap();
+}